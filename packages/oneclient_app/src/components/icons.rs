@@ -65,6 +65,7 @@ impl From<ProviderId> for IconType {
             ProviderId::Modrinth => IconType::Modrinth,
             ProviderId::CurseForge => IconType::Curseforge,
             ProviderId::Local => IconType::Folder,
+            ProviderId::SelfHosted => IconType::Globe01,
         }
     }
 }
@@ -36,6 +36,7 @@ mod toasts;
 mod toggle;
 mod update_prompt;
 mod version_card;
+mod world_picker;
 pub mod upload_mclogs;
 
 pub use account_switcher::AccountSwitcher;
@@ -79,3 +80,4 @@ pub use toasts::Toasts;
 pub use toggle::{toggle, toggle_controlled, toggle_disabled};
 pub use update_prompt::UpdatePromptOverlay;
 pub use version_card::VersionCard;
+pub use world_picker::WorldPicker;
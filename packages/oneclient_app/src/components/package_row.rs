@@ -1,8 +1,8 @@
 use freya::prelude::*;
 use freya::router::RouterContext;
-use oneclient_content::packages::ProviderId;
+use oneclient_content::packages::{ProviderId, WorldTarget};
 
-use crate::components::{Icon, IconType, toggle_controlled};
+use crate::components::{Icon, IconType, WorldPicker, toggle_controlled};
 use crate::hooks::{ClusterAction, loaded_image, use_cached_image, use_cluster_mutation};
 use crate::routes::Route;
 use crate::theme::colors;
@@ -44,6 +44,8 @@ pub struct PackageEntry {
     pub installed: bool,
     pub hash: Option<String>,
     pub manifest_default: bool,
+    /// Only set for installed datapacks which can be moved between worlds
+    pub world: Option<WorldTarget>,
     /// Private bundle dependency only set for bundle rows
     pub hidden: bool,
     /// Only set for browser-installed content bundle packages use the bundle update flow
@@ -155,12 +157,24 @@ impl Component for PackageRow {
                         });
                     }
                 };
+                let world_hash = item.hash.clone();
+                let on_move: EventHandler<WorldTarget> = (move |world| {
+                    if let Some(h) = &world_hash {
+                        cluster.mutate(ClusterAction::SetDatapackWorld {
+                            cluster_id,
+                            hash: h.clone(),
+                            world,
+                        });
+                    }
+                })
+                .into();
                 list_card(
                     &item,
                     package_type,
                     cluster_id,
                     icon,
                     on_toggle,
+                    on_move,
                     can_remove,
                     on_remove,
                     remove_hover,
@@ -178,6 +192,7 @@ fn list_card(
     cluster_id: i64,
     icon: impl IntoElement,
     on_toggle: EventHandler<()>,
+    on_move: EventHandler<WorldTarget>,
     can_remove: bool,
     on_remove: impl FnMut() + 'static,
     hovering: State<bool>,
@@ -193,6 +208,11 @@ fn list_card(
         .background(CARD_BG)
         .content(Content::Flex)
         .child(package_info(item, package_type, cluster_id, icon))
+        .maybe_child(
+            item.world
+                .clone()
+                .map(|world| WorldPicker::new(cluster_id, world).on_select(on_move)),
+        )
         .child(meta_size(item.size))
         .child(toggle_controlled(item.enabled, on_toggle))
         .child(remove_button(can_remove, on_remove, hovering))
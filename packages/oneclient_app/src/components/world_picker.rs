@@ -0,0 +1,81 @@
+use freya::prelude::*;
+use oneclient_core::packages::WorldTarget;
+
+use crate::components::Dropdown;
+use crate::hooks::{try_cluster_worlds, use_cluster_worlds};
+
+const ALL_WORLDS: &str = "All worlds";
+
+/// Which of a cluster's worlds a datapack goes into
+#[derive(Clone, PartialEq)]
+pub struct WorldPicker {
+    cluster_id: i64,
+    current: WorldTarget,
+    on_select: Option<EventHandler<WorldTarget>>,
+    width: Size,
+    key: DiffKey,
+}
+
+impl WorldPicker {
+    pub fn new(cluster_id: i64, current: WorldTarget) -> Self {
+        Self {
+            cluster_id,
+            current,
+            on_select: None,
+            width: Size::px(160.),
+            key: DiffKey::None,
+        }
+    }
+
+    pub fn width(mut self, width: impl Into<Size>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn on_select(mut self, handler: impl Into<EventHandler<WorldTarget>>) -> Self {
+        self.on_select = Some(handler.into());
+        self
+    }
+}
+
+impl KeyExt for WorldPicker {
+    fn write_key(&mut self) -> &mut DiffKey {
+        &mut self.key
+    }
+}
+
+impl Component for WorldPicker {
+    fn render(&self) -> impl IntoElement {
+        let worlds = try_cluster_worlds(&use_cluster_worlds(self.cluster_id)).unwrap_or_default();
+        let on_select = self.on_select.clone();
+
+        let folders: Vec<String> = worlds.iter().map(|world| world.folder.clone()).collect();
+        let mut labels = vec![ALL_WORLDS.to_string()];
+        labels.extend(worlds.iter().map(|world| world.display_name().to_string()));
+
+        // A world deleted since the pick still shows by folder name
+        let selected = match &self.current {
+            WorldTarget::AllWorlds => ALL_WORLDS.to_string(),
+            WorldTarget::World(folder) => worlds
+                .iter()
+                .find(|world| &world.folder == folder)
+                .map_or_else(|| folder.clone(), |world| world.display_name().to_string()),
+        };
+
+        Dropdown::new(selected, labels)
+            .width(self.width.clone())
+            .height(Size::px(28.))
+            .on_select(move |idx: usize| {
+                let target = match idx.checked_sub(1) {
+                    None => WorldTarget::AllWorlds,
+                    Some(i) => match folders.get(i) {
+                        Some(folder) => WorldTarget::World(folder.clone()),
+                        None => return,
+                    },
+                };
+                if let Some(handler) = &on_select {
+                    handler.call(target);
+                }
+            })
+    }
+}
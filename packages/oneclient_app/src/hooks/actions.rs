@@ -13,6 +13,8 @@ use oneclient_cluster::{
     ClusterStage, ClusterUpdate, GameSettingsProfile, PackageUpdateMode, ProfileUpdate,
 };
 use oneclient_common::domain::{ContentType, ProviderId};
+use oneclient_content::packages::PackageIndex;
+use oneclient_core::QuickPlay;
 use oneclient_core::settings::LauncherSettings;
 use oneclient_core::settings::store::{save_global_profile, save_settings_and_apply};
 use oneclient_db::models::ClusterId;
@@ -252,6 +254,38 @@ impl Actions {
         }
     }
 
+    /// Adding a url that is already there is a no-op
+    pub fn add_package_index(&self, url: &str) {
+        let index = PackageIndex::new(url);
+        if index.url.is_empty() {
+            return;
+        }
+        let mut added = false;
+        let updated = self.mutate_settings(|settings| {
+            if !settings
+                .package_indexes
+                .iter()
+                .any(|i| i.url == index.url || i.id == index.id)
+            {
+                settings.package_indexes.push(index);
+                added = true;
+            }
+        });
+
+        if let (true, Some(updated)) = (added, updated) {
+            self.persist(updated);
+        }
+    }
+
+    /// What was installed from it stays linked and just stops finding updates
+    pub fn remove_package_index(&self, id: &str) {
+        if let Some(updated) = self.mutate_settings(|settings| {
+            settings.package_indexes.retain(|index| index.id != id);
+        }) {
+            self.persist(updated);
+        }
+    }
+
     pub fn save_global_profile(&self, profile: GameSettingsProfile) {
         let actions = self.clone();
         spawn_forever(async move {
@@ -626,6 +660,11 @@ impl Actions {
     /// The pending flag is raised synchronously before the first `await` the
     /// claim itself is the guard against a double-click spawning two games
     pub fn launch_cluster(&self, cluster_id: ClusterId) {
+        self.launch_cluster_into(cluster_id, None);
+    }
+
+    /// `quick_play` skips the title screen straight into a world server or realm
+    pub fn launch_cluster_into(&self, cluster_id: ClusterId, quick_play: Option<QuickPlay>) {
         let claimed = self
             .station
             .clone()
@@ -640,7 +679,7 @@ impl Actions {
         let actions = self.clone();
         spawn_forever(async move {
             let started = Instant::now();
-            launch(&actions, cluster_id).await;
+            launch(&actions, cluster_id, quick_play.as_ref()).await;
 
             // An instant failure would hand the button back inside the same
             // click burst so hold it for the floor either way
@@ -698,12 +737,14 @@ impl Actions {
         });
     }
 
+    /// `world` only matters for a datapack
     pub fn install_package(
         &self,
         cluster_id: ClusterId,
         provider: ProviderId,
         project_id: impl Into<String>,
         version_id: impl Into<String>,
+        world: crate::install::WorldTarget,
     ) {
         let (project_id, version_id) = (project_id.into(), version_id.into());
         let actions = self.clone();
@@ -735,6 +776,7 @@ impl Actions {
                 &project_id,
                 &version_id,
                 cluster_id,
+                world,
             )
             .await;
 
@@ -1232,13 +1274,13 @@ impl Actions {
 
 /// Failures are reported as game events so the caller only has to know the
 /// attempt is over Everything before `launch_cluster` is pre-launch work
-async fn launch(actions: &Actions, cluster_id: ClusterId) {
+async fn launch(actions: &Actions, cluster_id: ClusterId, quick_play: Option<&QuickPlay>) {
     let Ok(state) = launcher::state() else { return };
     let events = state.services.events.clone();
 
     events.game_stage(cluster_id, oneclient_events::LaunchStage::Checking);
 
-    let account = match state.auth.default_account_for_launch().await {
+    let account = match oneclient_core::game::account_for_cluster(&state, cluster_id).await {
         Ok(account) => account,
         Err(err) => {
             events.game_failed(cluster_id, format!("{err:#}"));
@@ -1260,11 +1302,13 @@ async fn launch(actions: &Actions, cluster_id: ClusterId) {
         .resolve_package_updates_before_launch(&state, cluster_id)
         .await;
 
-    if let Err(err) = oneclient_core::launch_cluster(&state, cluster_id, &account, true).await {
+    if let Err(err) =
+        oneclient_core::launch_cluster_with(&state, cluster_id, &account, true, quick_play).await
+    {
         // A missing file is the one failure the launcher can fix itself and a
         // path inside our metadata folder gives the user nothing to act on
         if err.indicates_missing_files() {
-            repair_and_relaunch(&state, cluster_id, &account, err).await;
+            repair_and_relaunch(&state, cluster_id, &account, quick_play, err).await;
             return;
         }
 
@@ -1278,6 +1322,7 @@ async fn repair_and_relaunch(
     state: &std::sync::Arc<oneclient_core::LauncherState>,
     cluster_id: ClusterId,
     account: &oneclient_auth::MinecraftAccount,
+    quick_play: Option<&QuickPlay>,
     original: oneclient_core::LauncherError,
 ) {
     let events = state.services.events.clone();
@@ -1310,7 +1355,9 @@ async fn repair_and_relaunch(
         .body(report.summary())
         .send();
 
-    if let Err(err) = oneclient_core::launch_cluster(state, cluster_id, account, true).await {
+    if let Err(err) =
+        oneclient_core::launch_cluster_with(state, cluster_id, account, true, quick_play).await
+    {
         tracing::error!(cluster_id, "launch failed again after repair: {err:#}");
         events.game_failed(cluster_id, format!("{err:#}"));
     }
@@ -21,8 +21,8 @@ pub use queries::{
     CancelMicrosoftLoginKeys, CancelMicrosoftLoginMutation, ClusterAction, ClusterBundles,
     ClusterLogsQuery, FinishMicrosoftLoginMutation, LogAction, LogContentQuery, MigrationQuery,
     OnboardingBundlesQuery, RefreshAccountKeys, RemoveAccountKeys, ScreenshotAction,
-    SetDefaultAccountKeys, StorageAction, StorageActionMutation, StorageReportQuery, TermsQuery,
-    UploadLogKeys, UploadLogMutation, UseLogAction,
+    SetCredentialsPassphraseKeys, SetDefaultAccountKeys, StorageAction, StorageActionMutation, StorageReportQuery, TermsQuery,
+    UnlockCredentialsKeys, UploadLogKeys, UploadLogMutation, UseLogAction,
     UseRefreshAccount, UseRemoveAccount, UseScreenshotAction, UseSetDefaultAccount,
     UseStorageAction, UseUploadLog,
     VERSIONS_PAGE_SIZE, accounts_have_microsoft, bundle_overrides_map, bundles_with_status_items,
@@ -30,7 +30,7 @@ pub use queries::{
     content_type_for_slug, has_migration_data, invalidate_cluster_content_queries,
     invalidate_cluster_queries, invalidate_java_queries,
     invalidate_logs_queries, invalidate_profile_queries, invalidate_screenshots_queries,
-    invalidate_storage_queries, try_storage_report, use_storage_action, use_storage_report,
+    invalidate_storage_queries, try_cluster_worlds, use_cluster_worlds, try_storage_report, use_storage_action, use_storage_report,
     java_runtimes, latest_changelog_version, loaded_image, loader_versions,
     login_code_already_handled, migration_detection,
     mutation_error, mutation_is_pending, mutation_is_running, onboarding_bundles_items, package_meta_batch,
@@ -38,20 +38,22 @@ pub use queries::{
     query_is_busy, stale_hashes, use_package_updates,
     query_is_loading, reset_login_code_dedup, search_items, search_pending, search_total,
     settled_or_loading, terms_document, terms_error, terms_is_loading, try_account,
-    try_accounts, try_cluster_analytics, try_cluster_logs, try_cluster_screenshots,
+    try_accounts, try_cluster_account, try_cluster_analytics, try_credentials_lock, try_cluster_logs, try_cluster_screenshots,
     try_default_account, try_game_profile, try_global_analytics, try_log_content, use_account,
     use_accounts, use_add_microsoft_account, use_add_offline_account, use_begin_microsoft_login,
     use_bundle_overrides, use_bundle_updates, use_bundles_with_status, use_cached_image,
-    use_cancel_microsoft_login, use_changelog, use_cluster_analytics, use_cluster_content,
+    use_cancel_microsoft_login, use_changelog, use_cluster_account, use_cluster_analytics, use_cluster_content,
     use_cluster_logs, use_cluster_mutation, use_cluster_profile, use_cluster_screenshots,
-    use_cluster, use_cluster_settings, use_clusters, use_current_account, use_default_account,
+    use_cluster, use_cluster_settings, use_clusters, use_credentials_lock, use_current_account,
+    use_default_account,
     use_finish_microsoft_login, use_game_profile, use_global_analytics, use_java_runtimes,
     use_loader_versions, use_local_image, use_log_action, use_log_content, use_migration,
     use_named_profiles, use_onboarding_bundles, use_package_categories, use_package_meta_batch,
     use_package_project, use_package_search, use_package_versions, use_package_versions_when,
     use_player_profile,
     use_player_skin, use_provider_versions, use_refresh_account, use_refresh_all_accounts,
-    use_remove_account, use_screenshot_action, use_set_default_account, use_terms, use_upload_log,
+    use_remove_account, use_screenshot_action, use_set_credentials_passphrase,
+    use_set_default_account, use_terms, use_unlock_credentials, use_upload_log,
     use_version_metadata, use_versions, version_list, versions_metadata, versions_total,
 };
 
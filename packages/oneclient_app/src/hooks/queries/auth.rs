@@ -35,6 +35,21 @@ pub struct AccountKeys {
     pub id: Uuid,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClusterAccountKeys {
+    pub cluster_id: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CredentialsLockKeys;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CredentialsLock {
+    /// Accounts stay hidden until the passphrase is entered
+    pub locked: bool,
+    pub has_passphrase: bool,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ListAccountsQuery;
 
@@ -81,6 +96,37 @@ impl QueryCapability for AccountQuery {
     }
 }
 
+/// `None` is the default account
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClusterAccountQuery;
+
+impl QueryCapability for ClusterAccountQuery {
+    type Ok = Option<Uuid>;
+    type Err = LauncherError;
+    type Keys = ClusterAccountKeys;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        let state = crate::launcher::state()?;
+        oneclient_core::game::cluster_account(&state, keys.cluster_id).await
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CredentialsLockQuery;
+
+impl QueryCapability for CredentialsLockQuery {
+    type Ok = CredentialsLock;
+    type Err = LauncherError;
+    type Keys = CredentialsLockKeys;
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        Ok(CredentialsLock {
+            locked: oneclient_auth::credentials_locked().await?,
+            has_passphrase: oneclient_auth::has_credentials_passphrase().await?,
+        })
+    }
+}
+
 pub fn use_accounts() -> UseQuery<ListAccountsQuery> {
     use_query(Query::new(ListAccountsKeys, ListAccountsQuery))
 }
@@ -100,6 +146,22 @@ pub fn use_account(id: Uuid) -> UseQuery<AccountQuery> {
     use_query(Query::new(AccountKeys { id }, AccountQuery))
 }
 
+pub fn use_cluster_account(cluster_id: i64) -> UseQuery<ClusterAccountQuery> {
+    use_query(Query::new(ClusterAccountKeys { cluster_id }, ClusterAccountQuery))
+}
+
+pub fn use_credentials_lock() -> UseQuery<CredentialsLockQuery> {
+    use_query(Query::new(CredentialsLockKeys, CredentialsLockQuery))
+}
+
+pub fn try_credentials_lock(query: &UseQuery<CredentialsLockQuery>) -> CredentialsLock {
+    super::state::settled_or_loading(query).unwrap_or_default()
+}
+
+pub fn try_cluster_account(query: &UseQuery<ClusterAccountQuery>) -> Option<Uuid> {
+    super::state::settled_or_loading(query).flatten()
+}
+
 pub fn try_accounts(query: &UseQuery<ListAccountsQuery>) -> Option<Vec<MinecraftAccount>> {
     super::state::settled_or_loading(query)
 }
@@ -123,6 +185,13 @@ async fn invalidate_auth_queries(account_id: Option<Uuid>) {
     if let Some(id) = account_id {
         QueriesStorage::<AccountQuery>::try_invalidate_matching(AccountKeys { id }).await;
     }
+    // Removing an account sends its clusters back to the default
+    invalidate_cluster_account_queries().await;
+    QueriesStorage::<CredentialsLockQuery>::try_invalidate_matching(CredentialsLockKeys).await;
+}
+
+pub async fn invalidate_cluster_account_queries() {
+    QueriesStorage::<ClusterAccountQuery>::try_invalidate_all().await;
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -236,7 +305,8 @@ impl MutationCapability for RemoveAccountMutation {
     type Keys = RemoveAccountKeys;
 
     async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
-        Ok(crate::launcher::state()?.auth.remove_account(keys.id).await?)
+        let state = crate::launcher::state()?;
+        oneclient_core::game::remove_account(&state, keys.id).await
     }
 
     async fn on_settled(&self, keys: &Self::Keys, result: &Result<Self::Ok, Self::Err>) {
@@ -313,6 +383,56 @@ impl MutationCapability for RefreshAllAccountsMutation {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UnlockCredentialsMutation;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnlockCredentialsKeys {
+    pub passphrase: String,
+}
+
+impl MutationCapability for UnlockCredentialsMutation {
+    type Ok = ();
+    type Err = LauncherError;
+    type Keys = UnlockCredentialsKeys;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        Ok(oneclient_auth::unlock_credentials(&keys.passphrase).await?)
+    }
+
+    async fn on_settled(&self, _keys: &Self::Keys, result: &Result<Self::Ok, Self::Err>) {
+        if result.is_ok() {
+            invalidate_auth_queries(None).await;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SetCredentialsPassphraseMutation;
+
+/// `None` removes the passphrase
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SetCredentialsPassphraseKeys {
+    pub passphrase: Option<String>,
+}
+
+impl MutationCapability for SetCredentialsPassphraseMutation {
+    type Ok = ();
+    type Err = LauncherError;
+    type Keys = SetCredentialsPassphraseKeys;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        Ok(oneclient_auth::set_credentials_passphrase(keys.passphrase.as_deref()).await?)
+    }
+
+    async fn on_settled(&self, _keys: &Self::Keys, result: &Result<Self::Ok, Self::Err>) {
+        if result.is_ok() {
+            QueriesStorage::<CredentialsLockQuery>::try_invalidate_matching(CredentialsLockKeys)
+                .await;
+        }
+    }
+}
+
 pub type UseSetDefaultAccount = UseMutation<SetDefaultAccountMutation>;
 pub type UseRemoveAccount = UseMutation<RemoveAccountMutation>;
 pub type UseRefreshAccount = UseMutation<RefreshAccountMutation>;
@@ -353,6 +473,14 @@ pub fn use_refresh_all_accounts() -> UseMutation<RefreshAllAccountsMutation> {
     use_mutation(Mutation::new(RefreshAllAccountsMutation))
 }
 
+pub fn use_unlock_credentials() -> UseMutation<UnlockCredentialsMutation> {
+    use_mutation(Mutation::new(UnlockCredentialsMutation))
+}
+
+pub fn use_set_credentials_passphrase() -> UseMutation<SetCredentialsPassphraseMutation> {
+    use_mutation(Mutation::new(SetCredentialsPassphraseMutation))
+}
+
 /// True before it has ever been run as well as while running For button
 /// disabled state use [`mutation_is_running`] instead
 pub fn mutation_is_pending<M: MutationCapability>(mutation: &UseMutation<M>) -> bool {
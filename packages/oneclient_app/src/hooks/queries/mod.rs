@@ -33,6 +33,7 @@ mod storage;
 mod tos;
 mod version_metadata;
 mod versions;
+mod worlds;
 
 pub use analytics::{
     try_cluster_analytics, try_global_analytics, use_cluster_analytics, use_global_analytics,
@@ -40,10 +41,13 @@ pub use analytics::{
 pub use auth::{
     AddOfflineAccountKeys, BeginMicrosoftLoginMutation, CancelMicrosoftLoginKeys,
     CancelMicrosoftLoginMutation, FinishMicrosoftLoginMutation, RefreshAccountKeys,
-    RemoveAccountKeys, SetDefaultAccountKeys, UseRefreshAccount, UseRemoveAccount,
+    RemoveAccountKeys, SetCredentialsPassphraseKeys, SetDefaultAccountKeys, UnlockCredentialsKeys, UseRefreshAccount, UseRemoveAccount,
     UseSetDefaultAccount, accounts_have_microsoft, login_code_already_handled, mutation_error,
-    mutation_is_pending, mutation_is_running, reset_login_code_dedup, try_account, try_accounts, try_default_account,
-    use_account, use_accounts, use_add_microsoft_account, use_add_offline_account,
+    invalidate_cluster_account_queries, mutation_is_pending, mutation_is_running,
+    reset_login_code_dedup, try_account, try_accounts, try_cluster_account, try_credentials_lock,
+    try_default_account,
+    use_account, use_accounts, use_cluster_account, use_add_microsoft_account, use_add_offline_account,
+    use_credentials_lock, use_set_credentials_passphrase, use_unlock_credentials,
     use_begin_microsoft_login, use_cancel_microsoft_login, use_current_account,
     use_default_account, use_finish_microsoft_login, use_refresh_account, use_refresh_all_accounts,
     use_remove_account, use_set_default_account,
@@ -99,3 +103,4 @@ pub use state::{query_error, query_is_busy, query_is_loading, settled_or_loading
 pub use tos::{TermsQuery, terms_document, terms_error, terms_is_loading, use_terms};
 pub use version_metadata::{pick_version_metadata, use_version_metadata};
 pub use versions::{loader_versions, use_loader_versions, use_versions, versions_metadata};
+pub use worlds::{try_cluster_worlds, use_cluster_worlds};
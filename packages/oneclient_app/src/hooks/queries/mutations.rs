@@ -1,8 +1,11 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use freya::query::{Mutation, MutationCapability, QueriesStorage, UseMutation, use_mutation};
-use oneclient_content::packages::{ContentType, PackageStore};
+use oneclient_content::packages::{ContentType, PackageStore, WorldTarget};
+use oneclient_core::{LauncherResult, LauncherState};
 use oneclient_db::models::ClusterId;
+use uuid::Uuid;
 
 use super::bundles::{BundleOverridesQuery, BundleUpdatesQuery, BundlesWithStatusQuery};
 use super::cluster_content::ClusterContentQuery;
@@ -11,6 +14,7 @@ use super::package_updates::PackageUpdatesQuery;
 use super::settings_profiles::{
     ClusterProfileQuery, ClusterSettingsQuery, GameProfileQuery, ListNamedProfilesQuery,
 };
+use super::worlds::invalidate_worlds_queries;
 
 async fn timed(step: &'static str, fut: impl std::future::Future<Output = ()>) {
     let started = std::time::Instant::now();
@@ -31,6 +35,7 @@ pub async fn invalidate_cluster_queries() {
     timed("clusters", QueriesStorage::<ListClustersQuery>::try_invalidate_all()).await;
     timed("bundle_updates", QueriesStorage::<BundleUpdatesQuery>::try_invalidate_all()).await;
     timed("package_updates", QueriesStorage::<PackageUpdatesQuery>::try_invalidate_all()).await;
+    timed("worlds", invalidate_worlds_queries()).await;
     tracing::debug!(
         target: "oneclient_app::perf",
         ms = started.elapsed().as_millis() as u64,
@@ -78,6 +83,12 @@ pub enum ClusterAction {
         /// writes `Enabled` / `Disabled`
         manifest_default: bool,
     },
+    /// Moves an installed datapack between worlds
+    SetDatapackWorld {
+        cluster_id: ClusterId,
+        hash: String,
+        world: WorldTarget,
+    },
     ImportLocalFile {
         cluster_id: ClusterId,
         content_type: ContentType,
@@ -90,6 +101,11 @@ pub enum ClusterAction {
     VerifyFiles {
         cluster_id: ClusterId,
     },
+    /// `None` goes back to the default account
+    SetAccount {
+        cluster_id: ClusterId,
+        account_id: Option<Uuid>,
+    },
 }
 
 impl MutationCapability for ClusterMutation {
@@ -100,86 +116,7 @@ impl MutationCapability for ClusterMutation {
     async fn run(&self, keys: &ClusterAction) -> Result<(), String> {
         let started = std::time::Instant::now();
         let state = crate::launcher::state().map_err(|e| e.to_string())?;
-        let services = &state.services;
-        let content = &state.services.content();
-        let result = match keys {
-            ClusterAction::ToggleArtifact { cluster_id, hash } => {
-                // Applied to the game folder at next launch never mid-session
-                // Minecraft reads its mods once at startup
-                oneclient_core::toggle_artifact_enabled(*cluster_id, hash, content)
-                    .await
-                    .map(|_| ())
-            }
-            ClusterAction::RemoveArtifact { cluster_id, hash } => {
-                oneclient_core::remove_artifact_from_cluster(*cluster_id, hash, true, content).await
-            }
-            ClusterAction::RemoveBundlePackageFromDisk { cluster_id, hash } => {
-                oneclient_core::remove_artifact_from_cluster(*cluster_id, hash, false, content).await
-            }
-            ClusterAction::SetBundlePackageEnabled {
-                cluster_id,
-                bundle_name,
-                package_id,
-                enabled,
-                manifest_default,
-            } => {
-                oneclient_core::set_bundle_package_enabled(
-                    *cluster_id,
-                    bundle_name,
-                    package_id,
-                    *enabled,
-                    *manifest_default,
-                    content,
-                )
-                .await
-            }
-            ClusterAction::ImportLocalFile {
-                cluster_id,
-                content_type,
-                path,
-            } => PackageStore::import_local_file(path, *content_type, *cluster_id, content)
-                .await
-                .map(|row| {
-                    services
-                        .events
-                        .notify("Imported").body(format!("Added {}", row.file_name)).send();
-                }),
-            ClusterAction::SetDedicatedDir {
-                cluster_id,
-                dedicated,
-            } => {
-                state
-                    .clusters
-                    .set_dedicated_dir(
-                        *cluster_id,
-                        *dedicated,
-                        state.games.is_active(*cluster_id),
-                    )
-                    .await
-                .map_err(|err| oneclient_content::ContentError::InvalidData {
-                    reason: err.to_string(),
-                })
-            }
-            ClusterAction::VerifyFiles { cluster_id } => {
-                // Reports its own outcome not the generic failure toast a
-                // verify that finds nothing wrong is still a useful result
-                match oneclient_core::verify_cluster_files(&state, *cluster_id).await {
-                    Ok(report) => {
-                        let notify = services.events.notify("Verification complete");
-                        let notify = notify.body(report.summary());
-                        if report.unrepairable.is_empty() {
-                            notify.send();
-                        } else {
-                            notify.error().send();
-                        }
-                        Ok(())
-                    }
-                    Err(err) => Err(oneclient_content::ContentError::InvalidData {
-                        reason: err.to_string(),
-                    }),
-                }
-            }
-        };
+        let result = run_cluster_action(&state, keys).await;
         tracing::debug!(
             target: "oneclient_app::perf",
             ms = started.elapsed().as_millis() as u64,
@@ -189,16 +126,101 @@ impl MutationCapability for ClusterMutation {
         result.map_err(|e| e.to_string())
     }
 
-    async fn on_settled(&self, _keys: &ClusterAction, result: &Result<(), String>) {
+    async fn on_settled(&self, keys: &ClusterAction, result: &Result<(), String>) {
         if let Err(err) = result
             && let Ok(state) = crate::launcher::state()
         {
             state.services.events.notify("Action failed").body(err).error().send();
         }
+        if matches!(keys, ClusterAction::SetAccount { .. }) {
+            super::invalidate_cluster_account_queries().await;
+        }
         invalidate_cluster_queries().await;
     }
 }
 
+async fn run_cluster_action(
+    state: &Arc<LauncherState>,
+    keys: &ClusterAction,
+) -> LauncherResult<()> {
+    let services = &state.services;
+    let content = &state.services.content();
+    match keys {
+        ClusterAction::ToggleArtifact { cluster_id, hash } => {
+            // Applied to the game folder at next launch never mid-session
+            // Minecraft reads its mods once at startup
+            oneclient_core::toggle_artifact_enabled(*cluster_id, hash, content).await?;
+        }
+        ClusterAction::RemoveArtifact { cluster_id, hash } => {
+            oneclient_core::remove_artifact_from_cluster(*cluster_id, hash, true, content).await?
+        }
+        ClusterAction::RemoveBundlePackageFromDisk { cluster_id, hash } => {
+            oneclient_core::remove_artifact_from_cluster(*cluster_id, hash, false, content).await?
+        }
+        ClusterAction::SetBundlePackageEnabled {
+            cluster_id,
+            bundle_name,
+            package_id,
+            enabled,
+            manifest_default,
+        } => {
+            oneclient_core::set_bundle_package_enabled(
+                *cluster_id,
+                bundle_name,
+                package_id,
+                *enabled,
+                *manifest_default,
+                content,
+            )
+            .await?
+        }
+        ClusterAction::SetDatapackWorld {
+            cluster_id,
+            hash,
+            world,
+        } => PackageStore::set_datapack_world(*cluster_id, hash, world, content).await?,
+        ClusterAction::ImportLocalFile {
+            cluster_id,
+            content_type,
+            path,
+        } => {
+            let row =
+                PackageStore::import_local_file(path, *content_type, *cluster_id, content).await?;
+            services
+                .events
+                .notify("Imported")
+                .body(format!("Added {}", row.file_name))
+                .send();
+        }
+        ClusterAction::SetDedicatedDir {
+            cluster_id,
+            dedicated,
+        } => {
+            state
+                .clusters
+                .set_dedicated_dir(*cluster_id, *dedicated, state.games.is_active(*cluster_id))
+                .await?
+        }
+        ClusterAction::VerifyFiles { cluster_id } => {
+            // Reports its own outcome not the generic failure toast a
+            // verify that finds nothing wrong is still a useful result
+            let report = oneclient_core::verify_cluster_files(state, *cluster_id).await?;
+            let notify = services.events.notify("Verification complete");
+            let notify = notify.body(report.summary());
+            if report.unrepairable.is_empty() {
+                notify.send();
+            } else {
+                notify.error().send();
+            }
+        }
+        ClusterAction::SetAccount {
+            cluster_id,
+            account_id,
+        } => oneclient_core::game::set_cluster_account(state, *cluster_id, *account_id).await?,
+    }
+    Ok(())
+}
+
 pub fn use_cluster_mutation() -> UseMutation<ClusterMutation> {
     use_mutation(Mutation::new(ClusterMutation))
 }
@@ -44,6 +44,7 @@ pub async fn invalidate_storage_queries() {
 pub enum StorageAction {
     CleanUnreferencedCache,
     CleanLegacyClusterContent,
+    CleanUnusedJava,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -68,6 +69,9 @@ impl MutationCapability for StorageActionMutation {
                     .await
                     .map(|_| ())
             }
+            StorageAction::CleanUnusedJava => oneclient_core::storage::clean_unused_java(&state)
+                .await
+                .map(|_| ()),
         }
         .map_err(|e| e.to_string())
     }
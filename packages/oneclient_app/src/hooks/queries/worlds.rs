@@ -0,0 +1,39 @@
+use freya::query::{QueriesStorage, Query, QueryCapability, UseQuery, use_query};
+use oneclient_core::LauncherError;
+use oneclient_core::worlds::WorldInfo;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClusterWorldsKeys {
+    pub cluster_id: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClusterWorldsQuery;
+
+impl QueryCapability for ClusterWorldsQuery {
+    type Ok = Vec<WorldInfo>;
+    type Err = LauncherError;
+    type Keys = ClusterWorldsKeys;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        let state = crate::launcher::state()?;
+        let cluster = state.clusters.get(keys.cluster_id).await?;
+        Ok(oneclient_core::worlds::list_worlds(&cluster).await?)
+    }
+}
+
+pub fn use_cluster_worlds(cluster_id: i64) -> UseQuery<ClusterWorldsQuery> {
+    use_query(Query::new(
+        ClusterWorldsKeys { cluster_id },
+        ClusterWorldsQuery,
+    ))
+}
+
+pub fn try_cluster_worlds(query: &UseQuery<ClusterWorldsQuery>) -> Option<Vec<WorldInfo>> {
+    super::state::settled_or_loading(query)
+}
+
+/// A dedicated dir switch changes which saves folder the cluster reads
+pub async fn invalidate_worlds_queries() {
+    QueriesStorage::<ClusterWorldsQuery>::try_invalidate_all().await;
+}
@@ -1,10 +1,8 @@
-use std::sync::Arc;
-
-use oneclient_core::LauncherState;
-
 use oneclient_content::packages::PackageStore;
 use oneclient_events::Level;
 
+pub use oneclient_core::packages::{PackageInstall, WorldTarget, install_body, install_package};
+
 use crate::components::IconType;
 use crate::notifications::{
     ClusterUpdateItem, ClusterUpdateSummary, NotificationAction, NotificationActionKind,
@@ -170,173 +168,3 @@ pub async fn combined_cluster_update_spec(
         }],
     })
 }
-
-/// The session is detached not finished so the caller can reuse its notification
-/// as the "Installed" / "Install failed" result
-/// `session_id` is `None` if no download ran
-pub struct PackageInstall {
-    pub session_id: Option<uuid::Uuid>,
-    pub result: anyhow::Result<String>,
-    pub dependencies: Vec<String>,
-    /// Unresolved or failed dependencies the package still installs
-    pub missing_dependencies: Vec<String>,
-}
-
-pub fn install_body(name: &str, dependencies: &[String], missing: &[String]) -> String {
-    let mut body = format!("Added {name}");
-
-    if !dependencies.is_empty() {
-        body.push_str(&format!(
-            " with {} dependenc{}",
-            dependencies.len(),
-            if dependencies.len() == 1 { "y" } else { "ies" }
-        ));
-    }
-    body.push('.');
-
-    if !missing.is_empty() {
-        body.push_str(&format!(" Could not add: {}.", missing.join(", ")));
-    }
-
-    body
-}
-
-impl PackageInstall {
-    fn failed(err: anyhow::Error) -> Self {
-        Self {
-            session_id: None,
-            result: Err(err),
-            dependencies: Vec::new(),
-            missing_dependencies: Vec::new(),
-        }
-    }
-}
-
-pub async fn install_package(
-    state: &Arc<LauncherState>,
-    provider: oneclient_content::packages::ProviderId,
-    project_id: &str,
-    version_id: &str,
-    cluster_id: i64,
-) -> PackageInstall {
-    let lookup = async {
-        let provider_impl = state.services.packages.get(provider)?;
-        let project = provider_impl
-            .get_project(project_id, &state.services.content())
-            .await?;
-        let version = provider_impl
-            .get_version(project_id, version_id, &state.services.content())
-            .await?;
-        anyhow::Ok((project, version))
-    }
-    .await;
-
-    let (project, version) = match lookup {
-        Ok(found) => found,
-        Err(err) => return PackageInstall::failed(err),
-    };
-
-    // Resolved before the session starts so its children can be announced up front
-    let mut resolution = oneclient_content::packages::DependencyResolution::default();
-    if oneclient_content::packages::resolves_dependencies(project.content_type) {
-        match oneclient_content::packages::resolve_required(
-            provider,
-            &version,
-            cluster_id,
-            &state.services.content(),
-        )
-        .await
-        {
-            Ok(resolved) => resolution = resolved,
-            Err(err) => {
-                tracing::warn!(%err, "dependency resolution failed, installing package alone");
-            }
-        }
-    }
-
-    let session = oneclient_events::GroupedProgressSession::start(
-        &state.services.events,
-        format!("Installing {}", project.name),
-    );
-
-    let size = version.primary_file().map(|f| f.size).unwrap_or(0);
-    let dependency_bytes: u64 = resolution
-        .install
-        .iter()
-        .map(|dep| dep.version.primary_file().map(|f| f.size).unwrap_or(0))
-        .sum();
-    session.expect(
-        oneclient_events::TaskCategory::Packages,
-        1 + resolution.install.len() as u64,
-        size + dependency_bytes,
-    );
-
-    let mut installed_dependencies = Vec::new();
-    let mut missing_dependencies = resolution.unresolved;
-
-    // Dependencies first so the package is never in a cluster without them however the run ends
-    for dependency in &resolution.install {
-        let child = session.child(
-            dependency.project.name.clone(),
-            dependency
-                .version
-                .primary_file()
-                .map(|f| f.size)
-                .unwrap_or(0),
-            oneclient_events::TaskCategory::Packages,
-        );
-
-        let result = PackageStore::install_to_cluster(
-            provider,
-            &dependency.project,
-            &dependency.version,
-            cluster_id,
-            false,
-            false,
-            Some(&child),
-            &state.services.content(),
-        )
-        .await;
-
-        child.finish();
-
-        match result {
-            Ok(_) => installed_dependencies.push(dependency.project.name.clone()),
-            Err(err) => {
-                tracing::warn!(
-                    dependency = %dependency.project.name,
-                    %err,
-                    "failed to install dependency"
-                );
-                missing_dependencies.push(dependency.project.name.clone());
-            }
-        }
-    }
-
-    let child = session.child(
-        project.name.clone(),
-        size,
-        oneclient_events::TaskCategory::Packages,
-    );
-
-    let result = PackageStore::install_to_cluster(
-        provider,
-        &project,
-        &version,
-        cluster_id,
-        false,
-        false,
-        Some(&child),
-        &state.services.content(),
-    )
-    .await;
-
-    child.finish();
-
-    PackageInstall {
-        session_id: Some(session.detach()),
-        result: result.map(|_| project.name).map_err(anyhow::Error::from),
-        dependencies: installed_dependencies,
-        missing_dependencies,
-    }
-}
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use freya::router::RouterContext;
 use oneclient_content::packages::types::ProjectSummary;
-use oneclient_content::packages::{ContentType, ProviderId};
+use oneclient_content::packages::{ContentType, ProviderId, WorldTarget};
 
 use crate::components::{Button, Icon, IconType};
 use crate::hooks::{
@@ -400,11 +400,13 @@ impl Component for InstallButton {
                     .enabled(!installed && latest.is_some() && !installing)
                     .on_press(move |_| {
                         if let Some(version_id) = latest.clone() {
+                            // The package page is where a datapack gets a world
                             dispatch.install_package(
                                 cluster_id,
                                 provider,
                                 project_id.clone(),
                                 version_id,
+                                WorldTarget::AllWorlds,
                             );
                         }
                     })
@@ -23,7 +23,8 @@ use crate::hooks::{
     cluster_content_items, content_type_for_slug, pick_version_metadata, search_items,
     search_pending, search_total, settled_or_loading, use_browser_compat, use_browser_state_store,
     use_bundles_with_status, use_cluster_content, use_clusters, use_debounced,
-    use_package_categories, use_package_search, use_versions, use_view_state, versions_metadata,
+    use_package_categories, use_package_search, use_settings_snapshot, use_versions,
+    use_view_state, versions_metadata,
 };
 use crate::routes::Route;
 use crate::theme::colors;
@@ -110,6 +111,7 @@ impl Component for Browser {
         );
 
         let cluster = use_cluster(cluster_id);
+        let has_package_index = !use_settings_snapshot().settings.package_indexes.is_empty();
         let provider_id = *provider.read();
         let compat = *compatible_only.read();
         let cats = selected_categories.read().clone();
@@ -240,7 +242,7 @@ impl Component for Browser {
                     package_type: package_type.clone(),
                 }),
             ))
-            .child(controls(provider, query, view_mode))
+            .child(controls(provider, query, view_mode, has_package_index))
             .child(
                 rect()
                     .horizontal()
@@ -379,7 +381,10 @@ fn controls(
     provider: State<ProviderId>,
     query: State<String>,
     view_mode: State<ViewLayout>,
+    has_package_index: bool,
 ) -> impl IntoElement {
+    let self_hosted = has_package_index.then_some(ProviderId::SelfHosted);
+
     rect()
         .horizontal()
         .width(Size::fill())
@@ -397,11 +402,17 @@ fn controls(
         .child(
             SegmentedControl::new(provider)
                 .no_tint()
-                .segments(ProviderId::REMOTE_PROVIDERS.iter().map(|provider| {
-                    Segment::new(*provider)
-                        .icon(IconType::from(*provider))
-                        .label(provider.to_string())
-                }))
+                .segments(
+                    ProviderId::REMOTE_PROVIDERS
+                        .iter()
+                        .copied()
+                        .chain(self_hosted)
+                        .map(|provider| {
+                            Segment::new(provider)
+                                .icon(IconType::from(provider))
+                                .label(provider.to_string())
+                        }),
+                )
                 .into_element(),
         )
         .child(
@@ -1,5 +1,5 @@
 use freya::prelude::*;
-use oneclient_content::packages::{ContentType, ProviderId};
+use oneclient_content::packages::{ContentType, ProviderId, WorldTarget};
 
 use crate::components::ScrollArea;
 use crate::hooks::{
@@ -43,6 +43,13 @@ fn decode_package_id(package_id: &str) -> (ProviderId, String) {
     }
 }
 
+/// The world picked for a datapack install, every world for anything else
+fn chosen_world(world_choice: Option<State<WorldTarget>>) -> WorldTarget {
+    world_choice
+        .map(|world| world.read().clone())
+        .unwrap_or_default()
+}
+
 fn pill(text: String) -> impl IntoElement {
     rect()
         .center()
@@ -114,6 +121,9 @@ impl Component for BrowserPackage {
         let compatible_only = use_browser_compat();
         let dispatch = use_dispatch();
         let confirm = use_link_confirm();
+        let install_world = use_state(WorldTarget::default);
+        // Only a datapack is installed per world
+        let world_choice = (content_type == ContentType::DataPack).then_some(install_world);
 
         let cluster = use_cluster(cluster_id);
         let compat = *compatible_only.read();
@@ -177,6 +187,7 @@ impl Component for BrowserPackage {
                 dispatch.clone(),
                 installed.clone(),
                 installing,
+                world_choice,
             )
             .into_element(),
             (Some(_), _) => gallery_panel(gallery).into_element(),
@@ -197,6 +208,7 @@ impl Component for BrowserPackage {
                 confirm,
                 installed,
                 installing,
+                world_choice,
             ))
             .child(
                 rect()
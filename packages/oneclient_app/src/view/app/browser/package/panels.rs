@@ -89,6 +89,7 @@ pub(super) fn versions_panel(
     dispatch: Actions,
     installed: Option<Installed>,
     installing: bool,
+    world_choice: Option<State<WorldTarget>>,
 ) -> impl IntoElement {
     let current = *versions_page.read();
     let total_pages = total_versions.div_ceil(VERSIONS_PAGE_SIZE).max(1);
@@ -127,6 +128,7 @@ pub(super) fn versions_panel(
                     tag,
                     duplicated,
                     installing,
+                    world_choice,
                 )
                 .into_element()
             }))
@@ -203,6 +205,7 @@ fn version_row(
     // Saying which version is live only tells the user anything when there are several
     duplicated: bool,
     installing: bool,
+    world_choice: Option<State<WorldTarget>>,
 ) -> impl IntoElement {
     let version_id = v.version_id.clone();
     let mut chips: Vec<String> = v.loaders.iter().map(|l| l.to_string()).collect();
@@ -270,6 +273,7 @@ fn version_row(
             cluster_id,
             dispatch,
             installing,
+            world_choice,
         ))
 }
 
@@ -284,6 +288,7 @@ fn version_button(
     cluster_id: i64,
     dispatch: Actions,
     busy: bool,
+    world_choice: Option<State<WorldTarget>>,
 ) -> impl IntoElement {
     let Some(installed) = installed else {
         return Button::new()
@@ -296,6 +301,7 @@ fn version_button(
                     provider,
                     project_id.clone(),
                     version_id.clone(),
+                    chosen_world(world_choice),
                 );
             })
             .text("Install");
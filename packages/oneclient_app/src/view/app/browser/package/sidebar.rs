@@ -4,7 +4,7 @@ use oneclient_content::packages::ProviderId;
 use oneclient_content::packages::types::{ProjectDetail, ProjectMember};
 
 use crate::Actions;
-use crate::components::{Button, Icon, IconType};
+use crate::components::{Button, Icon, IconType, WorldPicker};
 use crate::theme::colors;
 use crate::ui::border_all_color;
 
@@ -18,6 +18,7 @@ pub(super) fn sidebar(
     confirm: State<Option<String>>,
     installed: Option<Installed>,
     installing: bool,
+    world_choice: Option<State<WorldTarget>>,
 ) -> impl IntoElement {
     let Some(project) = project else {
         return rect()
@@ -111,6 +112,11 @@ pub(super) fn sidebar(
                         ),
                 ),
         )
+        .maybe_child(world_choice.map(|mut world| {
+            WorldPicker::new(cluster_id, world.read().clone())
+                .width(Size::fill())
+                .on_select(move |target| world.set(target))
+        }))
         .child(
             Button::new()
                 .primary()
@@ -123,6 +129,7 @@ pub(super) fn sidebar(
                             provider,
                             project_id.clone(),
                             version_id,
+                            chosen_world(world_choice),
                         );
                     }
                 })
@@ -160,7 +167,7 @@ fn provider_project_url(project: &ProjectDetail) -> Option<String> {
             .iter()
             .find(|(label, _)| label == "Website")
             .map(|(_, url)| url.clone()),
-        ProviderId::Local => None,
+        ProviderId::Local | ProviderId::SelfHosted => None,
     }
 }
 
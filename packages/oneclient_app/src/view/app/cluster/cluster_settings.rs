@@ -1,17 +1,21 @@
 use freya::prelude::*;
 use oneclient_common::Patch;
+use oneclient_auth::MinecraftAccount;
 use oneclient_java::JavaRuntime;
 use oneclient_common::domain::GameLoader;
 use oneclient_core::settings::{
     GameSettingsProfile, PackageUpdateMode, ProfileUpdate, Resolution,
 };
 
+use uuid::Uuid;
+
 use crate::components::{
     Button, Dropdown, Icon, IconType, ScrollArea, TextInput, toggle, toggle_controlled,
     validate_number,
 };
 use crate::hooks::{
-    ClusterAction, java_runtimes, loader_versions, mutation_is_running, try_game_profile,
+    ClusterAction, java_runtimes, loader_versions, mutation_is_running, try_accounts,
+    try_cluster_account, try_game_profile, use_accounts, use_cluster_account,
     use_cluster_mutation, use_dispatch, use_game_profile, use_java_runtimes, use_loader_versions,
     use_settings_snapshot,
 };
@@ -47,6 +51,8 @@ impl Component for ClusterSettings {
             .unwrap_or(GameLoader::Fabric);
         let versions_query = use_loader_versions(mc_version, loader);
         let runtimes_query = use_java_runtimes();
+        let accounts_query = use_accounts();
+        let cluster_account_query = use_cluster_account(cluster_id);
 
         let Some(cluster) = cluster else {
             return cluster_not_found();
@@ -55,6 +61,8 @@ impl Component for ClusterSettings {
         let profile = try_game_profile(&profile_query).unwrap_or_else(|| global.clone());
         let versions = loader_versions(&versions_query);
         let runtimes = java_runtimes(&runtimes_query);
+        let accounts = try_accounts(&accounts_query).unwrap_or_default();
+        let account = try_cluster_account(&cluster_account_query);
 
         cluster_content()
             .child(
@@ -108,6 +116,15 @@ impl Component for ClusterSettings {
                         }
                         .into_element(),
                     )
+                    .child(section_header("ACCOUNT"))
+                    .child(
+                        AccountRow {
+                            cluster_id,
+                            value: account,
+                            accounts,
+                        }
+                        .into_element(),
+                    )
                     .child(section_header("CONTENT"))
                     .child(
                         BrowserUpdateModeRow {
@@ -670,6 +687,64 @@ impl Component for JavaRow {
     }
 }
 
+#[derive(PartialEq)]
+struct AccountRow {
+    cluster_id: i64,
+    value: Option<Uuid>,
+    accounts: Vec<MinecraftAccount>,
+}
+
+impl Component for AccountRow {
+    fn render(&self) -> impl IntoElement {
+        let cluster_id = self.cluster_id;
+        let overridden = self.value.is_some();
+        let mutation = use_cluster_mutation();
+
+        let mut options: Vec<String> = vec!["Default account".into()];
+        options.extend(self.accounts.iter().map(|account| account.username.clone()));
+
+        // An account removed elsewhere still shows as chosen until the next
+        // launch asks about it
+        let selected = self
+            .value
+            .map(|id| {
+                self.accounts
+                    .iter()
+                    .find(|account| account.id == id)
+                    .map_or_else(|| "Removed account".into(), |account| account.username.clone())
+            })
+            .unwrap_or_else(|| "Default account".into());
+
+        let ids: Vec<Uuid> = self.accounts.iter().map(|account| account.id).collect();
+
+        let control = Dropdown::new(selected, options)
+            .width(Size::px(220.))
+            .height(Size::px(34.))
+            .on_select(move |idx: usize| {
+                let account_id = idx.checked_sub(1).and_then(|idx| ids.get(idx).copied());
+                mutation.mutate(ClusterAction::SetAccount {
+                    cluster_id,
+                    account_id,
+                });
+            });
+
+        let on_reset: EventHandler<()> = (move |()| {
+            mutation.mutate(ClusterAction::SetAccount {
+                cluster_id,
+                account_id: None,
+            });
+        })
+        .into();
+
+        settings_row(
+            IconType::Users01,
+            "Account",
+            "Who this cluster plays as. Handy for an alt kept for one server.",
+            override_cell(control, overridden, on_reset),
+        )
+    }
+}
+
 #[derive(PartialEq)]
 struct BrowserUpdateModeRow {
     cluster_id: i64,
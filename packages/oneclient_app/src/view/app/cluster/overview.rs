@@ -1,11 +1,16 @@
 use freya::prelude::*;
 
-use crate::components::ScrollArea;
-use crate::hooks::{try_cluster_analytics, use_cluster_analytics};
+use oneclient_core::game::{ServerStat, last_played_server};
+
+use crate::components::{Button, Icon, IconType, ScrollArea};
+use crate::hooks::{
+    Actions, try_cluster_analytics, use_cluster_analytics, use_dispatch, use_game_snapshot,
+    use_launcher,
+};
 use crate::layout::cluster_content;
 use crate::theme::colors;
 use crate::ui::{centered_note};
-use crate::view::app::{analytics_body, analytics_placeholder};
+use crate::view::app::{analytics_body, analytics_placeholder, launch_button_state};
 
 use super::cluster_not_found;
 use crate::hooks::use_cluster;
@@ -18,6 +23,14 @@ pub struct ClusterOverview {
 impl Component for ClusterOverview {
     fn render(&self) -> impl IntoElement {
         let cluster_id = self.cluster_id;
+        let dispatch = use_dispatch();
+        let game = use_game_snapshot();
+        let launcher = use_launcher();
+        let (_, can_launch) = launch_button_state(
+            &game,
+            cluster_id,
+            launcher.fetching || launcher.syncing_bundles,
+        );
 
         let Some(cluster) = use_cluster(cluster_id) else {
             return cluster_content().child(cluster_not_found()).into_element();
@@ -34,6 +47,11 @@ impl Component for ClusterOverview {
             Some(a) => analytics_body(a),
         };
 
+        let rejoin = analytics
+            .as_ref()
+            .and_then(|a| last_played_server(&a.servers))
+            .map(|server| rejoin_button(cluster_id, server, can_launch, dispatch));
+
         cluster_content()
             .child(
                 ScrollArea::new()
@@ -44,7 +62,7 @@ impl Component for ClusterOverview {
                             .vertical()
                             .width(Size::fill())
                             .spacing(24.)
-                            .child(overview_header(&cluster.name))
+                            .child(overview_header(&cluster.name, rejoin))
                             .child(body),
                     ),
             )
@@ -52,22 +70,60 @@ impl Component for ClusterOverview {
     }
 }
 
-fn overview_header(name: &str) -> Element {
+fn overview_header(name: &str, rejoin: Option<Element>) -> Element {
     rect()
-        .vertical()
-        .spacing(2.)
-        .child(
-            label()
-                .text("Overview")
-                .font_size(20.)
-                .font_weight(FontWeight::SEMI_BOLD)
-                .color(colors::fg_primary()),
-        )
+        .horizontal()
+        .content(Content::Flex)
+        .width(Size::fill())
+        .cross_align(Alignment::Center)
+        .spacing(12.)
         .child(
-            label()
-                .text(format!("Play history & servers for {name}"))
-                .font_size(12.)
-                .color(colors::fg_secondary()),
+            rect()
+                .vertical()
+                .width(Size::flex(1.0))
+                .spacing(2.)
+                .child(
+                    label()
+                        .text("Overview")
+                        .font_size(20.)
+                        .font_weight(FontWeight::SEMI_BOLD)
+                        .color(colors::fg_primary()),
+                )
+                .child(
+                    label()
+                        .text(format!("Play history & servers for {name}"))
+                        .font_size(12.)
+                        .color(colors::fg_secondary()),
+                ),
         )
+        .maybe_child(rejoin)
+        .into_element()
+}
+
+/// Launches straight onto the server most recently joined from this cluster
+fn rejoin_button(
+    cluster_id: i64,
+    server: &ServerStat,
+    enabled: bool,
+    dispatch: Actions,
+) -> Element {
+    let target = server.quick_play();
+    // Raw IPs stay masked here like they are in the server list
+    let text = match server.port {
+        _ if server.is_ip => "Rejoin last server".to_string(),
+        Some(port) if port != 25565 => format!("Rejoin {}:{port}", server.address),
+        _ => format!("Rejoin {}", server.address),
+    };
+
+    Button::new()
+        .secondary()
+        .enabled(enabled)
+        .on_press(move |_| {
+            if enabled {
+                dispatch.launch_cluster_into(cluster_id, Some(target.clone()));
+            }
+        })
+        .child(Icon::new(IconType::Play).size(14.))
+        .child(label().text(text))
         .into_element()
 }
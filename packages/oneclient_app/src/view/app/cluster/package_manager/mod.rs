@@ -2,7 +2,7 @@ use std::collections::{HashMap, HashSet};
 
 use freya::prelude::*;
 use oneclient_common::search::{MatchScore, SearchQuery};
-use oneclient_content::packages::{CachedPackageMeta, ContentType, ProviderId};
+use oneclient_content::packages::{CachedPackageMeta, ContentType, ProviderId, WorldTarget};
 use oneclient_core::{BundleFileKind, BundleWithUpdateStatus, LinkedArtifactInfo};
 use oneclient_db::models::OverrideType;
 
@@ -225,6 +225,10 @@ fn make_row(
                 .map(|v| format!("Version {v}"))
         })
         .unwrap_or_default();
+    // A bundle places its own datapacks so only a standalone one can be moved
+    let world = installed_info
+        .filter(|i| i.content_type == ContentType::DataPack && bundle_name.is_none())
+        .map(|i| WorldTarget::from_world(i.target_world.clone()));
 
     PackageEntry {
         package_id,
@@ -241,6 +245,7 @@ fn make_row(
         manifest_default,
         installed: installed_info.is_some(),
         hash: installed_info.map(|i| i.hash.clone()),
+        world,
         update_available,
         hidden,
     }
@@ -881,7 +881,7 @@ fn crash_repair_button(
                 let diagnosis = oneclient_core::game::CrashDiagnosis::CorruptArchive {
                     file: jar.map(str::to_string),
                 };
-                oneclient_core::game::offer_repair(&state, target, &diagnosis).await;
+                oneclient_core::game::offer_crash_fix(&state, target, &diagnosis).await;
             });
         })
 }
@@ -5,15 +5,16 @@ use freya::query::{MutationCapability, MutationStateData, UseMutation};
 use oneclient_auth::{AccountKind, MinecraftAccount};
 use uuid::Uuid;
 
-use super::{section_header, settings_page};
+use super::{section_header, settings_page, settings_row, settings_row_disabled};
 use crate::components::{
     Avatar, Button, Icon, IconType, OverlayPopup, PlayerModel, TextInput, use_microsoft_login,
 };
 use crate::hooks::{
-    AddOfflineAccountKeys, RefreshAccountKeys, RemoveAccountKeys, SetDefaultAccountKeys,
-    accounts_have_microsoft, try_accounts, try_default_account, use_accounts,
-    use_add_offline_account, use_current_account, use_refresh_account, use_remove_account,
-    use_set_default_account,
+    AddOfflineAccountKeys, RefreshAccountKeys, RemoveAccountKeys, SetCredentialsPassphraseKeys,
+    SetDefaultAccountKeys, UnlockCredentialsKeys, accounts_have_microsoft, try_accounts,
+    try_credentials_lock, try_default_account, use_accounts, use_add_offline_account,
+    use_credentials_lock, use_current_account, use_refresh_account, use_remove_account,
+    use_set_credentials_passphrase, use_set_default_account, use_unlock_credentials,
 };
 use crate::theme::colors;
 use crate::ui::border_all_color;
@@ -31,17 +32,25 @@ impl Component for SettingsAccounts {
     fn render(&self) -> impl IntoElement {
         let accounts_query = use_accounts();
         let default_query = use_current_account();
+        let lock_query = use_credentials_lock();
 
         let msa = use_microsoft_login();
         let add_offline = use_add_offline_account();
         let set_default = use_set_default_account();
         let remove = use_remove_account();
         let refresh = use_refresh_account();
+        let unlock = use_unlock_credentials();
+        let set_passphrase = use_set_credentials_passphrase();
 
         let mut username = use_state(String::new);
         let mut show_offline = use_state(|| false);
         let mut closing_offline = use_state(|| false);
 
+        let mut unlock_input = use_state(String::new);
+        let mut passphrase = use_state(String::new);
+        let mut show_passphrase = use_state(|| false);
+        let mut closing_passphrase = use_state(|| false);
+
         use_side_effect(move || {
             if !*closing_offline.read() {
                 return;
@@ -59,6 +68,24 @@ impl Component for SettingsAccounts {
             }
         });
 
+        use_side_effect(move || {
+            if !*closing_passphrase.read() {
+                return;
+            }
+            match &*set_passphrase.read().state() {
+                MutationStateData::Settled { res: Ok(_), .. } => {
+                    closing_passphrase.set(false);
+                    show_passphrase.set(false);
+                    passphrase.set(String::new());
+                }
+                MutationStateData::Settled { res: Err(_), .. } => {
+                    closing_passphrase.set(false);
+                }
+                _ => {}
+            }
+        });
+
+        let lock = try_credentials_lock(&lock_query);
         let accounts = try_accounts(&accounts_query).unwrap_or_default();
         let default_account = try_default_account(&default_query);
         let default_id = default_account.as_ref().map(|a| a.id);
@@ -79,6 +106,30 @@ impl Component for SettingsAccounts {
             closing_offline.set(true);
         };
 
+        let unlock_error = mutation_err_text(&unlock);
+        let on_unlock = move |_| {
+            let entered = unlock_input.peek().clone();
+            if entered.is_empty() {
+                return;
+            }
+            unlock.mutate(UnlockCredentialsKeys {
+                passphrase: entered,
+            });
+            unlock_input.set(String::new());
+        };
+
+        let passphrase_error = mutation_err_text(&set_passphrase);
+        let on_confirm_passphrase = move |_| {
+            let entered = passphrase.peek().clone();
+            if entered.is_empty() {
+                return;
+            }
+            set_passphrase.mutate(SetCredentialsPassphraseKeys {
+                passphrase: Some(entered),
+            });
+            closing_passphrase.set(true);
+        };
+
         let mut rows: Vec<Element> = accounts
             .iter()
             .map(|account| {
@@ -95,10 +146,59 @@ impl Component for SettingsAccounts {
                 .into_element()
             })
             .collect();
-        if rows.is_empty() {
+        if lock.locked {
+            rows = vec![locked_state(unlock_input, unlock_error, on_unlock)];
+        } else if rows.is_empty() {
             rows.push(empty_state());
         }
 
+        let passphrase_actions = rect()
+            .horizontal()
+            .spacing(8.)
+            .maybe_child(lock.has_passphrase.then(|| {
+                Button::new()
+                    .ghost()
+                    .enabled(!lock.locked)
+                    .on_press(move |_| {
+                        set_passphrase.mutate(SetCredentialsPassphraseKeys { passphrase: None })
+                    })
+                    .text("Remove")
+                    .into_element()
+            }))
+            .child(
+                Button::new()
+                    .secondary()
+                    .enabled(!lock.locked)
+                    .on_press(move |_| show_passphrase.set(true))
+                    .text(if lock.has_passphrase {
+                        "Change"
+                    } else {
+                        "Set passphrase"
+                    }),
+            );
+        let passphrase_description = if lock.has_passphrase {
+            "Stored accounts are encrypted with your passphrase. It is asked for every time the launcher starts."
+        } else {
+            "Ask for a passphrase every time the launcher starts before stored accounts can be used."
+        };
+        let passphrase_row = if lock.locked {
+            settings_row_disabled(
+                IconType::Key01,
+                "Passphrase",
+                passphrase_description,
+                passphrase_actions,
+            )
+            .into_element()
+        } else {
+            settings_row(
+                IconType::Key01,
+                "Passphrase",
+                passphrase_description,
+                passphrase_actions,
+            )
+            .into_element()
+        };
+
         settings_page()
             .child(hero(
                 default_account,
@@ -113,6 +213,16 @@ impl Component for SettingsAccounts {
             ))
             .child(section_header("YOUR ACCOUNTS"))
             .children(rows)
+            .child(section_header("SECURITY"))
+            .child(passphrase_row)
+            .maybe_child(show_passphrase.read().then(|| {
+                passphrase_dialog(
+                    passphrase,
+                    passphrase_error,
+                    on_confirm_passphrase,
+                    show_passphrase,
+                )
+            }))
             .maybe_child(show_offline.read().then(|| {
                 offline_dialog(
                     username,
@@ -364,6 +474,126 @@ fn offline_dialog(
         .into_element()
 }
 
+fn passphrase_dialog(
+    passphrase: State<String>,
+    error: Option<String>,
+    on_confirm: impl FnMut(Event<PressEventData>) + 'static,
+    mut show_passphrase: State<bool>,
+) -> impl IntoElement {
+    OverlayPopup::new()
+        .on_close(move |()| show_passphrase.set(false))
+        .child(
+            rect()
+                .width(Size::window_percent(100.))
+                .height(Size::window_percent(100.))
+                .center()
+                .child(
+                    rect()
+                        .vertical()
+                        .width(Size::px(380.))
+                        .max_width(Size::window_percent(90.))
+                        .spacing(16.)
+                        .padding(Gaps::new_all(20.))
+                        .corner_radius(CornerRadius::new_all(16.))
+                        .background(colors::page_elevated())
+                        .border(border_all_color(1., colors::component_border()))
+                        .child(
+                            label()
+                                .text("Set passphrase")
+                                .font_size(18.)
+                                .font_weight(FontWeight::SEMI_BOLD)
+                                .color(colors::fg_primary()),
+                        )
+                        .child(
+                            rect()
+                                .vertical()
+                                .width(Size::fill())
+                                .spacing(6.)
+                                .child(field_label("Passphrase"))
+                                .child(
+                                    TextInput::new(passphrase)
+                                        .mode(InputMode::new_password())
+                                        .placeholder("New passphrase"),
+                                ),
+                        )
+                        .child(hint_line(
+                            IconType::InfoCircle,
+                            "A forgotten passphrase cannot be recovered. You would have to sign in again."
+                                .to_string(),
+                            colors::fg_secondary(),
+                        ))
+                        .map(error, |el, msg| {
+                            el.child(hint_line(IconType::AlertTriangle, msg, colors::danger()))
+                        })
+                        .child(
+                            rect()
+                                .horizontal()
+                                .width(Size::fill())
+                                .main_align(Alignment::End)
+                                .spacing(8.)
+                                .child(
+                                    Button::new()
+                                        .ghost()
+                                        .on_press(move |_| show_passphrase.set(false))
+                                        .text("Cancel"),
+                                )
+                                .child(
+                                    Button::new()
+                                        .primary()
+                                        .on_press(on_confirm)
+                                        .child(Icon::new(IconType::Key01).size(16.))
+                                        .text("Save"),
+                                ),
+                        ),
+                ),
+        )
+        .into_element()
+}
+
+/// Stands in for the account list until the passphrase is entered
+fn locked_state(
+    passphrase: State<String>,
+    error: Option<String>,
+    on_unlock: impl FnMut(Event<PressEventData>) + 'static,
+) -> Element {
+    rect()
+        .vertical()
+        .width(Size::fill())
+        .center()
+        .padding(Gaps::new_all(32.))
+        .spacing(12.)
+        .corner_radius(CornerRadius::new_all(12.))
+        .background(colors::page_elevated())
+        .child(
+            Icon::new(IconType::Key01)
+                .size(32.)
+                .color(colors::fg_secondary()),
+        )
+        .child(
+            label()
+                .text("Your accounts are locked. Enter your passphrase to use them.")
+                .font_size(14.)
+                .color(colors::fg_secondary()),
+        )
+        .child(
+            rect()
+                .horizontal()
+                .cross_align(Alignment::Center)
+                .spacing(8.)
+                .child(
+                    TextInput::new(passphrase)
+                        .mode(InputMode::new_password())
+                        .placeholder("Passphrase")
+                        .width(Size::px(220.)),
+                )
+                .child(Button::new().primary().on_press(on_unlock).text("Unlock")),
+        )
+        .map(error, |el, msg| {
+            el.child(hint_line(IconType::AlertTriangle, msg, colors::danger()))
+        })
+        .into_element()
+}
+
 fn field_label(text: &str) -> impl IntoElement {
     label()
         .text(text.to_string())
@@ -431,6 +661,7 @@ impl Component for AccountRow {
 
         let is_microsoft = self.kind == AccountKind::Microsoft;
         let expired = is_microsoft && self.expires <= Utc::now();
+        let refreshable = self.kind != AccountKind::Offline;
 
         let mut refreshing = use_state(|| false);
         let is_refreshing = *refreshing.read();
@@ -519,7 +750,7 @@ impl Component for AccountRow {
                             .color(colors::fg_secondary()),
                     ),
             )
-            .maybe_child(is_microsoft.then(|| {
+            .maybe_child(refreshable.then(|| {
                 Button::new()
                     .ghost()
                     .icon()
@@ -576,6 +807,7 @@ fn kind_label(kind: AccountKind) -> &'static str {
     match kind {
         AccountKind::Microsoft => "Microsoft",
         AccountKind::Offline => "Offline",
+        AccountKind::Yggdrasil => "Yggdrasil",
     }
 }
 
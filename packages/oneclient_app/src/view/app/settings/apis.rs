@@ -1,8 +1,9 @@
 use freya::prelude::*;
 
 use super::settings_page;
-use crate::components::{IconType, TextInput};
+use crate::components::{Button, Icon, IconType, TextInput};
 use crate::hooks::{use_dispatch, use_settings_snapshot};
+use crate::theme::colors;
 use crate::view::app::settings::{section_header, settings_row};
 
 fn normalize(value: &str) -> Option<String> {
@@ -38,6 +39,9 @@ impl Component for SettingsApis {
             let v = settings.custom_meta_url_base.clone().unwrap_or_default();
             move || v
         });
+        let mut new_index_url = use_state(String::new);
+        let package_indexes = settings.package_indexes.clone();
+        let index_dispatch = dispatch.clone();
 
         let mut first = use_state(|| true);
         use_side_effect(move || {
@@ -57,6 +61,33 @@ impl Component for SettingsApis {
             dispatch.set_settings(next);
         });
 
+        let on_add_index = {
+            let dispatch = index_dispatch.clone();
+            move |_| {
+                dispatch.add_package_index(&new_index_url.read());
+                new_index_url.set(String::new());
+            }
+        };
+        let indexes = package_indexes.into_iter().map(|index| {
+            let dispatch = index_dispatch.clone();
+            let id = index.id.clone();
+            settings_row(
+                IconType::Globe01,
+                "Package Index",
+                index.url,
+                Button::new()
+                    .ghost()
+                    .icon()
+                    .on_press(move |_| dispatch.remove_package_index(&id))
+                    .child(
+                        Icon::new(IconType::Trash01)
+                            .size(18.)
+                            .color(colors::fg_secondary()),
+                    ),
+            )
+            .into_element()
+        });
+
         settings_page()
             .child(section_header("PROVIDERS"))
             .child(settings_row(
@@ -75,6 +106,22 @@ impl Component for SettingsApis {
                     .placeholder("Default")
                     .width(Size::px(220.)),
             ))
+            .children(indexes)
+            .child(settings_row(
+                IconType::Globe01,
+                "Add Package Index",
+                "URL of a self-hosted JSON index searched alongside Modrinth and CurseForge.",
+                rect()
+                    .horizontal()
+                    .spacing(8.)
+                    .cross_align(Alignment::Center)
+                    .child(
+                        TextInput::new(new_index_url)
+                            .placeholder("https://")
+                            .width(Size::px(220.)),
+                    )
+                    .child(Button::new().secondary().on_press(on_add_index).text("Add")),
+            ))
             .child(section_header("ADVANCED"))
             .child(settings_row(
                 IconType::Globe01,
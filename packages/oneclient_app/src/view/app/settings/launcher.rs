@@ -1,7 +1,7 @@
 use freya::prelude::*;
 
 use super::settings_page;
-use crate::components::{IconType, link_button, toggle};
+use crate::components::{IconType, TextInput, link_button, toggle};
 use crate::hooks::{use_dispatch, use_launcher, use_settings_snapshot};
 use crate::platform;
 use crate::view::app::settings::{section_header, settings_row};
@@ -25,12 +25,27 @@ impl Component for SettingsLauncher {
             move || v
         });
 
+        let world_snapshots = use_state({
+            let v = settings.world_snapshots.enabled;
+            move || v
+        });
+
+        let download_limit = use_state({
+            let v = settings
+                .download_limit_kib
+                .map(|kib| kib.to_string())
+                .unwrap_or_default();
+            move || v
+        });
+
         let mut first = use_state(|| true);
         {
             let settings = settings.clone();
             use_side_effect(move || {
                 let discord = *discord_rpc.read();
                 let crash = *crash_reporting.read();
+                let snapshots = *world_snapshots.read();
+                let limit = download_limit.read().clone();
                 if *first.peek() {
                     first.set(false);
                     return;
@@ -38,6 +53,9 @@ impl Component for SettingsLauncher {
                 let mut next = settings.clone();
                 next.discord_enabled = discord;
                 next.crash_reporting = crash;
+                next.world_snapshots.enabled = snapshots;
+                // Anything but a positive number lifts the limit
+                next.download_limit_kib = limit.trim().parse().ok().filter(|kib| *kib > 0);
                 dispatch.set_settings(next);
             });
         }
@@ -59,6 +77,21 @@ impl Component for SettingsLauncher {
                 "Send anonymous crash and error reports to help fix bugs. Applies on restart.",
                 toggle(crash_reporting),
             ))
+            .child(settings_row(
+                IconType::ClockRewind,
+                "World Snapshots",
+                "Snapshot every world you played in when the game closes. Files that did not change are stored once.",
+                toggle(world_snapshots),
+            ))
+            .child(section_header("DOWNLOADS"))
+            .child(settings_row(
+                IconType::Download01,
+                "Download Limit",
+                "Cap in KB/s shared by all downloads. Background updates wait for your own installs.",
+                TextInput::new(download_limit)
+                    .placeholder("Unlimited")
+                    .width(Size::px(220.)),
+            ))
             .child(section_header("FOLDERS AND FILES"))
             .child(settings_row(
                 IconType::Folder,
@@ -55,6 +55,16 @@ impl Component for SettingsStorage {
                     empty: report.legacy_cluster_content.is_empty(),
                 }
                 .into_element(),
+            )
+            .child(
+                ReclaimRow {
+                    icon: IconType::CodeSnippet02,
+                    title: "Unused Java runtimes",
+                    description: unused_java_description(&report.unused_java_runtimes),
+                    action: StorageAction::CleanUnusedJava,
+                    empty: report.unused_java_runtimes.is_empty(),
+                }
+                .into_element(),
             );
 
         page = page.child(section_header("WHAT'S USING SPACE"));
@@ -86,7 +96,9 @@ impl Component for SettingsStorage {
 }
 
 fn hero(report: &StorageReport, refresh: Element) -> impl IntoElement {
-    let reclaimable = report.unreferenced_cache.bytes + report.legacy_cluster_content.bytes;
+    let reclaimable = report.unreferenced_cache.bytes
+        + report.legacy_cluster_content.bytes
+        + report.unused_java_runtimes.bytes;
 
     let subtitle = if reclaimable > 0 {
         format!("{} can be freed", format_bytes(reclaimable))
@@ -292,6 +304,19 @@ fn legacy_content_description(entry: &ReclaimableEntry) -> String {
     )
 }
 
+fn unused_java_description(entry: &ReclaimableEntry) -> String {
+    if entry.is_empty() {
+        return "Nothing here — every Java the launcher installed is still in use.".to_string();
+    }
+
+    format!(
+        "{} in {} runtime{} the launcher installed that no cluster uses any more.",
+        format_bytes(entry.bytes),
+        entry.files,
+        plural(entry.files)
+    )
+}
+
 fn empty_note(text: &'static str) -> impl IntoElement {
     rect()
         .width(Size::fill())
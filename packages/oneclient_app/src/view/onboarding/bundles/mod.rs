@@ -450,7 +450,7 @@ fn extras_section(
             let meta = match entry.provider {
                 ProviderId::Modrinth => mr_meta.get(&entry.package_id),
                 ProviderId::CurseForge => cf_meta.get(&entry.package_id),
-                ProviderId::Local => None,
+                ProviderId::Local | ProviderId::SelfHosted => None,
             };
             let name = meta
                 .map(|m| m.name.clone())
@@ -527,7 +527,7 @@ fn collect_ids(extras: &[OptionalMod]) -> (Vec<String>, Vec<String>) {
         match entry.provider {
             ProviderId::Modrinth => mr.push(entry.package_id.clone()),
             ProviderId::CurseForge => cf.push(entry.package_id.clone()),
-            ProviderId::Local => {}
+            ProviderId::Local | ProviderId::SelfHosted => {}
         }
     }
     (mr, cf)
@@ -7,6 +7,9 @@ use uuid::Uuid;
 pub enum AccountKind {
     Microsoft,
     Offline,
+    /// Signed in against a third-party Yggdrasil server (Drasl Ely.by
+    /// blessing-skin) and launched through authlib-injector
+    Yggdrasil,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +21,9 @@ pub struct MinecraftAccount {
     pub expires: DateTime<Utc>,
     #[serde(default = "default_account_kind")]
     pub kind: AccountKind,
+    /// API root of the Yggdrasil server only set for [`AccountKind::Yggdrasil`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_server: Option<String>,
 }
 
 fn default_account_kind() -> AccountKind {
@@ -33,6 +39,10 @@ impl MinecraftAccount {
         self.kind == AccountKind::Offline
     }
 
+    pub fn is_yggdrasil(&self) -> bool {
+        self.kind == AccountKind::Yggdrasil
+    }
+
     pub fn is_expired(&self) -> bool {
         self.expires <= Utc::now() + chrono::TimeDelta::seconds(60)
     }
@@ -121,4 +121,26 @@ pub enum AuthError {
 
     #[error("account already exists for username {username:?}")]
     DuplicateUsername { username: String },
+
+    #[error("{server}: {message}")]
+    Yggdrasil { server: String, message: String },
+
+    /// Authlib servers often reuse Mojang's UUIDs so a profile from one service
+    /// can carry the id of an account already signed in through another
+    #[error("{username} is already signed in through another service with the same profile id")]
+    AccountIdInUse { username: String },
+
+    /// The key is wrapped with a passphrase and nothing has unlocked it yet
+    #[error("stored accounts are locked; enter the credentials passphrase to unlock them")]
+    CredentialsLocked,
+
+    /// `auth.json` is sealed but `auth.key` is gone so nothing can open it
+    #[error("the key for the stored accounts is missing")]
+    CredentialsKeyMissing,
+
+    #[error("the credentials passphrase is incorrect")]
+    WrongPassphrase,
+
+    #[error("stored credentials could not be read: {0}")]
+    CorruptCredentials(String),
 }
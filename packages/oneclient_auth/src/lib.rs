@@ -1,6 +1,6 @@
 //! Construct [`AuthService`] in the composition layer and pass it down nothing
 //! in here reaches for a global or a database
-//! Accounts persist to `auth.json`
+//! Accounts persist to `auth.json` encrypted under the key in `auth.key`
 
 mod data;
 mod diagnostics;
@@ -9,6 +9,8 @@ mod msa;
 mod offline;
 mod service;
 mod store;
+mod vault;
+mod yggdrasil;
 
 pub use data::{
 	AccountKind, BrowserLogin, DeviceCodeLogin, MicrosoftLoginSession, MinecraftAccount,
@@ -19,3 +21,7 @@ pub use msa::PendingBrowserLogin;
 pub use offline::{offline_account, offline_uuid, validate_offline_username};
 pub use service::{AuthService, MICROSOFT_LOGIN_PROGRESS};
 pub use store::CredentialsStore;
+pub use vault::{
+	credentials_locked, has_credentials_passphrase, lock_credentials, set_credentials_passphrase,
+	unlock_credentials,
+};
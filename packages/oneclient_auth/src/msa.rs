@@ -546,6 +546,7 @@ async fn account_from_msa_token(
         #[allow(clippy::cast_possible_wrap)]
         expires: msa.obtained_at + chrono::TimeDelta::seconds(msa.expires_in as i64),
         kind: AccountKind::Microsoft,
+        auth_server: None,
     })
 }
 
@@ -50,5 +50,6 @@ pub fn offline_account(username: String) -> MinecraftAccount {
         refresh_token: String::new(),
         expires: Utc::now() + Duration::days(3650),
         kind: AccountKind::Offline,
+        auth_server: None,
     }
 }
@@ -7,10 +7,11 @@ use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::data::{MicrosoftLoginSession, MinecraftAccount};
+use crate::data::{AccountKind, MicrosoftLoginSession, MinecraftAccount};
 use crate::error::{AuthError, AuthResult, MinecraftAuthError};
 use crate::msa::{self, PendingBrowserLogin};
 use crate::store::{self, CredentialsStore};
+use crate::yggdrasil;
 
 /// Stable id for the Microsoft login's progress so a front-end can recognise
 /// it among everything else on the bus
@@ -191,6 +192,27 @@ impl AuthService {
 			.await
 	}
 
+	/// `server` may be the API root or any page advertising it the resolved
+	/// root is what gets stored
+	#[tracing::instrument(skip(self, password), fields(%server, %username))]
+	pub async fn add_yggdrasil_account(
+		&self,
+		server: &str,
+		username: &str,
+		password: &str,
+	) -> AuthResult<MinecraftAccount> {
+		let client = self.net.http();
+		let api_root = yggdrasil::resolve_api_root(client, server).await?;
+		let account = yggdrasil::authenticate(client, &api_root, username, password).await?;
+		tracing::info!(username = %account.username, %api_root, "Yggdrasil login succeeded");
+
+		self.store
+			.lock()
+			.await
+			.commit_account(account, &self.events)
+			.await
+	}
+
 	pub async fn list_accounts(&self) -> Vec<MinecraftAccount> {
 		self.store.lock().await.list_accounts()
 	}
@@ -238,12 +260,29 @@ impl AuthService {
 			.ok_or(AuthError::AccountNotFound(id))
 	}
 
-	/// The store lock is never held across the Microsoft handshake only around
-	/// the reads and the final write
+	/// A Yggdrasil token carries no expiry so the server is asked instead
+	/// Not reaching it keeps the token the game can still start offline
+	async fn needs_renewal(&self, account: &MinecraftAccount, force: bool) -> bool {
+		match account.kind {
+			AccountKind::Offline => false,
+			AccountKind::Microsoft => force || account.is_expired(),
+			AccountKind::Yggdrasil if force => true,
+			AccountKind::Yggdrasil => match yggdrasil::validate(self.net.http(), account).await {
+				Ok(valid) => !valid,
+				Err(err) => {
+					tracing::warn!("could not validate Yggdrasil token: {err}");
+					!store::is_transient_auth_error(&err)
+				}
+			},
+		}
+	}
+
+	/// The store lock is never held across the handshake only around the reads
+	/// and the final write
 	#[tracing::instrument(level = "debug", skip(self), fields(%id))]
 	async fn renew_token(&self, id: Uuid, force: bool) -> AuthResult<MinecraftAccount> {
 		let existing = self.account_snapshot(id).await?;
-		if !existing.is_microsoft() || (!force && !existing.is_expired()) {
+		if !self.needs_renewal(&existing, force).await {
 			return Ok(existing);
 		}
 
@@ -251,13 +290,21 @@ impl AuthService {
 		let _serialised = guard.lock().await;
 
 		// Re-read under the guard whoever held it may have just refreshed
-		let existing = self.account_snapshot(id).await?;
-		if !existing.is_microsoft() || (!force && !existing.is_expired()) {
-			return Ok(existing);
+		let current = self.account_snapshot(id).await?;
+		if current.access_token != existing.access_token {
+			return Ok(current);
 		}
 
-		tracing::info!(username = %existing.username, "renewing Microsoft access token");
-		match msa::refresh_microsoft_account(self.net.http(), &existing).await {
+		tracing::info!(username = %current.username, kind = ?current.kind, "renewing access token");
+		let renewed = match current.kind {
+			AccountKind::Microsoft => msa::refresh_microsoft_account(self.net.http(), &current)
+				.await
+				.map_err(AuthError::from),
+			AccountKind::Yggdrasil => yggdrasil::refresh(self.net.http(), &current).await,
+			AccountKind::Offline => return Ok(current),
+		};
+
+		match renewed {
 			Ok(refreshed) => {
 				self.store
 					.lock()
@@ -267,10 +314,9 @@ impl AuthService {
 				Ok(refreshed)
 			}
 			Err(err) => {
-				let err = AuthError::from(err);
 				if store::is_transient_auth_error(&err) {
 					tracing::warn!("keeping existing token after transient renewal failure: {err}");
-					Ok(existing)
+					Ok(current)
 				} else {
 					Err(err)
 				}
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use uuid::Uuid;
 
@@ -8,14 +10,41 @@ use oneclient_events::EventBus;
 use crate::data::{AccountKind, MinecraftAccount};
 use crate::error::{AuthError, AuthResult};
 use crate::offline::{offline_account, validate_offline_username};
+use crate::vault::{self, Opened};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Default)]
 pub struct CredentialsStore {
     pub users: HashMap<Uuid, MinecraftAccount>,
     pub default_user: Option<Uuid>,
+    /// The sealed file while its key waits on a passphrase the store reads as
+    /// empty and refuses every write until [`crate::unlock_credentials`]
+    #[serde(skip)]
+    locked: Option<LockedFile>,
+}
+
+#[derive(Debug)]
+struct LockedFile {
+    sealed: Vec<u8>,
+    /// Filled by the first read after the unlock writes swap it in
+    opened: OnceLock<Box<CredentialsStore>>,
+}
+
+impl LockedFile {
+    /// `CredentialsLocked` while the key is still wrapped
+    fn open(&self) -> AuthResult<CredentialsStore> {
+        let key = vault::unlocked_key()?.ok_or(AuthError::CredentialsLocked)?;
+        // A plaintext file is sealed by the next save
+        match vault::open(&key, &self.sealed)? {
+            Opened::Sealed(store) | Opened::Plaintext(store) => Ok(store),
+        }
+    }
 }
 
 impl CredentialsStore {
+    /// A file from before encryption is sealed on the spot
+    /// Locked credentials load as a locked store which opens by itself once
+    /// they are unlocked
+    /// A file that can never open is moved aside before starting empty
     #[tracing::instrument(level = "debug")]
     pub async fn load() -> AuthResult<Self> {
         let path = paths::auth_file()?;
@@ -23,35 +52,104 @@ impl CredentialsStore {
             return Ok(Self::default());
         }
 
-        match polyio::read_json(&path).await {
-            Ok(store) => Ok(store),
-            Err(err) => {
-                tracing::warn!("failed to read auth file: {err}");
+        let bytes = polyio::read(&path).await?;
+        let opened = match vault::data_key().await {
+            Ok(key) => vault::open(&key, &bytes),
+            Err(err @ AuthError::CredentialsKeyMissing) => Err(err),
+            Err(AuthError::CredentialsLocked) => {
+                tracing::info!("stored accounts are locked until the passphrase is entered");
+                return Ok(Self {
+                    locked: Some(LockedFile {
+                        sealed: bytes,
+                        opened: OnceLock::new(),
+                    }),
+                    ..Self::default()
+                });
+            }
+            Err(err) => return Err(err),
+        };
+
+        match opened {
+            Ok(Opened::Sealed(store)) => Ok(store),
+            Ok(Opened::Plaintext(store)) => {
+                tracing::info!("encrypting plaintext auth file");
+                store.save().await?;
+                Ok(store)
+            }
+            Err(err @ (AuthError::CredentialsKeyMissing | AuthError::CorruptCredentials(_))) => {
+                let aside = set_aside(&path).await?;
+                tracing::warn!(
+                    "failed to read auth file kept it as {}: {err}",
+                    aside.display()
+                );
                 Ok(Self::default())
             }
+            Err(err) => Err(err),
         }
     }
 
+    /// Writing while locked would replace every account with an empty store
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn save(&self) -> AuthResult<()> {
+        if self.locked.is_some() {
+            return Err(AuthError::CredentialsLocked);
+        }
         let path = paths::auth_file()?;
+        let key = vault::data_key().await?;
 
-        polyio::write_json_atomic(&path, self).await?;
+        polyio::write_atomic(&path, vault::seal(&key, self)?).await?;
+        vault::restrict_permissions(&path).await;
+        Ok(())
+    }
+
+    /// Swaps a locked store for the real one once its key is unlocked
+    /// `CredentialsLocked` while it still is
+    fn reopen(&mut self) -> AuthResult<()> {
+        let Some(locked) = &mut self.locked else {
+            return Ok(());
+        };
+        let store = match locked.opened.take() {
+            Some(store) => *store,
+            None => locked.open()?,
+        };
+        *self = store;
+        tracing::info!("opened the unlocked credentials");
         Ok(())
     }
 
+    /// What the getters read a locked store reads as empty until its key is
+    /// unlocked a file that then fails to open is logged since
+    /// [`crate::unlock_credentials`] already refuses one
+    fn readable(&self) -> &Self {
+        let Some(locked) = &self.locked else {
+            return self;
+        };
+        if let Some(opened) = locked.opened.get() {
+            return opened;
+        }
+        match locked.open() {
+            Ok(store) => &**locked.opened.get_or_init(|| Box::new(store)),
+            Err(AuthError::CredentialsLocked) => self,
+            Err(err) => {
+                tracing::error!("failed to open the unlocked credentials: {err}");
+                self
+            }
+        }
+    }
+
     pub fn has_microsoft_account(&self) -> bool {
-        self.users
+        self.readable()
+            .users
             .values()
             .any(|account| account.kind == AccountKind::Microsoft)
     }
 
     pub fn list_accounts(&self) -> Vec<MinecraftAccount> {
-        self.users.values().cloned().collect()
+        self.readable().users.values().cloned().collect()
     }
 
     pub fn get_account(&self, id: Uuid) -> Option<&MinecraftAccount> {
-        self.users.get(&id)
+        self.readable().users.get(&id)
     }
 
     #[tracing::instrument(level = "debug", skip_all, fields(username = %account.username))]
@@ -60,6 +158,17 @@ impl CredentialsStore {
         account: MinecraftAccount,
         events: &EventBus,
     ) -> AuthResult<MinecraftAccount> {
+        self.reopen()?;
+        // Signing in to the same account again replaces it one from another
+        // service would silently take over its slot
+        if let Some(existing) = self.users.get(&account.id)
+            && (existing.kind != account.kind || existing.auth_server != account.auth_server)
+        {
+            return Err(AuthError::AccountIdInUse {
+                username: existing.username.clone(),
+            });
+        }
+
         self.users.insert(account.id, account.clone());
 
         if self.default_user.is_none() {
@@ -91,6 +200,7 @@ impl CredentialsStore {
     }
 
     fn insert_offline_account(&mut self, username: String) -> AuthResult<MinecraftAccount> {
+        self.reopen()?;
         if !self.has_microsoft_account() {
             return Err(AuthError::OfflineRequiresMicrosoft);
         }
@@ -115,6 +225,7 @@ impl CredentialsStore {
         &mut self,
         account: MinecraftAccount,
     ) -> AuthResult<()> {
+        self.reopen()?;
         self.users.insert(account.id, account);
         self.save().await?;
         tracing::debug!("stored refreshed Microsoft account");
@@ -123,6 +234,7 @@ impl CredentialsStore {
 
     #[tracing::instrument(level = "debug", skip(self), fields(%id))]
     pub async fn remove_account(&mut self, id: Uuid) -> AuthResult<Option<MinecraftAccount>> {
+        self.reopen()?;
         let removed = self.users.remove(&id);
 
         if self.default_user == Some(id) {
@@ -135,6 +247,7 @@ impl CredentialsStore {
 
     #[tracing::instrument(level = "debug", skip(self), fields(?id))]
     pub async fn set_default_user(&mut self, id: Option<Uuid>) -> AuthResult<()> {
+        self.reopen()?;
         if let Some(id) = id
             && !self.users.contains_key(&id)
         {
@@ -148,6 +261,7 @@ impl CredentialsStore {
 
     #[tracing::instrument(level = "debug", skip_all)]
     pub async fn resolve_default_id(&mut self) -> AuthResult<Option<Uuid>> {
+        self.reopen()?;
         let id = self
             .default_user
             .or_else(|| self.users.keys().copied().next());
@@ -187,12 +301,24 @@ impl CredentialsStore {
     }
 }
 
+/// Renamed rather than deleted a restored `auth.key` can still open it
+async fn set_aside(path: &Path) -> AuthResult<PathBuf> {
+    let aside = path.with_extension(format!(
+        "json.unreadable-{}",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    polyio::rename(path, &aside).await?;
+    Ok(aside)
+}
+
 /// A transient failure must keep the existing token discarding it because
 /// Wi-Fi dropped would sign the user out of a working account
 pub(crate) fn is_transient_auth_error(err: &AuthError) -> bool {
-    matches!(
-        err,
-        AuthError::Minecraft(crate::error::MinecraftAuthError::RequestError { source, .. })
-            if source.is_connect() || source.is_timeout()
-    )
+    match err {
+        AuthError::Minecraft(crate::error::MinecraftAuthError::RequestError { source, .. }) => {
+            source.is_connect() || source.is_timeout()
+        }
+        AuthError::Request(source) => source.is_transient(),
+        _ => false,
+    }
 }
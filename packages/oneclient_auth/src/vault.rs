@@ -0,0 +1,416 @@
+//! `auth.json` is sealed with ChaCha20-Poly1305 under a random per-install key
+//! kept in `auth.key` beside it
+//! The key file alone is as good as the tokens so it can be wrapped with a
+//! passphrase (Argon2id) in which case nothing opens until
+//! [`unlock_credentials`] is called or `ONECLIENT_AUTH_PASSPHRASE` is set
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use oneclient_common::paths;
+
+use crate::error::{AuthError, AuthResult};
+
+const PASSPHRASE_ENV: &str = "ONECLIENT_AUTH_PASSPHRASE";
+
+const FORMAT_VERSION: u32 = 1;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+/// Binds each ciphertext to what it is so a sealed key cannot be passed off
+/// as a sealed store or the other way round
+const STORE_AAD: &[u8] = b"oneclient/auth.json/v1";
+const KEY_AAD: &[u8] = b"oneclient/auth.key/v1";
+
+/// Keys unwrapped by a passphrase this process keyed by the key file so an
+/// unlock never leaks into another launcher directory
+static UNLOCKED: LazyLock<Mutex<HashMap<PathBuf, DataKey>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+type DataKey = [u8; KEY_LEN];
+
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    version: u32,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum KeyFile {
+    Plain {
+        key: String,
+    },
+    /// `sealed` is the data key under a key derived from the passphrase
+    Passphrase {
+        salt: String,
+        sealed: Sealed,
+    },
+}
+
+pub(crate) enum Opened<T> {
+    Sealed(T),
+    /// A file from before encryption the caller re-saves it sealed
+    Plaintext(T),
+}
+
+pub(crate) fn seal<T: Serialize>(key: &DataKey, value: &T) -> AuthResult<Vec<u8>> {
+    let plaintext = serde_json::to_vec(value).map_err(corrupt)?;
+    let sealed = seal_bytes(key, &plaintext, STORE_AAD)?;
+    serde_json::to_vec(&sealed).map_err(corrupt)
+}
+
+pub(crate) fn open<T: DeserializeOwned>(key: &DataKey, bytes: &[u8]) -> AuthResult<Opened<T>> {
+    if let Ok(sealed) = serde_json::from_slice::<Sealed>(bytes) {
+        let plaintext = open_bytes(key, &sealed, STORE_AAD)?;
+        return serde_json::from_slice(&plaintext)
+            .map(Opened::Sealed)
+            .map_err(corrupt);
+    }
+
+    serde_json::from_slice(bytes)
+        .map(Opened::Plaintext)
+        .map_err(corrupt)
+}
+
+/// Created on first use a wrapped key comes from the unlock cache or the
+/// environment never from a prompt
+/// Never created while a sealed `auth.json` exists a new key cannot open it
+/// and the next save would replace every account
+pub(crate) async fn data_key() -> AuthResult<DataKey> {
+    let path = paths::auth_key_file()?;
+
+    loop {
+        let Some(file) = read_key_file(&path).await? else {
+            if store_is_sealed().await? {
+                return Err(AuthError::CredentialsKeyMissing);
+            }
+            match create_key(&path).await? {
+                Some(key) => return Ok(key),
+                // Another caller created it first read theirs
+                None => continue,
+            }
+        };
+
+        return match file {
+            KeyFile::Plain { key } => decode_key(&key),
+            KeyFile::Passphrase { salt, sealed } => {
+                if let Some(key) = cached(&path) {
+                    return Ok(key);
+                }
+
+                let passphrase =
+                    std::env::var(PASSPHRASE_ENV).map_err(|_| AuthError::CredentialsLocked)?;
+                let key = unwrap_key(&passphrase, &salt, &sealed)?;
+                remember(&path, key);
+                Ok(key)
+            }
+        };
+    }
+}
+
+/// Staged then hard-linked into place which fails when the key already exists
+/// so racing first runs settle on one key `None` when this one lost
+async fn create_key(path: &Path) -> AuthResult<Option<DataKey>> {
+    let key = random::<KEY_LEN>();
+    let staged = path.with_extension(format!("key.{}", uuid::Uuid::new_v4()));
+    write_key_file(
+        &staged,
+        &KeyFile::Plain {
+            key: BASE64.encode(key),
+        },
+    )
+    .await?;
+
+    let linked = tokio::fs::hard_link(&staged, path).await;
+    let _ = polyio::remove_file(&staged).await;
+    match linked {
+        Ok(()) => {
+            tracing::info!("generated a new credentials key");
+            Ok(Some(key))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
+        Err(err) => Err(polyio::IOError::from(err).into()),
+    }
+}
+
+/// A plaintext file from before encryption still gets a fresh key
+async fn store_is_sealed() -> AuthResult<bool> {
+    let path = paths::auth_file()?;
+    if !polyio::try_exists(&path).await? {
+        return Ok(false);
+    }
+    let bytes = polyio::read(&path).await?;
+    Ok(serde_json::from_slice::<Sealed>(&bytes).is_ok())
+}
+
+/// `Ok(false)` also when there is nothing to unlock no key yet or one
+/// without a passphrase
+pub async fn credentials_locked() -> AuthResult<bool> {
+    let path = paths::auth_key_file()?;
+    Ok(matches!(
+        read_key_file(&path).await?,
+        Some(KeyFile::Passphrase { .. })
+    ) && cached(&path).is_none())
+}
+
+/// Whether starting the launcher will ask for a passphrase
+pub async fn has_credentials_passphrase() -> AuthResult<bool> {
+    let path = paths::auth_key_file()?;
+    Ok(matches!(
+        read_key_file(&path).await?,
+        Some(KeyFile::Passphrase { .. })
+    ))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn unlock_credentials(passphrase: &str) -> AuthResult<()> {
+    let path = paths::auth_key_file()?;
+    if let Some(KeyFile::Passphrase { salt, sealed }) = read_key_file(&path).await? {
+        let key = unwrap_key(passphrase, &salt, &sealed)?;
+        ensure_store_opens(&key).await?;
+        remember(&path, key);
+        tracing::info!("credentials unlocked");
+    }
+    Ok(())
+}
+
+/// Stores loaded while locked open lazily with nowhere to report a bad file
+/// so it is refused here instead
+async fn ensure_store_opens(key: &DataKey) -> AuthResult<()> {
+    let path = paths::auth_file()?;
+    if !polyio::try_exists(&path).await? {
+        return Ok(());
+    }
+    let bytes = polyio::read(&path).await?;
+    open::<serde::de::IgnoredAny>(key, &bytes)?;
+    Ok(())
+}
+
+/// A wrapped key this process has already unwrapped
+pub(crate) fn unlocked_key() -> AuthResult<Option<DataKey>> {
+    Ok(cached(&paths::auth_key_file()?))
+}
+
+/// Forgets an unlocked key the next load needs the passphrase again
+pub fn lock_credentials() -> AuthResult<()> {
+    let path = paths::auth_key_file()?;
+    if let Ok(mut unlocked) = UNLOCKED.lock() {
+        unlocked.remove(&path);
+    }
+    Ok(())
+}
+
+/// Re-wraps the same data key `auth.json` is untouched
+/// `None` removes the passphrase the store must be unlocked either way
+#[tracing::instrument(level = "debug", skip_all, fields(set = passphrase.is_some()))]
+pub async fn set_credentials_passphrase(passphrase: Option<&str>) -> AuthResult<()> {
+    let path = paths::auth_key_file()?;
+    let key = data_key().await?;
+
+    let file = match passphrase {
+        Some(passphrase) => {
+            let salt = random::<SALT_LEN>();
+            let kek = derive_key(passphrase, &salt)?;
+            KeyFile::Passphrase {
+                salt: BASE64.encode(salt),
+                sealed: seal_bytes(&kek, &key, KEY_AAD)?,
+            }
+        }
+        None => KeyFile::Plain {
+            key: BASE64.encode(key),
+        },
+    };
+
+    write_key_file(&path, &file).await?;
+    remember(&path, key);
+    tracing::info!("updated the credentials passphrase");
+    Ok(())
+}
+
+/// Owner-only where the platform has the notion
+pub(crate) async fn restrict_permissions(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(err) =
+            tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).await
+        {
+            tracing::warn!(path = %path.display(), "could not restrict permissions: {err}");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+async fn read_key_file(path: &Path) -> AuthResult<Option<KeyFile>> {
+    if !polyio::try_exists(path).await? {
+        return Ok(None);
+    }
+    let bytes = polyio::read(path).await?;
+    serde_json::from_slice(&bytes).map(Some).map_err(corrupt)
+}
+
+async fn write_key_file(path: &Path, file: &KeyFile) -> AuthResult<()> {
+    polyio::write_json_atomic(path, file).await?;
+    restrict_permissions(path).await;
+    Ok(())
+}
+
+fn unwrap_key(passphrase: &str, salt: &str, sealed: &Sealed) -> AuthResult<DataKey> {
+    let salt = BASE64.decode(salt).map_err(corrupt)?;
+    let kek = derive_key(passphrase, &salt)?;
+    let key = open_bytes(&kek, sealed, KEY_AAD).map_err(|_| AuthError::WrongPassphrase)?;
+    key.try_into()
+        .map_err(|_| AuthError::CorruptCredentials("wrapped key has the wrong length".into()))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> AuthResult<DataKey> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| AuthError::CorruptCredentials(err.to_string()))?;
+    Ok(key)
+}
+
+fn seal_bytes(key: &DataKey, plaintext: &[u8], aad: &[u8]) -> AuthResult<Sealed> {
+    let nonce = random::<NONCE_LEN>();
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| AuthError::CorruptCredentials("encryption failed".into()))?;
+
+    Ok(Sealed {
+        version: FORMAT_VERSION,
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+/// Fails the same way for a wrong key and for tampering the cipher cannot
+/// tell them apart
+fn open_bytes(key: &DataKey, sealed: &Sealed, aad: &[u8]) -> AuthResult<Vec<u8>> {
+    if sealed.version != FORMAT_VERSION {
+        return Err(AuthError::CorruptCredentials(format!(
+            "unknown format version {}",
+            sealed.version
+        )));
+    }
+
+    let nonce = BASE64.decode(&sealed.nonce).map_err(corrupt)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(AuthError::CorruptCredentials(
+            "nonce has the wrong length".into(),
+        ));
+    }
+    let ciphertext = BASE64.decode(&sealed.ciphertext).map_err(corrupt)?;
+
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| AuthError::CorruptCredentials("authentication failed".into()))
+}
+
+fn decode_key(encoded: &str) -> AuthResult<DataKey> {
+    BASE64
+        .decode(encoded)
+        .map_err(corrupt)?
+        .try_into()
+        .map_err(|_| AuthError::CorruptCredentials("key has the wrong length".into()))
+}
+
+fn cached(path: &Path) -> Option<DataKey> {
+    UNLOCKED.lock().ok()?.get(path).copied()
+}
+
+fn remember(path: &Path, key: DataKey) {
+    if let Ok(mut unlocked) = UNLOCKED.lock() {
+        unlocked.insert(path.to_path_buf(), key);
+    }
+}
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn corrupt(err: impl std::fmt::Display) -> AuthError {
+    AuthError::CorruptCredentials(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Secret {
+        token: String,
+    }
+
+    fn secret() -> Secret {
+        Secret {
+            token: "eyJhbGciOi.secret".into(),
+        }
+    }
+
+    #[test]
+    fn sealed_store_round_trips_and_hides_the_token() {
+        let key = random::<KEY_LEN>();
+        let bytes = seal(&key, &secret()).unwrap();
+
+        assert!(!String::from_utf8_lossy(&bytes).contains("secret"));
+        assert!(matches!(open::<Secret>(&key, &bytes), Ok(Opened::Sealed(s)) if s == secret()));
+    }
+
+    #[test]
+    fn a_tampered_or_foreign_file_does_not_open() {
+        let key = random::<KEY_LEN>();
+        let mut sealed: Sealed = serde_json::from_slice(&seal(&key, &secret()).unwrap()).unwrap();
+
+        let other = random::<KEY_LEN>();
+        assert!(
+            open_bytes(&other, &sealed, STORE_AAD).is_err(),
+            "another install's key"
+        );
+        assert!(
+            open_bytes(&key, &sealed, KEY_AAD).is_err(),
+            "sealed for another purpose"
+        );
+
+        let mut ciphertext = BASE64.decode(&sealed.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        sealed.ciphertext = BASE64.encode(ciphertext);
+        assert!(open_bytes(&key, &sealed, STORE_AAD).is_err(), "flipped bit");
+    }
+
+    #[test]
+    fn plaintext_json_is_recognised_for_migration() {
+        let key = random::<KEY_LEN>();
+        let legacy = serde_json::to_vec(&secret()).unwrap();
+
+        assert!(matches!(open::<Secret>(&key, &legacy), Ok(Opened::Plaintext(s)) if s == secret()));
+    }
+}
@@ -0,0 +1,255 @@
+//! The authserver half of the Yggdrasil API as implemented by authlib-injector
+//! compatible servers (Drasl Ely.by blessing-skin)
+//! The password is only ever sent to `authenticate` what is kept is the access
+//! token and the client token it is bound to
+
+use chrono::{Duration, Utc};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+use uuid::Uuid;
+
+use oneclient_net::RequestError;
+
+use crate::data::{AccountKind, MinecraftAccount};
+use crate::error::{AuthError, AuthResult};
+
+/// authlib-injector's API Location Indication a server may advertise its API
+/// root on any page so users can paste the homepage
+const API_LOCATION_HEADER: &str = "X-Authlib-Injector-API-Location";
+
+#[derive(Debug, Deserialize)]
+struct GameProfile {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenResponse {
+    access_token: String,
+    client_token: String,
+    #[serde(default)]
+    available_profiles: Vec<GameProfile>,
+    selected_profile: Option<GameProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    error_message: String,
+}
+
+/// Follows the ALI header once and trims the trailing slash so the stored root
+/// is stable however the user typed it
+#[tracing::instrument(level = "debug", skip(client))]
+pub async fn resolve_api_root(client: &Client, server: &str) -> AuthResult<String> {
+    let server = server.trim();
+    let with_scheme = if server.contains("://") {
+        server.to_string()
+    } else {
+        format!("https://{server}")
+    };
+    let url = Url::parse(&with_scheme).map_err(RequestError::from)?;
+
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(RequestError::from)?;
+    let root = match response
+        .headers()
+        .get(API_LOCATION_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(location) => url.join(location).map_err(RequestError::from)?,
+        None => url,
+    };
+
+    Ok(root.as_str().trim_end_matches('/').to_string())
+}
+
+#[tracing::instrument(level = "debug", skip(client, password))]
+pub async fn authenticate(
+    client: &Client,
+    api_root: &str,
+    username: &str,
+    password: &str,
+) -> AuthResult<MinecraftAccount> {
+    let client_token = Uuid::new_v4().simple().to_string();
+    let body = json!({
+        "agent": { "name": "Minecraft", "version": 1 },
+        "username": username,
+        "password": password,
+        "clientToken": client_token,
+        "requestUser": false,
+    });
+    let token: TokenResponse = post(client, api_root, "authenticate", &body).await?;
+
+    let token = match (&token.selected_profile, token.available_profiles.as_slice()) {
+        (Some(_), _) => token,
+        // Some servers leave binding to the launcher with one profile there is
+        // nothing to choose
+        (None, [only]) => {
+            let body = json!({
+                "accessToken": token.access_token,
+                "clientToken": token.client_token,
+                "selectedProfile": { "id": only.id, "name": only.name },
+            });
+            post(client, api_root, "refresh", &body).await?
+        }
+        (None, []) => {
+            return Err(rejected(api_root, "this account has no Minecraft profile"));
+        }
+        (None, _) => {
+            return Err(rejected(
+                api_root,
+                "this account has several profiles; select one on the server's website first",
+            ));
+        }
+    };
+
+    account_from_token(api_root, token)
+}
+
+/// The server may rotate the access token and invalidate the old one so the
+/// result must replace the stored account
+#[tracing::instrument(level = "debug", skip_all, fields(username = %account.username))]
+pub async fn refresh(client: &Client, account: &MinecraftAccount) -> AuthResult<MinecraftAccount> {
+    let api_root = api_root(account)?;
+    let body = json!({
+        "accessToken": account.access_token,
+        "clientToken": account.refresh_token,
+    });
+    let token: TokenResponse = post(client, api_root, "refresh", &body).await?;
+
+    let mut refreshed = account_from_token(api_root, token)?;
+    refreshed.id = account.id;
+    Ok(refreshed)
+}
+
+/// `Ok(false)` means the server no longer accepts the token
+#[tracing::instrument(level = "debug", skip_all, fields(username = %account.username))]
+pub async fn validate(client: &Client, account: &MinecraftAccount) -> AuthResult<bool> {
+    let api_root = api_root(account)?;
+    let response = client
+        .post(endpoint(api_root, "validate"))
+        .json(&json!({
+            "accessToken": account.access_token,
+            "clientToken": account.refresh_token,
+        }))
+        .send()
+        .await
+        .map_err(RequestError::from)?;
+
+    let status = response.status();
+    if status.is_success() || status.is_client_error() {
+        return Ok(status.is_success());
+    }
+
+    // A server that is down says nothing about the token
+    let url = response.url().to_string();
+    let bytes = response.bytes().await.map_err(RequestError::from)?;
+    Err(RequestError::HttpStatus {
+        status: status.as_u16(),
+        url,
+        snippet: snippet(&bytes),
+    }
+    .into())
+}
+
+fn api_root(account: &MinecraftAccount) -> AuthResult<&str> {
+    account
+        .auth_server
+        .as_deref()
+        .ok_or_else(|| AuthError::Yggdrasil {
+            server: account.username.clone(),
+            message: "the account has no authentication server".into(),
+        })
+}
+
+fn endpoint(api_root: &str, action: &str) -> String {
+    format!("{api_root}/authserver/{action}")
+}
+
+async fn post(
+    client: &Client,
+    api_root: &str,
+    action: &str,
+    body: &serde_json::Value,
+) -> AuthResult<TokenResponse> {
+    let url = endpoint(api_root, action);
+    let response = client
+        .post(&url)
+        .json(body)
+        .send()
+        .await
+        .map_err(RequestError::from)?;
+    let status = response.status();
+    let bytes = response.bytes().await.map_err(RequestError::from)?;
+
+    if status.is_success() {
+        return serde_json::from_slice(&bytes).map_err(|source| {
+            RequestError::DeserializeError {
+                source,
+                type_name: "TokenResponse".into(),
+                url,
+                status: status.as_u16(),
+                snippet: snippet(&bytes),
+            }
+            .into()
+        });
+    }
+
+    // Yggdrasil errors are JSON with a human message anything else is the
+    // server being down or not an auth server at all
+    match serde_json::from_slice::<ErrorResponse>(&bytes) {
+        Ok(err) if !err.error_message.is_empty() => Err(rejected(api_root, &err.error_message)),
+        Ok(err) if !err.error.is_empty() => Err(rejected(api_root, &err.error)),
+        _ if status == StatusCode::NOT_FOUND => Err(rejected(
+            api_root,
+            "this address is not a Yggdrasil authentication server",
+        )),
+        _ => Err(RequestError::HttpStatus {
+            status: status.as_u16(),
+            url,
+            snippet: snippet(&bytes),
+        }
+        .into()),
+    }
+}
+
+fn account_from_token(api_root: &str, token: TokenResponse) -> AuthResult<MinecraftAccount> {
+    let profile = token
+        .selected_profile
+        .ok_or_else(|| rejected(api_root, "the server did not select a profile"))?;
+    let id = Uuid::parse_str(&profile.id)
+        .map_err(|_| rejected(api_root, "the server returned an invalid profile id"))?;
+
+    Ok(MinecraftAccount {
+        id,
+        username: profile.name,
+        access_token: token.access_token,
+        // Yggdrasil has no refresh token the client token is what renewal needs
+        refresh_token: token.client_token,
+        // The server decides when a token dies renewal asks it via `validate`
+        expires: Utc::now() + Duration::days(3650),
+        kind: AccountKind::Yggdrasil,
+        auth_server: Some(api_root.to_string()),
+    })
+}
+
+fn snippet(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).chars().take(240).collect()
+}
+
+fn rejected(api_root: &str, message: &str) -> AuthError {
+    AuthError::Yggdrasil {
+        server: api_root.to_string(),
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,135 @@
+use oneclient_auth::{
+    AccountKind, AuthError, AuthService, CredentialsStore, credentials_locked, lock_credentials,
+    offline_account, set_credentials_passphrase, unlock_credentials,
+};
+use oneclient_events::EventBus;
+use oneclient_net::{NetConfig, RequestClient};
+use uuid::Uuid;
+
+// The launcher dir is set once per process so everything touching disk runs
+// as one test in order
+#[tokio::test]
+async fn credentials_are_sealed_migrated_and_locked() {
+    let dir = std::env::temp_dir().join(format!("oneclient-auth-test-{}", Uuid::new_v4()));
+    oneclient_common::paths::set_launcher_dir(dir.clone());
+    let auth_file = dir.join("auth.json");
+
+    // A file written before encryption existed
+    let mut legacy = CredentialsStore::default();
+    let mut account = offline_account("MsaUser".into());
+    account.kind = AccountKind::Microsoft;
+    account.access_token = "legacy-access-token".into();
+    account.refresh_token = "legacy-refresh-token".into();
+    legacy.default_user = Some(account.id);
+    legacy.users.insert(account.id, account.clone());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(&auth_file, serde_json::to_vec(&legacy).unwrap()).unwrap();
+
+    let migrated = CredentialsStore::load().await.unwrap();
+    assert_eq!(
+        migrated.get_account(account.id).unwrap().access_token,
+        "legacy-access-token"
+    );
+    let on_disk = std::fs::read_to_string(&auth_file).unwrap();
+    assert!(
+        !on_disk.contains("legacy-access-token") && !on_disk.contains("MsaUser"),
+        "loading a plaintext file seals it"
+    );
+
+    let reloaded = CredentialsStore::load().await.unwrap();
+    assert_eq!(reloaded.default_user, Some(account.id));
+    assert_eq!(
+        reloaded.get_account(account.id).unwrap().refresh_token,
+        "legacy-refresh-token"
+    );
+
+    // Wrapping the key leaves the store as it is but needs the passphrase
+    assert!(!credentials_locked().await.unwrap());
+    set_credentials_passphrase(Some("correct horse"))
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&auth_file).unwrap(), on_disk);
+    assert!(
+        !std::fs::read_to_string(dir.join("auth.key"))
+            .unwrap()
+            .contains("\"key\""),
+        "the raw key is gone once wrapped"
+    );
+
+    lock_credentials().unwrap();
+    assert!(credentials_locked().await.unwrap());
+    let locked = CredentialsStore::load().await.unwrap();
+    assert!(locked.list_accounts().is_empty());
+    assert!(matches!(locked.save().await, Err(AuthError::CredentialsLocked)));
+    assert_eq!(
+        std::fs::read_to_string(&auth_file).unwrap(),
+        on_disk,
+        "a locked store never writes over the sealed file"
+    );
+    assert!(matches!(
+        unlock_credentials("wrong horse").await,
+        Err(AuthError::WrongPassphrase)
+    ));
+    assert!(credentials_locked().await.unwrap());
+
+    // The launcher still starts with the accounts out of reach and the same
+    // service sees them once unlocked
+    let (events, _) = EventBus::channel();
+    let net = RequestClient::new(NetConfig::default()).unwrap();
+    let service = AuthService::load(net, events).await.unwrap();
+    assert!(service.list_accounts().await.is_empty());
+    assert!(matches!(
+        service.default_account().await,
+        Err(AuthError::CredentialsLocked)
+    ));
+    unlock_credentials("correct horse").await.unwrap();
+    assert!(!credentials_locked().await.unwrap());
+    assert_eq!(
+        service.default_account().await.unwrap().map(|a| a.id),
+        Some(account.id)
+    );
+    assert_eq!(service.list_accounts().await.len(), 1);
+
+    let unlocked = CredentialsStore::load().await.unwrap();
+    assert_eq!(unlocked.list_accounts().len(), 1);
+
+    // Removing the passphrase needs no unlock afterwards
+    set_credentials_passphrase(None).await.unwrap();
+    lock_credentials().unwrap();
+    assert!(!credentials_locked().await.unwrap());
+    assert_eq!(
+        CredentialsStore::load()
+            .await
+            .unwrap()
+            .list_accounts()
+            .len(),
+        1
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&auth_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Without its key the sealed file is kept aside never overwritten
+    let sealed = std::fs::read(&auth_file).unwrap();
+    std::fs::remove_file(dir.join("auth.key")).unwrap();
+    let fresh = CredentialsStore::load().await.unwrap();
+    assert!(fresh.list_accounts().is_empty());
+    assert!(!auth_file.exists() && !dir.join("auth.key").exists());
+    let aside = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().contains("auth.json.unreadable-"))
+        .expect("the unreadable file is renamed");
+    assert_eq!(std::fs::read(aside).unwrap(), sealed);
+
+    // Two first saves racing each other agree on one key
+    let empty = CredentialsStore::default();
+    let (a, b) = tokio::join!(empty.save(), empty.save());
+    a.unwrap();
+    b.unwrap();
+    assert!(CredentialsStore::load().await.is_ok());
+}
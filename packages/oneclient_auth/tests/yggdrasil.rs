@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use oneclient_auth::{AccountKind, AuthError, AuthService, CredentialsStore};
+use oneclient_events::EventBus;
+use oneclient_net::{NetConfig, RequestClient};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use uuid::Uuid;
+
+const PROFILE_ID: &str = "8667ba71b85a4004af54457a9734eed7";
+const PASSWORD: &str = "hunter2";
+
+/// Just enough of a Yggdrasil server the homepage advertises the API root
+/// through the ALI header and `authenticate` leaves profile binding to the
+/// client like some servers do
+#[derive(Clone, Default)]
+struct MockServer {
+    valid_tokens: Arc<Mutex<HashSet<String>>>,
+}
+
+impl MockServer {
+    async fn start() -> (Self, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = Self::default();
+
+        let handler = server.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move { handler.serve(stream).await });
+            }
+        });
+
+        (server, base)
+    }
+
+    fn revoke_all(&self) {
+        self.valid_tokens.lock().unwrap().clear();
+    }
+
+    fn issue_token(&self) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        self.valid_tokens.lock().unwrap().insert(token.clone());
+        token
+    }
+
+    fn token_is_valid(&self, body: &Value) -> bool {
+        let token = body["accessToken"].as_str().unwrap_or_default();
+        self.valid_tokens.lock().unwrap().contains(token)
+    }
+
+    async fn serve(&self, mut stream: TcpStream) {
+        let mut buf = Vec::new();
+        let (head, body) = loop {
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await.unwrap();
+            if n == 0 {
+                return;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+
+            let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                continue;
+            };
+            let head = String::from_utf8_lossy(&buf[..end]).to_string();
+            let length = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length {
+                break (head, buf[end + 4..end + 4 + length].to_vec());
+            }
+        };
+
+        let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+        let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        let (status, headers, reply) = self.route(&path, &body);
+
+        let reply = reply.map(|v| v.to_string()).unwrap_or_default();
+        let response = format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+            reply.len()
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+    }
+
+    fn route(&self, path: &str, body: &Value) -> (&'static str, &'static str, Option<Value>) {
+        let profile = json!({ "id": PROFILE_ID, "name": "Steve" });
+        match path {
+            "/" => (
+                "200 OK",
+                "X-Authlib-Injector-API-Location: /api/yggdrasil/\r\n",
+                Some(json!({})),
+            ),
+            "/api/yggdrasil/authserver/authenticate" if body["password"] == PASSWORD => (
+                "200 OK",
+                "",
+                Some(json!({
+                    "accessToken": self.issue_token(),
+                    "clientToken": body["clientToken"],
+                    "availableProfiles": [profile],
+                })),
+            ),
+            "/api/yggdrasil/authserver/authenticate" => (
+                "403 Forbidden",
+                "",
+                Some(json!({
+                    "error": "ForbiddenOperationException",
+                    "errorMessage": "Invalid credentials. Invalid username or password.",
+                })),
+            ),
+            "/api/yggdrasil/authserver/refresh" if self.token_is_valid(body) => {
+                let old = body["accessToken"].as_str().unwrap_or_default();
+                self.valid_tokens.lock().unwrap().remove(old);
+                (
+                    "200 OK",
+                    "",
+                    Some(json!({
+                        "accessToken": self.issue_token(),
+                        "clientToken": body["clientToken"],
+                        "selectedProfile": profile,
+                    })),
+                )
+            }
+            "/api/yggdrasil/authserver/validate" if self.token_is_valid(body) => {
+                ("204 No Content", "", None)
+            }
+            "/api/yggdrasil/authserver/refresh" | "/api/yggdrasil/authserver/validate" => (
+                "403 Forbidden",
+                "",
+                Some(json!({
+                    "error": "ForbiddenOperationException",
+                    "errorMessage": "Invalid token.",
+                })),
+            ),
+            _ => ("404 Not Found", "", None),
+        }
+    }
+}
+
+fn service() -> AuthService {
+    oneclient_common::paths::set_launcher_dir(
+        std::env::temp_dir().join(format!("oneclient-auth-test-{}", Uuid::new_v4())),
+    );
+    let net = RequestClient::new(NetConfig::default()).expect("net client");
+    let (events, _rx) = EventBus::channel();
+    AuthService::with_store(CredentialsStore::default(), net, events)
+}
+
+#[tokio::test]
+async fn yggdrasil_account_signs_in_validates_and_refreshes() {
+    let (server, base) = MockServer::start().await;
+    let auth = service();
+
+    let account = auth
+        .add_yggdrasil_account(&base, "steve@example.com", PASSWORD)
+        .await
+        .expect("sign in against the mock server");
+    assert_eq!(account.kind, AccountKind::Yggdrasil);
+    assert_eq!(account.username, "Steve");
+    assert_eq!(account.id, Uuid::parse_str(PROFILE_ID).unwrap());
+    assert_eq!(
+        account.auth_server.as_deref(),
+        Some(format!("{base}/api/yggdrasil").as_str()),
+        "the ALI header points at the real API root"
+    );
+
+    let launch = auth.account_for_launch(account.id).await.unwrap();
+    assert_eq!(
+        launch.access_token, account.access_token,
+        "a token the server still accepts is kept"
+    );
+
+    server.revoke_all();
+    let err = auth.account_for_launch(account.id).await.unwrap_err();
+    assert!(
+        matches!(&err, AuthError::Yggdrasil { message, .. } if message.contains("Invalid token")),
+        "a revoked token cannot be refreshed either: {err}"
+    );
+
+    let relogin = auth
+        .add_yggdrasil_account(&base, "steve@example.com", PASSWORD)
+        .await
+        .unwrap();
+    let refreshed = auth.refresh_account(relogin.id).await.unwrap();
+    assert_ne!(refreshed.access_token, relogin.access_token);
+    assert_eq!(
+        refreshed.refresh_token, relogin.refresh_token,
+        "same client token"
+    );
+    assert_eq!(
+        auth.get_account(relogin.id).await.unwrap().access_token,
+        refreshed.access_token,
+        "the rotated token is stored"
+    );
+}
+
+#[tokio::test]
+async fn wrong_password_reports_the_servers_message() {
+    let (_server, base) = MockServer::start().await;
+    let auth = service();
+
+    let err = auth
+        .add_yggdrasil_account(
+            &format!("{base}/api/yggdrasil/"),
+            "steve@example.com",
+            "nope",
+        )
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("Invalid credentials"), "{err}");
+    assert!(auth.list_accounts().await.is_empty());
+}
+
+#[tokio::test]
+async fn a_profile_id_from_another_server_does_not_replace_the_account() {
+    let (_first, first_base) = MockServer::start().await;
+    let (_second, second_base) = MockServer::start().await;
+    let auth = service();
+
+    let account = auth
+        .add_yggdrasil_account(&first_base, "steve@example.com", PASSWORD)
+        .await
+        .unwrap();
+    let err = auth
+        .add_yggdrasil_account(&second_base, "steve@example.com", PASSWORD)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, AuthError::AccountIdInUse { .. }), "{err}");
+    assert_eq!(
+        auth.get_account(account.id).await.unwrap().auth_server,
+        account.auth_server,
+        "the first server's account is kept"
+    );
+}
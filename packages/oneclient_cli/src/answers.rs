@@ -0,0 +1,153 @@
+//! How a prompt gets answered without a window Flags are consulted first then
+//! stdin when a person is there to type otherwise the prompt is dismissed
+
+use oneclient_events::{Answer, Choice, ChoiceInput, ChoiceStyle};
+
+use crate::args::GlobalArgs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Preset {
+    id: String,
+    value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Answer(Answer),
+    /// Nothing on the command line covers it ask on stdin
+    Ask,
+    Dismiss,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnswerPolicy {
+    presets: Vec<Preset>,
+    yes: bool,
+    interactive: bool,
+}
+
+impl AnswerPolicy {
+    pub fn new(args: &GlobalArgs, stdin_is_terminal: bool) -> Self {
+        Self {
+            presets: args
+                .answers
+                .iter()
+                .map(|raw| match raw.split_once('=') {
+                    Some((id, value)) => Preset {
+                        id: id.to_string(),
+                        value: Some(value.to_string()),
+                    },
+                    None => Preset {
+                        id: raw.clone(),
+                        value: None,
+                    },
+                })
+                .collect(),
+            yes: args.yes,
+            interactive: stdin_is_terminal && !args.no_input,
+        }
+    }
+
+    /// An `--answer` for a choice that needs input but came without a value is
+    /// passed over rather than sent half-filled
+    pub fn decide(&self, choices: &[Choice]) -> Decision {
+        for preset in &self.presets {
+            let Some(choice) = choices.iter().find(|choice| choice.id == preset.id) else {
+                continue;
+            };
+            if let Some(answer) = answer_with(choice, preset.value.as_deref()) {
+                return Decision::Answer(answer);
+            }
+        }
+
+        if self.yes
+            && let Some(answer) = choices
+                .iter()
+                .filter(|choice| choice.style == ChoiceStyle::Primary)
+                .find_map(|choice| answer_with(choice, None))
+        {
+            return Decision::Answer(answer);
+        }
+
+        if self.interactive {
+            Decision::Ask
+        } else {
+            Decision::Dismiss
+        }
+    }
+}
+
+/// `None` when the choice needs input and `value` has none
+pub fn answer_with(choice: &Choice, value: Option<&str>) -> Option<Answer> {
+    let answer = Answer::new(choice.id);
+    match (&choice.input, value) {
+        (None, _) => Some(answer),
+        (Some(ChoiceInput::Folder { .. }), Some(value)) => Some(answer.with_folder(value)),
+        (Some(ChoiceInput::Selection { .. }), Some(value)) => Some(answer.with_selection(value)),
+        (Some(_), None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(answers: &[&str], yes: bool, interactive: bool) -> AnswerPolicy {
+        let args = GlobalArgs {
+            answers: answers.iter().map(ToString::to_string).collect(),
+            yes,
+            no_input: false,
+            passphrase_file: None,
+            verbose: 0,
+        };
+        AnswerPolicy::new(&args, interactive)
+    }
+
+    fn choices() -> Vec<Choice> {
+        vec![
+            Choice::primary("download", "Download").picks_selection("java-vendor"),
+            Choice::new("folder", "Choose folder").picks_folder("Select a folder"),
+            Choice::new("skip", "Skip"),
+        ]
+    }
+
+    #[test]
+    fn flags_win_and_carry_their_value() {
+        let decision = policy(&["other", "folder=/opt/java"], true, true).decide(&choices());
+        assert_eq!(
+            decision,
+            Decision::Answer(Answer::new("folder").with_folder("/opt/java"))
+        );
+    }
+
+    #[test]
+    fn a_flag_missing_required_input_is_passed_over() {
+        let decision = policy(&["download", "skip"], false, false).decide(&choices());
+        assert_eq!(decision, Decision::Answer(Answer::new("skip")));
+    }
+
+    #[test]
+    fn yes_only_takes_a_primary_choice_it_can_fill() {
+        let plain = vec![
+            Choice::new("keep", "Keep"),
+            Choice::primary("remove", "Remove"),
+        ];
+        assert_eq!(
+            policy(&[], true, false).decide(&plain),
+            Decision::Answer(Answer::new("remove"))
+        );
+        assert_eq!(
+            policy(&[], true, false).decide(&choices()),
+            Decision::Dismiss
+        );
+    }
+
+    #[test]
+    fn unanswered_prompts_ask_only_when_someone_can_type() {
+        assert_eq!(policy(&[], false, true).decide(&choices()), Decision::Ask);
+        assert_eq!(
+            policy(&[], false, false).decide(&choices()),
+            Decision::Dismiss
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use oneclient_common::domain::{GameLoader, ProviderId};
+
+/// Drives the launcher without a window Progress and prompts go to stderr game
+/// output to stdout
+#[derive(Debug, Parser)]
+#[command(name = "oneclient-cli", version, about)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+    /// Answer a prompt choice by id `ID=VALUE` supplies the folder or selection
+    /// it asks for Repeat for several prompts
+    #[arg(long = "answer", value_name = "ID[=VALUE]", global = true)]
+    pub answers: Vec<String>,
+
+    /// Take the primary choice of any prompt no `--answer` covers
+    #[arg(long, short = 'y', global = true)]
+    pub yes: bool,
+
+    /// Dismiss prompts no flag answers instead of asking on stdin
+    #[arg(long, global = true)]
+    pub no_input: bool,
+
+    /// Unlocks passphrase-protected credentials `ONECLIENT_AUTH_PASSPHRASE`
+    /// works too
+    #[arg(long, value_name = "FILE", global = true)]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Repeat for more launcher logging on stderr
+    #[arg(long, short = 'v', action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List create and delete clusters
+    #[command(subcommand)]
+    Clusters(ClustersCommand),
+
+    /// Install a package and its dependencies into a cluster
+    Install {
+        /// Cluster id or name
+        cluster: String,
+        provider: Provider,
+        /// Project id or slug as the provider knows it
+        project: String,
+        /// Defaults to the newest version that fits the cluster
+        #[arg(long)]
+        version: Option<String>,
+        /// Datapacks only the folder under `saves` of the one world to add it
+        /// to, every world when left out
+        #[arg(long, value_name = "FOLDER")]
+        world: Option<String>,
+    },
+
+    /// Bring bundle content up to date
+    #[command(subcommand)]
+    Bundles(BundlesCommand),
+
+    /// Check a cluster's files and repair what can be re-fetched
+    Verify {
+        /// Cluster id or name
+        cluster: String,
+    },
+
+    /// Launch a cluster and stream its log to stdout until the game exits
+    Launch {
+        /// Cluster id or name
+        cluster: String,
+        /// Account username or id defaults to the cluster's own account
+        #[arg(long)]
+        account: Option<String>,
+        /// Join a server (`host` or `host:port`) as soon as the game starts
+        #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["world", "realm"])]
+        join: Option<String>,
+        /// Open a singleplayer world by its folder name under `saves`
+        #[arg(long, value_name = "FOLDER", conflicts_with = "realm")]
+        world: Option<String>,
+        /// Join a realm by its id
+        #[arg(long, value_name = "ID")]
+        realm: Option<String>,
+    },
+
+    /// Sign in and choose the default account
+    #[command(subcommand)]
+    Accounts(AccountsCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ClustersCommand {
+    List,
+    Create {
+        name: String,
+        /// Minecraft version such as `1.21.4`
+        mc_version: String,
+        #[arg(long, default_value = "vanilla")]
+        loader: GameLoader,
+        #[arg(long)]
+        loader_version: Option<String>,
+    },
+    Delete {
+        /// Cluster id or name
+        cluster: String,
+        /// Keep the cluster's folder on disk
+        #[arg(long)]
+        keep_files: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BundlesCommand {
+    /// Sync the bundle catalog then apply updates to one cluster or all of them
+    Update {
+        /// Cluster id or name
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        cluster: Option<String>,
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccountsCommand {
+    List,
+    AddOffline {
+        username: String,
+    },
+    /// Sign in with a device code shown on stderr
+    LoginMicrosoft,
+    LoginYggdrasil {
+        /// The server's API root or any page advertising it
+        server: String,
+        username: String,
+        /// Read the password from the first line of stdin
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Set the default account or clear it with `--clear`
+    Default {
+        /// Account username or id
+        #[arg(required_unless_present = "clear", conflicts_with = "clear")]
+        account: Option<String>,
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Protect stored accounts with a passphrase read from the first line of
+    /// stdin or remove it with `--clear`
+    Passphrase {
+        #[arg(long)]
+        clear: bool,
+    },
+}
+
+/// The remote providers a package can be installed from by id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Provider {
+    Modrinth,
+    #[value(name = "curseforge")]
+    CurseForge,
+    #[value(name = "self-hosted")]
+    SelfHosted,
+}
+
+impl From<Provider> for ProviderId {
+    fn from(value: Provider) -> Self {
+        match value {
+            Provider::Modrinth => ProviderId::Modrinth,
+            Provider::CurseForge => ProviderId::CurseForge,
+            Provider::SelfHosted => ProviderId::SelfHosted,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::io::BufRead;
+use std::process::ExitCode;
+
+use anyhow::Context;
+
+use oneclient_core::LauncherState;
+
+use crate::args::AccountsCommand;
+
+pub async fn run(state: &LauncherState, command: AccountsCommand) -> anyhow::Result<ExitCode> {
+    match command {
+        AccountsCommand::List => {
+            let default = state
+                .auth
+                .default_account()
+                .await?
+                .map(|account| account.id);
+            for account in state.auth.list_accounts().await {
+                let marker = if Some(account.id) == default { "*" } else { "" };
+                println!(
+                    "{}\t{}\t{:?}{marker}",
+                    account.id, account.username, account.kind
+                );
+            }
+        }
+        AccountsCommand::AddOffline { username } => {
+            let account = state.auth.add_offline_account(username).await?;
+            println!("{}", account.id);
+        }
+        AccountsCommand::LoginMicrosoft => {
+            let session = state.auth.begin_microsoft_login().await?;
+            // Names the page and the code the loopback half of the login is
+            // useless without a browser on this machine
+            eprintln!("{}", session.device.message);
+
+            let account = state.auth.finish_microsoft_login(session).await?;
+            eprintln!("Signed in as {}", account.username);
+            println!("{}", account.id);
+        }
+        AccountsCommand::LoginYggdrasil {
+            server,
+            username,
+            password_stdin,
+        } => {
+            if !password_stdin {
+                anyhow::bail!("pass the password on stdin with --password-stdin");
+            }
+            let password = read_stdin_line()
+                .await
+                .context("could not read the password from stdin")?;
+
+            let account = state
+                .auth
+                .add_yggdrasil_account(&server, &username, &password)
+                .await?;
+            eprintln!("Signed in as {}", account.username);
+            println!("{}", account.id);
+        }
+        AccountsCommand::Default { account, clear } => {
+            let id = match (account, clear) {
+                (Some(spec), false) => Some(super::find_account(state, &spec).await?.id),
+                _ => None,
+            };
+            state.auth.set_default_account(id).await?;
+        }
+        AccountsCommand::Passphrase { clear } => {
+            let passphrase = if clear {
+                None
+            } else {
+                Some(
+                    read_stdin_line()
+                        .await
+                        .context("could not read the passphrase from stdin")?,
+                )
+            };
+            if passphrase.as_deref().is_some_and(str::is_empty) {
+                anyhow::bail!("the passphrase is empty pass `--clear` to remove it");
+            }
+            oneclient_auth::set_credentials_passphrase(passphrase.as_deref()).await?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn read_stdin_line() -> anyhow::Result<String> {
+    let line = tokio::task::spawn_blocking(|| {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line).map(|_| line)
+    })
+    .await??;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
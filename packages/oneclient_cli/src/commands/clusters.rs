@@ -0,0 +1,54 @@
+use std::process::ExitCode;
+
+use oneclient_core::{CreateClusterOptions, LauncherState};
+use oneclient_events::Signal;
+
+use crate::args::ClustersCommand;
+
+pub async fn run(state: &LauncherState, command: ClustersCommand) -> anyhow::Result<ExitCode> {
+    match command {
+        ClustersCommand::List => {
+            for cluster in state.clusters.list().await? {
+                let loader = match &cluster.mc_loader_version {
+                    Some(version) => format!("{} {version}", cluster.mc_loader),
+                    None => cluster.mc_loader.to_string(),
+                };
+                println!(
+                    "{}\t{}\t{}\t{loader}",
+                    cluster.id, cluster.name, cluster.mc_version
+                );
+            }
+        }
+        ClustersCommand::Create {
+            name,
+            mc_version,
+            loader,
+            loader_version,
+        } => {
+            let mut options = CreateClusterOptions::new(name, mc_version, loader);
+            if let Some(version) = loader_version {
+                options = options.loader_version(version);
+            }
+
+            let global = state.settings.read().global_game_settings.clone();
+            let cluster = state.clusters.create(&global, options).await?;
+            state.services.events.signal(Signal::ClustersChanged);
+            println!("{}", cluster.id);
+        }
+        ClustersCommand::Delete {
+            cluster,
+            keep_files,
+        } => {
+            let cluster = super::find_cluster(state, &cluster).await?;
+            if state.games.is_active(cluster.id) {
+                anyhow::bail!("{} is running; stop it first", cluster.name);
+            }
+
+            state.clusters.delete(cluster.id, !keep_files).await?;
+            state.services.events.signal(Signal::ClustersChanged);
+            eprintln!("Deleted {}", cluster.name);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
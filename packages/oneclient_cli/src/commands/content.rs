@@ -0,0 +1,142 @@
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use anyhow::Context;
+
+use oneclient_common::domain::ProviderId;
+use oneclient_core::packages::{WorldTarget, install_body, install_package, latest_version_for};
+use oneclient_core::{ApplyBundleUpdatesResult, LauncherState};
+use oneclient_events::GroupedProgressSession;
+
+use crate::args::BundlesCommand;
+
+pub async fn install(
+    state: &Arc<LauncherState>,
+    cluster: &str,
+    provider: ProviderId,
+    project: &str,
+    version: Option<String>,
+    world: Option<String>,
+) -> anyhow::Result<ExitCode> {
+    let cluster = super::find_cluster(state, cluster).await?;
+    let version = match version {
+        Some(version) => version,
+        None => latest_version_for(state, provider, project, cluster.id)
+            .await?
+            .with_context(|| {
+                format!(
+                    "{project} has no version for {} {}",
+                    cluster.mc_loader, cluster.mc_version
+                )
+            })?,
+    };
+
+    let install = install_package(
+        state,
+        provider,
+        project,
+        &version,
+        cluster.id,
+        WorldTarget::from_world(world),
+    )
+    .await;
+    let name = install.result?;
+    eprintln!(
+        "{}",
+        install_body(&name, &install.dependencies, &install.missing_dependencies)
+    );
+
+    Ok(if install.missing_dependencies.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(2)
+    })
+}
+
+pub async fn bundles(
+    state: &Arc<LauncherState>,
+    command: BundlesCommand,
+) -> anyhow::Result<ExitCode> {
+    let BundlesCommand::Update { cluster, all } = command;
+    let content = state.services.content();
+    state.bundles.sync(&content).await?;
+
+    let results = if all {
+        // Same order as the app's startup sync so new bundle clusters exist
+        // before their content is applied
+        oneclient_core::clusters::apply_remote_migrations(state).await?;
+        oneclient_core::clusters::ensure_from_bundles(state).await?;
+
+        let session = GroupedProgressSession::start(&state.services.events, "Updating mods");
+        let changed = oneclient_content::bundles::sync_all_cluster_bundles(
+            state.bundles.as_ref(),
+            &content,
+            Some(&session),
+        )
+        .await;
+        session.finish();
+        changed
+    } else {
+        let cluster = super::find_cluster(state, cluster.as_deref().unwrap_or_default()).await?;
+        let result =
+            oneclient_core::apply_bundle_updates(cluster.id, state.bundles.as_ref(), &content)
+                .await?;
+        vec![(cluster.id, result)]
+    };
+
+    let mut failed = false;
+    for (cluster_id, result) in &results {
+        let name = match state.clusters.get(*cluster_id).await {
+            Ok(cluster) => cluster.name,
+            Err(_) => cluster_id.to_string(),
+        };
+        failed |= report_bundle_updates(&name, result);
+    }
+    if results.is_empty() {
+        eprintln!("Bundle content is up to date");
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Returns whether anything failed
+fn report_bundle_updates(cluster: &str, result: &ApplyBundleUpdatesResult) -> bool {
+    eprintln!(
+        "{cluster}: {} updated, {} added, {} removed",
+        result.updates_applied.len(),
+        result.additions_applied.len(),
+        result.removals_applied.len()
+    );
+
+    let failures = result
+        .updates_failed
+        .iter()
+        .chain(&result.additions_failed)
+        .chain(&result.removals_failed)
+        .collect::<Vec<_>>();
+    for failure in &failures {
+        eprintln!("  failed: {failure}");
+    }
+    !failures.is_empty()
+}
+
+/// Exits non-zero only when something is left that could not be repaired
+pub async fn verify(state: &Arc<LauncherState>, cluster: &str) -> anyhow::Result<ExitCode> {
+    let cluster = super::find_cluster(state, cluster).await?;
+    let report = oneclient_core::verify_cluster_files(state, cluster.id).await?;
+
+    eprintln!("{}: {}", cluster.name, report.summary());
+    for path in &report.unrepairable {
+        println!("{path}");
+    }
+
+    Ok(if report.unrepairable.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
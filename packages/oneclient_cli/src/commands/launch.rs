@@ -0,0 +1,98 @@
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use anyhow::{Context, bail};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use oneclient_core::{GameExit, LauncherState, QuickPlay};
+use oneclient_events::GameEvent;
+
+use crate::events::Forwarded;
+
+/// The log itself is written by the event loop this only waits for the game's
+/// session to end Ctrl-C stops the game rather than abandoning it
+/// Anything but a clean exit is a failure so a script can tell a crash apart
+pub async fn run(
+    state: &Arc<LauncherState>,
+    events: &broadcast::Sender<Forwarded>,
+    cluster: &str,
+    account: Option<&str>,
+    quick_play: Option<QuickPlay>,
+) -> anyhow::Result<ExitCode> {
+    let cluster = super::find_cluster(state, cluster).await?;
+    let account = match account {
+        Some(spec) => {
+            let account = super::find_account(state, spec).await?;
+            state.auth.account_for_launch(account.id).await?
+        }
+        None => oneclient_core::game::account_for_cluster(state, cluster.id)
+            .await?
+            .context("no account is signed in; add one with `accounts login-microsoft` or `accounts add-offline`")?,
+    };
+
+    // Subscribed before launching so an early exit is not missed
+    let mut stages = events.subscribe();
+    let launched =
+        oneclient_core::launch_cluster_with(state, cluster.id, &account, true, quick_play.as_ref())
+            .await?;
+    match launched.pid {
+        Some(pid) => eprintln!(
+            "Launched {} as {} (pid {pid})",
+            cluster.name, account.username
+        ),
+        None => eprintln!("Launched {} as {}", cluster.name, account.username),
+    }
+
+    // `Exited` comes before the launcher has cleaned up after the game so the
+    // session task is what ends the wait returning earlier drops the runtime
+    // mid-cleanup
+    let mut session = launched.session;
+    let mut exit = None;
+    let mut listening = true;
+    let mut stopping = false;
+    loop {
+        tokio::select! {
+            finished = &mut session => {
+                match finished {
+                    Ok(finished) => exit = Some(finished),
+                    Err(err) => tracing::warn!("session cleanup failed: {err}"),
+                }
+                break;
+            }
+            event = stages.recv(), if listening => match event {
+                Ok(Forwarded::Game(GameEvent::Failed { cluster_id, message }))
+                    if cluster_id == cluster.id => bail!("{} failed: {message}", cluster.name),
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => listening = false,
+            },
+            _ = tokio::signal::ctrl_c(), if !stopping => {
+                eprintln!("Stopping {}", cluster.name);
+                stopping = true;
+                // Nothing to kill once the game is gone the session is only
+                // finishing its cleanup
+                state.games.kill(cluster.id);
+            }
+        }
+    }
+
+    match exit {
+        Some(exit) if exit.is_clean() => {
+            eprintln!("{} exited", cluster.name);
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(GameExit::Exited { code: Some(code), .. }) => {
+            eprintln!("{} exited with code {code}", cluster.name);
+            // A code that does not fit a byte is reported as a plain failure
+            Ok(ExitCode::from(
+                u8::try_from(code).ok().filter(|code| *code != 0).unwrap_or(1),
+            ))
+        }
+        Some(GameExit::Exited { code: None, .. }) => {
+            eprintln!("{} was stopped by a signal", cluster.name);
+            Ok(ExitCode::FAILURE)
+        }
+        Some(GameExit::Failed(err)) => bail!("lost track of {}: {err}", cluster.name),
+        None => bail!("{} session ended without an exit status", cluster.name),
+    }
+}
@@ -0,0 +1,82 @@
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use anyhow::{Context, bail};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use oneclient_auth::MinecraftAccount;
+use oneclient_core::{Cluster, LauncherState, QuickPlay};
+
+use crate::args::Command;
+use crate::events::Forwarded;
+
+mod accounts;
+mod clusters;
+mod content;
+mod launch;
+
+pub async fn run(
+    state: &Arc<LauncherState>,
+    events: &broadcast::Sender<Forwarded>,
+    command: Command,
+) -> anyhow::Result<ExitCode> {
+    match command {
+        Command::Clusters(command) => clusters::run(state, command).await,
+        Command::Install {
+            cluster,
+            provider,
+            project,
+            version,
+            world,
+        } => content::install(state, &cluster, provider.into(), &project, version, world).await,
+        Command::Bundles(command) => content::bundles(state, command).await,
+        Command::Verify { cluster } => content::verify(state, &cluster).await,
+        Command::Launch {
+            cluster,
+            account,
+            join,
+            world,
+            realm,
+        } => {
+            let quick_play = join
+                .map(|address| QuickPlay::Multiplayer { address })
+                .or(world.map(|world| QuickPlay::Singleplayer { world }))
+                .or(realm.map(|realm_id| QuickPlay::Realms { realm_id }));
+            launch::run(state, events, &cluster, account.as_deref(), quick_play).await
+        }
+        Command::Accounts(command) => accounts::run(state, command).await,
+    }
+}
+
+/// A number is tried as an id first so a cluster named `1` needs its id
+pub async fn find_cluster(state: &LauncherState, spec: &str) -> anyhow::Result<Cluster> {
+    if let Ok(id) = spec.parse::<i64>()
+        && let Ok(cluster) = state.clusters.get(id).await
+    {
+        return Ok(cluster);
+    }
+
+    let mut matches: Vec<Cluster> = state
+        .clusters
+        .list()
+        .await?
+        .into_iter()
+        .filter(|cluster| cluster.name.eq_ignore_ascii_case(spec))
+        .collect();
+    match matches.len() {
+        0 => bail!("no cluster with id or name {spec:?}"),
+        1 => Ok(matches.remove(0)),
+        n => bail!("{n} clusters are named {spec:?}; use an id from `clusters list`"),
+    }
+}
+
+pub async fn find_account(state: &LauncherState, spec: &str) -> anyhow::Result<MinecraftAccount> {
+    let accounts = state.auth.list_accounts().await;
+    let id = Uuid::parse_str(spec).ok();
+
+    accounts
+        .into_iter()
+        .find(|account| Some(account.id) == id || account.username.eq_ignore_ascii_case(spec))
+        .with_context(|| format!("no account with username or id {spec:?}"))
+}
@@ -0,0 +1,142 @@
+//! Everything the launcher emits goes to stderr as plain lines except the
+//! game's own log which is the only thing written to stdout so it can be piped
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, broadcast};
+use uuid::Uuid;
+
+use oneclient_events::{
+    Answer, Event, EventReceiver, GameEvent, GroupedProgressEvent, Level, Notification,
+    ProgressEvent, PromptRequest, Signal,
+};
+
+use crate::answers::{AnswerPolicy, Decision, answer_with};
+
+/// What commands wait on logs are printed by the loop itself so a slow
+/// subscriber can never drop a line
+#[derive(Debug, Clone)]
+pub enum Forwarded {
+    Game(GameEvent),
+    Signal(Signal),
+}
+
+pub fn spawn(mut rx: EventReceiver, policy: AnswerPolicy) -> broadcast::Sender<Forwarded> {
+    let (forward, _) = broadcast::channel(64);
+    let sender = forward.clone();
+    let policy = Arc::new(policy);
+    // Two prompts raised together must not interleave on the terminal
+    let stdin = Arc::new(Mutex::new(()));
+
+    tokio::spawn(async move {
+        let mut sessions: HashMap<Uuid, String> = HashMap::new();
+
+        while let Some(event) = rx.recv().await {
+            match event {
+                Event::Notification(Notification::Message(message)) => match message.level {
+                    Level::Info => eprintln!("{}: {}", message.title, message.body),
+                    Level::Error => eprintln!("error: {}: {}", message.title, message.body),
+                },
+                Event::Notification(Notification::Prompt(request)) => {
+                    let policy = Arc::clone(&policy);
+                    let stdin = Arc::clone(&stdin);
+                    tokio::spawn(async move { answer(request, &policy, &stdin).await });
+                }
+                Event::Progress(ProgressEvent::Complete { title, body, .. }) => {
+                    eprintln!("{title}: {body}");
+                }
+                Event::Progress(ProgressEvent::Update { .. }) => {}
+                Event::Progress(ProgressEvent::Grouped(event)) => match event {
+                    GroupedProgressEvent::Start { session_id, title } => {
+                        eprintln!("{title}...");
+                        sessions.insert(session_id, title);
+                    }
+                    GroupedProgressEvent::AddChild { label, .. } => {
+                        tracing::info!("downloading {label}");
+                    }
+                    GroupedProgressEvent::End { session_id } => {
+                        if let Some(title) = sessions.remove(&session_id) {
+                            eprintln!("{title}: done");
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Game(GameEvent::Log { line, .. }) => {
+                    let mut stdout = std::io::stdout().lock();
+                    let _ = writeln!(stdout, "{line}");
+                }
+                Event::Game(event) => {
+                    let _ = sender.send(Forwarded::Game(event));
+                }
+                Event::Signal(signal) => {
+                    let _ = sender.send(Forwarded::Signal(signal));
+                }
+            }
+        }
+    });
+
+    forward
+}
+
+async fn answer(request: PromptRequest, policy: &AnswerPolicy, stdin: &Mutex<()>) {
+    eprintln!("{}", request.title);
+    eprintln!("  {}", request.body);
+
+    let answer = match policy.decide(&request.choices) {
+        Decision::Answer(answer) => {
+            eprintln!("  -> {}", answer.id);
+            Some(answer)
+        }
+        Decision::Dismiss => {
+            let choices: Vec<&str> = request.choices.iter().map(|choice| choice.id).collect();
+            eprintln!("  -> dismissed (answer with --answer one of {choices:?})");
+            None
+        }
+        Decision::Ask => {
+            let _guard = stdin.lock().await;
+            let choices = request.choices.clone();
+            let dismiss = request.dismiss.clone();
+            tokio::task::spawn_blocking(move || ask(&choices, dismiss.as_deref()))
+                .await
+                .unwrap_or(None)
+        }
+    };
+
+    let _ = request.reply.send(answer);
+}
+
+/// Runs on a blocking thread a closed stdin counts as dismissing
+fn ask(choices: &[oneclient_events::Choice], dismiss: Option<&str>) -> Option<Answer> {
+    for (index, choice) in choices.iter().enumerate() {
+        eprintln!("  {}) {}", index + 1, choice.label);
+    }
+    eprintln!("  0) {}", dismiss.unwrap_or("Dismiss"));
+
+    let choice = loop {
+        let line = prompt_line("  > ")?;
+        match line.trim().parse::<usize>() {
+            Ok(0) => return None,
+            Ok(picked) if picked <= choices.len() => break &choices[picked - 1],
+            _ => eprintln!("  pick a number from 0 to {}", choices.len()),
+        }
+    };
+
+    if choice.input.is_none() {
+        return answer_with(choice, None);
+    }
+    let value = prompt_line(&format!("  {}: ", choice.label))?;
+    answer_with(choice, Some(value.trim()))
+}
+
+fn prompt_line(label: &str) -> Option<String> {
+    eprint!("{label}");
+    let _ = std::io::stderr().flush();
+
+    let mut line = String::new();
+    match std::io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line),
+    }
+}
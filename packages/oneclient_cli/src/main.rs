@@ -0,0 +1,84 @@
+use std::io::IsTerminal;
+use std::process::ExitCode;
+
+use anyhow::Context;
+use clap::Parser;
+use tokio::runtime::Builder;
+use tracing_subscriber::EnvFilter;
+
+use oneclient_core::LauncherState;
+use oneclient_events::EventBus;
+
+use crate::answers::AnswerPolicy;
+use crate::args::{Cli, GlobalArgs};
+
+mod answers;
+mod args;
+mod commands;
+mod events;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().max_blocking_threads(64);
+
+    // Debug builds emit unoptimized async code that can overflow the default 2MB stack
+    #[cfg(debug_assertions)]
+    builder.thread_stack_size(3 * 1024 * 1024);
+
+    let rt = builder.build().expect("Failed to build the tokio runtime");
+    init_logging(cli.global.verbose);
+
+    match rt.block_on(run(cli)) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// stdout is kept for command output and the game log `RUST_LOG` overrides `-v`
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => "warn",
+        1 => "info",
+        _ => "debug",
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+async fn run(cli: Cli) -> anyhow::Result<ExitCode> {
+    unlock_credentials(&cli.global).await?;
+
+    let (bus, rx) = EventBus::channel();
+    // Started before the state so nothing emitted during startup waits on a consumer
+    let forwarded = events::spawn(
+        rx,
+        AnswerPolicy::new(&cli.global, std::io::stdin().is_terminal()),
+    );
+    let state = LauncherState::new(bus).await?;
+
+    commands::run(&state, &forwarded, cli.command).await
+}
+
+async fn unlock_credentials(args: &GlobalArgs) -> anyhow::Result<()> {
+    let Some(path) = &args.passphrase_file else {
+        return Ok(());
+    };
+    if !oneclient_auth::credentials_locked().await? {
+        return Ok(());
+    }
+
+    let passphrase = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("could not read {}", path.display()))?;
+    oneclient_auth::unlock_credentials(passphrase.trim_end_matches(['\r', '\n'])).await?;
+    Ok(())
+}
@@ -34,6 +34,12 @@ pub enum ClusterError {
 	#[error(transparent)]
 	Screenshots(#[from] crate::screenshots::ScreenshotsError),
 
+	#[error(transparent)]
+	Servers(#[from] crate::servers::ServersError),
+
+	#[error(transparent)]
+	Worlds(#[from] crate::worlds::WorldsError),
+
 	#[error(transparent)]
 	Request(#[from] oneclient_net::RequestError),
 
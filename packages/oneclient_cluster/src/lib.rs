@@ -5,6 +5,7 @@
 mod cluster;
 mod error;
 mod manager;
+mod nbt;
 mod options;
 mod profile;
 mod stage;
@@ -12,6 +13,8 @@ mod stage;
 pub mod logs;
 pub mod profiles;
 pub mod screenshots;
+pub mod servers;
+pub mod worlds;
 
 pub use cluster::{Cluster, ClusterLinkTarget};
 pub use error::{ClusterError, ClusterResult};
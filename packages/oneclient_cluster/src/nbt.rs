@@ -0,0 +1,419 @@
+//! Just enough of Minecraft's NBT format for the files the launcher edits in
+//! place keys keep their order and unknown tags round-trip untouched so the
+//! game never loses anything a newer version wrote
+
+use thiserror::Error;
+
+/// The game refuses anything nested deeper so a file that does is corrupt
+const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, Error)]
+pub enum NbtError {
+    #[error("NBT data ended early")]
+    UnexpectedEnd,
+    #[error("unknown NBT tag id {0}")]
+    InvalidTag(u8),
+    #[error("NBT root is not a compound")]
+    RootNotCompound,
+    #[error("NBT is nested more than {MAX_DEPTH} levels deep")]
+    TooDeep,
+    #[error("negative NBT length {0}")]
+    NegativeLength(i32),
+}
+
+pub type NbtResult<T> = Result<T, NbtError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Self::Byte(_) => 1,
+            Self::Short(_) => 2,
+            Self::Int(_) => 3,
+            Self::Long(_) => 4,
+            Self::Float(_) => 5,
+            Self::Double(_) => 6,
+            Self::ByteArray(_) => 7,
+            Self::String(_) => 8,
+            Self::List(_) => 9,
+            Self::Compound(_) => 10,
+            Self::IntArray(_) => 11,
+            Self::LongArray(_) => 12,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Any integer tag widened the game is not consistent about which width
+    /// it writes for a field across versions
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Byte(value) => Some(value.into()),
+            Self::Short(value) => Some(value.into()),
+            Self::Int(value) => Some(value.into()),
+            Self::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_i64().map(|value| value != 0)
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Self::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound(Vec<(String, Tag)>);
+
+impl Compound {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, tag)| tag)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Tag> {
+        self.0
+            .iter_mut()
+            .find(|(name, _)| name == key)
+            .map(|(_, tag)| tag)
+    }
+
+    /// Replaces in place so the key keeps its position
+    pub fn insert(&mut self, key: impl Into<String>, tag: Tag) {
+        let key = key.into();
+        match self.get_mut(&key) {
+            Some(existing) => *existing = tag,
+            None => self.0.push((key, tag)),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Tag> {
+        let index = self.0.iter().position(|(name, _)| name == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Tag::as_str)
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(Tag::as_i64)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(Tag::as_bool)
+    }
+
+    pub fn get_compound(&self, key: &str) -> Option<&Compound> {
+        self.get(key).and_then(Tag::as_compound)
+    }
+}
+
+/// Uncompressed bytes gzip is the caller's business since only some files use it
+pub fn read(bytes: &[u8]) -> NbtResult<(String, Compound)> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.u8()? != 10 {
+        return Err(NbtError::RootNotCompound);
+    }
+    let name = reader.string()?;
+    let root = reader.compound(0)?;
+    Ok((name, root))
+}
+
+pub fn write(name: &str, root: &Compound) -> Vec<u8> {
+    let mut out = vec![10];
+    write_string(&mut out, name);
+    write_compound(&mut out, root);
+    out
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> NbtResult<&[u8]> {
+        let end = self.pos.checked_add(len).ok_or(NbtError::UnexpectedEnd)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(NbtError::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> NbtResult<[u8; N]> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> NbtResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> NbtResult<i32> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn len(&mut self) -> NbtResult<usize> {
+        let len = self.i32()?;
+        usize::try_from(len).map_err(|_| NbtError::NegativeLength(len))
+    }
+
+    fn string(&mut self) -> NbtResult<String> {
+        let len = u16::from_be_bytes(self.array()?);
+        Ok(decode_mutf8(self.take(len.into())?))
+    }
+
+    fn compound(&mut self, depth: usize) -> NbtResult<Compound> {
+        let mut compound = Compound::new();
+        loop {
+            let id = self.u8()?;
+            if id == 0 {
+                return Ok(compound);
+            }
+            let name = self.string()?;
+            let tag = self.tag(id, depth + 1)?;
+            compound.0.push((name, tag));
+        }
+    }
+
+    fn tag(&mut self, id: u8, depth: usize) -> NbtResult<Tag> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+
+        Ok(match id {
+            1 => Tag::Byte(i8::from_be_bytes(self.array()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.len()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|&b| b as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item_id = self.u8()?;
+                let len = self.len()?;
+                // An empty list may carry any id including 0
+                if item_id == 0 && len > 0 {
+                    return Err(NbtError::InvalidTag(0));
+                }
+                let mut items = Vec::with_capacity(len.min(1024));
+                for _ in 0..len {
+                    items.push(self.tag(item_id, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            10 => Tag::Compound(self.compound(depth)?),
+            11 => {
+                let len = self.len()?;
+                let mut items = Vec::with_capacity(len.min(1024));
+                for _ in 0..len {
+                    items.push(self.i32()?);
+                }
+                Tag::IntArray(items)
+            }
+            12 => {
+                let len = self.len()?;
+                let mut items = Vec::with_capacity(len.min(1024));
+                for _ in 0..len {
+                    items.push(i64::from_be_bytes(self.array()?));
+                }
+                Tag::LongArray(items)
+            }
+            other => return Err(NbtError::InvalidTag(other)),
+        })
+    }
+}
+
+fn write_compound(out: &mut Vec<u8>, compound: &Compound) {
+    for (name, tag) in &compound.0 {
+        out.push(tag.id());
+        write_string(out, name);
+        write_tag(out, tag);
+    }
+    out.push(0);
+}
+
+fn write_tag(out: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(value) => out.extend(value.to_be_bytes()),
+        Tag::Short(value) => out.extend(value.to_be_bytes()),
+        Tag::Int(value) => out.extend(value.to_be_bytes()),
+        Tag::Long(value) => out.extend(value.to_be_bytes()),
+        Tag::Float(value) => out.extend(value.to_be_bytes()),
+        Tag::Double(value) => out.extend(value.to_be_bytes()),
+        Tag::ByteArray(items) => {
+            write_len(out, items.len());
+            out.extend(items.iter().map(|&b| b as u8));
+        }
+        Tag::String(value) => write_string(out, value),
+        Tag::List(items) => {
+            out.push(items.first().map_or(0, Tag::id));
+            write_len(out, items.len());
+            for item in items {
+                write_tag(out, item);
+            }
+        }
+        Tag::Compound(compound) => write_compound(out, compound),
+        Tag::IntArray(items) => {
+            write_len(out, items.len());
+            for item in items {
+                out.extend(item.to_be_bytes());
+            }
+        }
+        Tag::LongArray(items) => {
+            write_len(out, items.len());
+            for item in items {
+                out.extend(item.to_be_bytes());
+            }
+        }
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend(i32::try_from(len).unwrap_or(i32::MAX).to_be_bytes());
+}
+
+/// Strings longer than the u16 length prefix allows are cut at a char boundary
+/// the game would refuse to save them at all
+fn write_string(out: &mut Vec<u8>, value: &str) {
+    let mut encoded = encode_mutf8(value);
+    if encoded.len() > usize::from(u16::MAX) {
+        let mut end = usize::from(u16::MAX);
+        // Continuation bytes are 10xxxxxx in both UTF-8 and its Java variant
+        while end > 0 && encoded[end] & 0xC0 == 0x80 {
+            end -= 1;
+        }
+        encoded.truncate(end);
+    }
+    out.extend((encoded.len() as u16).to_be_bytes());
+    out.extend(encoded);
+}
+
+/// Java's modified UTF-8 writes NUL as two bytes and astral characters as
+/// surrogate pairs anything else is plain UTF-8
+fn encode_mutf8(value: &str) -> Vec<u8> {
+    if !value.chars().any(|c| c == '\0' || u32::from(c) > 0xFFFF) {
+        return value.as_bytes().to_vec();
+    }
+
+    let mut out = Vec::with_capacity(value.len() + 8);
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => out.push(unit as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                out.push(0xC0 | (unit >> 6) as u8);
+                out.push(0x80 | (unit & 0x3F) as u8);
+            }
+            _ => {
+                out.push(0xE0 | (unit >> 12) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                out.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+    out
+}
+
+fn decode_mutf8(bytes: &[u8]) -> String {
+    if let Ok(value) = std::str::from_utf8(bytes) {
+        return value.to_string();
+    }
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().copied();
+    while let Some(first) = iter.next() {
+        let mut next = || u16::from(iter.next().unwrap_or(0x80) & 0x3F);
+        let unit = match first {
+            0x00..=0x7F => u16::from(first),
+            0xC0..=0xDF => (u16::from(first & 0x1F) << 6) | next(),
+            0xE0..=0xEF => (u16::from(first & 0x0F) << 12) | (next() << 6) | next(),
+            _ => 0xFFFD,
+        };
+        units.push(unit);
+    }
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_keys_survive_a_round_trip_in_order() {
+        let mut inner = Compound::new();
+        inner.insert("ip", Tag::String("mc.example.com".to_string()));
+        inner.insert("futureField", Tag::LongArray(vec![1, -2]));
+
+        let mut root = Compound::new();
+        root.insert("servers", Tag::List(vec![Tag::Compound(inner)]));
+        root.insert("empty", Tag::List(Vec::new()));
+        root.insert("flag", Tag::Byte(1));
+
+        let (name, read_back) = read(&write("", &root)).unwrap();
+        assert_eq!(name, "");
+        assert_eq!(read_back, root);
+        assert_eq!(
+            read_back.0.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            ["servers", "empty", "flag"]
+        );
+    }
+
+    #[test]
+    fn java_strings_encode_nul_and_emoji_the_java_way() {
+        let value = "a\0b😀";
+        let encoded = encode_mutf8(value);
+        assert_eq!(&encoded[..4], &[b'a', 0xC0, 0x80, b'b']);
+        assert_eq!(encoded.len(), 4 + 6);
+        assert_eq!(decode_mutf8(&encoded), value);
+    }
+
+    #[test]
+    fn truncated_data_is_an_error_not_a_panic() {
+        let mut root = Compound::new();
+        root.insert("name", Tag::String("World".to_string()));
+        let bytes = write("", &root);
+        for len in 0..bytes.len() {
+            assert!(read(&bytes[..len]).is_err());
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! The multiplayer list in `servers.dat` the game rewrites the whole file when
+//! its server screen changes so edits here belong while the cluster is closed
+//! Entries a bundle pushes are remembered per bundle so a later sync only ever
+//! touches its own and never the ones the player added
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::cluster::Cluster;
+use crate::error::ClusterResult;
+use crate::nbt::{self, Compound, NbtError, Tag};
+
+pub const SERVERS_FILE: &str = "servers.dat";
+/// The game keeps the previous list here before each save so it is the
+/// backup players already know to look for
+const BACKUP_FILE: &str = "servers.dat_old";
+const OWNERSHIP_REL_PATH: &str = ".oneclient/shared_servers.json";
+const DEFAULT_PORT_SUFFIX: &str = ":25565";
+
+#[derive(Debug, Error)]
+pub enum ServersError {
+    #[error("servers.dat is unreadable: {0}")]
+    Nbt(#[from] NbtError),
+    #[error("{0} is already in the server list")]
+    Duplicate(String),
+    #[error("{0} is not in the server list")]
+    NotFound(String),
+    #[error("position {index} is outside a server list of {len}")]
+    OutOfRange { index: usize, len: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerEntry {
+    pub name: String,
+    pub address: String,
+    /// Base64 PNG the game caches from the last ping
+    pub icon: Option<String>,
+    /// `None` is the game's "prompt" the player has not chosen yet
+    pub accept_textures: Option<bool>,
+    /// Direct Connect saves its last address as a hidden entry
+    pub hidden: bool,
+    /// Keys this launcher does not know about carried through unchanged
+    extra: Compound,
+}
+
+impl ServerEntry {
+    pub fn new(name: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            address: address.into(),
+            icon: None,
+            accept_textures: None,
+            hidden: false,
+            extra: Compound::new(),
+        }
+    }
+
+    /// The same server written with or without its default port or in another
+    /// case is still the same server
+    pub fn is_address(&self, address: &str) -> bool {
+        normalize_address(&self.address) == normalize_address(address)
+    }
+
+    fn from_nbt(mut compound: Compound) -> Self {
+        let mut take_str = |key: &str| match compound.remove(key) {
+            Some(Tag::String(value)) => Some(value),
+            _ => None,
+        };
+        let name = take_str("name").unwrap_or_default();
+        let address = take_str("ip").unwrap_or_default();
+        let icon = take_str("icon");
+        let accept_textures = compound
+            .remove("acceptTextures")
+            .and_then(|tag| tag.as_bool());
+        let hidden = compound
+            .remove("hidden")
+            .and_then(|tag| tag.as_bool())
+            .unwrap_or(false);
+
+        Self {
+            name,
+            address,
+            icon,
+            accept_textures,
+            hidden,
+            extra: compound,
+        }
+    }
+
+    fn to_nbt(&self) -> Compound {
+        let mut compound = self.extra.clone();
+        compound.insert("name", Tag::String(self.name.clone()));
+        compound.insert("ip", Tag::String(self.address.clone()));
+        if let Some(icon) = &self.icon {
+            compound.insert("icon", Tag::String(icon.clone()));
+        }
+        if let Some(accept) = self.accept_textures {
+            compound.insert("acceptTextures", Tag::Byte(accept.into()));
+        }
+        if self.hidden {
+            compound.insert("hidden", Tag::Byte(1));
+        }
+        compound
+    }
+}
+
+fn normalize_address(address: &str) -> String {
+    let address = address.trim().to_ascii_lowercase();
+    match address.strip_suffix(DEFAULT_PORT_SUFFIX) {
+        Some(host) => host.to_string(),
+        None => address,
+    }
+}
+
+/// Root keys besides `servers` are kept so saving never drops them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerList {
+    root: Compound,
+    entries: Vec<ServerEntry>,
+}
+
+impl ServerList {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ServersError> {
+        let (_, mut root) = nbt::read(bytes)?;
+        let entries = match root.remove("servers") {
+            Some(Tag::List(items)) => items
+                .into_iter()
+                .filter_map(|item| match item {
+                    Tag::Compound(compound) => Some(ServerEntry::from_nbt(compound)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(Self { root, entries })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut root = self.root.clone();
+        root.insert(
+            "servers",
+            Tag::List(
+                self.entries
+                    .iter()
+                    .map(|entry| Tag::Compound(entry.to_nbt()))
+                    .collect(),
+            ),
+        );
+        nbt::write("", &root)
+    }
+
+    /// A missing file is an empty list the game creates it on first save
+    pub async fn load(path: &Path) -> ClusterResult<Self> {
+        if !polyio::try_exists(path).await.unwrap_or(false) {
+            return Ok(Self::default());
+        }
+        let bytes = polyio::read(path).await?;
+        Ok(Self::from_bytes(&bytes)?)
+    }
+
+    pub async fn save(&self, path: &Path) -> ClusterResult<()> {
+        if polyio::try_exists(path).await.unwrap_or(false) {
+            let backup = path.with_file_name(BACKUP_FILE);
+            if let Err(err) = polyio::copy(path, &backup).await {
+                tracing::warn!(error = %err, "failed to back up servers.dat");
+            }
+        }
+        polyio::write_atomic(path, self.to_bytes()).await?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[ServerEntry] {
+        &self.entries
+    }
+
+    pub fn position(&self, address: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.is_address(address))
+    }
+
+    pub fn add(&mut self, entry: ServerEntry) -> Result<(), ServersError> {
+        if self.position(&entry.address).is_some() {
+            return Err(ServersError::Duplicate(entry.address));
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, address: &str) -> Result<ServerEntry, ServersError> {
+        let index = self
+            .position(address)
+            .ok_or_else(|| ServersError::NotFound(address.to_string()))?;
+        Ok(self.entries.remove(index))
+    }
+
+    /// `to` is the index the entry ends up at
+    pub fn move_to(&mut self, address: &str, to: usize) -> Result<(), ServersError> {
+        let from = self
+            .position(address)
+            .ok_or_else(|| ServersError::NotFound(address.to_string()))?;
+        let len = self.entries.len();
+        if to >= len {
+            return Err(ServersError::OutOfRange { index: to, len });
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        Ok(())
+    }
+}
+
+fn servers_path(cluster: &Cluster) -> ClusterResult<std::path::PathBuf> {
+    Ok(cluster.game_dir()?.join(SERVERS_FILE))
+}
+
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn list_servers(cluster: &Cluster) -> ClusterResult<Vec<ServerEntry>> {
+    Ok(ServerList::load(&servers_path(cluster)?).await?.entries)
+}
+
+#[tracing::instrument(level = "debug", skip(cluster, entry), fields(cluster_id = cluster.id, address = %entry.address))]
+pub async fn add_server(cluster: &Cluster, entry: ServerEntry) -> ClusterResult<()> {
+    let path = servers_path(cluster)?;
+    let mut list = ServerList::load(&path).await?;
+    list.add(entry)?;
+    list.save(&path).await
+}
+
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn remove_server(cluster: &Cluster, address: &str) -> ClusterResult<ServerEntry> {
+    let path = servers_path(cluster)?;
+    let mut list = ServerList::load(&path).await?;
+    let removed = list.remove(address)?;
+    list.save(&path).await?;
+    Ok(removed)
+}
+
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn move_server(cluster: &Cluster, address: &str, to: usize) -> ClusterResult<()> {
+    let path = servers_path(cluster)?;
+    let mut list = ServerList::load(&path).await?;
+    list.move_to(address, to)?;
+    list.save(&path).await
+}
+
+/// Normalized addresses each source put in the list including ones the
+/// player has since removed so those stay removed
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ownership {
+    #[serde(default)]
+    sources: HashMap<String, Vec<String>>,
+}
+
+impl Ownership {
+    async fn load(game_dir: &Path) -> Self {
+        match polyio::read(game_dir.join(OWNERSHIP_REL_PATH)).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+                tracing::warn!(error = %err, "corrupt shared server ownership; starting fresh");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SharedServersReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl SharedServersReport {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Brings `source`'s entries in the list at `game_dir` in line with `shared`
+/// A server the player already had stays theirs and one they removed after
+/// it was pushed is not pushed again an empty `shared` withdraws the source
+#[tracing::instrument(level = "debug", skip(game_dir, shared), fields(game_dir = %game_dir.display(), count = shared.len()))]
+pub async fn sync_shared_servers(
+    game_dir: &Path,
+    source: &str,
+    shared: &[ServerEntry],
+) -> ClusterResult<SharedServersReport> {
+    let path = game_dir.join(SERVERS_FILE);
+    let mut ownership = Ownership::load(game_dir).await;
+    let previous: HashSet<String> = ownership
+        .sources
+        .get(source)
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+
+    let mut list = ServerList::load(&path).await?;
+    let report = merge_shared(&mut list, &previous, shared);
+
+    let owned: Vec<String> = shared
+        .iter()
+        .map(|entry| normalize_address(&entry.address))
+        .filter(|address| previous.contains(address) || report.added.contains(address))
+        .collect();
+    let ownership_changed = owned.iter().collect::<HashSet<_>>() != previous.iter().collect();
+
+    if !report.is_empty() {
+        list.save(&path).await?;
+    }
+    if ownership_changed {
+        if owned.is_empty() {
+            ownership.sources.remove(source);
+        } else {
+            ownership.sources.insert(source.to_string(), owned);
+        }
+        polyio::write_json_atomic(game_dir.join(OWNERSHIP_REL_PATH), &ownership).await?;
+    }
+
+    Ok(report)
+}
+
+/// Report entries are normalized addresses
+fn merge_shared(
+    list: &mut ServerList,
+    previous: &HashSet<String>,
+    shared: &[ServerEntry],
+) -> SharedServersReport {
+    let mut report = SharedServersReport::default();
+    let wanted: HashSet<String> = shared
+        .iter()
+        .map(|entry| normalize_address(&entry.address))
+        .collect();
+
+    list.entries.retain(|entry| {
+        let address = normalize_address(&entry.address);
+        let withdrawn = previous.contains(&address) && !wanted.contains(&address);
+        if withdrawn {
+            report.removed.push(address);
+        }
+        !withdrawn
+    });
+
+    for entry in shared {
+        let address = normalize_address(&entry.address);
+        match list.position(&entry.address) {
+            // Only the name is pushed the icon and texture prompt are the
+            // game's own state for the entry
+            Some(index) if previous.contains(&address) => {
+                let existing = &mut list.entries[index];
+                if existing.name != entry.name || existing.address != entry.address {
+                    existing.name = entry.name.clone();
+                    existing.address = entry.address.clone();
+                    report.updated.push(address);
+                }
+            }
+            Some(_) => {}
+            None if previous.contains(&address) => {}
+            None => {
+                list.entries.push(entry.clone());
+                report.added.push(address);
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_of(entries: &[(&str, &str)]) -> ServerList {
+        let mut list = ServerList::default();
+        for (name, address) in entries {
+            list.add(ServerEntry::new(*name, *address)).unwrap();
+        }
+        list
+    }
+
+    fn addresses(list: &ServerList) -> Vec<&str> {
+        list.entries()
+            .iter()
+            .map(|entry| entry.address.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn unknown_fields_and_root_keys_are_kept() {
+        let mut extra = Compound::new();
+        extra.insert("name", Tag::String("Lobby".to_string()));
+        extra.insert("ip", Tag::String("lobby.example.com".to_string()));
+        extra.insert("preventsChatReports", Tag::Byte(1));
+        let mut root = Compound::new();
+        root.insert("servers", Tag::List(vec![Tag::Compound(extra)]));
+        root.insert("someModKey", Tag::Int(7));
+
+        let mut list = ServerList::from_bytes(&nbt::write("", &root)).unwrap();
+        list.add(ServerEntry::new("Test", "test.example.com"))
+            .unwrap();
+
+        let mut written = ServerList::from_bytes(&list.to_bytes()).unwrap();
+        assert_eq!(written, list);
+        assert_eq!(written.root.remove("someModKey"), Some(Tag::Int(7)));
+        assert_eq!(
+            written.entries[0].extra.remove("preventsChatReports"),
+            Some(Tag::Byte(1))
+        );
+        assert_eq!(written.entries[0].address, "lobby.example.com");
+        assert_eq!(written.entries.len(), 2);
+    }
+
+    #[test]
+    fn the_default_port_and_case_do_not_make_a_new_server() {
+        let mut list = list_of(&[("Lobby", "Lobby.Example.com")]);
+        assert!(matches!(
+            list.add(ServerEntry::new("Again", "lobby.example.com:25565")),
+            Err(ServersError::Duplicate(_))
+        ));
+        list.add(ServerEntry::new("Other port", "lobby.example.com:25570"))
+            .unwrap();
+    }
+
+    #[test]
+    fn moving_places_the_entry_at_the_target_index() {
+        let mut list = list_of(&[("A", "a"), ("B", "b"), ("C", "c")]);
+        list.move_to("c", 0).unwrap();
+        assert_eq!(addresses(&list), ["c", "a", "b"]);
+        list.move_to("c", 2).unwrap();
+        assert_eq!(addresses(&list), ["a", "b", "c"]);
+        assert!(matches!(
+            list.move_to("a", 3),
+            Err(ServersError::OutOfRange { index: 3, len: 3 })
+        ));
+    }
+
+    #[test]
+    fn shared_servers_leave_the_players_own_alone() {
+        let mut list = list_of(&[("Mine", "mine.example.com"), ("Test", "test.example.com")]);
+        let shared = [
+            ServerEntry::new("Studio test", "test.example.com"),
+            ServerEntry::new("Studio staging", "staging.example.com"),
+        ];
+
+        let report = merge_shared(&mut list, &HashSet::new(), &shared);
+        assert_eq!(report.added, ["staging.example.com"]);
+        assert!(report.updated.is_empty());
+        // Already the player's so not renamed and not claimed
+        assert_eq!(list.entries()[1].name, "Test");
+        assert_eq!(
+            addresses(&list),
+            [
+                "mine.example.com",
+                "test.example.com",
+                "staging.example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_sources_own_entries_are_updated_or_withdrawn() {
+        let mut list = list_of(&[
+            ("Mine", "mine.example.com"),
+            ("Old name", "staging.example.com"),
+            ("Retired", "old.example.com"),
+        ]);
+        let previous: HashSet<String> =
+            ["staging.example.com", "old.example.com", "gone.example.com"]
+                .into_iter()
+                .map(String::from)
+                .collect();
+        let shared = [
+            ServerEntry::new("Staging", "staging.example.com"),
+            ServerEntry::new("Gone", "gone.example.com"),
+        ];
+
+        let report = merge_shared(&mut list, &previous, &shared);
+        assert_eq!(report.updated, ["staging.example.com"]);
+        assert_eq!(report.removed, ["old.example.com"]);
+        // The player deleted this one after it was pushed
+        assert!(report.added.is_empty());
+        assert_eq!(
+            addresses(&list),
+            ["mine.example.com", "staging.example.com"]
+        );
+        assert_eq!(list.entries()[1].name, "Staging");
+    }
+
+    #[tokio::test]
+    async fn a_withdrawn_source_removes_its_servers_on_disk() {
+        let dir = polyio::testing::ScratchDir::new("shared_servers");
+        let shared = [ServerEntry::new("Studio test", "test.example.com")];
+        list_of(&[("Mine", "mine.example.com")])
+            .save(&dir.join(SERVERS_FILE))
+            .await
+            .unwrap();
+
+        let report = sync_shared_servers(dir.path(), "studio", &shared)
+            .await
+            .unwrap();
+        assert_eq!(report.added, ["test.example.com"]);
+        assert!(
+            sync_shared_servers(dir.path(), "studio", &shared)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let report = sync_shared_servers(dir.path(), "studio", &[])
+            .await
+            .unwrap();
+        assert_eq!(report.removed, ["test.example.com"]);
+        let list = ServerList::load(&dir.join(SERVERS_FILE)).await.unwrap();
+        assert_eq!(addresses(&list), ["mine.example.com"]);
+        assert!(dir.join(BACKUP_FILE).exists());
+    }
+}
@@ -0,0 +1,383 @@
+//! Zips in the game's own `backups` folder named the way its "Make Backup"
+//! button names them so backups made in-game list and restore here too
+
+use std::path::{Path, PathBuf};
+
+use async_zip::{Compression, ZipEntryBuilder};
+use chrono::{DateTime, Local, NaiveDateTime, SubsecRound, TimeZone, Utc};
+
+use super::{WorldsError, is_plain_folder, world_dir};
+use crate::cluster::Cluster;
+use crate::error::ClusterResult;
+
+pub const BACKUPS_DIR: &str = "backups";
+pub(super) const STAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+const STAMP_LEN: usize = "2000-01-01_00-00-00".len();
+/// Held open by a running game copying it would restore a stale lock
+pub(super) const SESSION_LOCK: &str = "session.lock";
+pub(super) const RESTORING_PREFIX: &str = ".restoring-";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldBackup {
+    pub path: PathBuf,
+    /// Folder name of the world it holds
+    pub world: String,
+    pub created: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+fn backups_dir(cluster: &Cluster) -> ClusterResult<PathBuf> {
+    Ok(cluster.game_dir()?.join(BACKUPS_DIR))
+}
+
+/// `None` for anything in the folder that is not a world backup a `-2` style
+/// counter after the stamp is ours from backups made within the same second
+fn parse_backup_name(name: &str) -> Option<(DateTime<Utc>, String)> {
+    let stem = name.strip_suffix(".zip")?;
+    let (stamp, rest) = (stem.get(..STAMP_LEN)?, stem.get(STAMP_LEN..)?);
+    let rest = match rest.strip_prefix('-') {
+        Some(counted) => counted.trim_start_matches(|c: char| c.is_ascii_digit()),
+        None => rest,
+    };
+    let world = rest.strip_prefix('_').filter(|world| !world.is_empty())?;
+    let naive = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+    let created = Local.from_local_datetime(&naive).earliest()?;
+    Some((created.with_timezone(&Utc), world.to_string()))
+}
+
+/// Newest first `world` narrows it to one world's backups
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn list_world_backups(
+    cluster: &Cluster,
+    world: Option<&str>,
+) -> ClusterResult<Vec<WorldBackup>> {
+    list_backups_in(&backups_dir(cluster)?, world).await
+}
+
+async fn list_backups_in(dir: &Path, world: Option<&str>) -> ClusterResult<Vec<WorldBackup>> {
+    let mut out = Vec::new();
+    let Ok(mut entries) = polyio::read_dir(dir).await else {
+        return Ok(out);
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let Some((created, backup_world)) = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .and_then(parse_backup_name)
+        else {
+            continue;
+        };
+        if world.is_some_and(|world| world != backup_world) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata().await else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+
+        out.push(WorldBackup {
+            path,
+            world: backup_world,
+            created,
+            size_bytes: meta.len(),
+        });
+    }
+
+    out.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+    Ok(out)
+}
+
+/// Safe on a world the game has open only in the sense that nothing is
+/// changed the copy may be mid-save so callers stop the game first
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn backup_world(cluster: &Cluster, folder: &str) -> ClusterResult<WorldBackup> {
+    let source = world_dir(cluster, folder)?;
+    backup_world_at(&source, folder, &backups_dir(cluster)?).await
+}
+
+async fn backup_world_at(source: &Path, folder: &str, dir: &Path) -> ClusterResult<WorldBackup> {
+    if !source.is_dir() {
+        return Err(WorldsError::NotFound(folder.to_string()).into());
+    }
+
+    // Whole seconds since that is all the name and so a later listing keeps
+    let now = Local::now().trunc_subsecs(0);
+    let stamp = now.format(STAMP_FORMAT).to_string();
+    polyio::create_dir_all(dir).await?;
+    let dest = free_backup_path(dir, &stamp, folder).await;
+
+    let partial = dest.with_extension("partial");
+    if let Err(err) = write_world_zip(source, folder, &partial).await {
+        polyio::remove_file(&partial).await.ok();
+        return Err(err);
+    }
+    polyio::rename(&partial, &dest).await?;
+
+    let size_bytes = polyio::stat(&dest).await?.len();
+    Ok(WorldBackup {
+        path: dest,
+        world: folder.to_string(),
+        created: now.with_timezone(&Utc),
+        size_bytes,
+    })
+}
+
+/// The counter sits before the world name so it can never be mistaken for
+/// part of it
+async fn free_backup_path(dir: &Path, stamp: &str, folder: &str) -> PathBuf {
+    let mut dest = dir.join(format!("{stamp}_{folder}.zip"));
+    let mut counter = 2;
+    while polyio::try_exists(&dest).await.unwrap_or(false) {
+        dest = dir.join(format!("{stamp}-{counter}_{folder}.zip"));
+        counter += 1;
+    }
+    dest
+}
+
+/// Entries sit under the world's folder name as the game's own backups do
+async fn write_world_zip(source: &Path, folder: &str, dest: &Path) -> ClusterResult<()> {
+    let file = tokio::fs::File::create(dest).await?;
+    let mut writer = async_zip::tokio::write::ZipFileWriter::with_tokio(file);
+
+    for (relative, path) in world_files(source).await? {
+        if relative == SESSION_LOCK {
+            continue;
+        }
+        let data = polyio::read(&path).await?;
+        let builder =
+            ZipEntryBuilder::new(format!("{folder}/{relative}").into(), Compression::Deflate);
+        writer
+            .write_entry_whole(builder, &data)
+            .await
+            .map_err(polyio::IOError::from)?;
+    }
+
+    writer.close().await.map_err(polyio::IOError::from)?;
+    Ok(())
+}
+
+/// Slash-separated relative paths symlinks are skipped so a link out of the
+/// world can never pull outside files into a backup
+pub(super) async fn world_files(root: &Path) -> ClusterResult<Vec<(String, PathBuf)>> {
+    let mut out = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut entries = polyio::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file()
+                && let Some(relative) = relative_slash_path(root, &path)
+            {
+                out.push((relative, path));
+            }
+        }
+    }
+
+    out.sort();
+    Ok(out)
+}
+
+fn relative_slash_path(root: &Path, path: &Path) -> Option<String> {
+    let parts: Vec<&str> = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    Some(parts.join("/"))
+}
+
+/// Returns the restored world's folder
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn restore_world_backup(cluster: &Cluster, backup: &Path) -> ClusterResult<String> {
+    let dir = backups_dir(cluster)?;
+    let backup = polyio::ensure_under(backup, [&dir])?
+        .ok_or_else(|| WorldsError::InvalidBackup(backup.display().to_string()))?;
+    let saves = cluster.game_dir()?.join(super::SAVES_DIR);
+    restore_world_at(&backup, &saves).await
+}
+
+async fn restore_world_at(backup: &Path, saves: &Path) -> ClusterResult<String> {
+    let invalid = || WorldsError::InvalidBackup(backup.display().to_string());
+    let folder = backup_folder(backup).await?.ok_or_else(invalid)?;
+
+    // Extracted beside the target so the final move is a rename on one disk
+    let staging = saves.join(format!("{RESTORING_PREFIX}{folder}"));
+    if polyio::try_exists(&staging).await.unwrap_or(false) {
+        polyio::remove_dir_all(&staging).await?;
+    }
+    polyio::create_dir_all(&staging).await?;
+
+    let result = async {
+        polyio::extract_zip(backup, &staging).await?;
+        let extracted = staging.join(&folder);
+        if !super::level::has_level(&extracted) {
+            return Err(invalid().into());
+        }
+
+        swap_in(&extracted, &saves.join(&folder)).await
+    }
+    .await;
+
+    polyio::remove_dir_all(&staging).await.ok();
+    result.map(|()| folder)
+}
+
+/// A world already at `target` goes to the trash first so the restore itself
+/// can be undone
+pub(super) async fn swap_in(staged: &Path, target: &Path) -> ClusterResult<()> {
+    if polyio::try_exists(target).await.unwrap_or(false) {
+        super::trash_world(target.to_path_buf()).await?;
+    }
+    polyio::rename(staged, target).await?;
+    Ok(())
+}
+
+/// The one top-level folder every entry sits under `None` when there is not
+/// exactly one
+async fn backup_folder(backup: &Path) -> ClusterResult<Option<String>> {
+    let reader = async_zip::tokio::read::fs::ZipFileReader::new(backup)
+        .await
+        .map_err(polyio::IOError::from)?;
+
+    let mut folder: Option<String> = None;
+    for entry in reader.file().entries() {
+        let name = entry
+            .filename()
+            .as_str()
+            .map_err(polyio::IOError::from)?
+            .replace('\\', "/");
+        let Some(top) = name.split('/').next().filter(|_| name.contains('/')) else {
+            return Ok(None);
+        };
+        match &folder {
+            Some(existing) if existing != top => return Ok(None),
+            Some(_) => {}
+            None => folder = Some(top.to_string()),
+        }
+    }
+
+    Ok(folder.filter(|folder| is_plain_folder(folder)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_names_match_the_games_own() {
+        let (created, world) = parse_backup_name("2024-03-09_17-05-42_New World.zip").unwrap();
+        assert_eq!(world, "New World");
+        assert_eq!(
+            created
+                .with_timezone(&Local)
+                .format(STAMP_FORMAT)
+                .to_string(),
+            "2024-03-09_17-05-42"
+        );
+        // Underscores in the world name are part of it
+        assert_eq!(
+            parse_backup_name("2024-03-09_17-05-42_my_world.zip").map(|(_, world)| world),
+            Some("my_world".to_string())
+        );
+        assert_eq!(
+            parse_backup_name("2024-03-09_17-05-42-2_my_world.zip").map(|(_, world)| world),
+            Some("my_world".to_string())
+        );
+        assert_eq!(parse_backup_name("2024-03-09_17-05-42_.zip"), None);
+        assert_eq!(parse_backup_name("notes.zip"), None);
+        assert_eq!(parse_backup_name("2024-03-09_17-05-42_world.tar"), None);
+    }
+
+    #[tokio::test]
+    async fn a_backup_restores_to_the_same_world() {
+        let root = polyio::testing::ScratchDir::new("world_backup");
+        let saves = root.join("saves");
+        let world = saves.join("Castle");
+        polyio::create_dir_all(world.join("region")).await.unwrap();
+        polyio::write(world.join("level.dat"), b"level")
+            .await
+            .unwrap();
+        polyio::write(world.join("region/r.0.0.mca"), b"chunks")
+            .await
+            .unwrap();
+        polyio::write(world.join(SESSION_LOCK), b"lock")
+            .await
+            .unwrap();
+
+        let backups = root.join(BACKUPS_DIR);
+        let backup = backup_world_at(&world, "Castle", &backups).await.unwrap();
+        assert_eq!(backup.world, "Castle");
+        assert_eq!(
+            list_backups_in(&backups, Some("Castle")).await.unwrap(),
+            vec![backup.clone()]
+        );
+        assert!(
+            list_backups_in(&backups, Some("Other"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        polyio::remove_dir_all(&world).await.unwrap();
+        let folder = restore_world_at(&backup.path, &saves).await.unwrap();
+        assert_eq!(folder, "Castle");
+        assert_eq!(
+            polyio::read(world.join("region/r.0.0.mca")).await.unwrap(),
+            b"chunks"
+        );
+        assert!(!world.join(SESSION_LOCK).exists());
+        assert!(!saves.join(format!("{RESTORING_PREFIX}Castle")).exists());
+    }
+
+    #[tokio::test]
+    async fn backups_in_the_same_second_keep_each_other() {
+        let root = polyio::testing::ScratchDir::new("world_backup_same_second");
+        let world = root.join("saves/Castle");
+        polyio::create_dir_all(&world).await.unwrap();
+        polyio::write(world.join("level.dat"), b"level")
+            .await
+            .unwrap();
+
+        let backups = root.join(BACKUPS_DIR);
+        let stamp = Local::now().format(STAMP_FORMAT).to_string();
+        polyio::create_dir_all(&backups).await.unwrap();
+        polyio::write(backups.join(format!("{stamp}_Castle.zip")), b"first")
+            .await
+            .unwrap();
+
+        let dest = free_backup_path(&backups, &stamp, "Castle").await;
+        assert_eq!(dest, backups.join(format!("{stamp}-2_Castle.zip")));
+        backup_world_at(&world, "Castle", &backups).await.unwrap();
+        assert_eq!(
+            polyio::read(backups.join(format!("{stamp}_Castle.zip")))
+                .await
+                .unwrap(),
+            b"first"
+        );
+    }
+
+    #[tokio::test]
+    async fn an_archive_that_is_not_one_world_is_refused() {
+        let root = polyio::testing::ScratchDir::new("world_backup_invalid");
+        let zip = root.join("2024-03-09_17-05-42_loose.zip");
+        let file = tokio::fs::File::create(&zip).await.unwrap();
+        let mut writer = async_zip::tokio::write::ZipFileWriter::with_tokio(file);
+        for name in ["a/level.dat", "b/level.dat"] {
+            let builder = ZipEntryBuilder::new(name.to_string().into(), Compression::Stored);
+            writer.write_entry_whole(builder, b"x").await.unwrap();
+        }
+        writer.close().await.unwrap();
+
+        assert!(restore_world_at(&zip, &root.join("saves")).await.is_err());
+        assert!(!root.join("saves/a").exists());
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+use super::WorldsError;
+use crate::error::ClusterResult;
+use crate::nbt::{self, Compound};
+
+const LEVEL_FILE: &str = "level.dat";
+/// The game's own copy from the save before last
+const LEVEL_BACKUP_FILE: &str = "level.dat_old";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    fn from_id(id: i64) -> Option<Self> {
+        match id {
+            0 => Some(Self::Survival),
+            1 => Some(Self::Creative),
+            2 => Some(Self::Adventure),
+            3 => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    fn from_id(id: i64) -> Option<Self> {
+        match id {
+            0 => Some(Self::Peaceful),
+            1 => Some(Self::Easy),
+            2 => Some(Self::Normal),
+            3 => Some(Self::Hard),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "peaceful" => Some(Self::Peaceful),
+            "easy" => Some(Self::Easy),
+            "normal" => Some(Self::Normal),
+            "hard" => Some(Self::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// Every field is optional because each has moved or appeared at some point
+/// across the versions a cluster can run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LevelSummary {
+    pub name: Option<String>,
+    pub game_mode: Option<GameMode>,
+    pub hardcore: bool,
+    pub last_played: Option<DateTime<Utc>>,
+    pub seed: Option<i64>,
+    /// The release name such as `1.21.4` absent before 1.9
+    pub version: Option<String>,
+    pub difficulty: Option<Difficulty>,
+}
+
+pub(super) fn summarize(root: &Compound) -> LevelSummary {
+    let Some(data) = root.get_compound("Data") else {
+        return LevelSummary::default();
+    };
+
+    // 1.16 moved the seed under WorldGenSettings
+    let seed = data
+        .get_compound("WorldGenSettings")
+        .and_then(|settings| settings.get_i64("seed"))
+        .or_else(|| data.get_i64("RandomSeed"));
+    // Newer snapshots replaced the byte with a named setting
+    let difficulty = data
+        .get_i64("Difficulty")
+        .and_then(Difficulty::from_id)
+        .or_else(|| {
+            data.get_compound("difficulty_settings")
+                .and_then(|settings| settings.get_str("difficulty"))
+                .and_then(Difficulty::from_name)
+        });
+
+    LevelSummary {
+        name: data.get_str("LevelName").map(str::to_string),
+        game_mode: data.get_i64("GameType").and_then(GameMode::from_id),
+        hardcore: data.get_bool("hardcore").unwrap_or(false),
+        last_played: data
+            .get_i64("LastPlayed")
+            .and_then(DateTime::from_timestamp_millis),
+        seed,
+        version: data
+            .get_compound("Version")
+            .and_then(|version| version.get_str("Name"))
+            .map(str::to_string),
+        difficulty,
+    }
+}
+
+/// Falls back to `level.dat_old` like the game does when a crash mid-save
+/// left the main file unreadable
+pub(super) async fn read_level(world_dir: &Path) -> Option<LevelSummary> {
+    for file in [LEVEL_FILE, LEVEL_BACKUP_FILE] {
+        match read_level_file(&world_dir.join(file)).await {
+            Ok(Some(summary)) => return Some(summary),
+            Ok(None) => {}
+            Err(err) => {
+                tracing::debug!(world = %world_dir.display(), file, error = %err, "unreadable level data");
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn has_level(world_dir: &Path) -> bool {
+    world_dir.join(LEVEL_FILE).is_file() || world_dir.join(LEVEL_BACKUP_FILE).is_file()
+}
+
+/// When the game last saved the world it rewrites `level.dat` on every save
+pub(super) async fn level_saved_at(world_dir: &Path) -> Option<DateTime<Utc>> {
+    let modified = polyio::stat(world_dir.join(LEVEL_FILE))
+        .await
+        .ok()?
+        .modified()
+        .ok()?;
+    Some(modified.into())
+}
+
+async fn read_level_file(path: &Path) -> ClusterResult<Option<LevelSummary>> {
+    if !polyio::try_exists(path).await.unwrap_or(false) {
+        return Ok(None);
+    }
+    let raw = polyio::read(path).await?;
+    // Always gzip when the game writes it but some tools save it plain
+    let bytes = if raw.starts_with(&[0x1F, 0x8B]) {
+        polyio::gunzip(&raw).await?
+    } else {
+        raw
+    };
+    let (_, root) = nbt::read(&bytes).map_err(WorldsError::from)?;
+    Ok(Some(summarize(&root)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::Tag;
+
+    fn compound(entries: Vec<(&str, Tag)>) -> Compound {
+        let mut compound = Compound::new();
+        for (key, tag) in entries {
+            compound.insert(key, tag);
+        }
+        compound
+    }
+
+    #[test]
+    fn modern_level_data_reads_every_field() {
+        let data = compound(vec![
+            ("LevelName", Tag::String("Survival Island".to_string())),
+            ("GameType", Tag::Int(0)),
+            ("hardcore", Tag::Byte(1)),
+            ("LastPlayed", Tag::Long(1_700_000_000_000)),
+            ("Difficulty", Tag::Byte(3)),
+            (
+                "WorldGenSettings",
+                Tag::Compound(compound(vec![("seed", Tag::Long(-42))])),
+            ),
+            (
+                "Version",
+                Tag::Compound(compound(vec![("Name", Tag::String("1.21.4".to_string()))])),
+            ),
+        ]);
+        let summary = summarize(&compound(vec![("Data", Tag::Compound(data))]));
+
+        assert_eq!(summary.name.as_deref(), Some("Survival Island"));
+        assert_eq!(summary.game_mode, Some(GameMode::Survival));
+        assert!(summary.hardcore);
+        assert_eq!(
+            summary.last_played.map(|time| time.timestamp()),
+            Some(1_700_000_000)
+        );
+        assert_eq!(summary.seed, Some(-42));
+        assert_eq!(summary.version.as_deref(), Some("1.21.4"));
+        assert_eq!(summary.difficulty, Some(Difficulty::Hard));
+    }
+
+    #[test]
+    fn older_and_newer_layouts_find_the_moved_fields() {
+        let old = compound(vec![
+            ("RandomSeed", Tag::Long(7)),
+            ("GameType", Tag::Int(1)),
+        ]);
+        let summary = summarize(&compound(vec![("Data", Tag::Compound(old))]));
+        assert_eq!(summary.seed, Some(7));
+        assert_eq!(summary.game_mode, Some(GameMode::Creative));
+        assert_eq!(summary.version, None);
+
+        let new = compound(vec![(
+            "difficulty_settings",
+            Tag::Compound(compound(vec![(
+                "difficulty",
+                Tag::String("peaceful".to_string()),
+            )])),
+        )]);
+        let summary = summarize(&compound(vec![("Data", Tag::Compound(new))]));
+        assert_eq!(summary.difficulty, Some(Difficulty::Peaceful));
+    }
+}
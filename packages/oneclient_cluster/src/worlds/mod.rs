@@ -0,0 +1,173 @@
+//! Saves under a cluster's game dir for a non-dedicated cluster that is the
+//! shared `.minecraft` so its worlds are every such cluster's not this one's
+//! Worlds are named by folder and resolved against the cluster's own game dir
+//! a path from the caller is only ever taken for a backup or snapshot and
+//! checked first
+
+mod backup;
+mod level;
+mod snapshot;
+
+use std::path::{Component, Path, PathBuf};
+
+use bytes::Bytes;
+use thiserror::Error;
+
+use crate::cluster::Cluster;
+use crate::error::ClusterResult;
+
+pub use backup::{WorldBackup, backup_world, list_world_backups, restore_world_backup};
+pub use level::{Difficulty, GameMode, LevelSummary};
+pub use snapshot::{
+    SnapshotPolicy, WorldSnapshot, list_world_snapshots, restore_world_snapshot,
+    snapshot_played_worlds,
+};
+
+pub const SAVES_DIR: &str = "saves";
+const ICON_FILE: &str = "icon.png";
+
+#[derive(Debug, Error)]
+pub enum WorldsError {
+    #[error("invalid world folder name: {0}")]
+    InvalidName(String),
+    #[error("no world in folder {0}")]
+    NotFound(String),
+    #[error("not a world backup: {0}")]
+    InvalidBackup(String),
+    #[error("failed to move world to trash: {0}")]
+    Trash(String),
+    #[error(transparent)]
+    Nbt(#[from] crate::nbt::NbtError),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldInfo {
+    pub folder: String,
+    pub path: PathBuf,
+    /// `None` when neither `level.dat` nor the game's backup of it parses
+    pub level: Option<LevelSummary>,
+    pub icon: Option<PathBuf>,
+    /// Lives in the shared `.minecraft` where every non-dedicated cluster
+    /// sees and plays it
+    pub shared: bool,
+}
+
+impl WorldInfo {
+    pub fn display_name(&self) -> &str {
+        self.level
+            .as_ref()
+            .and_then(|level| level.name.as_deref())
+            .unwrap_or(&self.folder)
+    }
+}
+
+fn saves_dir(cluster: &Cluster) -> ClusterResult<PathBuf> {
+    Ok(cluster.game_dir()?.join(SAVES_DIR))
+}
+
+/// A single plain path component anything else could reach outside `saves`
+pub fn is_plain_folder(folder: &str) -> bool {
+    let mut components = Path::new(folder).components();
+    matches!(components.next(), Some(Component::Normal(name)) if name == folder)
+        && components.next().is_none()
+}
+
+fn world_dir(cluster: &Cluster, folder: &str) -> ClusterResult<PathBuf> {
+    if !is_plain_folder(folder) {
+        return Err(WorldsError::InvalidName(folder.to_string()).into());
+    }
+    Ok(saves_dir(cluster)?.join(folder))
+}
+
+async fn read_world_at(path: PathBuf, folder: String, shared: bool) -> WorldInfo {
+    let icon = path.join(ICON_FILE);
+    WorldInfo {
+        level: level::read_level(&path).await,
+        icon: icon.is_file().then_some(icon),
+        folder,
+        path,
+        shared,
+    }
+}
+
+/// Most recently played first folders without level data are not worlds
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn list_worlds(cluster: &Cluster) -> ClusterResult<Vec<WorldInfo>> {
+    let shared = !cluster.uses_dedicated_dir();
+    let mut out = Vec::new();
+
+    let Ok(mut entries) = polyio::read_dir(saves_dir(cluster)?).await else {
+        return Ok(out);
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let Some(folder) = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if !path.is_dir() || !level::has_level(&path) {
+            continue;
+        }
+        out.push(read_world_at(path, folder, shared).await);
+    }
+
+    out.sort_by(|a, b| {
+        let played = |world: &WorldInfo| world.level.as_ref().and_then(|level| level.last_played);
+        played(b)
+            .cmp(&played(a))
+            .then_with(|| a.folder.cmp(&b.folder))
+    });
+    Ok(out)
+}
+
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn read_world(cluster: &Cluster, folder: &str) -> ClusterResult<WorldInfo> {
+    let path = world_dir(cluster, folder)?;
+    if !level::has_level(&path) {
+        return Err(WorldsError::NotFound(folder.to_string()).into());
+    }
+    Ok(read_world_at(path, folder.to_string(), !cluster.uses_dedicated_dir()).await)
+}
+
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn load_world_icon(cluster: &Cluster, folder: &str) -> ClusterResult<Option<Bytes>> {
+    let path = world_dir(cluster, folder)?.join(ICON_FILE);
+    if !polyio::try_exists(&path).await.unwrap_or(false) {
+        return Ok(None);
+    }
+    Ok(Some(Bytes::from(polyio::read(&path).await?)))
+}
+
+/// To the system trash so a mistaken delete can be undone there
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn delete_world(cluster: &Cluster, folder: &str) -> ClusterResult<()> {
+    let path = world_dir(cluster, folder)?;
+    if !polyio::try_exists(&path).await.unwrap_or(false) {
+        return Err(WorldsError::NotFound(folder.to_string()).into());
+    }
+    trash_world(path).await
+}
+
+async fn trash_world(path: PathBuf) -> ClusterResult<()> {
+    tokio::task::spawn_blocking(move || trash::delete(&path))
+        .await
+        .map_err(std::io::Error::other)?
+        .map_err(|err| WorldsError::Trash(err.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_folders_cannot_climb_out_of_saves() {
+        assert!(is_plain_folder("New World"));
+        assert!(is_plain_folder("..world"));
+        for name in ["", ".", "..", "../other", "a/b", "/abs", "world/"] {
+            assert!(!is_plain_folder(name), "{name:?}");
+        }
+    }
+}
@@ -0,0 +1,753 @@
+//! Taken automatically when a session ends every file is stored once under its
+//! SHA-1 and a snapshot is only a JSON list of them so a region file nothing
+//! was built in costs no space in the next one
+//! Kept under the launcher's own folder not the game dir a snapshot has to
+//! outlive the world and even the cluster it came from
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Local, SubsecRound, Utc};
+use oneclient_common::paths;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, MutexGuard};
+
+use super::backup::{RESTORING_PREFIX, SESSION_LOCK, STAMP_FORMAT, swap_in, world_files};
+use super::{WorldsError, is_plain_folder, level};
+use crate::cluster::Cluster;
+use crate::error::ClusterResult;
+
+const OBJECTS_DIR: &str = "objects";
+const SNAPSHOTS_DIR: &str = "snapshots";
+const SHARED_SCOPE: &str = "shared";
+const CLUSTERS_SCOPE: &str = "clusters";
+const MANIFEST_VERSION: u32 = 1;
+const LOCK_FILE: &str = "objects.lock";
+
+/// Held from storing objects to sweeping them so one session's sweep never
+/// sees objects another has stored but not yet listed in a manifest
+static STORE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// [`STORE_LOCK`] plus a lock on a file in the store since the CLI can run
+/// beside the launcher over the same one
+struct StoreLock {
+    _file: std::fs::File,
+    _guard: MutexGuard<'static, ()>,
+}
+
+async fn lock_store(root: &Path) -> ClusterResult<StoreLock> {
+    let guard = STORE_LOCK.lock().await;
+    polyio::create_dir_all(root).await?;
+    let path = root.join(LOCK_FILE);
+    let file = tokio::task::spawn_blocking(move || {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        file.lock()?;
+        Ok::<_, std::io::Error>(file)
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+    Ok(StoreLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotPolicy {
+    pub enabled: bool,
+    /// Never below one the snapshot just taken is always kept
+    pub keep_recent: u32,
+    /// Days back each of which keeps its oldest snapshot
+    pub keep_daily: u32,
+    /// Weeks back each of which keeps its oldest snapshot
+    pub keep_weekly: u32,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep_recent: 5,
+            keep_daily: 1,
+            keep_weekly: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldSnapshot {
+    /// The manifest what restore is given back
+    pub path: PathBuf,
+    pub world: String,
+    pub created: DateTime<Utc>,
+    /// What a restore writes most of it is shared with other snapshots on disk
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    world: String,
+    created: DateTime<Utc>,
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ManifestFile {
+    path: String,
+    sha1: String,
+    size: u64,
+    /// Milliseconds a file whose size and time are unchanged is not hashed again
+    modified: i64,
+}
+
+impl Manifest {
+    fn summary(&self, path: PathBuf) -> WorldSnapshot {
+        WorldSnapshot {
+            path,
+            world: self.world.clone(),
+            created: self.created,
+            size_bytes: self.files.iter().map(|file| file.size).sum(),
+        }
+    }
+
+    fn same_content(&self, files: &[ManifestFile]) -> bool {
+        self.files.len() == files.len()
+            && self
+                .files
+                .iter()
+                .zip(files)
+                .all(|(a, b)| a.path == b.path && a.sha1 == b.sha1)
+    }
+}
+
+/// Each game dir has its own history the shared `.minecraft` one is common to
+/// every non-dedicated cluster like its worlds
+fn scope_dir(root: &Path, cluster: &Cluster) -> PathBuf {
+    let snapshots = root.join(SNAPSHOTS_DIR);
+    if cluster.uses_dedicated_dir() {
+        snapshots.join(CLUSTERS_SCOPE).join(&cluster.folder_name)
+    } else {
+        snapshots.join(SHARED_SCOPE)
+    }
+}
+
+fn object_path(root: &Path, sha1: &str) -> PathBuf {
+    root.join(OBJECTS_DIR).join(&sha1[..2]).join(sha1)
+}
+
+fn is_sha1(hash: &str) -> bool {
+    hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Manifests come off disk a path in one must not climb out of the world
+fn is_plain_relative(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Snapshots every world the session saved a world unchanged since its last
+/// snapshot gets no new one
+#[tracing::instrument(level = "debug", skip(cluster, policy), fields(cluster_id = cluster.id))]
+pub async fn snapshot_played_worlds(
+    cluster: &Cluster,
+    since: DateTime<Utc>,
+    policy: &SnapshotPolicy,
+) -> ClusterResult<Vec<WorldSnapshot>> {
+    if !policy.enabled {
+        return Ok(Vec::new());
+    }
+    let root = paths::world_snapshots_dir()?;
+    let scope = scope_dir(&root, cluster);
+    snapshot_played_at(&root, &scope, &super::saves_dir(cluster)?, since, policy).await
+}
+
+async fn snapshot_played_at(
+    root: &Path,
+    scope: &Path,
+    saves: &Path,
+    since: DateTime<Utc>,
+    policy: &SnapshotPolicy,
+) -> ClusterResult<Vec<WorldSnapshot>> {
+    let _lock = lock_store(root).await?;
+    let mut taken = Vec::new();
+    let mut pruned = false;
+
+    let Ok(mut entries) = polyio::read_dir(saves).await else {
+        return Ok(taken);
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let Some(folder) = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if !path.is_dir()
+            || level::level_saved_at(&path)
+                .await
+                .is_none_or(|saved| saved < since)
+        {
+            continue;
+        }
+
+        let history = scope.join(&folder);
+        // One world that cannot be read must not cost every other its snapshot
+        match snapshot_world_at(root, &history, &path, &folder).await {
+            Ok(Some(snapshot)) => taken.push(snapshot),
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(world = folder, error = %err, "failed to snapshot world");
+            }
+        }
+        match prune_history(&history, policy).await {
+            Ok(removed) => pruned |= removed > 0,
+            Err(err) => {
+                tracing::warn!(world = folder, error = %err, "failed to prune world snapshots");
+            }
+        }
+    }
+
+    if pruned && let Err(err) = sweep_objects(root).await {
+        tracing::warn!(error = %err, "left unused world snapshot objects in place");
+    }
+    Ok(taken)
+}
+
+async fn snapshot_world_at(
+    root: &Path,
+    history: &Path,
+    source: &Path,
+    folder: &str,
+) -> ClusterResult<Option<WorldSnapshot>> {
+    let previous = read_history(history)
+        .await
+        .into_iter()
+        .next()
+        .map(|(_, manifest)| manifest);
+    let known: HashMap<&str, &ManifestFile> = previous
+        .iter()
+        .flat_map(|manifest| &manifest.files)
+        // Read back from disk so a damaged entry is stored afresh not trusted
+        .filter(|file| is_plain_relative(&file.path) && is_sha1(&file.sha1))
+        .map(|file| (file.path.as_str(), file))
+        .collect();
+
+    let mut files = Vec::new();
+    for (relative, path) in world_files(source).await? {
+        if relative == SESSION_LOCK {
+            continue;
+        }
+        let meta = polyio::stat(&path).await?;
+        let size = meta.len();
+        let modified = meta
+            .modified()
+            .map(|time| DateTime::<Utc>::from(time).timestamp_millis())
+            .unwrap_or_default();
+
+        let sha1 = match known.get(relative.as_str()) {
+            Some(file)
+                if file.size == size
+                    && file.modified == modified
+                    && object_path(root, &file.sha1).is_file() =>
+            {
+                file.sha1.clone()
+            }
+            _ => store_object(root, &path).await?,
+        };
+        files.push(ManifestFile {
+            path: relative,
+            sha1,
+            size,
+            modified,
+        });
+    }
+
+    if previous.is_some_and(|manifest| manifest.same_content(&files)) {
+        return Ok(None);
+    }
+
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        world: folder.to_string(),
+        created: Utc::now().trunc_subsecs(3),
+        files,
+    };
+    let bytes = serde_json::to_vec(&manifest)?;
+    // The content hash keeps two snapshots taken in one second apart
+    let name = format!(
+        "{}_{}.json",
+        manifest.created.with_timezone(&Local).format(STAMP_FORMAT),
+        &polyio::sha1_bytes(&bytes)[..8]
+    );
+    let path = history.join(name);
+    polyio::create_dir_all(history).await?;
+    polyio::write_atomic(&path, bytes).await?;
+
+    Ok(Some(manifest.summary(path)))
+}
+
+/// Hashed while it is copied in under a temporary name so the object is named
+/// after the bytes it holds even if the game touched the file mid-copy and an
+/// interrupted copy is never taken for the object
+async fn store_object(root: &Path, path: &Path) -> ClusterResult<String> {
+    let objects = root.join(OBJECTS_DIR);
+    polyio::create_dir_all(&objects).await?;
+    let partial = objects.join(format!("{}.partial", uuid::Uuid::new_v4()));
+    let sha1 = match polyio::sha1_copy(path, &partial).await {
+        Ok(sha1) => sha1,
+        Err(err) => {
+            polyio::remove_file(&partial).await.ok();
+            return Err(err.into());
+        }
+    };
+
+    let dest = object_path(root, &sha1);
+    if dest.is_file() {
+        polyio::remove_file(&partial).await?;
+        return Ok(sha1);
+    }
+    if let Some(parent) = dest.parent() {
+        polyio::create_dir_all(parent).await?;
+    }
+    polyio::rename(&partial, &dest).await?;
+    Ok(sha1)
+}
+
+/// Newest first unreadable manifests are skipped not fatal
+async fn read_history(history: &Path) -> Vec<(PathBuf, Manifest)> {
+    let mut out = Vec::new();
+    let Ok(mut entries) = polyio::read_dir(history).await else {
+        return out;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        match polyio::read_json::<Manifest>(&path).await {
+            Ok(manifest) => out.push((path, manifest)),
+            Err(err) => {
+                tracing::debug!(path = %path.display(), error = %err, "skipping unreadable world snapshot");
+            }
+        }
+    }
+
+    out.sort_by_key(|(_, manifest)| std::cmp::Reverse(manifest.created));
+    out
+}
+
+/// Returns how many snapshots were deleted
+async fn prune_history(history: &Path, policy: &SnapshotPolicy) -> ClusterResult<usize> {
+    let snapshots = read_history(history).await;
+    let created: Vec<DateTime<Local>> = snapshots
+        .iter()
+        .map(|(_, manifest)| manifest.created.with_timezone(&Local))
+        .collect();
+    let keep = retained(&created, policy);
+
+    let mut removed = 0;
+    for (index, (path, _)) in snapshots.iter().enumerate() {
+        if !keep.contains(&index) {
+            polyio::remove_file(path).await?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Indices into `created` which is newest first
+fn retained(created: &[DateTime<Local>], policy: &SnapshotPolicy) -> HashSet<usize> {
+    let recent = created.len().min(policy.keep_recent.max(1) as usize);
+    let mut keep: HashSet<usize> = (0..recent).collect();
+    keep.extend(oldest_per_bucket(created, policy.keep_daily, |time| {
+        (time.year(), time.ordinal())
+    }));
+    keep.extend(oldest_per_bucket(created, policy.keep_weekly, |time| {
+        let week = time.iso_week();
+        (week.year(), week.week())
+    }));
+    keep
+}
+
+/// The oldest in each of the `count` newest buckets so a day's or week's
+/// snapshot stays put as later sessions in it come and go
+fn oldest_per_bucket<K: PartialEq>(
+    created: &[DateTime<Local>],
+    count: u32,
+    bucket: impl Fn(&DateTime<Local>) -> K,
+) -> Vec<usize> {
+    let mut out: Vec<(K, usize)> = Vec::new();
+    for (index, time) in created.iter().enumerate() {
+        let key = bucket(time);
+        if let Some((last, oldest)) = out.last_mut()
+            && *last == key
+        {
+            *oldest = index;
+        } else if out.len() < count as usize {
+            out.push((key, index));
+        } else {
+            break;
+        }
+    }
+    out.into_iter().map(|(_, index)| index).collect()
+}
+
+/// Mark and sweep over every scope an object is shared by any world in any
+/// game dir that happened to hold the same bytes
+/// Anything it cannot read could hold the only reference to an object so it
+/// gives up rather than guess
+async fn sweep_objects(root: &Path) -> ClusterResult<()> {
+    let snapshots = root.join(SNAPSHOTS_DIR);
+    if !polyio::try_exists(&snapshots).await? {
+        return Ok(());
+    }
+
+    let mut referenced = HashSet::new();
+    let mut pending = vec![snapshots];
+    while let Some(dir) = pending.pop() {
+        let mut entries = polyio::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                let manifest: Manifest = polyio::read_json(&path).await?;
+                referenced.extend(manifest.files.into_iter().map(|file| file.sha1));
+            }
+        }
+    }
+
+    let Ok(mut shards) = polyio::read_dir(root.join(OBJECTS_DIR)).await else {
+        return Ok(());
+    };
+    while let Some(shard) = shards.next_entry().await? {
+        let path = shard.path();
+        // Left by a store that died mid-copy nothing is storing while this holds the lock
+        if path.extension().is_some_and(|ext| ext == "partial") {
+            polyio::remove_file(&path).await?;
+            continue;
+        }
+        let Ok(mut objects) = polyio::read_dir(&path).await else {
+            continue;
+        };
+        while let Some(object) = objects.next_entry().await? {
+            let keep = object
+                .file_name()
+                .to_str()
+                .is_some_and(|name| referenced.contains(name));
+            if !keep {
+                polyio::remove_file(object.path()).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Newest first `world` narrows it to one world's snapshots
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn list_world_snapshots(
+    cluster: &Cluster,
+    world: Option<&str>,
+) -> ClusterResult<Vec<WorldSnapshot>> {
+    let scope = scope_dir(&paths::world_snapshots_dir()?, cluster);
+    list_snapshots_in(&scope, world).await
+}
+
+async fn list_snapshots_in(scope: &Path, world: Option<&str>) -> ClusterResult<Vec<WorldSnapshot>> {
+    let mut histories = Vec::new();
+    match world {
+        Some(world) if !is_plain_folder(world) => {
+            return Err(WorldsError::InvalidName(world.to_string()).into());
+        }
+        Some(world) => histories.push(scope.join(world)),
+        None => {
+            if let Ok(mut entries) = polyio::read_dir(scope).await {
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    histories.push(entry.path());
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    for history in histories {
+        out.extend(
+            read_history(&history)
+                .await
+                .into_iter()
+                .map(|(path, manifest)| manifest.summary(path)),
+        );
+    }
+    out.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created));
+    Ok(out)
+}
+
+/// Returns the restored world's folder
+#[tracing::instrument(level = "debug", skip(cluster), fields(cluster_id = cluster.id))]
+pub async fn restore_world_snapshot(cluster: &Cluster, snapshot: &Path) -> ClusterResult<String> {
+    let root = paths::world_snapshots_dir()?;
+    let snapshot = polyio::ensure_under(snapshot, [scope_dir(&root, cluster)])?
+        .ok_or_else(|| WorldsError::InvalidBackup(snapshot.display().to_string()))?;
+    restore_snapshot_at(&root, &snapshot, &super::saves_dir(cluster)?).await
+}
+
+async fn restore_snapshot_at(root: &Path, snapshot: &Path, saves: &Path) -> ClusterResult<String> {
+    let invalid = || WorldsError::InvalidBackup(snapshot.display().to_string());
+    let manifest: Manifest = polyio::read_json(snapshot).await?;
+    let folder = manifest.world;
+    let well_formed = is_plain_folder(&folder)
+        && manifest
+            .files
+            .iter()
+            .all(|file| is_plain_relative(&file.path) && is_sha1(&file.sha1));
+    if !well_formed {
+        return Err(invalid().into());
+    }
+
+    let staging = saves.join(format!("{RESTORING_PREFIX}{folder}"));
+    if polyio::try_exists(&staging).await.unwrap_or(false) {
+        polyio::remove_dir_all(&staging).await?;
+    }
+
+    let result = async {
+        let staged = staging.join(&folder);
+        for file in &manifest.files {
+            let dest = staged.join(&file.path);
+            if let Some(parent) = dest.parent() {
+                polyio::create_dir_all(parent).await?;
+            }
+            polyio::copy(object_path(root, &file.sha1), &dest).await?;
+        }
+        if !level::has_level(&staged) {
+            return Err(invalid().into());
+        }
+        swap_in(&staged, &saves.join(&folder)).await
+    }
+    .await;
+
+    polyio::remove_dir_all(&staging).await.ok();
+    result.map(|()| folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn kept(created: &[DateTime<Local>], policy: &SnapshotPolicy) -> Vec<usize> {
+        let mut keep: Vec<usize> = retained(created, policy).into_iter().collect();
+        keep.sort_unstable();
+        keep
+    }
+
+    #[test]
+    fn retention_keeps_recent_plus_the_oldest_of_each_day_and_week() {
+        // Mon 11th twice Sun 10th twice Tue 5th newest first
+        let created = [at(11, 20), at(11, 9), at(10, 18), at(10, 8), at(5, 12)];
+        let policy = SnapshotPolicy {
+            enabled: true,
+            keep_recent: 1,
+            keep_daily: 2,
+            keep_weekly: 2,
+        };
+        // 0 recent 1 and 3 the oldest of the two newest days 1 again for this
+        // week and 4 as the oldest of the week before
+        assert_eq!(kept(&created, &policy), vec![0, 1, 3, 4]);
+
+        let none = SnapshotPolicy {
+            keep_recent: 0,
+            keep_daily: 0,
+            keep_weekly: 0,
+            ..policy
+        };
+        assert_eq!(kept(&created, &none), vec![0]);
+    }
+
+    async fn write_world(world: &Path, region: &[u8]) {
+        polyio::create_dir_all(world.join("region")).await.unwrap();
+        polyio::write(world.join("level.dat"), b"level")
+            .await
+            .unwrap();
+        polyio::write(world.join("region/r.0.0.mca"), region)
+            .await
+            .unwrap();
+        polyio::write(world.join("region/r.1.0.mca"), b"far away")
+            .await
+            .unwrap();
+    }
+
+    async fn count_objects(root: &Path) -> usize {
+        let mut count = 0;
+        let mut shards = polyio::read_dir(root.join(OBJECTS_DIR)).await.unwrap();
+        while let Some(shard) = shards.next_entry().await.unwrap() {
+            let mut objects = polyio::read_dir(shard.path()).await.unwrap();
+            while objects.next_entry().await.unwrap().is_some() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    async fn partials(root: &Path) -> usize {
+        let mut count = 0;
+        let mut entries = polyio::read_dir(root.join(OBJECTS_DIR)).await.unwrap();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            if entry.path().extension().is_some_and(|ext| ext == "partial") {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    #[tokio::test]
+    async fn unchanged_files_are_stored_once_and_pruned_ones_swept() {
+        let dir = polyio::testing::ScratchDir::new("world_snapshot");
+        let (root, scope, saves) = (
+            dir.join("store"),
+            dir.join("store/snapshots/shared"),
+            dir.join("saves"),
+        );
+        let world = saves.join("Castle");
+        let since = Utc::now() - Duration::minutes(1);
+        let policy = SnapshotPolicy {
+            keep_recent: 1,
+            keep_daily: 0,
+            keep_weekly: 0,
+            ..SnapshotPolicy::default()
+        };
+
+        write_world(&world, b"chunks").await;
+        let first = snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(count_objects(&root).await, 3);
+        assert_eq!(partials(&root).await, 0);
+
+        // Nothing saved since so no second snapshot
+        let again = snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        assert!(again.is_empty());
+
+        write_world(&world, b"more chunks").await;
+        let second = snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 1);
+        // Only the changed region is new and the first snapshot's copy of it
+        // went with the first snapshot
+        assert_eq!(count_objects(&root).await, 3);
+        assert_eq!(
+            list_snapshots_in(&scope, Some("Castle")).await.unwrap(),
+            second
+        );
+
+        polyio::remove_dir_all(&world).await.unwrap();
+        let folder = restore_snapshot_at(&root, &second[0].path, &saves)
+            .await
+            .unwrap();
+        assert_eq!(folder, "Castle");
+        assert_eq!(
+            polyio::read(world.join("region/r.0.0.mca")).await.unwrap(),
+            b"more chunks"
+        );
+        assert!(!saves.join(format!("{RESTORING_PREFIX}Castle")).exists());
+    }
+
+    #[tokio::test]
+    async fn a_damaged_previous_manifest_is_not_trusted() {
+        let dir = polyio::testing::ScratchDir::new("world_snapshot_damaged");
+        let (root, scope, saves) = (dir.join("store"), dir.join("scope"), dir.join("saves"));
+        write_world(&saves.join("Castle"), b"chunks").await;
+        let since = Utc::now() - Duration::minutes(1);
+        let policy = SnapshotPolicy::default();
+
+        let first = snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        let mut manifest: Manifest = polyio::read_json(&first[0].path).await.unwrap();
+        for file in &mut manifest.files {
+            file.sha1 = "x".to_string();
+        }
+        polyio::write(&first[0].path, serde_json::to_vec(&manifest).unwrap())
+            .await
+            .unwrap();
+
+        let again = snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        assert_eq!(again.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_manifest_stops_the_sweep() {
+        let dir = polyio::testing::ScratchDir::new("world_snapshot_unreadable");
+        let (root, scope, saves) = (
+            dir.join("store"),
+            dir.join("store/snapshots/shared"),
+            dir.join("saves"),
+        );
+        let world = saves.join("Castle");
+        let since = Utc::now() - Duration::minutes(1);
+        let policy = SnapshotPolicy {
+            keep_recent: 1,
+            keep_daily: 0,
+            keep_weekly: 0,
+            ..SnapshotPolicy::default()
+        };
+
+        write_world(&world, b"chunks").await;
+        snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        polyio::create_dir_all(scope.join("Other")).await.unwrap();
+        polyio::write(scope.join("Other/broken.json"), b"{ not json")
+            .await
+            .unwrap();
+
+        write_world(&world, b"more chunks").await;
+        let second = snapshot_played_at(&root, &scope, &saves, since, &policy)
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 1);
+        // The pruned snapshot's region stays since the sweep gave up
+        assert_eq!(count_objects(&root).await, 4);
+    }
+
+    #[tokio::test]
+    async fn worlds_not_saved_in_the_session_are_left_alone() {
+        let dir = polyio::testing::ScratchDir::new("world_snapshot_idle");
+        let (root, saves) = (dir.join("store"), dir.join("saves"));
+        write_world(&saves.join("Old"), b"chunks").await;
+
+        let later = Utc::now() + Duration::minutes(1);
+        let taken = snapshot_played_at(
+            &root,
+            &root.join("scope"),
+            &saves,
+            later,
+            &SnapshotPolicy::default(),
+        )
+        .await
+        .unwrap();
+        assert!(taken.is_empty());
+        assert!(!root.join(OBJECTS_DIR).exists());
+    }
+}
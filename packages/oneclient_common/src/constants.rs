@@ -12,6 +12,10 @@ pub const CURSEFORGE_API_URL: &str = "https://api.curseforge.com/v1";
 pub const CURSEFORGE_GAME_ID: u32 = 432;
 pub const METADATA_API_URL: &str = "https://meta.polyfrost.org";
 pub const MCLOGS_API_URL: &str = "https://api.mclo.gs/1";
+pub const MOJANG_JAVA_RUNTIMES_URL: &str =
+	"https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+pub const AUTHLIB_INJECTOR_LATEST_URL: &str =
+	"https://authlib-injector.yushi.moe/artifact/latest.json";
 pub const SKYCLIENT_BASE_URL: &str =
 	"https://raw.githubusercontent.com/SkyblockClient/SkyblockClient-REPO/refs/heads/main/v1";
 pub const META_URL_BASE: &str = "https://data-v2.polyfrost.org";
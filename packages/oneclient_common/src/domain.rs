@@ -93,6 +93,9 @@ pub enum ProviderId {
     Modrinth = 0,
     CurseForge = 1,
     Local = 2,
+    /// The JSON indexes in the launcher settings its ids say which index they came from
+    #[strum(to_string = "Self-hosted")]
+    SelfHosted = 3,
 }
 
 impl ProviderId {
@@ -104,6 +107,7 @@ impl ProviderId {
             Self::Modrinth => "modrinth",
             Self::CurseForge => "curseforge",
             Self::Local => "local",
+            Self::SelfHosted => "self_hosted",
         }
     }
 
@@ -111,7 +115,7 @@ impl ProviderId {
         match self {
             Self::Modrinth => "https://modrinth.com/",
             Self::CurseForge => "https://www.curseforge.com/",
-            Self::Local => "",
+            Self::Local | Self::SelfHosted => "",
         }
     }
 
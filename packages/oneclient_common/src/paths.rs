@@ -53,6 +53,11 @@ pub fn auth_file() -> PathsResult<PathBuf> {
 	Ok(launcher_dir()?.join("auth.json"))
 }
 
+/// Seals [`auth_file`] the two only mean anything together
+pub fn auth_key_file() -> PathsResult<PathBuf> {
+	Ok(launcher_dir()?.join("auth.key"))
+}
+
 pub fn logs_dir() -> PathsResult<PathBuf> {
 	Ok(launcher_dir()?.join("logs"))
 }
@@ -69,6 +74,17 @@ pub fn shared_minecraft_dir() -> PathsResult<PathBuf> {
 	Ok(launcher_dir()?.join(".minecraft"))
 }
 
+/// User data not a cache skins saved here are the only copy once replaced
+pub fn skins_dir() -> PathsResult<PathBuf> {
+	Ok(launcher_dir()?.join("skins"))
+}
+
+/// User data like skins snapshots here may be the only copy of a world the
+/// player has since deleted
+pub fn world_snapshots_dir() -> PathsResult<PathBuf> {
+	Ok(launcher_dir()?.join("backups").join("worlds"))
+}
+
 /// Presence marks a cluster folder as its own game dir instead of the shared `.minecraft`
 pub const DEDICATED_MARKER: &str = ".dedicated_directory";
 
@@ -13,7 +13,7 @@ use crate::bundles::types::{BundleArchive, BundleFile, BundleFileKind};
 use oneclient_events::{GroupedProgressChild, GroupedProgressSession, TaskCategory, TaskPhase};
 use oneclient_common::domain::{ContentType, GameLoader};
 use crate::packages::store::{PackageStore, evict_if_unused, try_unlink_materialized};
-use crate::packages::types::ExternalFile;
+use crate::packages::types::{ExternalFile, WorldTarget};
 use crate::ctx::ContentCtx;
 
 fn is_base62(s: &str) -> bool {
@@ -114,6 +114,7 @@ pub async fn install_package_from_bundle(
                 &project,
                 &version,
                 cluster_id,
+                &WorldTarget::AllWorlds,
                 skip_compatibility,
                 false,
                 child,
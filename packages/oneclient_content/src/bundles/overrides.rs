@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::ContentResult;
 use polyio::{sha1_bytes, sha1_file};
+use oneclient_cluster::servers::{self, SERVERS_FILE, ServerList};
 use oneclient_events::EventBus;
 
 const ALWAYS_UPDATE_GLOBS: &[&str] = &["config/fabric_loader_dependencies.json"];
@@ -79,6 +80,9 @@ async fn sync_bundle_overrides_at(
     let previous = lock.bundles.remove(bundle_name).unwrap_or_default();
     let mut next: HashMap<String, String> = HashMap::new();
     let mut report = OverrideSyncReport::default();
+    // `None` once the bundle's file fails to parse the servers it pushed before
+    // stay rather than being withdrawn over one bad file
+    let mut shared_servers = Some(ServerList::default());
 
     for (name, bytes) in entries {
         let rel = name.trim_start_matches(OVERRIDES_PREFIX);
@@ -86,6 +90,19 @@ async fn sync_bundle_overrides_at(
             continue;
         }
 
+        // Merged entry by entry rather than as a file so the player's own
+        // servers are never overwritten
+        if rel == SERVERS_FILE {
+            match ServerList::from_bytes(&bytes) {
+                Ok(list) => shared_servers = Some(list),
+                Err(err) => {
+                    tracing::warn!(error = %err, "bundle ships an unreadable servers.dat");
+                    shared_servers = None;
+                }
+            }
+            continue;
+        }
+
         let new_sha1 = sha1_bytes(&bytes);
         let dest = root.join(polyio::sanitize_path(rel));
         let disk_sha1 = current_sha1(&dest).await;
@@ -133,8 +150,19 @@ async fn sync_bundle_overrides_at(
         }
     }
 
+    // Run even without the file so a bundle that stops shipping servers
+    // withdraws them
+    if let Some(shared_servers) = shared_servers {
+        match servers::sync_shared_servers(root, bundle_name, shared_servers.entries()).await {
+            Ok(servers) if !servers.is_empty() => report.written.push(SERVERS_FILE.to_string()),
+            Ok(_) => {}
+            Err(err) => tracing::warn!(error = %err, "failed to sync bundle servers"),
+        }
+    }
+
     for (rel, base_sha1) in &previous {
-        if next.contains_key(rel) {
+        // Locks written before servers were merged still list the file
+        if next.contains_key(rel) || rel == SERVERS_FILE {
             continue;
         }
         let dest = root.join(polyio::sanitize_path(rel));
@@ -478,6 +506,58 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn bundle_servers_merge_into_the_players_list() {
+        let root = polyio::testing::ScratchDir::new("servers");
+        let path = root.join(SERVERS_FILE);
+        let mut mine = ServerList::default();
+        mine.add(servers::ServerEntry::new("Mine", "mine.example.com"))
+            .unwrap();
+        mine.save(&path).await.unwrap();
+
+        let mut shared = ServerList::default();
+        shared
+            .add(servers::ServerEntry::new("Studio test", "test.example.com"))
+            .unwrap();
+        let v1 = root.join("v1.mrpack");
+        write_bundle(&v1, &[("overrides/servers.dat", &shared.to_bytes())]).await;
+
+        let report = sync(&v1, root.path()).await;
+        assert_eq!(report.written, vec![SERVERS_FILE.to_string()]);
+        let list = ServerList::load(&path).await.unwrap();
+        let names: Vec<&str> = list.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Mine", "Studio test"]);
+
+        let v2 = root.join("v2.mrpack");
+        write_bundle(&v2, &[("overrides/config/a.toml", b"a")]).await;
+        sync(&v2, root.path()).await;
+        let list = ServerList::load(&path).await.unwrap();
+        assert_eq!(list.entries().len(), 1);
+        assert_eq!(list.entries()[0].name, "Mine");
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_servers_file_keeps_the_bundles_servers() {
+        let root = polyio::testing::ScratchDir::new("servers_unreadable");
+        let path = root.join(SERVERS_FILE);
+
+        let mut shared = ServerList::default();
+        shared
+            .add(servers::ServerEntry::new("Studio test", "test.example.com"))
+            .unwrap();
+        let v1 = root.join("v1.mrpack");
+        write_bundle(&v1, &[("overrides/servers.dat", &shared.to_bytes())]).await;
+        sync(&v1, root.path()).await;
+
+        let v2 = root.join("v2.mrpack");
+        write_bundle(&v2, &[("overrides/servers.dat", b"not nbt")]).await;
+        let report = sync(&v2, root.path()).await;
+        assert!(report.written.is_empty());
+        let list = ServerList::load(&path).await.unwrap();
+        assert_eq!(list.entries().len(), 1);
+        assert_eq!(list.entries()[0].name, "Studio test");
+    }
+
     #[tokio::test]
     async fn bootstrap_pre_existing_edit_is_never_overwritten() {
         let root = polyio::testing::ScratchDir::new("bootstrap");
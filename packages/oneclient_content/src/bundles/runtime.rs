@@ -5,6 +5,7 @@ use crate::bundles::manager::BundlesManager;
 use crate::bundles::ApplyBundleUpdatesResult;
 use crate::bundles::updates::apply_bundle_updates_for_all_clusters;
 use oneclient_events::GroupedProgressSession;
+use oneclient_net::Priority;
 use crate::ctx::ContentCtx;
 
 static BUNDLE_SYNCING: AtomicBool = AtomicBool::new(false);
@@ -22,7 +23,8 @@ pub async fn sync_all_cluster_bundles(
 ) -> Vec<(i64, ApplyBundleUpdatesResult)> {
     tracing::info!("syncing all cluster bundles");
     BUNDLE_SYNCING.store(true, Ordering::Relaxed);
-    let changed = match apply_bundle_updates_for_all_clusters(bundles, ctx, session).await {
+    let sync = apply_bundle_updates_for_all_clusters(bundles, ctx, session);
+    let changed = match Priority::Background.scope(sync).await {
         Ok(changed) => changed,
         Err(err) => {
             tracing::error!("failed to apply bundle updates for clusters: {err:#}");
@@ -10,6 +10,7 @@ use oneclient_db::models::{BundleTrackedArtifactRow, ClusterBundleOverrideRow, O
 use tokio::sync::Mutex as AsyncMutex;
 
 use futures_util::StreamExt;
+use oneclient_net::Priority;
 
 use crate::bundles::install::{
     BUNDLE_INSTALL_CONCURRENCY, disable_was_deliberate, find_user_suppression,
@@ -46,7 +47,8 @@ pub async fn check_bundle_updates(
     ctx: &ContentCtx,
 ) -> ContentResult<BundleUpdateCheckResult> {
     let overrides = bundle_dao::list_overrides(&ctx.db, cluster_id).await?;
-    check_bundle_updates_inner(cluster_id, bundles, ctx, &overrides).await
+    let check = check_bundle_updates_inner(cluster_id, bundles, ctx, &overrides);
+    Priority::Background.scope(check).await
 }
 
 #[tracing::instrument(level = "debug", skip(bundles, ctx, overrides))]
@@ -0,0 +1,474 @@
+//! A pre-launch look over the mods folder as the game will see it catching
+//! the mistakes that otherwise only show up as a crash on startup
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use oneclient_common::domain::GameLoader;
+
+use crate::error::ContentResult;
+use crate::packages::jar_metadata::{self, JarMetadata};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModAuditFinding {
+	/// As it sits in the mods folder
+	pub file_name: String,
+	pub mod_name: String,
+	pub kind: ModAuditKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModAuditKind {
+	/// `kept` is the copy that stays the newest one
+	DuplicateModId { mod_id: String, kept: String },
+	WrongLoader { loader: GameLoader },
+	/// As the jar declares it
+	UnsupportedMinecraft { range: String },
+}
+
+impl std::fmt::Display for ModAuditFinding {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let Self {
+			file_name,
+			mod_name,
+			kind,
+		} = self;
+		match kind {
+			ModAuditKind::DuplicateModId { mod_id, kept } => {
+				write!(f, "{file_name}: another copy of {mod_name} ({mod_id}) is in {kept}")
+			}
+			ModAuditKind::WrongLoader { loader } => {
+				write!(f, "{file_name}: {mod_name} is a {loader} mod")
+			}
+			ModAuditKind::UnsupportedMinecraft { range } => {
+				write!(f, "{file_name}: {mod_name} needs Minecraft {range}")
+			}
+		}
+	}
+}
+
+/// Vanilla clusters never load the folder so there is nothing to audit
+#[tracing::instrument(level = "debug", skip(mods_dir), fields(mods_dir = %mods_dir.as_ref().display()))]
+pub async fn audit_mods_dir(
+	mods_dir: impl AsRef<Path>,
+	cluster_loader: GameLoader,
+	mc_version: &str,
+) -> ContentResult<Vec<ModAuditFinding>> {
+	if cluster_loader == GameLoader::Vanilla {
+		return Ok(Vec::new());
+	}
+
+	let Ok(mut entries) = polyio::read_dir(mods_dir.as_ref()).await else {
+		return Ok(Vec::new());
+	};
+
+	let mut jars = Vec::new();
+	while let Ok(Some(entry)) = entries.next_entry().await {
+		let path = entry.path();
+		let Some(name) = path.file_name().and_then(|n| n.to_str()).map(str::to_owned) else {
+			continue;
+		};
+		if !name.ends_with(".jar") {
+			continue;
+		}
+
+		match jar_metadata::read_jar_metadata(&path).await {
+			Ok(metadata) => jars.push((name, metadata)),
+			Err(err) => tracing::debug!(file = %name, %err, "skipping unreadable jar in audit"),
+		}
+	}
+	jars.sort_by(|a, b| a.0.cmp(&b.0));
+
+	Ok(audit_mods(&jars, cluster_loader, mc_version))
+}
+
+/// One finding per file at most a jar for the wrong loader is not also
+/// checked for duplicates or its Minecraft range
+#[must_use]
+pub fn audit_mods(
+	jars: &[(String, Vec<JarMetadata>)],
+	cluster_loader: GameLoader,
+	mc_version: &str,
+) -> Vec<ModAuditFinding> {
+	let mut findings = Vec::new();
+	let mut fitting: Vec<(&str, &JarMetadata)> = Vec::new();
+
+	for (file_name, metadata) in jars {
+		// No metadata is a library or a resource bundle not ours to judge
+		let Some(first) = metadata.first() else {
+			continue;
+		};
+
+		// Multi-loader jars carry one metadata file per loader any match will do
+		match metadata.iter().find(|m| m.fits_loader(cluster_loader)) {
+			Some(primary) => fitting.push((file_name, primary)),
+			None => findings.push(ModAuditFinding {
+				file_name: file_name.clone(),
+				mod_name: first.display_name().to_string(),
+				kind: ModAuditKind::WrongLoader {
+					loader: first.loader,
+				},
+			}),
+		}
+	}
+
+	let mut by_id: BTreeMap<&str, Vec<(&str, &JarMetadata)>> = BTreeMap::new();
+	for (file_name, metadata) in &fitting {
+		by_id
+			.entry(metadata.mod_id.as_str())
+			.or_default()
+			.push((file_name, metadata));
+	}
+
+	let mut duplicated = Vec::new();
+	for (mod_id, copies) in &by_id {
+		if copies.len() < 2 {
+			continue;
+		}
+
+		let Some((kept, _)) = copies.iter().max_by(|a, b| {
+			compare_versions(
+				a.1.version.as_deref().unwrap_or_default(),
+				b.1.version.as_deref().unwrap_or_default(),
+			)
+			.then_with(|| b.0.cmp(a.0))
+		}) else {
+			continue;
+		};
+
+		for (file_name, metadata) in copies {
+			if file_name == kept {
+				continue;
+			}
+			duplicated.push(*file_name);
+			findings.push(ModAuditFinding {
+				file_name: (*file_name).to_string(),
+				mod_name: metadata.display_name().to_string(),
+				kind: ModAuditKind::DuplicateModId {
+					mod_id: (*mod_id).to_string(),
+					kept: (*kept).to_string(),
+				},
+			});
+		}
+	}
+
+	for (file_name, metadata) in &fitting {
+		if duplicated.contains(file_name) {
+			continue;
+		}
+		let Some(range) = &metadata.minecraft else {
+			continue;
+		};
+		if minecraft_range_allows(metadata.loader, range, mc_version) == Some(false) {
+			findings.push(ModAuditFinding {
+				file_name: (*file_name).to_string(),
+				mod_name: metadata.display_name().to_string(),
+				kind: ModAuditKind::UnsupportedMinecraft {
+					range: range.clone(),
+				},
+			});
+		}
+	}
+
+	findings
+}
+
+/// `None` when the range or the version is not something this can read
+/// snapshots custom predicates and so on which are never reported
+#[must_use]
+pub fn minecraft_range_allows(loader: GameLoader, range: &str, version: &str) -> Option<bool> {
+	let version = Version::parse(version)?;
+	match loader {
+		GameLoader::Forge | GameLoader::NeoForge => maven_range_allows(range, &version),
+		GameLoader::Fabric | GameLoader::Quilt | GameLoader::LegacyFabric => {
+			fabric_range_allows(range, &version)
+		}
+		GameLoader::Vanilla => None,
+	}
+}
+
+/// `||` separates alternatives whitespace separates predicates that must all hold
+fn fabric_range_allows(range: &str, version: &Version) -> Option<bool> {
+	let mut any = false;
+	for alternative in range.split("||") {
+		let mut all = true;
+		for predicate in alternative.split_whitespace() {
+			all &= fabric_predicate_allows(predicate, version)?;
+		}
+		any |= all;
+	}
+	Some(any)
+}
+
+fn fabric_predicate_allows(predicate: &str, version: &Version) -> Option<bool> {
+	if predicate == "*" {
+		return Some(true);
+	}
+
+	let (operator, rest) = ["~", "^", ">=", "<=", ">", "<", "="]
+		.into_iter()
+		.find_map(|op| predicate.strip_prefix(op).map(|rest| (op, rest)))
+		.unwrap_or(("", predicate));
+
+	// `1.21.x` covers every patch of 1.21
+	if let Some(prefix) = rest
+		.strip_suffix(".x")
+		.or_else(|| rest.strip_suffix(".X"))
+		.or_else(|| rest.strip_suffix(".*"))
+	{
+		let lower = Version::parse(prefix)?;
+		return Some(version.cmp(&lower).is_ge() && version.release.starts_with(&lower.release));
+	}
+
+	let target = Version::parse(rest)?;
+	let ordering = version.cmp(&target);
+	Some(match operator {
+		">=" => ordering.is_ge(),
+		"<=" => ordering.is_le(),
+		">" => ordering.is_gt(),
+		"<" => ordering.is_lt(),
+		"~" => ordering.is_ge() && version.shares_prefix(&target, 2),
+		"^" => ordering.is_ge() && version.shares_prefix(&target, 1),
+		_ => ordering.is_eq(),
+	})
+}
+
+/// Maven ranges `[1.20.1,1.21)` and unions of them a bare version is only a
+/// recommendation in Maven and many Forge mods write one loosely
+fn maven_range_allows(range: &str, version: &Version) -> Option<bool> {
+	let range = range.trim();
+	if !range.starts_with(['[', '(']) {
+		return None;
+	}
+
+	let mut any = false;
+	let mut rest = range;
+	while !rest.is_empty() {
+		let end = rest.find([']', ')'])?;
+		let (set, tail) = rest.split_at(end + 1);
+		any |= maven_set_allows(set, version)?;
+		rest = tail.trim_start_matches([',', ' ']);
+	}
+	Some(any)
+}
+
+fn maven_set_allows(set: &str, version: &Version) -> Option<bool> {
+	// A stray bracket after the last set leaves nothing between the two
+	if set.len() < 2 || !set.starts_with(['[', '(']) {
+		return None;
+	}
+	let inclusive_low = set.starts_with('[');
+	let inclusive_high = set.ends_with(']');
+	let inner = &set[1..set.len() - 1];
+
+	let Some((low, high)) = inner.split_once(',') else {
+		// `[1.20.1]` pins one version
+		return Some(version.cmp(&Version::parse(inner)?).is_eq());
+	};
+
+	let low_ok = match low.trim() {
+		"" => true,
+		low => {
+			let ordering = version.cmp(&Version::parse(low)?);
+			if inclusive_low { ordering.is_ge() } else { ordering.is_gt() }
+		}
+	};
+	let high_ok = match high.trim() {
+		"" => true,
+		high => {
+			let ordering = version.cmp(&Version::parse(high)?);
+			if inclusive_high { ordering.is_le() } else { ordering.is_lt() }
+		}
+	};
+	Some(low_ok && high_ok)
+}
+
+/// Falls back to comparing the strings for versions that are not dotted numbers
+fn compare_versions(left: &str, right: &str) -> Ordering {
+	match (Version::parse(left), Version::parse(right)) {
+		(Some(left), Some(right)) => left.cmp(&right),
+		(Some(_), None) => Ordering::Greater,
+		(None, Some(_)) => Ordering::Less,
+		(None, None) => left.cmp(right),
+	}
+}
+
+/// Dotted numbers with an optional `-pre` part build metadata after `+` is ignored
+#[derive(Debug, PartialEq, Eq)]
+struct Version {
+	release: Vec<u64>,
+	pre: Option<String>,
+}
+
+impl Version {
+	fn parse(raw: &str) -> Option<Self> {
+		let raw = raw.trim();
+		let raw = raw.split_once('+').map_or(raw, |(version, _)| version);
+		let (release, pre) = match raw.split_once('-') {
+			Some((release, pre)) => (release, Some(pre.to_string())),
+			None => (raw, None),
+		};
+
+		let release = release
+			.split('.')
+			.map(|part| part.parse().ok())
+			.collect::<Option<Vec<u64>>>()?;
+		Some(Self { release, pre })
+	}
+
+	fn component(&self, index: usize) -> u64 {
+		self.release.get(index).copied().unwrap_or(0)
+	}
+
+	fn shares_prefix(&self, other: &Self, len: usize) -> bool {
+		(0..len).all(|index| self.component(index) == other.component(index))
+	}
+}
+
+impl Ord for Version {
+	/// Missing parts count as zero so `1.21` and `1.21.0` are the same version
+	fn cmp(&self, other: &Self) -> Ordering {
+		let len = self.release.len().max(other.release.len());
+		for index in 0..len {
+			let ordering = self.component(index).cmp(&other.component(index));
+			if ordering.is_ne() {
+				return ordering;
+			}
+		}
+
+		match (&self.pre, &other.pre) {
+			(None, None) => Ordering::Equal,
+			(None, Some(_)) => Ordering::Greater,
+			(Some(_), None) => Ordering::Less,
+			(Some(left), Some(right)) => left.cmp(right),
+		}
+	}
+}
+
+impl PartialOrd for Version {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn jar(mod_id: &str, loader: GameLoader, version: &str, minecraft: Option<&str>) -> JarMetadata {
+		JarMetadata {
+			mod_id: mod_id.into(),
+			name: None,
+			version: Some(version.into()),
+			loader,
+			minecraft: minecraft.map(Into::into),
+			dependencies: Vec::new(),
+			provides: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn fabric_ranges_follow_the_loader_semantics() {
+		let allows = |range| minecraft_range_allows(GameLoader::Fabric, range, "1.21.4");
+
+		assert_eq!(allows("*"), Some(true));
+		assert_eq!(allows(">=1.21"), Some(true));
+		assert_eq!(allows(">=1.20 <1.21"), Some(false));
+		assert_eq!(allows("1.20.x || 1.21.x"), Some(true));
+		assert_eq!(allows("~1.21.2"), Some(true));
+		assert_eq!(allows("~1.20"), Some(false));
+		assert_eq!(allows("1.21.4"), Some(true));
+		assert_eq!(allows(">=1.21.5-beta.1"), Some(false));
+		assert_eq!(allows(">=24w14a"), None, "snapshot predicates are not judged");
+	}
+
+	#[test]
+	fn maven_ranges_cover_bounds_and_unions() {
+		let allows = |range| minecraft_range_allows(GameLoader::Forge, range, "1.20.1");
+
+		assert_eq!(allows("[1.20.1,1.20.2)"), Some(true));
+		assert_eq!(allows("[1.20,1.20.1)"), Some(false));
+		assert_eq!(allows("(1.20.1,)"), Some(false));
+		assert_eq!(allows("[1.19,1.19.4],[1.20,1.21)"), Some(true));
+		assert_eq!(allows("[1.20.1]"), Some(true));
+		assert_eq!(allows("1.19.2"), None, "a bare maven version is only a hint");
+		assert_eq!(allows("[1.20,1.21)]"), None, "a stray closing bracket");
+		assert_eq!(allows("[1.20,1.21),)"), None);
+	}
+
+	#[test]
+	fn audit_flags_wrong_loader_and_keeps_the_newest_duplicate() {
+		let jars = vec![
+			("sodium-0.5.jar".to_string(), vec![jar("sodium", GameLoader::Fabric, "0.5.11", None)]),
+			("sodium-0.6.jar".to_string(), vec![jar("sodium", GameLoader::Fabric, "0.6.0", None)]),
+			("create.jar".to_string(), vec![jar("create", GameLoader::Forge, "0.5.1", None)]),
+			("library.jar".to_string(), Vec::new()),
+		];
+
+		let findings = audit_mods(&jars, GameLoader::Fabric, "1.21.4");
+
+		assert_eq!(
+			findings,
+			[
+				ModAuditFinding {
+					file_name: "create.jar".into(),
+					mod_name: "create".into(),
+					kind: ModAuditKind::WrongLoader {
+						loader: GameLoader::Forge,
+					},
+				},
+				ModAuditFinding {
+					file_name: "sodium-0.5.jar".into(),
+					mod_name: "sodium".into(),
+					kind: ModAuditKind::DuplicateModId {
+						mod_id: "sodium".into(),
+						kept: "sodium-0.6.jar".into(),
+					},
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn audit_reports_jars_built_for_another_minecraft() {
+		let jars = vec![
+			(
+				"old.jar".to_string(),
+				vec![jar("old", GameLoader::Fabric, "1.0.0", Some(">=1.20 <1.21"))],
+			),
+			(
+				"fine.jar".to_string(),
+				vec![jar("fine", GameLoader::Fabric, "1.0.0", Some("~1.21"))],
+			),
+		];
+
+		let findings = audit_mods(&jars, GameLoader::Quilt, "1.21.1");
+
+		assert_eq!(findings.len(), 1);
+		assert_eq!(
+			findings[0].kind,
+			ModAuditKind::UnsupportedMinecraft {
+				range: ">=1.20 <1.21".into(),
+			}
+		);
+	}
+
+	#[test]
+	fn multi_loader_jars_and_legacy_fabric_are_not_misreported() {
+		let both = vec![(
+			"both.jar".to_string(),
+			vec![
+				jar("both", GameLoader::Fabric, "1.0.0", None),
+				jar("both", GameLoader::Forge, "1.0.0", None),
+			],
+		)];
+		assert!(audit_mods(&both, GameLoader::Forge, "1.20.1").is_empty());
+
+		let legacy = vec![(
+			"legacy.jar".to_string(),
+			vec![jar("legacy", GameLoader::Fabric, "1.0.0", Some("1.8.9"))],
+		)];
+		assert!(audit_mods(&legacy, GameLoader::LegacyFabric, "1.8.9").is_empty());
+	}
+}
@@ -1,18 +1,24 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use oneclient_common::domain::{ContentType, GameLoader, ProviderId};
-use oneclient_db::models::ClusterRow;
+use oneclient_db::dao::artifact as artifact_dao;
+use oneclient_db::models::{ArtifactRow, ClusterRow};
+use oneclient_events::{Choice, GroupedProgressChild, Prompt};
 
 use crate::ctx::ContentCtx;
 use crate::error::ContentResult;
+use crate::packages::error::PackageError;
 use crate::packages::provider::PackageProvider;
 use crate::packages::store::PackageStore;
 use crate::packages::types::{
 	DependencyKind, ProjectDetail, ReleaseType, VersionDependency, VersionDetail, VersionSummary,
+	WorldTarget,
 };
+use crate::packages::updates::unlink_other_versions;
 
-/// Caps the transitive walk so a provider serving a cycle can't spin forever
-const MAX_DEPTH: usize = 4;
+/// Backstop for a provider handing out fresh ids in a loop
+/// real cycles already stop at the seen set
+const MAX_DEPTH: usize = 16;
 
 const VERSION_WINDOW: usize = 50;
 
@@ -22,82 +28,490 @@ pub struct ResolvedDependency {
 	pub version: VersionDetail,
 }
 
+/// Installed but turned off the plan turns it back on rather than adding a
+/// second copy
+#[derive(Debug, Clone)]
+pub struct DisabledDependency {
+	pub hash: String,
+	pub name: String,
+}
+
+/// An installed project something in the plan pins to another version
+#[derive(Debug, Clone)]
+pub struct PlannedUpgrade {
+	pub from_version_id: String,
+	pub from_version_number: String,
+	pub to: ResolvedDependency,
+	/// Project id of the package that pins it
+	pub required_by: String,
+	pub required_by_name: String,
+	/// The pin is on an older release than the one installed
+	pub downgrade: bool,
+}
+
+impl PlannedUpgrade {
+	fn describe(&self) -> String {
+		format!(
+			"{} {} → {}{} for {}",
+			self.to.project.name,
+			self.from_version_number,
+			self.to.version.version_number,
+			if self.downgrade { " (downgrade)" } else { "" },
+			self.required_by_name,
+		)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+	/// One side declares the other incompatible
+	Incompatible,
+	/// A dependency pins a version the cluster would not end up with
+	VersionMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyConflict {
+	pub kind: ConflictKind,
+	/// Project id of the side declaring the dependency
+	pub source: String,
+	pub target: String,
+	pub reason: String,
+}
+
 #[derive(Debug, Clone, Default)]
-pub struct DependencyResolution {
+pub struct DependencyPlan {
 	/// Ordered breadth-first from the root
 	/// already-present ones are filtered out
 	pub install: Vec<ResolvedDependency>,
+	pub upgrade: Vec<PlannedUpgrade>,
+	pub enable: Vec<DisabledDependency>,
+	/// Checked against the whole cluster as it would look once the plan is applied
+	pub conflicts: Vec<DependencyConflict>,
 	pub unresolved: Vec<String>,
 }
 
-impl DependencyResolution {
+impl DependencyPlan {
 	pub fn is_empty(&self) -> bool {
-		self.install.is_empty() && self.unresolved.is_empty()
+		self.install.is_empty()
+			&& self.upgrade.is_empty()
+			&& self.enable.is_empty()
+			&& self.conflicts.is_empty()
+			&& self.unresolved.is_empty()
+	}
+
+	/// Applying it as is would leave conflicting packages in the cluster
+	pub fn is_broken(&self) -> bool {
+		!self.conflicts.is_empty()
 	}
 }
 
-#[tracing::instrument(level = "debug", skip(root, ctx), fields(project_id = %root.project_id, version_id = %root.version_id))]
-pub async fn resolve_required(
+/// What is linked to the cluster from the same provider
+/// another provider's ids mean nothing in this graph
+struct InstalledPackage {
+	hash: String,
+	name: String,
+	enabled: bool,
+	/// `None` when the link never recorded a version it still counts as present
+	version: Option<VersionDetail>,
+}
+
+/// One version in the cluster as it would look after the plan
+struct GraphNode<'a> {
+	project_id: &'a str,
+	name: &'a str,
+	version: &'a VersionDetail,
+}
+
+#[tracing::instrument(level = "debug", skip(project, root, ctx), fields(project_id = %root.project_id, version_id = %root.version_id))]
+pub async fn plan_dependencies(
 	provider_id: ProviderId,
+	project: &ProjectDetail,
 	root: &VersionDetail,
 	cluster_id: i64,
 	ctx: &ContentCtx,
-) -> ContentResult<DependencyResolution> {
-	let mut resolution = DependencyResolution::default();
-	if !root.dependencies.iter().any(is_required) {
-		return Ok(resolution);
-	}
-
+) -> ContentResult<DependencyPlan> {
 	let provider = ctx.providers.get(provider_id)?;
 	let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
-
-	// Seeded with installed projects and the root against refetches and self-referencing graphs
-	let mut seen = installed_project_ids(provider_id, cluster_id, ctx).await?;
-	seen.insert(root.project_id.clone());
-
-	let mut queue: VecDeque<(VersionDependency, usize)> = root
+	let installed =
+		installed_packages(provider, provider_id, cluster_id, &root.project_id, ctx).await?;
+
+	let mut plan = DependencyPlan::default();
+	let mut names = HashMap::from([(root.project_id.clone(), project.name.clone())]);
+	// Seeded with the root against self-referencing graphs
+	let mut seen = HashSet::from([root.project_id.clone()]);
+	let mut queue: VecDeque<(VersionDependency, String, usize)> = root
 		.dependencies
 		.iter()
 		.filter(|dep| is_required(dep))
-		.map(|dep| (dep.clone(), 1))
+		.map(|dep| (dep.clone(), root.project_id.clone(), 1))
 		.collect();
 
-	while let Some((dep, depth)) = queue.pop_front() {
-		if let Some(project_id) = &dep.project_id
-			&& seen.contains(project_id)
-		{
-			continue;
+	while let Some((dep, required_by, depth)) = queue.pop_front() {
+		if let Some(project_id) = &dep.project_id {
+			if seen.contains(project_id) {
+				continue;
+			}
+			if let Some(present) = installed.get(project_id)
+				&& satisfies(present, &dep)
+			{
+				plan_enable(&mut plan, present);
+				continue;
+			}
 		}
 
 		let resolved = match resolve_one(provider, &dep, &cluster, ctx).await {
 			Ok(Some(resolved)) => resolved,
 			Ok(None) => {
 				tracing::warn!(?dep, "no compatible version for dependency");
-				resolution.unresolved.push(dependency_label(&dep));
+				plan.unresolved.push(dependency_label(&dep));
 				continue;
 			}
 			Err(err) => {
 				tracing::warn!(?dep, %err, "failed to resolve dependency");
-				resolution.unresolved.push(dependency_label(&dep));
+				plan.unresolved.push(dependency_label(&dep));
 				continue;
 			}
 		};
 
 		// A version-pinned dependency only reveals its project once fetched so dedupe again here
-		if !seen.insert(resolved.version.project_id.clone()) {
+		let project_id = resolved.version.project_id.clone();
+		if !seen.insert(project_id.clone()) {
 			continue;
 		}
 
+		names.insert(project_id.clone(), resolved.project.name.clone());
+
 		if depth < MAX_DEPTH {
 			for next in resolved.version.dependencies.iter().filter(|d| is_required(d)) {
-				queue.push_back((next.clone(), depth + 1));
+				queue.push_back((next.clone(), project_id.clone(), depth + 1));
+			}
+		}
+
+		match installed.get(&project_id) {
+			None => plan.install.push(resolved),
+			Some(present) => match &present.version {
+				Some(version) if version.version_id != resolved.version.version_id => {
+					plan.upgrade.push(PlannedUpgrade {
+						from_version_id: version.version_id.clone(),
+						from_version_number: version.version_number.clone(),
+						downgrade: resolved.version.published < version.published,
+						required_by_name: names
+							.get(&required_by)
+							.cloned()
+							.unwrap_or_else(|| required_by.clone()),
+						to: resolved,
+						required_by,
+					})
+				}
+				// The same version or a link that never recorded one is already there
+				_ => plan_enable(&mut plan, present),
+			},
+		}
+	}
+
+	let upgraded: HashSet<&str> = plan
+		.upgrade
+		.iter()
+		.map(|upgrade| upgrade.to.version.project_id.as_str())
+		.collect();
+
+	let mut nodes = vec![GraphNode {
+		project_id: &root.project_id,
+		name: &project.name,
+		version: root,
+	}];
+	let enabled: HashSet<&str> = plan.enable.iter().map(|off| off.hash.as_str()).collect();
+	for (project_id, present) in &installed {
+		if let Some(version) = &present.version
+			&& (present.enabled || enabled.contains(present.hash.as_str()))
+			&& !upgraded.contains(project_id.as_str())
+		{
+			nodes.push(GraphNode {
+				project_id,
+				name: &present.name,
+				version,
+			});
+		}
+	}
+	for resolved in plan.install.iter().chain(plan.upgrade.iter().map(|u| &u.to)) {
+		nodes.push(GraphNode {
+			project_id: &resolved.version.project_id,
+			name: &resolved.project.name,
+			version: &resolved.version,
+		});
+	}
+
+	let conflicts = find_conflicts(&nodes);
+	if !conflicts.is_empty() {
+		tracing::warn!(conflicts = conflicts.len(), "dependency plan has conflicts");
+	}
+	plan.conflicts = conflicts;
+
+	Ok(plan)
+}
+
+/// Conflicts and pin-forced version changes go to the user
+/// a dismissal or a caller with nobody to ask refuses the plan
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn confirm_dependency_plan(plan: &DependencyPlan, ctx: &ContentCtx) -> ContentResult<()> {
+	if !plan.is_broken() && plan.upgrade.is_empty() {
+		return Ok(());
+	}
+
+	let reasons: Vec<String> = plan
+		.conflicts
+		.iter()
+		.map(|conflict| conflict.reason.clone())
+		.collect();
+	let changes: Vec<String> = plan.upgrade.iter().map(PlannedUpgrade::describe).collect();
+	let refused = || -> PackageError {
+		if reasons.is_empty() {
+			PackageError::DependencyChangesDeclined(changes.clone())
+		} else {
+			PackageError::DependencyConflicts(reasons.clone())
+		}
+	};
+
+	let (title, choice) = if plan.is_broken() {
+		("Package conflicts", Choice::danger("install", "Install anyway"))
+	} else {
+		("Change installed packages", Choice::primary("install", "Continue"))
+	};
+
+	let answer = ctx
+		.events
+		.ask(
+			Prompt::new(title, plan_prompt_body(&reasons, &changes))
+				.option(choice, ())
+				.dismiss("Cancel"),
+		)
+		.await;
+
+	match answer {
+		Ok(Some(_)) => {
+			tracing::warn!(
+				conflicts = reasons.len(),
+				changes = changes.len(),
+				"user accepted the dependency plan"
+			);
+			Ok(())
+		}
+		Ok(None) => Err(refused().into()),
+		Err(err) => {
+			tracing::warn!("could not ask about the dependency plan: {err}");
+			Err(refused().into())
+		}
+	}
+}
+
+/// A plan that could not be worked out goes to the user too
+/// installing without one is only ever on their say so
+#[tracing::instrument(level = "debug", skip(project, root, ctx), fields(project_id = %root.project_id))]
+pub async fn plan_dependencies_or_ask(
+	provider_id: ProviderId,
+	project: &ProjectDetail,
+	root: &VersionDetail,
+	cluster_id: i64,
+	ctx: &ContentCtx,
+) -> ContentResult<DependencyPlan> {
+	let err = match plan_dependencies(provider_id, project, root, cluster_id, ctx).await {
+		Ok(plan) => return Ok(plan),
+		Err(err) => err,
+	};
+
+	let answer = ctx
+		.events
+		.ask(
+			Prompt::new(
+				"Dependencies unavailable",
+				format!(
+					"The dependencies of {} could not be checked: {err}\n\nInstalling it without them may stop the game from starting.",
+					project.name
+				),
+			)
+			.option(Choice::danger("install", "Install without dependencies"), ())
+			.dismiss("Cancel"),
+		)
+		.await;
+
+	match answer {
+		Ok(Some(_)) => {
+			tracing::warn!(%err, "user chose to install without dependencies");
+			Ok(DependencyPlan::default())
+		}
+		Ok(None) => Err(err),
+		Err(ask_err) => {
+			tracing::warn!("could not ask about the failed dependency check: {ask_err}");
+			Err(err)
+		}
+	}
+}
+
+fn plan_prompt_body(reasons: &[String], changes: &[String]) -> String {
+	let bullets = |lines: &[String]| {
+		lines
+			.iter()
+			.map(|line| format!("• {line}"))
+			.collect::<Vec<_>>()
+			.join("\n")
+	};
+
+	let mut sections = Vec::new();
+	if !reasons.is_empty() {
+		sections.push(format!(
+			"Going ahead would leave the cluster with conflicting packages:\n{}",
+			bullets(reasons)
+		));
+	}
+	if !changes.is_empty() {
+		sections.push(format!(
+			"Going ahead would change the version of installed packages:\n{}",
+			bullets(changes)
+		));
+	}
+	sections.join("\n\n")
+}
+
+/// Download first unlink second like a browser update
+/// `world` is the one the package that needs it is going into a datapack
+/// already installed keeps its own world instead
+#[tracing::instrument(level = "debug", skip(upgrade, child, ctx), fields(project_id = %upgrade.to.version.project_id))]
+pub async fn apply_dependency_upgrade(
+	provider_id: ProviderId,
+	upgrade: &PlannedUpgrade,
+	cluster_id: i64,
+	world: &WorldTarget,
+	child: Option<&GroupedProgressChild>,
+	ctx: &ContentCtx,
+) -> ContentResult<ArtifactRow> {
+	let current = artifact_dao::list_cluster_artifacts_for_project(
+		&ctx.db,
+		cluster_id,
+		provider_id as i64,
+		&upgrade.to.version.project_id,
+		None,
+	)
+	.await?;
+	let world = match current.first() {
+		Some(link) => WorldTarget::from_world(
+			artifact_dao::get_cluster_artifact_world(&ctx.db, cluster_id, &link.hash).await?,
+		),
+		None => world.clone(),
+	};
+
+	let installed = PackageStore::install_to_cluster(
+		provider_id,
+		&upgrade.to.project,
+		&upgrade.to.version,
+		cluster_id,
+		&world,
+		false,
+		false,
+		child,
+		ctx,
+	)
+	.await?;
+
+	unlink_other_versions(
+		cluster_id,
+		provider_id,
+		&upgrade.to.version.project_id,
+		&installed.hash,
+		ctx,
+	)
+	.await?;
+
+	Ok(installed)
+}
+
+/// Pairs are reported once however many sides declare them
+fn find_conflicts(nodes: &[GraphNode<'_>]) -> Vec<DependencyConflict> {
+	let by_project: HashMap<&str, &GraphNode<'_>> =
+		nodes.iter().map(|node| (node.project_id, node)).collect();
+	let by_version: HashMap<&str, &GraphNode<'_>> = nodes
+		.iter()
+		.map(|node| (node.version.version_id.as_str(), node))
+		.collect();
+
+	let mut conflicts = Vec::new();
+	let mut reported: HashSet<(ConflictKind, &str, &str)> = HashSet::new();
+
+	for node in nodes {
+		for dep in &node.version.dependencies {
+			let target = dep
+				.project_id
+				.as_deref()
+				.and_then(|id| by_project.get(id))
+				.or_else(|| dep.version_id.as_deref().and_then(|id| by_version.get(id)));
+			let Some(target) = target else {
+				continue;
+			};
+			if target.project_id == node.project_id {
+				continue;
 			}
+
+			let pinned = dep.version_id.as_deref();
+			let (kind, reason) = match dep.kind {
+				// A pinned incompatibility only rules out that one version
+				DependencyKind::Incompatible
+					if pinned.is_none_or(|id| id == target.version.version_id) =>
+				{
+					(
+						ConflictKind::Incompatible,
+						format!("{} is incompatible with {}", node.name, target.name),
+					)
+				}
+				DependencyKind::Required
+					if pinned.is_some_and(|id| id != target.version.version_id) =>
+				{
+					(
+						ConflictKind::VersionMismatch,
+						format!(
+							"{} needs a different version of {} than {}",
+							node.name, target.name, target.version.version_number
+						),
+					)
+				}
+				_ => continue,
+			};
+
+			let pair = if kind == ConflictKind::Incompatible && target.project_id < node.project_id {
+				(kind, target.project_id, node.project_id)
+			} else {
+				(kind, node.project_id, target.project_id)
+			};
+			if !reported.insert(pair) {
+				continue;
+			}
+
+			conflicts.push(DependencyConflict {
+				kind,
+				source: node.project_id.to_string(),
+				target: target.project_id.to_string(),
+				reason,
+			});
 		}
+	}
 
-		resolution.install.push(resolved);
+	conflicts
+}
+
+/// Once per link however many packages need it
+fn plan_enable(plan: &mut DependencyPlan, present: &InstalledPackage) {
+	if !present.enabled && !plan.enable.iter().any(|off| off.hash == present.hash) {
+		plan.enable.push(DisabledDependency {
+			hash: present.hash.clone(),
+			name: present.name.clone(),
+		});
 	}
+}
 
-	Ok(resolution)
+fn satisfies(present: &InstalledPackage, dep: &VersionDependency) -> bool {
+	match (&present.version, &dep.version_id) {
+		(Some(version), Some(pinned)) => version.version_id == *pinned,
+		_ => true,
+	}
 }
 
 fn is_required(dep: &VersionDependency) -> bool {
@@ -145,7 +559,7 @@ async fn resolve_one(
 }
 
 /// Falls back to the newest prerelease when no release fits the cluster
-pub(crate) async fn pick_version(
+pub async fn pick_version(
 	provider: &dyn PackageProvider,
 	project_id: &str,
 	cluster: &ClusterRow,
@@ -208,16 +622,58 @@ fn fits_cluster(version: &VersionSummary, cluster: &ClusterRow, loader: GameLoad
 	version.loaders.is_empty() || version.loaders.iter().any(|l| loader.compatible_with(*l))
 }
 
-async fn installed_project_ids(
+async fn installed_packages(
+	provider: &dyn PackageProvider,
 	provider_id: ProviderId,
 	cluster_id: i64,
+	replacing: &str,
 	ctx: &ContentCtx,
-) -> ContentResult<HashSet<String>> {
-	Ok(PackageStore::list_linked_artifacts(cluster_id, ctx)
+) -> ContentResult<HashMap<String, InstalledPackage>> {
+	let linked: Vec<_> = PackageStore::list_linked_artifacts(cluster_id, ctx)
 		.await?
 		.into_iter()
 		.filter(|linked| linked.provider == Some(provider_id))
-		.filter_map(|linked| linked.project_id)
+		.filter(|linked| linked.project_id.as_deref().is_some_and(|id| id != replacing))
+		.collect();
+
+	let version_ids: Vec<String> = linked
+		.iter()
+		.filter_map(|linked| linked.version_id.clone())
+		.collect();
+	let mut versions: HashMap<String, VersionDetail> = if version_ids.is_empty() {
+		HashMap::new()
+	} else {
+		match provider.get_versions(&version_ids, ctx).await {
+			Ok(versions) => versions
+				.into_iter()
+				.map(|version| (version.version_id.clone(), version))
+				.collect(),
+			// Installed versions only refine the plan so carry on without them
+			Err(err) => {
+				tracing::warn!(%err, "failed to fetch installed dependency versions");
+				HashMap::new()
+			}
+		}
+	};
+
+	Ok(linked
+		.into_iter()
+		.filter_map(|linked| {
+			let project_id = linked.project_id?;
+			let version = linked.version_id.and_then(|id| versions.remove(&id));
+			let name = linked
+				.display_name
+				.unwrap_or_else(|| linked.file_name.clone());
+			Some((
+				project_id,
+				InstalledPackage {
+					hash: linked.hash,
+					name,
+					enabled: linked.enabled,
+					version,
+				},
+			))
+		})
 		.collect())
 }
 
@@ -270,6 +726,95 @@ mod tests {
 		}
 	}
 
+	fn version(project_id: &str, version_id: &str, dependencies: Vec<VersionDependency>) -> VersionDetail {
+		VersionDetail {
+			version_id: version_id.into(),
+			project_id: project_id.into(),
+			name: version_id.into(),
+			version_number: version_id.into(),
+			changelog: None,
+			game_versions: Vec::new(),
+			loaders: Vec::new(),
+			published: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+			downloads: 0,
+			files: Vec::new(),
+			dependencies,
+		}
+	}
+
+	fn dep(project_id: &str, version_id: Option<&str>, kind: DependencyKind) -> VersionDependency {
+		VersionDependency {
+			project_id: Some(project_id.into()),
+			version_id: version_id.map(Into::into),
+			kind,
+		}
+	}
+
+	fn nodes(versions: &[VersionDetail]) -> Vec<GraphNode<'_>> {
+		versions
+			.iter()
+			.map(|version| GraphNode {
+				project_id: &version.project_id,
+				name: &version.project_id,
+				version,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn incompatible_pairs_are_reported_once() {
+		let versions = [
+			version("sodium", "s1", vec![dep("optifine", None, DependencyKind::Incompatible)]),
+			version("optifine", "o1", vec![dep("sodium", None, DependencyKind::Incompatible)]),
+		];
+
+		let conflicts = find_conflicts(&nodes(&versions));
+		assert_eq!(conflicts.len(), 1);
+		assert_eq!(conflicts[0].kind, ConflictKind::Incompatible);
+		assert_eq!(conflicts[0].reason, "sodium is incompatible with optifine");
+	}
+
+	#[test]
+	fn a_pinned_incompatibility_spares_other_versions() {
+		let versions = [
+			version("iris", "i1", vec![dep("sodium", Some("s-old"), DependencyKind::Incompatible)]),
+			version("sodium", "s-new", Vec::new()),
+		];
+
+		assert!(find_conflicts(&nodes(&versions)).is_empty());
+	}
+
+	#[test]
+	fn a_pin_on_another_version_is_a_mismatch() {
+		let versions = [
+			version("addon", "a1", vec![dep("lib", Some("lib-1"), DependencyKind::Required)]),
+			version("lib", "lib-2", Vec::new()),
+		];
+
+		let conflicts = find_conflicts(&nodes(&versions));
+		assert_eq!(conflicts.len(), 1);
+		assert_eq!(conflicts[0].kind, ConflictKind::VersionMismatch);
+		assert_eq!((conflicts[0].source.as_str(), conflicts[0].target.as_str()), ("addon", "lib"));
+	}
+
+	#[test]
+	fn optional_and_satisfied_dependencies_are_fine() {
+		let versions = [
+			version(
+				"addon",
+				"a1",
+				vec![
+					dep("lib", Some("lib-2"), DependencyKind::Required),
+					dep("extra", Some("x9"), DependencyKind::Optional),
+				],
+			),
+			version("lib", "lib-2", Vec::new()),
+			version("extra", "x1", Vec::new()),
+		];
+
+		assert!(find_conflicts(&nodes(&versions)).is_empty());
+	}
+
 	#[test]
 	fn prefers_the_newest_release_over_a_newer_prerelease() {
 		let cluster = cluster(GameLoader::Fabric, "1.21.4");
@@ -336,4 +881,11 @@ mod tests {
 
 		assert!(choose_version(candidates, &cluster, GameLoader::Fabric).is_none());
 	}
+
+	#[test]
+	fn a_forced_upgrade_alone_is_listed_in_the_prompt() {
+		let body = plan_prompt_body(&[], &["Lib 2.0 → 1.0 (downgrade) for Addon".into()]);
+		assert!(!body.contains("conflicting"));
+		assert!(body.contains("• Lib 2.0 → 1.0 (downgrade) for Addon"));
+	}
 }
@@ -13,6 +13,8 @@ pub enum PackageError {
 	UnsupportedModpackFormat,
 	#[error("modpack install had {failed} of {total} file failures")]
 	PartialModpackInstall { failed: u64, total: u64 },
+	#[error("{0} can only be downloaded from the provider's website")]
+	NotDistributable(String),
 	#[error("missing API key for provider {0:?}")]
 	MissingApiKey(ProviderId),
 	#[error("unsupported package body type")]
@@ -21,6 +23,10 @@ pub enum PackageError {
 	IncompatibleMcVersion,
 	#[error("package is not compatible with cluster loader")]
 	IncompatibleLoader,
+	#[error("installing would leave conflicting packages: {}", .0.join("; "))]
+	DependencyConflicts(Vec<String>),
+	#[error("installing would change installed packages: {}", .0.join("; "))]
+	DependencyChangesDeclined(Vec<String>),
 	#[error("cluster {0} not found")]
 	ClusterNotFound(i64),
 	#[error("artifact file missing at {0}")]
@@ -31,8 +37,14 @@ pub enum PackageError {
 	UnsupportedProvider(ProviderId),
 	#[error("package provider {0:?} is not registered")]
 	ProviderNotRegistered(ProviderId),
+	#[error("no package index is configured for {0}")]
+	UnknownPackageIndex(String),
 	#[error("invalid local file: {0}")]
 	InvalidLocalFile(String),
+	#[error("invalid world folder name: {0}")]
+	InvalidWorld(String),
+	#[error("only datapacks are installed per world")]
+	NotPerWorld,
 }
 
 pub type PackageResult<T> = Result<T, PackageError>;
@@ -0,0 +1,958 @@
+//! What a mod jar says about itself
+//!
+//! Only used when no provider knows the file a hand-dropped jar is otherwise a
+//! file name and a hash
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use oneclient_common::domain::{ContentType, GameLoader, ProviderId};
+use oneclient_db::dao::artifact as artifact_dao;
+use oneclient_db::models::ArtifactRow;
+
+use crate::ctx::ContentCtx;
+use crate::error::ContentResult;
+use crate::packages::store::{PackageStore, artifact_absolute_path};
+use crate::packages::types::{DependencyKind, LinkedArtifactInfo};
+
+pub const FABRIC_MOD_JSON: &str = "fabric.mod.json";
+pub const QUILT_MOD_JSON: &str = "quilt.mod.json";
+pub const FORGE_MODS_TOML: &str = "META-INF/mods.toml";
+pub const NEOFORGE_MODS_TOML: &str = "META-INF/neoforge.mods.toml";
+pub const MCMOD_INFO: &str = "mcmod.info";
+const MANIFEST: &str = "META-INF/MANIFEST.MF";
+
+/// Fabric and Quilt nest under `META-INF/jars` Forge and NeoForge under `META-INF/jarjar`
+const NESTED_JAR_DIRS: [&str; 2] = ["META-INF/jars/", "META-INF/jarjar/"];
+
+/// Loader and runtime ids every mod lists they are not something a cluster installs
+const PLATFORM_IDS: [&str; 7] = [
+	"minecraft",
+	"java",
+	"fabricloader",
+	"quilt_loader",
+	"forge",
+	"neoforge",
+	"fml",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarMetadata {
+	pub mod_id: String,
+	pub name: Option<String>,
+	pub version: Option<String>,
+	pub loader: GameLoader,
+	/// As declared e.g. `>=1.21 <1.22` or `[1.20.1,1.21)`
+	pub minecraft: Option<String>,
+	pub dependencies: Vec<JarDependency>,
+	/// Ids this jar also answers to its declared aliases and nested jars
+	pub provides: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JarDependency {
+	pub mod_id: String,
+	pub kind: DependencyKind,
+	pub version_range: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JarIssue {
+	MissingDependency { mod_id: String, dependency: String },
+	Incompatible { mod_id: String, other: String },
+}
+
+impl JarIssue {
+	pub fn mod_id(&self) -> &str {
+		match self {
+			Self::MissingDependency { mod_id, .. } | Self::Incompatible { mod_id, .. } => mod_id,
+		}
+	}
+}
+
+impl std::fmt::Display for JarIssue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::MissingDependency { mod_id, dependency } => {
+				write!(f, "{mod_id} needs {dependency} which is not installed")
+			}
+			Self::Incompatible { mod_id, other } => {
+				write!(f, "{mod_id} does not work alongside {other}")
+			}
+		}
+	}
+}
+
+impl JarMetadata {
+	pub fn display_name(&self) -> &str {
+		self.name.as_deref().unwrap_or(&self.mod_id)
+	}
+
+	/// Legacy Fabric mods ship a plain `fabric.mod.json` so they read as Fabric
+	pub fn fits_loader(&self, cluster_loader: GameLoader) -> bool {
+		cluster_loader.compatible_with(self.loader)
+			|| (cluster_loader == GameLoader::LegacyFabric && self.loader == GameLoader::Fabric)
+	}
+
+	fn is_known_as(&self, id: &str) -> bool {
+		self.mod_id == id || self.provides.iter().any(|provided| provided == id)
+	}
+}
+
+/// Empty for a jar with no metadata file a library or a resource bundle
+#[tracing::instrument(level = "debug", skip(path), fields(path = %path.as_ref().display()))]
+pub async fn read_jar_metadata(path: impl AsRef<Path>) -> ContentResult<Vec<JarMetadata>> {
+	let entries = polyio::read_zip_file_entries(path, wanted_entry).await?;
+	let mut mods = parse_jar_entries(&entries);
+
+	let nested = nested_jar_ids(&entries).await;
+	if let Some(first) = mods.first_mut() {
+		first.provides.extend(nested);
+	}
+
+	Ok(mods)
+}
+
+/// Every parseable metadata file in the jar one that fails to parse is skipped
+pub fn parse_jar_entries(entries: &[(String, Vec<u8>)]) -> Vec<JarMetadata> {
+	let entry = |name: &str| {
+		entries
+			.iter()
+			.find(|(entry, _)| entry == name)
+			.map(|(_, bytes)| bytes.as_slice())
+	};
+	let jar_version = entry(MANIFEST).and_then(manifest_version);
+
+	let mut mods = Vec::new();
+	let mut parse = |name: &str, parsed: Result<Vec<JarMetadata>, String>| match parsed {
+		Ok(found) => mods.extend(found),
+		Err(err) => tracing::debug!(entry = name, %err, "unreadable mod metadata"),
+	};
+
+	if let Some(bytes) = entry(FABRIC_MOD_JSON) {
+		parse(FABRIC_MOD_JSON, parse_fabric(bytes));
+	}
+	if let Some(bytes) = entry(QUILT_MOD_JSON) {
+		parse(QUILT_MOD_JSON, parse_quilt(bytes));
+	}
+	if let Some(bytes) = entry(NEOFORGE_MODS_TOML) {
+		parse(
+			NEOFORGE_MODS_TOML,
+			parse_mods_toml(bytes, Some(GameLoader::NeoForge), jar_version.as_deref()),
+		);
+	} else if let Some(bytes) = entry(FORGE_MODS_TOML) {
+		parse(FORGE_MODS_TOML, parse_mods_toml(bytes, None, jar_version.as_deref()));
+	}
+	if let Some(bytes) = entry(MCMOD_INFO) {
+		parse(MCMOD_INFO, parse_mcmod_info(bytes));
+	}
+
+	mods
+}
+
+/// Checks ids only version ranges are left to the loader
+pub fn jar_dependency_issues(mods: &[JarMetadata]) -> Vec<JarIssue> {
+	let present = |id: &str| mods.iter().any(|other| other.is_known_as(id));
+
+	let mut issues = Vec::new();
+	let mut reported = HashSet::new();
+	for metadata in mods {
+		for dep in &metadata.dependencies {
+			let issue = match dep.kind {
+				DependencyKind::Required if !present(&dep.mod_id) => JarIssue::MissingDependency {
+					mod_id: metadata.mod_id.clone(),
+					dependency: dep.mod_id.clone(),
+				},
+				DependencyKind::Incompatible if present(&dep.mod_id) => JarIssue::Incompatible {
+					mod_id: metadata.mod_id.clone(),
+					other: dep.mod_id.clone(),
+				},
+				_ => continue,
+			};
+			if reported.insert(issue.clone()) {
+				issues.push(issue);
+			}
+		}
+	}
+
+	issues
+}
+
+/// Enabled mods only each paired with its link
+#[tracing::instrument(level = "debug", skip(ctx))]
+pub async fn cluster_jar_metadata(
+	cluster_id: i64,
+	ctx: &ContentCtx,
+) -> ContentResult<Vec<(LinkedArtifactInfo, Vec<JarMetadata>)>> {
+	let linked = PackageStore::list_linked_artifacts(cluster_id, ctx).await?;
+
+	let mut out = Vec::new();
+	for link in linked {
+		if !link.enabled || link.content_type != ContentType::Mod {
+			continue;
+		}
+		let Some(row) = artifact_dao::get_artifact_by_hash(&ctx.db, &link.hash).await? else {
+			continue;
+		};
+
+		match read_jar_metadata(artifact_absolute_path(&row.path)?).await {
+			Ok(metadata) => out.push((link, metadata)),
+			Err(err) => tracing::debug!(file = %link.file_name, %err, "could not read jar metadata"),
+		}
+	}
+
+	Ok(out)
+}
+
+/// Names an imported jar no provider recognised and warns when it was built
+/// for another loader
+/// Best effort an unreadable jar stays a bare file
+#[tracing::instrument(level = "debug", skip(row, path, ctx), fields(hash = %row.hash))]
+pub(crate) async fn identify_local_jar(
+	row: &ArtifactRow,
+	path: &Path,
+	cluster_loader: GameLoader,
+	ctx: &ContentCtx,
+) {
+	let metadata = match read_jar_metadata(path).await {
+		Ok(metadata) => metadata,
+		Err(err) => {
+			tracing::debug!(%err, "imported file is not a readable jar");
+			return;
+		}
+	};
+	let Some(primary) = metadata.first() else {
+		return;
+	};
+
+	if cluster_loader.is_modded() && !metadata.iter().any(|m| m.fits_loader(cluster_loader)) {
+		tracing::warn!(mod_id = %primary.mod_id, loader = ?primary.loader, "imported mod is for another loader");
+		ctx.events
+			.notify("Mod for another loader")
+			.body(format!(
+				"{} is a {} mod and will not load in a {} cluster",
+				primary.display_name(),
+				primary.loader,
+				cluster_loader
+			))
+			.error()
+			.send();
+	}
+
+	if let Err(err) = record_local_release(row, primary, ctx).await {
+		tracing::warn!(%err, "could not record jar metadata");
+	}
+}
+
+async fn record_local_release(
+	row: &ArtifactRow,
+	metadata: &JarMetadata,
+	ctx: &ContentCtx,
+) -> ContentResult<()> {
+	// A provider release already names the file better than the jar can
+	if artifact_dao::get_release_by_hash(&ctx.db, &row.hash)
+		.await?
+		.is_some()
+	{
+		return Ok(());
+	}
+
+	let version = metadata.version.as_deref().unwrap_or_default();
+	// Two builds of one mod both reporting `1.0` must not overwrite each other
+	let version_id = format!("{version}+{}", &row.hash[..row.hash.len().min(8)]);
+	artifact_dao::upsert_provider_release(
+		&ctx.db,
+		ProviderId::Local as i64,
+		&metadata.mod_id,
+		&version_id,
+		&row.hash,
+		metadata.display_name(),
+		version,
+		None,
+		"[]",
+		&serde_json::to_string(&[metadata.loader])?,
+	)
+	.await?;
+
+	Ok(())
+}
+
+fn wanted_entry(name: &str) -> bool {
+	matches!(
+		name,
+		FABRIC_MOD_JSON | QUILT_MOD_JSON | FORGE_MODS_TOML | NEOFORGE_MODS_TOML | MCMOD_INFO | MANIFEST
+	) || is_nested_jar(name)
+}
+
+fn is_nested_jar(name: &str) -> bool {
+	name.ends_with(".jar") && NESTED_JAR_DIRS.iter().any(|dir| name.starts_with(dir))
+}
+
+/// One level deep is enough for fabric-api style bundles
+async fn nested_jar_ids(entries: &[(String, Vec<u8>)]) -> Vec<String> {
+	let mut ids = Vec::new();
+	for (name, bytes) in entries {
+		if !is_nested_jar(name) {
+			continue;
+		}
+
+		// A named reader not an async closure the closure's future is not
+		// `Send` and would leak that into every caller that spawns
+		let inner = match polyio::read_zip_bytes_entries(bytes.clone(), |entry| {
+			wanted_entry(entry) && !is_nested_jar(entry)
+		})
+		.await
+		{
+			Ok(inner) => inner,
+			Err(err) => {
+				tracing::debug!(jar = %name, %err, "unreadable nested jar");
+				continue;
+			}
+		};
+		for nested in parse_jar_entries(&inner) {
+			ids.push(nested.mod_id);
+			ids.extend(nested.provides);
+		}
+	}
+
+	ids
+}
+
+fn manifest_version(bytes: &[u8]) -> Option<String> {
+	String::from_utf8_lossy(bytes).lines().find_map(|line| {
+		line.strip_prefix("Implementation-Version:")
+			.map(|value| value.trim().to_string())
+	})
+}
+
+/// Fabric tolerates raw newlines and tabs inside strings serde_json does not
+fn lenient_json<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+	let text = String::from_utf8_lossy(bytes).replace(['\n', '\r', '\t'], " ");
+	serde_json::from_str(text.trim_start_matches('\u{feff}')).map_err(|err| err.to_string())
+}
+
+/// `None` for an unexpanded build placeholder like `${version}`
+fn declared(value: Option<String>) -> Option<String> {
+	value.filter(|value| !value.trim().is_empty() && !value.contains("${"))
+}
+
+fn is_platform(id: &str) -> bool {
+	PLATFORM_IDS.contains(&id)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VersionSpec {
+	One(String),
+	Any(Vec<String>),
+	Other(serde::de::IgnoredAny),
+}
+
+impl VersionSpec {
+	fn range(&self) -> Option<String> {
+		match self {
+			Self::One(range) => Some(range.clone()),
+			Self::Any(ranges) => Some(ranges.join(" || ")),
+			Self::Other(_) => None,
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct FabricModJson {
+	id: String,
+	#[serde(default)]
+	name: Option<String>,
+	#[serde(default)]
+	version: Option<String>,
+	#[serde(default)]
+	provides: Vec<String>,
+	#[serde(default)]
+	depends: BTreeMap<String, VersionSpec>,
+	#[serde(default)]
+	recommends: BTreeMap<String, VersionSpec>,
+	#[serde(default)]
+	suggests: BTreeMap<String, VersionSpec>,
+	#[serde(default)]
+	breaks: BTreeMap<String, VersionSpec>,
+}
+
+fn parse_fabric(bytes: &[u8]) -> Result<Vec<JarMetadata>, String> {
+	let json: FabricModJson = lenient_json(bytes)?;
+
+	let mut dependencies = Vec::new();
+	for (table, kind) in [
+		(&json.depends, DependencyKind::Required),
+		(&json.recommends, DependencyKind::Optional),
+		(&json.suggests, DependencyKind::Optional),
+		(&json.breaks, DependencyKind::Incompatible),
+	] {
+		dependencies.extend(table.iter().filter(|(id, _)| !is_platform(id)).map(|(id, spec)| {
+			JarDependency {
+				mod_id: id.clone(),
+				kind,
+				version_range: spec.range(),
+			}
+		}));
+	}
+
+	Ok(vec![JarMetadata {
+		minecraft: json.depends.get("minecraft").and_then(VersionSpec::range),
+		mod_id: json.id,
+		name: json.name,
+		version: declared(json.version),
+		loader: GameLoader::Fabric,
+		dependencies,
+		provides: json.provides,
+	}])
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+	quilt_loader: QuiltLoader,
+}
+
+#[derive(Deserialize)]
+struct QuiltLoader {
+	id: String,
+	#[serde(default)]
+	version: Option<String>,
+	#[serde(default)]
+	metadata: Option<QuiltMetadata>,
+	#[serde(default)]
+	provides: Vec<QuiltProvide>,
+	#[serde(default)]
+	depends: Vec<QuiltDependency>,
+	#[serde(default)]
+	breaks: Vec<QuiltDependency>,
+}
+
+#[derive(Deserialize)]
+struct QuiltMetadata {
+	#[serde(default)]
+	name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltProvide {
+	Id(String),
+	Entry { id: String },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltDependency {
+	Id(String),
+	Entry {
+		id: String,
+		#[serde(default)]
+		versions: Option<VersionSpec>,
+		#[serde(default)]
+		optional: bool,
+	},
+	Other(serde::de::IgnoredAny),
+}
+
+impl QuiltDependency {
+	fn parts(&self) -> Option<(&str, Option<String>, bool)> {
+		match self {
+			Self::Id(id) => Some((id, None, false)),
+			Self::Entry {
+				id,
+				versions,
+				optional,
+			} => Some((id, versions.as_ref().and_then(VersionSpec::range), *optional)),
+			Self::Other(_) => None,
+		}
+	}
+}
+
+fn parse_quilt(bytes: &[u8]) -> Result<Vec<JarMetadata>, String> {
+	let json: QuiltModJson = lenient_json(bytes)?;
+	let loader = json.quilt_loader;
+
+	let mut minecraft = None;
+	let mut dependencies = Vec::new();
+	for (dep, breaks) in loader
+		.depends
+		.iter()
+		.map(|dep| (dep, false))
+		.chain(loader.breaks.iter().map(|dep| (dep, true)))
+	{
+		let Some((id, range, optional)) = dep.parts() else {
+			continue;
+		};
+		if id == "minecraft" && !breaks {
+			minecraft = range.clone();
+		}
+		if is_platform(id) {
+			continue;
+		}
+		let kind = match (breaks, optional) {
+			(true, _) => DependencyKind::Incompatible,
+			(false, true) => DependencyKind::Optional,
+			(false, false) => DependencyKind::Required,
+		};
+		dependencies.push(JarDependency {
+			mod_id: id.to_string(),
+			kind,
+			version_range: range,
+		});
+	}
+
+	Ok(vec![JarMetadata {
+		mod_id: loader.id,
+		name: loader.metadata.and_then(|metadata| metadata.name),
+		version: declared(loader.version),
+		loader: GameLoader::Quilt,
+		minecraft,
+		dependencies,
+		provides: loader
+			.provides
+			.into_iter()
+			.map(|provide| match provide {
+				QuiltProvide::Id(id) | QuiltProvide::Entry { id } => id,
+			})
+			.collect(),
+	}])
+}
+
+#[derive(Deserialize)]
+struct ModsToml {
+	#[serde(default)]
+	mods: Vec<TomlMod>,
+	#[serde(default)]
+	dependencies: BTreeMap<String, Vec<TomlDependency>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TomlMod {
+	mod_id: String,
+	#[serde(default)]
+	version: Option<String>,
+	#[serde(default)]
+	display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TomlDependency {
+	mod_id: String,
+	/// Forge
+	#[serde(default)]
+	mandatory: Option<bool>,
+	/// NeoForge `required` `optional` `incompatible` `discouraged`
+	#[serde(default, rename = "type")]
+	kind: Option<String>,
+	#[serde(default)]
+	version_range: Option<String>,
+}
+
+impl TomlDependency {
+	fn kind(&self) -> Option<DependencyKind> {
+		match self.kind.as_deref().map(str::to_lowercase).as_deref() {
+			Some("required") => Some(DependencyKind::Required),
+			Some("optional") => Some(DependencyKind::Optional),
+			Some("incompatible") => Some(DependencyKind::Incompatible),
+			Some(_) => None,
+			None => Some(if self.mandatory.unwrap_or(false) {
+				DependencyKind::Required
+			} else {
+				DependencyKind::Optional
+			}),
+		}
+	}
+}
+
+/// `loader` is `None` for `mods.toml` which early NeoForge shipped too
+/// a dependency on `neoforge` tells them apart
+fn parse_mods_toml(
+	bytes: &[u8],
+	loader: Option<GameLoader>,
+	jar_version: Option<&str>,
+) -> Result<Vec<JarMetadata>, String> {
+	let text = String::from_utf8_lossy(bytes);
+	let toml: ModsToml = toml::from_str(&text).map_err(|err| err.to_string())?;
+
+	let loader = loader.unwrap_or_else(|| {
+		let neoforge = toml
+			.dependencies
+			.values()
+			.flatten()
+			.any(|dep| dep.mod_id == "neoforge");
+		if neoforge {
+			GameLoader::NeoForge
+		} else {
+			GameLoader::Forge
+		}
+	});
+
+	Ok(toml
+		.mods
+		.into_iter()
+		.map(|entry| {
+			let declared_deps = toml
+				.dependencies
+				.get(&entry.mod_id)
+				.map(Vec::as_slice)
+				.unwrap_or_default();
+			let minecraft = declared_deps
+				.iter()
+				.find(|dep| dep.mod_id == "minecraft")
+				.and_then(|dep| dep.version_range.clone());
+			let dependencies = declared_deps
+				.iter()
+				.filter(|dep| !is_platform(&dep.mod_id))
+				.filter_map(|dep| {
+					Some(JarDependency {
+						mod_id: dep.mod_id.clone(),
+						kind: dep.kind()?,
+						version_range: dep.version_range.clone(),
+					})
+				})
+				.collect();
+
+			let version = match entry.version.as_deref() {
+				Some("${file.jarVersion}") => jar_version.map(str::to_string),
+				_ => declared(entry.version),
+			};
+
+			JarMetadata {
+				mod_id: entry.mod_id,
+				name: entry.display_name,
+				version,
+				loader,
+				minecraft,
+				dependencies,
+				provides: Vec::new(),
+			}
+		})
+		.collect())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum McmodInfo {
+	List(Vec<McmodEntry>),
+	Versioned {
+		#[serde(rename = "modList")]
+		mod_list: Vec<McmodEntry>,
+	},
+}
+
+#[derive(Deserialize)]
+struct McmodEntry {
+	modid: String,
+	#[serde(default)]
+	name: Option<String>,
+	#[serde(default)]
+	version: Option<String>,
+	#[serde(default)]
+	mcversion: Option<String>,
+	/// `modid@range` or a bare id
+	#[serde(default, rename = "requiredMods")]
+	required_mods: Vec<String>,
+}
+
+fn parse_mcmod_info(bytes: &[u8]) -> Result<Vec<JarMetadata>, String> {
+	let entries = match lenient_json::<McmodInfo>(bytes)? {
+		McmodInfo::List(entries) | McmodInfo::Versioned { mod_list: entries } => entries,
+	};
+
+	Ok(entries
+		.into_iter()
+		.map(|entry| JarMetadata {
+			dependencies: entry
+				.required_mods
+				.iter()
+				.filter_map(|required| {
+					let (id, range) = match required.split_once('@') {
+						Some((id, range)) => (id, Some(range.to_string())),
+						None => (required.as_str(), None),
+					};
+					let id = id.trim();
+					(!is_platform(&id.to_lowercase())).then(|| JarDependency {
+						mod_id: id.to_string(),
+						kind: DependencyKind::Required,
+						version_range: range,
+					})
+				})
+				.collect(),
+			mod_id: entry.modid,
+			name: entry.name,
+			version: declared(entry.version),
+			loader: GameLoader::Forge,
+			minecraft: declared(entry.mcversion),
+			provides: Vec::new(),
+		})
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entries(files: &[(&str, &str)]) -> Vec<(String, Vec<u8>)> {
+		files
+			.iter()
+			.map(|(name, body)| (name.to_string(), body.as_bytes().to_vec()))
+			.collect()
+	}
+
+	#[test]
+	fn reads_fabric_mod_json() {
+		let mods = parse_jar_entries(&entries(&[(
+			FABRIC_MOD_JSON,
+			r#"{
+				"schemaVersion": 1,
+				"id": "sodium",
+				"name": "Sodium",
+				"version": "0.6.0",
+				"provides": ["embeddium"],
+				"depends": { "minecraft": ">=1.21", "fabricloader": "*", "fabric-api": ["*"] },
+				"breaks": { "optifabric": "*" }
+			}"#,
+		)]));
+
+		assert_eq!(mods.len(), 1);
+		let sodium = &mods[0];
+		assert_eq!(sodium.mod_id, "sodium");
+		assert_eq!(sodium.loader, GameLoader::Fabric);
+		assert_eq!(sodium.minecraft.as_deref(), Some(">=1.21"));
+		assert_eq!(sodium.provides, ["embeddium"]);
+		assert_eq!(
+			sodium.dependencies,
+			[
+				JarDependency {
+					mod_id: "fabric-api".into(),
+					kind: DependencyKind::Required,
+					version_range: Some("*".into()),
+				},
+				JarDependency {
+					mod_id: "optifabric".into(),
+					kind: DependencyKind::Incompatible,
+					version_range: Some("*".into()),
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn fabric_json_with_raw_newlines_still_parses() {
+		let mods = parse_jar_entries(&entries(&[(
+			FABRIC_MOD_JSON,
+			"{\"id\": \"sloppy\", \"description\": \"line one\nline two\"}",
+		)]));
+		assert_eq!(mods[0].mod_id, "sloppy");
+	}
+
+	#[test]
+	fn reads_quilt_mod_json() {
+		let mods = parse_jar_entries(&entries(&[(
+			QUILT_MOD_JSON,
+			r#"{
+				"schema_version": 1,
+				"quilt_loader": {
+					"id": "qsl_thing",
+					"version": "2.0.0",
+					"metadata": { "name": "QSL Thing" },
+					"depends": [
+						{ "id": "minecraft", "versions": ">=1.20" },
+						"quilted_fabric_api",
+						{ "id": "modmenu", "optional": true }
+					],
+					"breaks": [{ "id": "oldthing" }]
+				}
+			}"#,
+		)]));
+
+		let thing = &mods[0];
+		assert_eq!(thing.name.as_deref(), Some("QSL Thing"));
+		assert_eq!(thing.loader, GameLoader::Quilt);
+		assert_eq!(thing.minecraft.as_deref(), Some(">=1.20"));
+		let kinds: Vec<_> = thing
+			.dependencies
+			.iter()
+			.map(|dep| (dep.mod_id.as_str(), dep.kind))
+			.collect();
+		assert_eq!(
+			kinds,
+			[
+				("quilted_fabric_api", DependencyKind::Required),
+				("modmenu", DependencyKind::Optional),
+				("oldthing", DependencyKind::Incompatible),
+			]
+		);
+	}
+
+	#[test]
+	fn reads_forge_mods_toml_with_a_manifest_version() {
+		let mods = parse_jar_entries(&entries(&[
+			(
+				FORGE_MODS_TOML,
+				r#"
+modLoader = "javafml"
+loaderVersion = "[47,)"
+
+[[mods]]
+modId = "create"
+version = "${file.jarVersion}"
+displayName = "Create"
+
+[[dependencies.create]]
+    modId = "forge"
+    mandatory = true
+    versionRange = "[47.1.3,)"
+
+[[dependencies.create]]
+    modId = "minecraft"
+    mandatory = true
+    versionRange = "[1.20.1,1.20.2)"
+
+[[dependencies.create]]
+    modId = "flywheel"
+    mandatory = true
+    versionRange = "[0.6.10,0.6.11)"
+"#,
+			),
+			(MANIFEST, "Manifest-Version: 1.0\r\nImplementation-Version: 0.5.1.f\r\n"),
+		]));
+
+		let create = &mods[0];
+		assert_eq!(create.loader, GameLoader::Forge);
+		assert_eq!(create.version.as_deref(), Some("0.5.1.f"));
+		assert_eq!(create.minecraft.as_deref(), Some("[1.20.1,1.20.2)"));
+		assert_eq!(create.dependencies.len(), 1);
+		assert_eq!(create.dependencies[0].mod_id, "flywheel");
+	}
+
+	#[test]
+	fn a_neoforge_dependency_marks_an_old_style_mods_toml() {
+		let mods = parse_jar_entries(&entries(&[(
+			FORGE_MODS_TOML,
+			r#"
+[[mods]]
+modId = "early"
+
+[[dependencies.early]]
+    modId = "neoforge"
+    type = "required"
+"#,
+		)]));
+		assert_eq!(mods[0].loader, GameLoader::NeoForge);
+	}
+
+	#[test]
+	fn neoforge_dependency_types_map_to_kinds() {
+		let mods = parse_jar_entries(&entries(&[(
+			NEOFORGE_MODS_TOML,
+			r#"
+[[mods]]
+modId = "neo"
+version = "${version}"
+
+[[dependencies.neo]]
+    modId = "jei"
+    type = "optional"
+
+[[dependencies.neo]]
+    modId = "rubidium"
+    type = "incompatible"
+
+[[dependencies.neo]]
+    modId = "meh"
+    type = "discouraged"
+"#,
+		)]));
+
+		let neo = &mods[0];
+		assert_eq!(neo.loader, GameLoader::NeoForge);
+		assert_eq!(neo.version, None, "placeholders are not versions");
+		let kinds: Vec<_> = neo.dependencies.iter().map(|dep| dep.kind).collect();
+		assert_eq!(kinds, [DependencyKind::Optional, DependencyKind::Incompatible]);
+	}
+
+	#[test]
+	fn reads_both_mcmod_info_layouts() {
+		let list = parse_jar_entries(&entries(&[(
+			MCMOD_INFO,
+			r#"[{ "modid": "jei", "name": "Just Enough Items", "version": "4.16", "mcversion": "1.12.2", "requiredMods": ["Forge@[14.23,)", "baubles"] }]"#,
+		)]));
+		assert_eq!(list[0].minecraft.as_deref(), Some("1.12.2"));
+		assert_eq!(list[0].dependencies.len(), 1);
+		assert_eq!(list[0].dependencies[0].mod_id, "baubles");
+
+		let versioned = parse_jar_entries(&entries(&[(
+			MCMOD_INFO,
+			r#"{ "modListVersion": 2, "modList": [{ "modid": "old", "mcversion": "${mcversion}" }] }"#,
+		)]));
+		assert_eq!(versioned[0].mod_id, "old");
+		assert_eq!(versioned[0].minecraft, None);
+	}
+
+	#[test]
+	fn broken_metadata_is_skipped_not_fatal() {
+		let mods = parse_jar_entries(&entries(&[
+			(FABRIC_MOD_JSON, "{ not json"),
+			(MCMOD_INFO, r#"[{ "modid": "fine" }]"#),
+		]));
+		assert_eq!(mods.len(), 1);
+		assert_eq!(mods[0].mod_id, "fine");
+	}
+
+	fn fabric(id: &str, depends: &[&str], breaks: &[&str], provides: &[&str]) -> JarMetadata {
+		let dep = |id: &&str, kind| JarDependency {
+			mod_id: id.to_string(),
+			kind,
+			version_range: None,
+		};
+		JarMetadata {
+			mod_id: id.into(),
+			name: None,
+			version: None,
+			loader: GameLoader::Fabric,
+			minecraft: None,
+			dependencies: depends
+				.iter()
+				.map(|id| dep(id, DependencyKind::Required))
+				.chain(breaks.iter().map(|id| dep(id, DependencyKind::Incompatible)))
+				.collect(),
+			provides: provides.iter().map(|id| id.to_string()).collect(),
+		}
+	}
+
+	#[test]
+	fn dependency_issues_count_provided_and_nested_ids() {
+		let mods = [
+			fabric("addon", &["fabric-api-base", "cloth-config"], &[], &[]),
+			fabric("fabric-api", &[], &[], &["fabric-api-base"]),
+			fabric("sodium", &[], &["optifabric"], &[]),
+			fabric("optifabric", &[], &[], &[]),
+		];
+
+		assert_eq!(
+			jar_dependency_issues(&mods),
+			[
+				JarIssue::MissingDependency {
+					mod_id: "addon".into(),
+					dependency: "cloth-config".into(),
+				},
+				JarIssue::Incompatible {
+					mod_id: "sodium".into(),
+					other: "optifabric".into(),
+				},
+			]
+		);
+	}
+
+	/// Launch and import spawn tasks that await this so it must stay `Send`
+	#[test]
+	fn reading_a_jar_is_send() {
+		fn assert_send<T: Send>(_: T) {}
+		assert_send(read_jar_metadata("unused.jar"));
+	}
+}
@@ -1,6 +1,8 @@
+pub mod audit;
 pub mod activity;
 pub mod dependencies;
 pub mod error;
+pub mod jar_metadata;
 pub mod metadata_cache;
 pub mod modpack;
 pub mod provider;
@@ -18,11 +20,14 @@ pub use metadata_cache::{
 };
 pub use activity::reconcile_duplicate_activity;
 pub use dependencies::{
-    DependencyResolution, ResolvedDependency, resolve_required, resolves_dependencies,
+    ConflictKind, DependencyConflict, DependencyPlan, DisabledDependency, PlannedUpgrade,
+    ResolvedDependency,
+    apply_dependency_upgrade, confirm_dependency_plan, pick_version, plan_dependencies,
+    plan_dependencies_or_ask, resolves_dependencies,
 };
 pub use file_identity::{curseforge_fingerprint, FileIdentity};
 pub use error::{PackageError, PackageResult};
-pub use provider::{PackageProvider, PackageProviderRegistry};
+pub use provider::{PackageIndex, PackageProvider, PackageProviderRegistry, SelfHostedProvider};
 pub use store::PackageStore;
 pub use types::*;
 pub use updates::{
@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use oneclient_common::domain::{GameLoader, ProviderId};
+use oneclient_db::dao::cluster as cluster_dao;
+use oneclient_db::models::ClusterRow;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ctx::ContentCtx;
+use crate::error::{ContentError, ContentResult};
+use crate::packages::error::PackageError;
+use crate::packages::store::PackageStore;
+use crate::packages::types::{ProjectDetail, VersionDetail};
+
+use super::export::{self, ModpackExportOptions, ModpackExportReport, OVERRIDES_DIR};
+
+pub const CURSEFORGE_MANIFEST: &str = "manifest.json";
+const CURSEFORGE_MANIFEST_TYPE: &str = "minecraftModpack";
+const CURSEFORGE_MANIFEST_VERSION: u32 = 1;
+
+pub struct CurseForgeInstaller;
+
+pub struct CurseForgeExporter;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeManifest {
+	minecraft: CurseForgeMinecraft,
+	#[serde(default)]
+	manifest_type: String,
+	#[serde(default = "default_manifest_version")]
+	manifest_version: u32,
+	#[serde(default)]
+	name: String,
+	#[serde(default)]
+	version: String,
+	#[serde(default)]
+	author: String,
+	#[serde(default)]
+	files: Vec<CurseForgeFileEntry>,
+	#[serde(default = "default_overrides")]
+	overrides: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeMinecraft {
+	version: String,
+	#[serde(default)]
+	mod_loaders: Vec<CurseForgeModLoader>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CurseForgeModLoader {
+	/// `<loader>-<version>` e.g. `forge-47.2.0`
+	id: String,
+	#[serde(default)]
+	primary: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CurseForgeFileEntry {
+	#[serde(rename = "projectID")]
+	project_id: u32,
+	#[serde(rename = "fileID")]
+	file_id: u32,
+	#[serde(default = "default_required")]
+	required: bool,
+}
+
+const fn default_manifest_version() -> u32 {
+	CURSEFORGE_MANIFEST_VERSION
+}
+
+fn default_overrides() -> String {
+	String::from(OVERRIDES_DIR)
+}
+
+const fn default_required() -> bool {
+	true
+}
+
+impl CurseForgeManifest {
+	/// The primary entry wins packs from older exporters mark none
+	fn loader(&self) -> Option<(GameLoader, Option<String>)> {
+		let loaders = &self.minecraft.mod_loaders;
+		let entry = loaders
+			.iter()
+			.find(|loader| loader.primary)
+			.or_else(|| loaders.first())?;
+		parse_loader_id(&entry.id)
+	}
+}
+
+impl CurseForgeInstaller {
+	#[tracing::instrument(skip(ctx))]
+	pub async fn install_archive(
+		archive_path: PathBuf,
+		cluster_id: i64,
+		ctx: &ContentCtx,
+	) -> ContentResult<()> {
+		tracing::info!("installing curseforge modpack to cluster");
+		let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
+
+		let bytes = polyio::read(&archive_path).await?;
+		let manifest_bytes =
+			polyio::try_read_zip_entry_bytes(std::io::Cursor::new(&bytes), CURSEFORGE_MANIFEST)
+				.await
+				.map_err(|err| match err {
+					polyio::IOError::FileNotFoundInZip { .. } => {
+						PackageError::UnsupportedModpackFormat.into()
+					}
+					err => ContentError::from(err),
+				})?;
+		let manifest: CurseForgeManifest = serde_json::from_slice(&manifest_bytes)?;
+		if manifest.manifest_type != CURSEFORGE_MANIFEST_TYPE {
+			return Err(PackageError::UnsupportedModpackFormat.into());
+		}
+
+		let cluster = apply_loader(&manifest, cluster, ctx).await?;
+
+		let entries: Vec<&CurseForgeFileEntry> =
+			manifest.files.iter().filter(|entry| entry.required).collect();
+		let skipped = manifest.files.len() - entries.len();
+		if skipped > 0 {
+			tracing::info!(skipped, "leaving out files the pack marks optional");
+		}
+
+		let (versions, projects) = resolve_files(&entries, ctx).await?;
+
+		let mut failed = 0u64;
+		let total = entries.len() as u64;
+		let progress_id = Uuid::new_v4();
+
+		for (index, entry) in entries.into_iter().enumerate() {
+			ctx.events.progress(
+				progress_id,
+				"Installing Modpack Files",
+				index as u64,
+				total,
+			);
+
+			let result = match (
+				versions.get(&entry.file_id.to_string()),
+				projects.get(&entry.project_id.to_string()),
+			) {
+				(Some(version), Some(project)) => {
+					install_curseforge_file(project, version, &cluster, ctx).await
+				}
+				_ => Err(PackageError::ArtifactMissing(format!(
+					"curseforge file {}/{}",
+					entry.project_id, entry.file_id
+				))
+				.into()),
+			};
+
+			if let Err(err) = result {
+				failed += 1;
+				tracing::warn!(
+					project_id = entry.project_id,
+					file_id = entry.file_id,
+					error = %err,
+					"modpack file install failed"
+				);
+			}
+		}
+
+		ctx.events
+			.progress(progress_id, "Installing Modpack Files", total, total);
+
+		let cluster_root = oneclient_common::paths::cluster_dir(&cluster.folder_name)?;
+		super::extract_overrides(bytes, &manifest.overrides, &cluster_root, |_| true).await?;
+
+		if failed > 0 {
+			tracing::error!(failed, total, "modpack install completed with failures");
+			return Err(PackageError::PartialModpackInstall { failed, total }.into());
+		}
+
+		tracing::info!(total, "modpack install complete");
+		Ok(())
+	}
+}
+
+impl CurseForgeExporter {
+	/// CurseForge files become `files` entries everything else is bundled
+	/// under `overrides/` since a manifest can only point at CurseForge ids
+	#[tracing::instrument(skip(options, ctx))]
+	pub async fn export_cluster(
+		cluster_id: i64,
+		dest: &Path,
+		options: &ModpackExportOptions,
+		ctx: &ContentCtx,
+	) -> ContentResult<ModpackExportReport> {
+		tracing::info!("exporting cluster as curseforge modpack");
+		let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
+		let mut report = ModpackExportReport::default();
+
+		let mod_loaders = curseforge_mod_loaders(
+			&cluster,
+			options.resolved_loader_version(&cluster),
+			&mut report,
+		);
+
+		let mut files = Vec::new();
+		let mut overrides = Vec::new();
+
+		for artifact in export::exported_artifacts(cluster_id, &mut report, ctx).await? {
+			let ids = match (
+				artifact.info.provider,
+				&artifact.info.project_id,
+				&artifact.info.version_id,
+			) {
+				(Some(ProviderId::CurseForge), Some(project_id), Some(version_id)) => {
+					project_id.parse().ok().zip(version_id.parse().ok())
+				}
+				_ => None,
+			};
+
+			let Some((project_id, file_id)) = ids else {
+				if artifact.info.provider == Some(ProviderId::Modrinth) {
+					report.warnings.push(format!(
+						"{} is a Modrinth file; CurseForge packs cannot link it so it was bundled",
+						artifact.path
+					));
+				}
+				report.overrides.push(artifact.path.clone());
+				overrides.push((artifact.path, artifact.source));
+				continue;
+			};
+
+			report.referenced.push(artifact.path);
+			files.push(CurseForgeFileEntry {
+				project_id,
+				file_id,
+				required: true,
+			});
+		}
+
+		for (pack_path, source) in
+			export::override_files(&cluster, &options.include_paths, &mut report).await?
+		{
+			report.overrides.push(pack_path.clone());
+			overrides.push((pack_path, source));
+		}
+
+		let manifest = CurseForgeManifest {
+			minecraft: CurseForgeMinecraft {
+				version: cluster.mc_version.clone(),
+				mod_loaders,
+			},
+			manifest_type: String::from(CURSEFORGE_MANIFEST_TYPE),
+			manifest_version: CURSEFORGE_MANIFEST_VERSION,
+			name: options.pack_name(&cluster),
+			version: options.version.clone(),
+			author: options.author.clone().unwrap_or_default(),
+			files,
+			overrides: String::from(OVERRIDES_DIR),
+		};
+
+		export::write_pack_archive(
+			dest,
+			CURSEFORGE_MANIFEST,
+			serde_json::to_vec_pretty(&manifest)?,
+			&overrides,
+		)
+		.await?;
+
+		tracing::info!(
+			referenced = report.referenced.len(),
+			overrides = report.overrides.len(),
+			"curseforge export complete"
+		);
+		Ok(report)
+	}
+}
+
+/// The pack decides the loader the minecraft version is only checked since
+/// moving a cluster between versions is a migration not an install step
+async fn apply_loader(
+	manifest: &CurseForgeManifest,
+	cluster: ClusterRow,
+	ctx: &ContentCtx,
+) -> ContentResult<ClusterRow> {
+	if manifest.minecraft.version != cluster.mc_version {
+		tracing::warn!(
+			pack = %manifest.minecraft.version,
+			cluster = %cluster.mc_version,
+			"modpack targets a different minecraft version"
+		);
+	}
+
+	let Some((loader, version)) = manifest.loader() else {
+		if !manifest.minecraft.mod_loaders.is_empty() {
+			tracing::warn!("modpack names no loader the launcher supports");
+		}
+		return Ok(cluster);
+	};
+
+	Ok(cluster_dao::set_loader(&ctx.db, cluster.id, loader as i64, version.as_deref()).await?)
+}
+
+/// One batched call for the files and one for their projects instead of two
+/// requests per mod
+async fn resolve_files(
+	entries: &[&CurseForgeFileEntry],
+	ctx: &ContentCtx,
+) -> ContentResult<(
+	HashMap<String, VersionDetail>,
+	HashMap<String, ProjectDetail>,
+)> {
+	if entries.is_empty() {
+		return Ok((HashMap::new(), HashMap::new()));
+	}
+
+	let provider = ctx.providers.get(ProviderId::CurseForge)?;
+
+	let file_ids: Vec<String> = entries.iter().map(|entry| entry.file_id.to_string()).collect();
+	let versions: HashMap<String, VersionDetail> = provider
+		.get_versions(&file_ids, ctx)
+		.await?
+		.into_iter()
+		.map(|version| (version.version_id.clone(), version))
+		.collect();
+
+	let mut project_ids: Vec<String> = entries
+		.iter()
+		.map(|entry| entry.project_id.to_string())
+		.collect();
+	project_ids.sort();
+	project_ids.dedup();
+	let projects: HashMap<String, ProjectDetail> = provider
+		.get_projects(&project_ids, ctx)
+		.await?
+		.into_iter()
+		.map(|project| (project.id.clone(), project))
+		.collect();
+
+	Ok((versions, projects))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(project_id = %project.id, version_id = %version.version_id))]
+async fn install_curseforge_file(
+	project: &ProjectDetail,
+	version: &VersionDetail,
+	cluster: &ClusterRow,
+	ctx: &ContentCtx,
+) -> ContentResult<()> {
+	let file = version.primary_file().ok_or(PackageError::NoPrimaryFile)?;
+	if file.url.is_empty() {
+		return Err(PackageError::NotDistributable(file.file_name.clone()).into());
+	}
+
+	let artifact = PackageStore::download_and_cache(
+		ProviderId::CurseForge,
+		project,
+		version,
+		false,
+		None,
+		ctx,
+	)
+	.await?;
+	PackageStore::link_artifact(&artifact, cluster, None, ctx).await?;
+
+	Ok(())
+}
+
+/// The CurseForge app refuses a manifest whose loader id has no version
+fn curseforge_mod_loaders(
+	cluster: &ClusterRow,
+	loader_version: Option<String>,
+	report: &mut ModpackExportReport,
+) -> Vec<CurseForgeModLoader> {
+	let loader = GameLoader::from_repr(cluster.mc_loader as u8).unwrap_or_default();
+	let Some(key) = curseforge_loader_key(loader) else {
+		if loader.is_modded() {
+			report
+				.warnings
+				.push(format!("CurseForge has no {loader} loader; the pack will not name one"));
+		}
+		return Vec::new();
+	};
+
+	match loader_version {
+		Some(version) => vec![CurseForgeModLoader {
+			id: format!("{key}-{version}"),
+			primary: true,
+		}],
+		None => {
+			report.warnings.push(format!(
+				"no {loader} version is pinned; the pack will not name a loader"
+			));
+			Vec::new()
+		}
+	}
+}
+
+const fn curseforge_loader_key(loader: GameLoader) -> Option<&'static str> {
+	match loader {
+		GameLoader::Vanilla | GameLoader::LegacyFabric => None,
+		GameLoader::Forge => Some("forge"),
+		GameLoader::NeoForge => Some("neoforge"),
+		GameLoader::Quilt => Some("quilt"),
+		GameLoader::Fabric => Some("fabric"),
+	}
+}
+
+/// `forge-47.2.0` `neoforge-21.1.77` `fabric-0.16.9` `quilt-0.27.1`
+fn parse_loader_id(id: &str) -> Option<(GameLoader, Option<String>)> {
+	let (name, version) = match id.split_once('-') {
+		Some((name, version)) => (name, Some(version.to_string())),
+		None => (id, None),
+	};
+	let loader: GameLoader = name.parse().ok()?;
+	loader
+		.is_modded()
+		.then(|| (loader, version.filter(|version| !version.is_empty())))
+}
+
+#[tracing::instrument(level = "debug", skip(options, ctx))]
+pub async fn export_cluster_curseforge(
+	cluster_id: i64,
+	dest: &Path,
+	options: &ModpackExportOptions,
+	ctx: &ContentCtx,
+) -> ContentResult<ModpackExportReport> {
+	CurseForgeExporter::export_cluster(cluster_id, dest, options, ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::packages::modpack::test_support::cluster;
+
+	#[test]
+	fn loader_ids_split_into_loader_and_version() {
+		assert_eq!(
+			parse_loader_id("forge-47.2.0"),
+			Some((GameLoader::Forge, Some(String::from("47.2.0"))))
+		);
+		assert_eq!(
+			parse_loader_id("neoforge-21.1.77"),
+			Some((GameLoader::NeoForge, Some(String::from("21.1.77"))))
+		);
+		assert_eq!(
+			parse_loader_id("fabric-0.16.9"),
+			Some((GameLoader::Fabric, Some(String::from("0.16.9"))))
+		);
+		assert_eq!(parse_loader_id("quilt"), Some((GameLoader::Quilt, None)));
+		assert_eq!(parse_loader_id("liteloader-1.0"), None);
+	}
+
+	#[test]
+	fn manifest_reads_the_curseforge_field_names() {
+		let raw = serde_json::json!({
+			"minecraft": {
+				"version": "1.20.1",
+				"modLoaders": [
+					{ "id": "fabric-0.15.0", "primary": false },
+					{ "id": "forge-47.2.0", "primary": true }
+				]
+			},
+			"manifestType": "minecraftModpack",
+			"manifestVersion": 1,
+			"name": "Pack",
+			"files": [
+				{ "projectID": 238222, "fileID": 4712866, "required": true },
+				{ "projectID": 32274, "fileID": 4571247, "required": false }
+			],
+			"overrides": "overrides"
+		});
+
+		let manifest: CurseForgeManifest = serde_json::from_value(raw).unwrap();
+
+		assert_eq!(manifest.manifest_type, CURSEFORGE_MANIFEST_TYPE);
+		assert_eq!(manifest.files[0].project_id, 238222);
+		assert_eq!(manifest.files[0].file_id, 4712866);
+		assert!(!manifest.files[1].required);
+		assert_eq!(
+			manifest.loader(),
+			Some((GameLoader::Forge, Some(String::from("47.2.0"))))
+		);
+	}
+
+	#[test]
+	fn missing_fields_fall_back_to_the_exporter_defaults() {
+		let raw = serde_json::json!({
+			"minecraft": { "version": "1.20.1", "modLoaders": [{ "id": "fabric-0.15.0" }] },
+			"manifestType": "minecraftModpack",
+			"files": [{ "projectID": 1, "fileID": 2 }]
+		});
+
+		let manifest: CurseForgeManifest = serde_json::from_value(raw).unwrap();
+
+		assert_eq!(manifest.overrides, OVERRIDES_DIR);
+		assert!(manifest.files[0].required);
+		assert_eq!(
+			manifest.loader(),
+			Some((GameLoader::Fabric, Some(String::from("0.15.0"))))
+		);
+	}
+
+	#[test]
+	fn exported_loader_ids_parse_back() {
+		let mut report = ModpackExportReport::default();
+		let forge = cluster(GameLoader::Forge, Some("47.2.0"));
+
+		let loaders = curseforge_mod_loaders(&forge, forge.mc_loader_version.clone(), &mut report);
+
+		assert_eq!(loaders.len(), 1);
+		assert!(loaders[0].primary);
+		assert_eq!(
+			parse_loader_id(&loaders[0].id),
+			Some((GameLoader::Forge, Some(String::from("47.2.0"))))
+		);
+		assert!(report.warnings.is_empty());
+	}
+
+	#[test]
+	fn loaders_curseforge_cannot_name_are_warned_about() {
+		let mut report = ModpackExportReport::default();
+
+		let loaders = curseforge_mod_loaders(
+			&cluster(GameLoader::LegacyFabric, Some("0.16.9")),
+			Some(String::from("0.16.9")),
+			&mut report,
+		);
+		assert!(loaders.is_empty());
+		assert_eq!(report.warnings.len(), 1);
+
+		let vanilla = curseforge_mod_loaders(&cluster(GameLoader::Vanilla, None), None, &mut report);
+		assert!(vanilla.is_empty());
+		assert_eq!(report.warnings.len(), 1, "vanilla needs no loader");
+	}
+
+	#[test]
+	fn manifest_writes_the_curseforge_field_names() {
+		let manifest = CurseForgeManifest {
+			minecraft: CurseForgeMinecraft {
+				version: String::from("1.20.1"),
+				mod_loaders: vec![CurseForgeModLoader {
+					id: String::from("forge-47.2.0"),
+					primary: true,
+				}],
+			},
+			manifest_type: String::from(CURSEFORGE_MANIFEST_TYPE),
+			manifest_version: CURSEFORGE_MANIFEST_VERSION,
+			name: String::from("Pack"),
+			version: String::from("1.0.0"),
+			author: String::new(),
+			files: vec![CurseForgeFileEntry {
+				project_id: 238222,
+				file_id: 4712866,
+				required: true,
+			}],
+			overrides: String::from(OVERRIDES_DIR),
+		};
+
+		let json = serde_json::to_value(&manifest).unwrap();
+
+		assert_eq!(json["manifestType"], "minecraftModpack");
+		assert_eq!(json["manifestVersion"], 1);
+		assert_eq!(json["minecraft"]["modLoaders"][0]["id"], "forge-47.2.0");
+		assert_eq!(json["files"][0]["projectID"], 238222);
+		assert_eq!(json["files"][0]["fileID"], 4712866);
+		assert_eq!(json["overrides"], "overrides");
+	}
+}
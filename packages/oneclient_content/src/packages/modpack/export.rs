@@ -0,0 +1,309 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use oneclient_common::paths;
+use oneclient_db::dao::artifact as artifact_dao;
+use oneclient_db::models::ClusterRow;
+
+use crate::ctx::ContentCtx;
+use crate::error::ContentResult;
+use crate::packages::store::{self, PackageStore, manifest};
+use crate::packages::types::LinkedArtifactInfo;
+
+pub(crate) const OVERRIDES_DIR: &str = "overrides";
+
+#[derive(Debug, Clone)]
+pub struct ModpackExportOptions {
+	/// Defaults to the cluster name
+	pub name: Option<String>,
+	pub version: String,
+	pub summary: Option<String>,
+	pub author: Option<String>,
+	/// A cluster with no pinned loader version resolves "latest" at launch
+	/// which a pack cannot express so the caller supplies the resolved one
+	pub loader_version: Option<String>,
+	/// Relative to the game directory a folder is taken whole except in the
+	/// shared `.minecraft` where only files are
+	pub include_paths: Vec<String>,
+}
+
+impl Default for ModpackExportOptions {
+	fn default() -> Self {
+		Self {
+			name: None,
+			version: String::from("1.0.0"),
+			summary: None,
+			author: None,
+			loader_version: None,
+			include_paths: vec![String::from("config")],
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModpackExportReport {
+	/// Pack paths written as a provider reference
+	pub referenced: Vec<String>,
+	/// Pack paths whose bytes went into `overrides/`
+	pub overrides: Vec<String>,
+	pub warnings: Vec<String>,
+}
+
+/// An enabled cluster link with the cached file behind it
+pub(crate) struct ExportedArtifact {
+	/// Slash-separated relative to the game directory e.g. `mods/sodium.jar`
+	pub path: String,
+	pub info: LinkedArtifactInfo,
+	pub source: PathBuf,
+}
+
+impl ModpackExportOptions {
+	pub(crate) fn pack_name(&self, cluster: &ClusterRow) -> String {
+		self.name.clone().unwrap_or_else(|| cluster.name.clone())
+	}
+
+	pub(crate) fn resolved_loader_version(&self, cluster: &ClusterRow) -> Option<String> {
+		self.loader_version
+			.clone()
+			.or_else(|| cluster.mc_loader_version.clone())
+	}
+}
+
+/// Disabled links are left out a pack has no way to ship a file switched off
+#[tracing::instrument(level = "debug", skip(ctx))]
+pub(crate) async fn exported_artifacts(
+	cluster_id: i64,
+	report: &mut ModpackExportReport,
+	ctx: &ContentCtx,
+) -> ContentResult<Vec<ExportedArtifact>> {
+	let mut out = Vec::new();
+
+	for info in PackageStore::list_linked_artifacts(cluster_id, ctx).await? {
+		let path = manifest::entry_path(info.content_type.folder_name(), &info.cluster_file_name);
+		if !info.enabled {
+			report
+				.warnings
+				.push(format!("{path} is disabled and was left out"));
+			continue;
+		}
+
+		let Some(row) = artifact_dao::get_artifact_by_hash(&ctx.db, &info.hash).await? else {
+			continue;
+		};
+		let source = store::artifact_absolute_path(&row.path)?;
+		if !source.exists() {
+			report
+				.warnings
+				.push(format!("{path} is missing from the package cache and was left out"));
+			continue;
+		}
+
+		out.push(ExportedArtifact { path, info, source });
+	}
+
+	Ok(out)
+}
+
+/// Links the launcher materialized are skipped they are already in the pack
+/// as artifacts and in the shared directory may belong to another cluster
+#[tracing::instrument(level = "debug", skip(cluster, include_paths), fields(cluster_id = cluster.id))]
+pub(crate) async fn override_files(
+	cluster: &ClusterRow,
+	include_paths: &[String],
+	report: &mut ModpackExportReport,
+) -> ContentResult<Vec<(String, PathBuf)>> {
+	let game_dir = paths::cluster_game_dir(&cluster.folder_name)?;
+	let shared = !paths::cluster_uses_dedicated_dir(&cluster.folder_name);
+	Ok(collect_overrides(&game_dir, shared, include_paths, report).await)
+}
+
+/// A folder in the shared `.minecraft` holds every other non-dedicated
+/// cluster's files and the player's own so there only files named one by one
+/// go in
+async fn collect_overrides(
+	game_dir: &Path,
+	shared: bool,
+	include_paths: &[String],
+	report: &mut ModpackExportReport,
+) -> Vec<(String, PathBuf)> {
+	let materialized: HashSet<String> = manifest::load(game_dir)
+		.await
+		.map(|loaded| loaded.entries.into_iter().map(|entry| entry.path).collect())
+		.unwrap_or_default();
+
+	let mut out = Vec::new();
+	for include in include_paths {
+		let Some(relative) = safe_relative(include) else {
+			report
+				.warnings
+				.push(format!("{include} is outside the game directory and was left out"));
+			continue;
+		};
+
+		let root = game_dir.join(&relative);
+		let files = if root.is_dir() && shared {
+			report.warnings.push(format!(
+				"{include} is a folder in the shared .minecraft and was left out; pick the files in it to include"
+			));
+			continue;
+		} else if root.is_dir() {
+			store::walk_files(&root).await
+		} else if root.is_file() {
+			vec![root]
+		} else {
+			continue;
+		};
+
+		for file in files {
+			let Some(pack_path) = pack_path(game_dir, &file) else {
+				continue;
+			};
+			if materialized.contains(&pack_path) || pack_path == manifest::MANIFEST_NAME {
+				continue;
+			}
+			out.push((pack_path, file));
+		}
+	}
+
+	out.sort();
+	out.dedup_by(|a, b| a.0 == b.0);
+	out
+}
+
+/// Written beside `dest` and renamed so a failed export never leaves a
+/// truncated archive under the name the user picked
+#[tracing::instrument(level = "debug", skip(index_bytes, overrides))]
+pub(crate) async fn write_pack_archive(
+	dest: &Path,
+	index_name: &str,
+	index_bytes: Vec<u8>,
+	overrides: &[(String, PathBuf)],
+) -> ContentResult<()> {
+	use async_zip::{Compression, ZipEntryBuilder};
+
+	if let Some(parent) = dest.parent() {
+		polyio::create_dir_all(parent).await?;
+	}
+
+	let partial = dest.with_extension("partial");
+	let file = tokio::fs::File::create(&partial).await?;
+	let mut writer = async_zip::tokio::write::ZipFileWriter::with_tokio(file);
+
+	let builder = ZipEntryBuilder::new(index_name.to_string().into(), Compression::Deflate);
+	writer
+		.write_entry_whole(builder, &index_bytes)
+		.await
+		.map_err(polyio::IOError::from)?;
+
+	for (pack_path, source) in overrides {
+		let data = polyio::read(source).await?;
+		let name = format!("{OVERRIDES_DIR}/{pack_path}");
+		let builder = ZipEntryBuilder::new(name.into(), Compression::Deflate);
+		writer
+			.write_entry_whole(builder, &data)
+			.await
+			.map_err(polyio::IOError::from)?;
+	}
+
+	writer.close().await.map_err(polyio::IOError::from)?;
+	polyio::rename(&partial, dest).await?;
+
+	Ok(())
+}
+
+/// `None` for anything that climbs out or is absolute
+pub(super) fn safe_relative(path: &str) -> Option<PathBuf> {
+	let path = Path::new(path);
+	if path.as_os_str().is_empty() {
+		return None;
+	}
+
+	path.components()
+		.all(|component| matches!(component, Component::Normal(_)))
+		.then(|| path.to_path_buf())
+}
+
+fn pack_path(game_dir: &Path, file: &Path) -> Option<String> {
+	let relative = file.strip_prefix(game_dir).ok()?;
+	let parts: Vec<&str> = relative
+		.components()
+		.map(|component| component.as_os_str().to_str())
+		.collect::<Option<_>>()?;
+	Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn include_paths_cannot_escape_the_game_dir() {
+		assert_eq!(safe_relative("config"), Some(PathBuf::from("config")));
+		assert_eq!(
+			safe_relative("config/sodium.json"),
+			Some(PathBuf::from("config/sodium.json"))
+		);
+		assert_eq!(safe_relative("../saves"), None);
+		assert_eq!(safe_relative("config/../../secret"), None);
+		assert_eq!(safe_relative("/etc/passwd"), None);
+		assert_eq!(safe_relative(""), None);
+	}
+
+	#[test]
+	fn pack_paths_are_slash_separated() {
+		let game_dir = Path::new("game");
+		let file = game_dir.join("config").join("nested").join("a.toml");
+
+		assert_eq!(pack_path(game_dir, &file).as_deref(), Some("config/nested/a.toml"));
+		assert_eq!(pack_path(game_dir, Path::new("elsewhere/a.toml")), None);
+	}
+
+	#[tokio::test]
+	async fn shared_dir_folders_need_their_files_picked() {
+		let root = polyio::testing::ScratchDir::new("pack_overrides_shared");
+		let game_dir = root.join("game");
+		polyio::create_dir_all(game_dir.join("config")).await.unwrap();
+		polyio::write(game_dir.join("config/other.json"), b"{}".as_slice())
+			.await
+			.unwrap();
+		polyio::write(game_dir.join("options.txt"), b"fov:90".as_slice())
+			.await
+			.unwrap();
+		let include = [String::from("config"), String::from("options.txt")];
+
+		let mut report = ModpackExportReport::default();
+		let shared = collect_overrides(&game_dir, true, &include, &mut report).await;
+		let paths: Vec<&str> = shared.iter().map(|(path, _)| path.as_str()).collect();
+		assert_eq!(paths, ["options.txt"]);
+		assert_eq!(report.warnings.len(), 1);
+
+		let mut report = ModpackExportReport::default();
+		let dedicated = collect_overrides(&game_dir, false, &include, &mut report).await;
+		let paths: Vec<&str> = dedicated.iter().map(|(path, _)| path.as_str()).collect();
+		assert_eq!(paths, ["config/other.json", "options.txt"]);
+		assert!(report.warnings.is_empty());
+	}
+
+	#[tokio::test]
+	async fn archive_holds_the_index_and_overrides() {
+		let root = polyio::testing::ScratchDir::new("pack_archive");
+		let source = root.join("options.txt");
+		polyio::write(&source, b"fov:90".as_slice()).await.unwrap();
+
+		let dest = root.join("out").join("pack.mrpack");
+		write_pack_archive(
+			&dest,
+			"modrinth.index.json",
+			b"{}".to_vec(),
+			&[(String::from("options.txt"), source)],
+		)
+		.await
+		.unwrap();
+
+		let entries = polyio::read_zip_file_entries(&dest, |_| true).await.unwrap();
+		let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+		assert_eq!(names, ["modrinth.index.json", "overrides/options.txt"]);
+		assert_eq!(entries[1].1, b"fov:90");
+		assert!(!dest.with_extension("partial").exists());
+	}
+}
@@ -1,12 +1,47 @@
+mod curseforge;
+mod export;
 mod mrpack;
+mod update;
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use futures_lite::AsyncReadExt;
 
-pub use mrpack::{install_mrpack_to_cluster, MrpackInstaller};
+pub use curseforge::{export_cluster_curseforge, CurseForgeExporter, CurseForgeInstaller, CURSEFORGE_MANIFEST};
+pub use export::{ModpackExportOptions, ModpackExportReport};
+pub use mrpack::{export_cluster_mrpack, install_mrpack_to_cluster, MrpackExporter, MrpackInstaller, MRPACK_INDEX};
+
+pub use update::{apply_modpack_update, check_modpack_update, diff_modpack_update, ModpackDiff, ModpackUpdate};
 
 use crate::ctx::ContentCtx;
 use crate::error::ContentResult;
+use crate::packages::error::PackageError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModpackFormat {
+	Mrpack,
+	CurseForge,
+}
+
+/// Decided by the index file inside not the extension CurseForge packs are
+/// plain `.zip` and people rename `.mrpack` files all the time
+#[tracing::instrument(level = "debug", skip(archive_path))]
+pub async fn detect_modpack_format(archive_path: impl AsRef<Path>) -> ContentResult<ModpackFormat> {
+	let entries = polyio::read_zip_file_entries(archive_path, |name| {
+		name == MRPACK_INDEX || name == CURSEFORGE_MANIFEST
+	})
+	.await?;
+
+	let has = |index: &str| entries.iter().any(|(name, _)| name == index);
+	if has(MRPACK_INDEX) {
+		Ok(ModpackFormat::Mrpack)
+	} else if has(CURSEFORGE_MANIFEST) {
+		Ok(ModpackFormat::CurseForge)
+	} else {
+		Err(PackageError::UnsupportedModpackFormat.into())
+	}
+}
 
 #[tracing::instrument(level = "debug", skip(archive_path, ctx))]
 pub async fn install_modpack_archive(
@@ -14,5 +49,113 @@ pub async fn install_modpack_archive(
 	cluster_id: i64,
 	ctx: &ContentCtx,
 ) -> ContentResult<()> {
-	mrpack::install_mrpack_to_cluster(archive_path.as_ref().to_path_buf(), cluster_id, ctx).await
+	let archive_path = archive_path.as_ref().to_path_buf();
+
+	match detect_modpack_format(&archive_path).await? {
+		ModpackFormat::Mrpack => {
+			mrpack::install_mrpack_to_cluster(archive_path, cluster_id, ctx).await
+		}
+		ModpackFormat::CurseForge => {
+			CurseForgeInstaller::install_archive(archive_path, cluster_id, ctx).await
+		}
+	}
+}
+
+/// Entries that would land outside `dest` are skipped and so is any `keep`
+/// turns down by its path inside the folder
+async fn extract_overrides(
+	bytes: Vec<u8>,
+	folder: &str,
+	dest: &Path,
+	keep: impl Fn(&str) -> bool,
+) -> ContentResult<()> {
+	let prefix = format!("{}/", folder.trim_end_matches('/'));
+
+	polyio::read_zip_entries_bytes(bytes, async |_, entry, reader| {
+		let name = entry.filename().as_str().map_err(io_err)?;
+		let Some((rest, relative)) = override_path(name, &prefix) else {
+			return Ok(());
+		};
+		if !keep(rest) {
+			return Ok(());
+		}
+
+		let target = dest.join(relative);
+		if let Some(parent) = target.parent() {
+			polyio::create_dir_all(parent).await.map_err(io_err)?;
+		}
+
+		let mut file_bytes = Vec::new();
+		reader.read_to_end(&mut file_bytes).await.map_err(io_err)?;
+		polyio::write(&target, &file_bytes).await.map_err(io_err)?;
+		Ok(())
+	})
+	.await?;
+
+	Ok(())
+}
+
+/// SHA-1 of every override keyed by its path inside the folder
+async fn override_hashes(bytes: Vec<u8>, folder: &str) -> ContentResult<BTreeMap<String, String>> {
+	let prefix = format!("{}/", folder.trim_end_matches('/'));
+	let mut hashes = BTreeMap::new();
+
+	polyio::read_zip_entries_bytes(bytes, async |_, entry, reader| {
+		let name = entry.filename().as_str().map_err(io_err)?;
+		let Some((rest, _)) = override_path(name, &prefix) else {
+			return Ok(());
+		};
+		let rest = rest.to_string();
+
+		let mut file_bytes = Vec::new();
+		reader.read_to_end(&mut file_bytes).await.map_err(io_err)?;
+		hashes.insert(rest, polyio::sha1_bytes(&file_bytes));
+		Ok(())
+	})
+	.await?;
+
+	Ok(hashes)
+}
+
+/// The path inside the overrides folder and where it lands under the cluster
+fn override_path<'a>(name: &'a str, prefix: &str) -> Option<(&'a str, PathBuf)> {
+	let rest = name.strip_prefix(prefix)?;
+	if rest.is_empty() || name.ends_with('/') {
+		return None;
+	}
+	let Some(relative) = export::safe_relative(rest) else {
+		tracing::warn!(entry = %name, "skipping override outside the cluster");
+		return None;
+	};
+	Some((rest, relative))
+}
+
+fn io_err(err: impl std::error::Error + Send + Sync + 'static) -> polyio::IOError {
+	polyio::IOError::PathIOError {
+		source: std::io::Error::other(err),
+		path: String::new(),
+	}
+}
+
+#[cfg(test)]
+mod test_support {
+	use oneclient_common::domain::GameLoader;
+	use oneclient_db::models::ClusterRow;
+
+	pub(super) fn cluster(loader: GameLoader, loader_version: Option<&str>) -> ClusterRow {
+		ClusterRow {
+			id: 1,
+			name: "c".into(),
+			folder_name: "c".into(),
+			setting_profile_name: None,
+			mc_version: "1.21.4".into(),
+			mc_loader: loader as i64,
+			stage: 0,
+			mc_loader_version: loader_version.map(Into::into),
+			created_at: None,
+			last_played: None,
+			overall_played: None,
+			linked_modpack_hash: None,
+		}
+	}
 }
@@ -1,43 +1,104 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use futures_lite::AsyncReadExt;
-use oneclient_db::models::ClusterRow;
-use serde::Deserialize;
+use oneclient_db::dao::cluster as cluster_dao;
+use oneclient_db::models::{ArtifactRow, ClusterPatch, ClusterRow};
+use oneclient_events::{Choice, Prompt};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use oneclient_common::domain::ContentType;
+use oneclient_common::constants::MODRINTH_CDN_PREFIX;
+use oneclient_common::domain::{ContentType, GameLoader, ProviderId};
 use crate::packages::error::PackageError;
 use crate::packages::store::{self, PackageStore};
 use crate::packages::types::ExternalFile;
 use crate::ctx::ContentCtx;
 use crate::error::ContentResult;
 
+use super::export::{self, ModpackExportOptions, ModpackExportReport};
+use super::io_err;
+
+pub const MRPACK_INDEX: &str = "modrinth.index.json";
+const MRPACK_FORMAT_VERSION: u32 = 1;
+
 pub struct MrpackInstaller;
 
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct MrpackManifest {
+pub struct MrpackExporter;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MrpackManifest {
+    #[serde(default = "default_format_version")]
+    format_version: u32,
     #[serde(default)]
-    files: Vec<MrpackFileEntry>,
+    game: String,
+    #[serde(default)]
+    version_id: String,
     #[serde(default)]
-    dependencies: HashMap<String, String>,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    #[serde(default)]
+    pub(super) files: Vec<MrpackFileEntry>,
+    /// Ordered so the same cluster always exports byte-identical indexes
+    #[serde(default)]
+    pub(super) dependencies: BTreeMap<String, String>,
 }
 
-#[derive(Deserialize)]
-struct MrpackFileEntry {
-    path: String,
-    hashes: MrpackHashes,
+#[derive(Serialize, Deserialize)]
+pub(super) struct MrpackFileEntry {
+    pub(super) path: String,
+    pub(super) hashes: MrpackHashes,
+    /// Absent means required on both sides
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    env: Option<MrpackEnv>,
     #[serde(default)]
     downloads: Vec<String>,
     #[serde(rename = "fileSize")]
     file_size: u64,
 }
 
-#[derive(Deserialize)]
-struct MrpackHashes {
-    sha1: String,
+#[derive(Serialize, Deserialize)]
+pub(super) struct MrpackHashes {
+    pub(super) sha1: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha512: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct MrpackEnv {
+    #[serde(default)]
+    client: MrpackEnvSupport,
+    #[serde(default)]
+    server: MrpackEnvSupport,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum MrpackEnvSupport {
+    #[default]
+    Required,
+    Optional,
+    Unsupported,
+}
+
+impl MrpackFileEntry {
+    pub(super) fn client_support(&self) -> MrpackEnvSupport {
+        self.env.as_ref().map_or(MrpackEnvSupport::Required, |env| env.client)
+    }
+
+    pub(super) fn file_name(&self) -> &str {
+        Path::new(&self.path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&self.path)
+    }
+}
+
+const fn default_format_version() -> u32 {
+    MRPACK_FORMAT_VERSION
 }
 
 impl MrpackInstaller {
@@ -58,7 +119,7 @@ impl MrpackInstaller {
         polyio::read_zip_entries_bytes(bytes.to_vec(), async |_, entry, reader| {
             let entry_name = entry.filename().as_str().map_err(io_err)?;
 
-            if entry_name == "modrinth.index.json" {
+            if entry_name == MRPACK_INDEX {
                 let mut buf = Vec::new();
                 reader.read_to_end(&mut buf).await.map_err(io_err)?;
                 manifest_bytes = Some(buf);
@@ -70,75 +131,281 @@ impl MrpackInstaller {
         let manifest_bytes = manifest_bytes.ok_or(PackageError::UnsupportedModpackFormat)?;
         let manifest: MrpackManifest = serde_json::from_slice(&manifest_bytes)?;
 
-        let mut failed = 0u64;
-        let total = manifest.files.len() as u64;
-        let progress_id = Uuid::new_v4();
-
-        for (index, entry) in manifest.files.into_iter().enumerate() {
-            ctx.events.progress(
-                progress_id,
-                "Installing Modpack Files",
-                index as u64,
-                total,
-            );
-
-            let content_type = content_type_from_path(&entry.path);
-            let hash = entry.hashes.sha1.to_ascii_lowercase();
-            let file_name = Path::new(&entry.path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or(&entry.path)
-                .to_string();
-
-            let path_str = entry.path.clone();
-
-            if let Err(err) = install_mrpack_file(
-                entry,
-                content_type,
-                hash,
-                file_name,
-                &cluster,
-                ctx,
-            )
-            .await
-            {
-                failed += 1;
-                tracing::warn!(path = %path_str, error = %err, "modpack file install failed");
-            }
+        let files = client_files(manifest.files, ctx).await;
+        let total = files.len() as u64;
+        let failed = install_mrpack_entries(files, &cluster, ctx).await;
+
+        // Recorded even on a partial install the cluster still came from this
+        // pack and the update flow needs to know which version it was
+        let pack_hash = polyio::sha1_bytes(&bytes);
+        cluster_dao::update(
+            &ctx.db,
+            cluster.id,
+            &ClusterPatch {
+                linked_modpack_hash: Some(Some(pack_hash)),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        super::extract_overrides(bytes.to_vec(), export::OVERRIDES_DIR, &cluster_root, |_| true)
+            .await?;
+
+        if failed > 0 {
+            tracing::error!(failed, total, "modpack install completed with failures");
+            return Err(PackageError::PartialModpackInstall { failed, total }.into());
         }
 
-        ctx.events
-            .progress(progress_id, "Installing Modpack Files", total, total);
+        tracing::info!(total, "modpack install complete");
+        Ok(())
+    }
+}
 
-        polyio::read_zip_entries_bytes(bytes.to_vec(), async |_, entry, reader| {
-            let name = entry.filename().as_str().map_err(io_err)?;
+/// Returns how many entries failed each is logged where it happens
+pub(super) async fn install_mrpack_entries(
+    files: Vec<MrpackFileEntry>,
+    cluster: &ClusterRow,
+    ctx: &ContentCtx,
+) -> u64 {
+    let mut failed = 0u64;
+    let total = files.len() as u64;
+    let progress_id = Uuid::new_v4();
+
+    for (index, entry) in files.into_iter().enumerate() {
+        ctx.events.progress(
+            progress_id,
+            "Installing Modpack Files",
+            index as u64,
+            total,
+        );
+
+        let content_type = content_type_from_path(&entry.path);
+        let hash = entry.hashes.sha1.to_ascii_lowercase();
+        let file_name = entry.file_name().to_string();
+
+        let path_str = entry.path.clone();
+
+        if let Err(err) = install_mrpack_file(
+            entry,
+            content_type,
+            hash,
+            file_name,
+            cluster,
+            ctx,
+        )
+        .await
+        {
+            failed += 1;
+            tracing::warn!(path = %path_str, error = %err, "modpack file install failed");
+        }
+    }
+
+    ctx.events
+        .progress(progress_id, "Installing Modpack Files", total, total);
 
-            let Some(rest) = name.strip_prefix("overrides/") else {
-                return Ok(());
+    failed
+}
+
+#[tracing::instrument(level = "debug")]
+pub(super) async fn read_mrpack_index(archive_path: &Path) -> ContentResult<MrpackManifest> {
+    let entries = polyio::read_zip_file_entries(archive_path, |name| name == MRPACK_INDEX).await?;
+    let (_, index) = entries
+        .into_iter()
+        .next()
+        .ok_or(PackageError::UnsupportedModpackFormat)?;
+
+    Ok(serde_json::from_slice(&index)?)
+}
+
+impl MrpackExporter {
+    /// Modrinth-hosted artifacts become `files` entries everything else is
+    /// bundled under `overrides/` since the format only accepts downloads
+    /// from an allow-listed set of hosts
+    #[tracing::instrument(skip(options, ctx))]
+    pub async fn export_cluster(
+        cluster_id: i64,
+        dest: &Path,
+        options: &ModpackExportOptions,
+        ctx: &ContentCtx,
+    ) -> ContentResult<ModpackExportReport> {
+        tracing::info!("exporting cluster as mrpack");
+        let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
+        let mut report = ModpackExportReport::default();
+
+        let dependencies = mrpack_dependencies(
+            &cluster,
+            options.resolved_loader_version(&cluster),
+            &mut report,
+        );
+
+        let mut files = Vec::new();
+        let mut overrides = Vec::new();
+
+        for artifact in export::exported_artifacts(cluster_id, &mut report, ctx).await? {
+            let remote = match (
+                artifact.info.provider,
+                &artifact.info.project_id,
+                &artifact.info.version_id,
+            ) {
+                (Some(ProviderId::Modrinth), Some(project_id), Some(version_id)) => {
+                    Some(modrinth_download_url(project_id, version_id, &artifact.info.file_name)?)
+                }
+                _ => None,
             };
-            if rest.is_empty() || name.ends_with('/') {
-                return Ok(());
-            }
 
-            let dest = cluster_root.join(rest);
-            if let Some(parent) = dest.parent() {
-                polyio::create_dir_all(parent).await.map_err(io_err)?;
-            }
+            let Some(url) = remote else {
+                if artifact.info.provider == Some(ProviderId::CurseForge) {
+                    report.warnings.push(format!(
+                        "{} is a CurseForge file; Modrinth packs cannot link it so it was bundled",
+                        artifact.path
+                    ));
+                }
+                report.overrides.push(artifact.path.clone());
+                overrides.push((artifact.path, artifact.source));
+                continue;
+            };
 
-            let mut file_bytes = Vec::new();
-            reader.read_to_end(&mut file_bytes).await.map_err(io_err)?;
-            polyio::write(&dest, &file_bytes).await.map_err(io_err)?;
-            Ok(())
-        })
+            let sha512 =
+                polyio::checksum_file(&artifact.source, polyio::ChecksumAlgorithm::Sha512).await?;
+            let file_size = polyio::stat(&artifact.source).await?.len();
+
+            report.referenced.push(artifact.path.clone());
+            files.push(MrpackFileEntry {
+                path: artifact.path,
+                hashes: MrpackHashes {
+                    sha1: artifact.info.hash,
+                    sha512: Some(sha512),
+                },
+                env: None,
+                downloads: vec![url],
+                file_size,
+            });
+        }
+
+        for (pack_path, source) in
+            export::override_files(&cluster, &options.include_paths, &mut report).await?
+        {
+            report.overrides.push(pack_path.clone());
+            overrides.push((pack_path, source));
+        }
+
+        let index = MrpackManifest {
+            format_version: MRPACK_FORMAT_VERSION,
+            game: String::from("minecraft"),
+            version_id: options.version.clone(),
+            name: options.pack_name(&cluster),
+            summary: options.summary.clone(),
+            files,
+            dependencies,
+        };
+
+        export::write_pack_archive(
+            dest,
+            MRPACK_INDEX,
+            serde_json::to_vec_pretty(&index)?,
+            &overrides,
+        )
         .await?;
 
-        if failed > 0 {
-            tracing::error!(failed, total, "modpack install completed with failures");
-            return Err(PackageError::PartialModpackInstall { failed, total }.into());
+        tracing::info!(
+            referenced = report.referenced.len(),
+            overrides = report.overrides.len(),
+            "mrpack export complete"
+        );
+        Ok(report)
+    }
+}
+
+fn mrpack_dependencies(
+    cluster: &ClusterRow,
+    loader_version: Option<String>,
+    report: &mut ModpackExportReport,
+) -> BTreeMap<String, String> {
+    let mut dependencies = BTreeMap::new();
+    dependencies.insert(String::from("minecraft"), cluster.mc_version.clone());
+
+    let loader = GameLoader::from_repr(cluster.mc_loader as u8).unwrap_or_default();
+    let Some(key) = mrpack_loader_key(loader) else {
+        return dependencies;
+    };
+
+    match loader_version {
+        Some(version) => {
+            dependencies.insert(key.to_string(), version);
         }
+        None => report.warnings.push(format!(
+            "no {loader} version is pinned; the pack will not name a loader version"
+        )),
+    }
 
-        tracing::info!(total, "modpack install complete");
-        Ok(())
+    dependencies
+}
+
+/// Legacy Fabric ships the regular Fabric loader so it shares the key
+const fn mrpack_loader_key(loader: GameLoader) -> Option<&'static str> {
+    match loader {
+        GameLoader::Vanilla => None,
+        GameLoader::Forge => Some("forge"),
+        GameLoader::NeoForge => Some("neoforge"),
+        GameLoader::Quilt => Some("quilt-loader"),
+        GameLoader::Fabric | GameLoader::LegacyFabric => Some("fabric-loader"),
+    }
+}
+
+/// The same layout [`crate::bundles`] parses back out of a download URL
+fn modrinth_download_url(
+    project_id: &str,
+    version_id: &str,
+    file_name: &str,
+) -> ContentResult<String> {
+    let mut url = url::Url::parse(MODRINTH_CDN_PREFIX)?;
+    url.path_segments_mut()
+        .map_err(|()| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+        .pop_if_empty()
+        .extend([project_id, "versions", version_id, file_name]);
+    Ok(url.into())
+}
+
+/// Server-only files are dropped and optional ones go to the user one prompt
+/// each before any download starts
+pub(super) async fn client_files(files: Vec<MrpackFileEntry>, ctx: &ContentCtx) -> Vec<MrpackFileEntry> {
+    let mut out = Vec::with_capacity(files.len());
+
+    for entry in files {
+        match entry.client_support() {
+            MrpackEnvSupport::Required => out.push(entry),
+            MrpackEnvSupport::Unsupported => {
+                tracing::debug!(path = %entry.path, "skipping server-only modpack file");
+            }
+            MrpackEnvSupport::Optional => {
+                if wants_optional_file(&entry.path, ctx).await {
+                    out.push(entry);
+                } else {
+                    tracing::info!(path = %entry.path, "optional modpack file declined");
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Dismissed or undeliverable counts as no a headless install takes only
+/// what the pack requires
+async fn wants_optional_file(path: &str, ctx: &ContentCtx) -> bool {
+    let prompt = Prompt::new(
+        "Optional modpack file",
+        format!("This modpack lists {path} as optional. Install it?"),
+    )
+    .option(Choice::primary("install", "Install"), ())
+    .dismiss("Skip");
+
+    match ctx.events.ask(prompt).await {
+        Ok(answer) => answer.is_some(),
+        Err(err) => {
+            tracing::warn!("could not ask about optional file {path}: {err}");
+            false
+        }
     }
 }
 
@@ -151,13 +418,31 @@ async fn install_mrpack_file(
     cluster: &ClusterRow,
     ctx: &ContentCtx,
 ) -> ContentResult<()> {
+    let sha512 = entry.hashes.sha512.clone();
+    let artifact = resolve_mrpack_file(entry, content_type, hash, &file_name, ctx).await?;
+
+    if let Some(expected) = sha512 {
+        verify_sha512(&artifact, &expected).await?;
+    }
+
+    PackageStore::link_artifact(&artifact, cluster, Some(&file_name), ctx).await?;
+
+    Ok(())
+}
+
+async fn resolve_mrpack_file(
+    entry: MrpackFileEntry,
+    content_type: ContentType,
+    hash: String,
+    file_name: &str,
+    ctx: &ContentCtx,
+) -> ContentResult<ArtifactRow> {
     if let Some(row) =
         oneclient_db::dao::artifact::get_artifact_by_hash(&ctx.db, &hash).await?
     {
         let path = store::artifact_absolute_path(&row.path)?;
         if path.exists() {
-            PackageStore::link_artifact(&row, cluster, Some(&file_name), ctx).await?;
-            return Ok(());
+            return Ok(row);
         }
     }
 
@@ -168,7 +453,7 @@ async fn install_mrpack_file(
         let project = provider
             .get_project(&version.project_id, ctx)
             .await?;
-        let artifact = PackageStore::download_and_cache(
+        return PackageStore::download_and_cache(
             provider_id,
             &project,
             &version,
@@ -176,9 +461,7 @@ async fn install_mrpack_file(
             None,
             ctx,
         )
-        .await?;
-        PackageStore::link_artifact(&artifact, cluster, Some(&file_name), ctx).await?;
-        return Ok(());
+        .await;
     }
 
     let url = entry
@@ -188,15 +471,26 @@ async fn install_mrpack_file(
         .ok_or(PackageError::NoPrimaryFile)?;
 
     let external = ExternalFile {
-        name: file_name.clone(),
+        name: file_name.to_string(),
         url,
         sha1: hash,
         size: entry.file_size,
         content_type,
     };
 
-    let artifact = store::download_external(&external, false, None, ctx).await?;
-    PackageStore::link_artifact(&artifact, cluster, Some(&file_name), ctx).await?;
+    store::download_external(&external, false, None, ctx).await
+}
+
+/// The store keys artifacts by sha1 so this is checked on top of whatever
+/// the download already verified
+async fn verify_sha512(artifact: &ArtifactRow, expected: &str) -> ContentResult<()> {
+    let path = store::artifact_absolute_path(&artifact.path)?;
+    let actual = polyio::checksum_file(&path, polyio::ChecksumAlgorithm::Sha512).await?;
+    let expected = polyio::normalize_hash(expected);
+
+    if actual != expected {
+        return Err(PackageError::HashMismatch { expected, actual }.into());
+    }
 
     Ok(())
 }
@@ -206,13 +500,6 @@ fn content_type_from_path(path: &str) -> ContentType {
     ContentType::from_folder_name(top).unwrap_or(ContentType::Mod)
 }
 
-fn io_err(err: impl std::error::Error + Send + Sync + 'static) -> polyio::IOError {
-    polyio::IOError::PathIOError {
-        source: std::io::Error::other(err),
-        path: String::new(),
-    }
-}
-
 #[tracing::instrument(level = "debug", skip(ctx))]
 pub async fn install_mrpack_to_cluster(
     archive_path: PathBuf,
@@ -221,3 +508,112 @@ pub async fn install_mrpack_to_cluster(
 ) -> ContentResult<()> {
     MrpackInstaller::install_archive(archive_path, cluster_id, ctx).await
 }
+
+#[tracing::instrument(level = "debug", skip(options, ctx))]
+pub async fn export_cluster_mrpack(
+    cluster_id: i64,
+    dest: &Path,
+    options: &ModpackExportOptions,
+    ctx: &ContentCtx,
+) -> ContentResult<ModpackExportReport> {
+    MrpackExporter::export_cluster(cluster_id, dest, options, ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages::modpack::test_support::cluster;
+
+    #[test]
+    fn download_url_matches_the_modrinth_cdn_layout() {
+        let url = modrinth_download_url("AANobbMI", "abc123", "sodium 0.6+mc1.21.jar").unwrap();
+
+        assert_eq!(
+            url,
+            "https://cdn.modrinth.com/data/AANobbMI/versions/abc123/sodium%200.6+mc1.21.jar"
+        );
+    }
+
+    #[test]
+    fn dependencies_name_the_loader_by_its_mrpack_key() {
+        let mut report = ModpackExportReport::default();
+        let fabric = cluster(GameLoader::Fabric, Some("0.16.9"));
+
+        let deps = mrpack_dependencies(&fabric, fabric.mc_loader_version.clone(), &mut report);
+
+        assert_eq!(deps.get("minecraft").map(String::as_str), Some("1.21.4"));
+        assert_eq!(deps.get("fabric-loader").map(String::as_str), Some("0.16.9"));
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn an_unpinned_loader_is_warned_about_not_guessed() {
+        let mut report = ModpackExportReport::default();
+
+        let deps = mrpack_dependencies(&cluster(GameLoader::NeoForge, None), None, &mut report);
+
+        assert_eq!(deps.len(), 1, "only minecraft: {deps:?}");
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn index_uses_the_spec_field_names() {
+        let index = MrpackManifest {
+            format_version: MRPACK_FORMAT_VERSION,
+            game: String::from("minecraft"),
+            version_id: String::from("1.0.0"),
+            name: String::from("Pack"),
+            summary: None,
+            files: vec![MrpackFileEntry {
+                path: String::from("mods/a.jar"),
+                hashes: MrpackHashes {
+                    sha1: String::from("aa"),
+                    sha512: Some(String::from("bb")),
+                },
+                env: None,
+                downloads: vec![String::from("https://cdn.modrinth.com/data/a/versions/b/a.jar")],
+                file_size: 3,
+            }],
+            dependencies: BTreeMap::from([(String::from("minecraft"), String::from("1.21.4"))]),
+        };
+
+        let json = serde_json::to_value(&index).unwrap();
+
+        assert_eq!(json["formatVersion"], 1);
+        assert_eq!(json["versionId"], "1.0.0");
+        assert_eq!(json["files"][0]["fileSize"], 3);
+        assert_eq!(json["files"][0]["hashes"]["sha512"], "bb");
+        assert!(json.get("summary").is_none());
+    }
+
+    #[test]
+    fn env_decides_what_the_client_installs() {
+        let raw = serde_json::json!([
+            { "path": "mods/a.jar", "hashes": { "sha1": "aa" }, "fileSize": 1 },
+            {
+                "path": "mods/b.jar",
+                "hashes": { "sha1": "bb" },
+                "env": { "client": "optional", "server": "required" },
+                "fileSize": 1
+            },
+            {
+                "path": "mods/c.jar",
+                "hashes": { "sha1": "cc" },
+                "env": { "client": "unsupported", "server": "required" },
+                "fileSize": 1
+            }
+        ]);
+
+        let files: Vec<MrpackFileEntry> = serde_json::from_value(raw).unwrap();
+        let support: Vec<_> = files.iter().map(MrpackFileEntry::client_support).collect();
+
+        assert_eq!(
+            support,
+            [
+                MrpackEnvSupport::Required,
+                MrpackEnvSupport::Optional,
+                MrpackEnvSupport::Unsupported,
+            ]
+        );
+    }
+}
\ No newline at end of file
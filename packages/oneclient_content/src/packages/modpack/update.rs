@@ -0,0 +1,483 @@
+//! Only files the previous pack version listed are ever unlinked
+//! anything the user added by hand has no entry in the old index and is left
+//! where it is
+//! Overrides are compared by hash against what the old pack wrote so a config
+//! the user edited is kept rather than overwritten or deleted
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use oneclient_common::domain::ProviderId;
+use oneclient_db::dao::artifact as artifact_dao;
+use oneclient_db::dao::cluster as cluster_dao;
+use oneclient_db::models::{ClusterPatch, ClusterRow};
+
+use crate::ctx::ContentCtx;
+use crate::error::{ContentError, ContentResult};
+use crate::packages::error::PackageError;
+use crate::packages::store::{self, PackageStore};
+use crate::packages::FileIdentity;
+use crate::packages::types::{VersionDetail, VersionSummary};
+use crate::packages::updates::unlink_superseded;
+
+use super::export::OVERRIDES_DIR;
+use super::mrpack::{self, MrpackEnvSupport, MrpackFileEntry, MrpackManifest};
+
+/// Modrinth returns every version in one response this only bounds the page
+const VERSION_LIMIT: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct ModpackUpdate {
+	pub cluster_id: i64,
+	pub project_id: String,
+	pub installed_version_id: String,
+	pub installed_version_name: String,
+	/// Newest first
+	pub newer: Vec<VersionSummary>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModpackDiff {
+	/// Pack paths e.g. `mods/sodium.jar`
+	pub added: Vec<String>,
+	pub removed: Vec<String>,
+	/// Same path different file
+	pub changed: Vec<String>,
+	/// `(installed, target)` when the pack moves to another loader version
+	pub loader_version: Option<(Option<String>, String)>,
+	/// Override paths e.g. `config/sodium-options.json`
+	pub overrides_added: Vec<String>,
+	pub overrides_removed: Vec<String>,
+	pub overrides_changed: Vec<String>,
+	/// Overrides the new version changes or drops that the user edited since
+	/// the pack wrote them left as they are
+	pub overrides_kept: Vec<String>,
+}
+
+impl ModpackDiff {
+	/// Kept overrides are left as they are so they are not a change
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty()
+			&& self.removed.is_empty()
+			&& self.changed.is_empty()
+			&& self.loader_version.is_none()
+			&& self.overrides_added.is_empty()
+			&& self.overrides_removed.is_empty()
+			&& self.overrides_changed.is_empty()
+	}
+}
+
+/// `Ok(None)` for a cluster with no linked pack or one Modrinth does not know
+#[tracing::instrument(level = "debug", skip(ctx))]
+pub async fn check_modpack_update(
+	cluster_id: i64,
+	ctx: &ContentCtx,
+) -> ContentResult<Option<ModpackUpdate>> {
+	let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
+	let Some(installed) = installed_version(&cluster, ctx).await? else {
+		return Ok(None);
+	};
+
+	let provider = ctx.providers.get(ProviderId::Modrinth)?;
+	let page = provider
+		.list_versions(&installed.project_id, None, None, 0, VERSION_LIMIT, ctx)
+		.await?;
+
+	let mut newer: Vec<VersionSummary> = page
+		.items
+		.into_iter()
+		.filter(|version| version.published > installed.published)
+		.collect();
+	newer.sort_by_key(|version| std::cmp::Reverse(version.published));
+
+	Ok(Some(ModpackUpdate {
+		cluster_id,
+		project_id: installed.project_id,
+		installed_version_id: installed.version_id,
+		installed_version_name: installed.version_number,
+		newer,
+	}))
+}
+
+#[tracing::instrument(level = "debug", skip(ctx))]
+pub async fn diff_modpack_update(
+	cluster_id: i64,
+	version_id: &str,
+	ctx: &ContentCtx,
+) -> ContentResult<ModpackDiff> {
+	let plan = UpdatePlan::load(cluster_id, version_id, ctx).await?;
+	Ok(plan.diff)
+}
+
+/// Download first unlink second so a failed download leaves the cluster as it was
+#[tracing::instrument(skip(ctx))]
+pub async fn apply_modpack_update(
+	cluster_id: i64,
+	version_id: &str,
+	ctx: &ContentCtx,
+) -> ContentResult<ModpackDiff> {
+	tracing::info!("updating cluster to a newer modpack version");
+	let UpdatePlan {
+		cluster,
+		cluster_root,
+		old,
+		new,
+		archive,
+		target_hash,
+		diff,
+	} = UpdatePlan::load(cluster_id, version_id, ctx).await?;
+
+	let mc_version = new.dependencies.get("minecraft");
+	if mc_version.is_some_and(|version| *version != cluster.mc_version) {
+		return Err(ContentError::InvalidData {
+			reason: format!(
+				"this pack version needs Minecraft {}; migrate the cluster first",
+				mc_version.map(String::as_str).unwrap_or_default()
+			),
+		});
+	}
+
+	let incoming: Vec<MrpackFileEntry> = new
+		.files
+		.into_iter()
+		.filter(|entry| diff.added.contains(&entry.path) || diff.changed.contains(&entry.path))
+		.collect();
+	let incoming = mrpack::client_files(incoming, ctx).await;
+	let total = incoming.len() as u64;
+	let failed = mrpack::install_mrpack_entries(incoming, &cluster, ctx).await;
+	if failed > 0 {
+		tracing::error!(failed, total, "modpack update stopped before dropping the old files");
+		return Err(PackageError::PartialModpackInstall { failed, total }.into());
+	}
+
+	let old_hashes: HashMap<&str, &str> = old
+		.files
+		.iter()
+		.map(|entry| (entry.path.as_str(), entry.hashes.sha1.as_str()))
+		.collect();
+	for path in diff.removed.iter().chain(&diff.changed) {
+		let Some(hash) = old_hashes.get(path.as_str()) else {
+			continue;
+		};
+		let hash = polyio::normalize_hash(hash);
+		if artifact_dao::get_cluster_artifact(&ctx.db, cluster.id, &hash)
+			.await?
+			.is_some()
+		{
+			unlink_superseded(cluster.id, &hash, ctx).await?;
+		}
+	}
+
+	if let Some((_, version)) = &diff.loader_version {
+		cluster_dao::set_loader(&ctx.db, cluster.id, cluster.mc_loader, Some(version)).await?;
+	}
+
+	let incoming: HashSet<&str> = diff
+		.overrides_added
+		.iter()
+		.chain(&diff.overrides_changed)
+		.map(String::as_str)
+		.collect();
+	super::extract_overrides(
+		polyio::read(&archive).await?,
+		OVERRIDES_DIR,
+		&cluster_root,
+		|path| incoming.contains(path),
+	)
+	.await?;
+	for path in &diff.overrides_removed {
+		if let Err(err) = polyio::remove_file(cluster_root.join(path)).await {
+			tracing::warn!(path, "could not remove a dropped override: {err}");
+		}
+	}
+
+	cluster_dao::update(
+		&ctx.db,
+		cluster.id,
+		&ClusterPatch {
+			linked_modpack_hash: Some(Some(target_hash)),
+			..Default::default()
+		},
+	)
+	.await?;
+
+	tracing::info!(
+		added = diff.added.len(),
+		removed = diff.removed.len(),
+		changed = diff.changed.len(),
+		overrides_kept = diff.overrides_kept.len(),
+		"modpack update complete"
+	);
+	Ok(diff)
+}
+
+struct UpdatePlan {
+	cluster: ClusterRow,
+	cluster_root: std::path::PathBuf,
+	old: MrpackManifest,
+	new: MrpackManifest,
+	archive: std::path::PathBuf,
+	target_hash: String,
+	diff: ModpackDiff,
+}
+
+impl UpdatePlan {
+	async fn load(cluster_id: i64, version_id: &str, ctx: &ContentCtx) -> ContentResult<Self> {
+		let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
+		let installed = installed_version(&cluster, ctx)
+			.await?
+			.ok_or(PackageError::NotModpack)?;
+
+		// Both packs go through the package cache a locally imported archive is
+		// fetched once by its Modrinth version and reused after that
+		let old_row = PackageStore::resolve_or_download(
+			ProviderId::Modrinth,
+			&installed.project_id,
+			&installed.version_id,
+			ctx,
+		)
+		.await?;
+		let new_row = PackageStore::resolve_or_download(
+			ProviderId::Modrinth,
+			&installed.project_id,
+			version_id,
+			ctx,
+		)
+		.await?;
+
+		let old_archive = store::artifact_absolute_path(&old_row.path)?;
+		let old = mrpack::read_mrpack_index(&old_archive).await?;
+		let archive = store::artifact_absolute_path(&new_row.path)?;
+		let new = mrpack::read_mrpack_index(&archive).await?;
+
+		let mut diff = diff_indexes(&old, &new);
+
+		let cluster_root = oneclient_common::paths::cluster_dir(&cluster.folder_name)?;
+		let old_overrides =
+			super::override_hashes(polyio::read(&old_archive).await?, OVERRIDES_DIR).await?;
+		let new_overrides =
+			super::override_hashes(polyio::read(&archive).await?, OVERRIDES_DIR).await?;
+		let on_disk =
+			disk_hashes(&cluster_root, old_overrides.keys().chain(new_overrides.keys())).await;
+		diff_overrides(&old_overrides, &new_overrides, &on_disk, &mut diff);
+		let target = new.dependencies.iter().find_map(|(key, version)| {
+			(key != "minecraft").then_some(version)
+		});
+		if let Some(target) = target
+			&& cluster.mc_loader_version.as_ref() != Some(target)
+		{
+			diff.loader_version = Some((cluster.mc_loader_version.clone(), target.clone()));
+		}
+
+		Ok(Self {
+			cluster,
+			cluster_root,
+			old,
+			new,
+			archive,
+			target_hash: new_row.hash,
+			diff,
+		})
+	}
+}
+
+async fn installed_version(
+	cluster: &ClusterRow,
+	ctx: &ContentCtx,
+) -> ContentResult<Option<VersionDetail>> {
+	let Some(hash) = &cluster.linked_modpack_hash else {
+		return Ok(None);
+	};
+
+	let provider = ctx.providers.get(ProviderId::Modrinth)?;
+	let identity = FileIdentity::from_sha1(hash);
+	let mut found = provider
+		.lookup_versions(std::slice::from_ref(&identity), ctx)
+		.await?;
+
+	if found.is_empty() {
+		tracing::debug!(hash, "linked modpack is not on modrinth");
+	}
+	Ok(found.remove(&identity.sha1))
+}
+
+/// Server-only entries are left out on both sides the client never had them
+fn diff_indexes(old: &MrpackManifest, new: &MrpackManifest) -> ModpackDiff {
+	let client = |index: &MrpackManifest| -> BTreeMap<String, String> {
+		index
+			.files
+			.iter()
+			.filter(|entry| entry.client_support() != MrpackEnvSupport::Unsupported)
+			.map(|entry| (entry.path.clone(), polyio::normalize_hash(&entry.hashes.sha1)))
+			.collect()
+	};
+	let old = client(old);
+	let new = client(new);
+
+	let mut diff = ModpackDiff::default();
+	for (path, hash) in &new {
+		match old.get(path) {
+			None => diff.added.push(path.clone()),
+			Some(previous) if previous != hash => diff.changed.push(path.clone()),
+			Some(_) => {}
+		}
+	}
+	diff.removed = old
+		.keys()
+		.filter(|path| !new.contains_key(*path))
+		.cloned()
+		.collect();
+
+	diff
+}
+
+/// Only the paths that exist a file that cannot be read counts as edited
+async fn disk_hashes<'a>(
+	root: &Path,
+	paths: impl Iterator<Item = &'a String>,
+) -> HashMap<String, String> {
+	let mut hashes = HashMap::new();
+	for path in paths {
+		let target = root.join(path);
+		if hashes.contains_key(path) || !polyio::try_exists(&target).await.unwrap_or(false) {
+			continue;
+		}
+		let hash = polyio::sha1_file(&target).await.unwrap_or_default();
+		hashes.insert(path.clone(), hash);
+	}
+	hashes
+}
+
+/// `on_disk` holds the hash of every override path present in the cluster
+fn diff_overrides(
+	old: &BTreeMap<String, String>,
+	new: &BTreeMap<String, String>,
+	on_disk: &HashMap<String, String>,
+	diff: &mut ModpackDiff,
+) {
+	for (path, hash) in new {
+		let disk = on_disk.get(path);
+		if disk == Some(hash) {
+			continue;
+		}
+		match old.get(path) {
+			// Whatever the user did to it the pack has nothing new to say
+			Some(previous) if previous == hash => {}
+			Some(previous) if disk.is_none_or(|disk| disk == previous) => {
+				diff.overrides_changed.push(path.clone());
+			}
+			None if disk.is_none() => diff.overrides_added.push(path.clone()),
+			// Edited since the pack wrote it or a file of the user's own
+			_ => diff.overrides_kept.push(path.clone()),
+		}
+	}
+
+	for (path, previous) in old {
+		if new.contains_key(path) {
+			continue;
+		}
+		match on_disk.get(path) {
+			None => {}
+			Some(disk) if disk == previous => diff.overrides_removed.push(path.clone()),
+			Some(_) => diff.overrides_kept.push(path.clone()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn index(files: serde_json::Value) -> MrpackManifest {
+		serde_json::from_value(serde_json::json!({ "files": files })).unwrap()
+	}
+
+	fn file(path: &str, sha1: &str) -> serde_json::Value {
+		serde_json::json!({ "path": path, "hashes": { "sha1": sha1 }, "fileSize": 1 })
+	}
+
+	#[test]
+	fn diff_sorts_files_into_added_removed_and_changed() {
+		let old = index(serde_json::json!([
+			file("mods/kept.jar", "aa"),
+			file("mods/bumped.jar", "bb"),
+			file("mods/dropped.jar", "cc"),
+		]));
+		let new = index(serde_json::json!([
+			file("mods/kept.jar", "AA"),
+			file("mods/bumped.jar", "b2"),
+			file("mods/fresh.jar", "dd"),
+		]));
+
+		let diff = diff_indexes(&old, &new);
+
+		assert_eq!(diff.added, ["mods/fresh.jar"]);
+		assert_eq!(diff.removed, ["mods/dropped.jar"]);
+		assert_eq!(diff.changed, ["mods/bumped.jar"], "hash case is not a change");
+	}
+
+	#[test]
+	fn overrides_the_user_edited_are_kept() {
+		let map = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+			pairs
+				.iter()
+				.map(|(path, hash)| (path.to_string(), hash.to_string()))
+				.collect()
+		};
+		let old = map(&[
+			("config/untouched.json", "a1"),
+			("config/edited.json", "b1"),
+			("config/dropped.json", "c1"),
+			("config/dropped-edited.json", "d1"),
+			("config/same.json", "e1"),
+		]);
+		let new = map(&[
+			("config/untouched.json", "a2"),
+			("config/edited.json", "b2"),
+			("config/same.json", "e1"),
+			("config/fresh.json", "f1"),
+			("config/clash.json", "g1"),
+		]);
+		let on_disk: HashMap<String, String> = map(&[
+			("config/untouched.json", "a1"),
+			("config/edited.json", "b-user"),
+			("config/dropped.json", "c1"),
+			("config/dropped-edited.json", "d-user"),
+			("config/same.json", "e-user"),
+			("config/clash.json", "g-user"),
+		])
+		.into_iter()
+		.collect();
+
+		let mut diff = ModpackDiff::default();
+		diff_overrides(&old, &new, &on_disk, &mut diff);
+
+		assert_eq!(diff.overrides_changed, ["config/untouched.json"]);
+		assert_eq!(diff.overrides_added, ["config/fresh.json"]);
+		assert_eq!(diff.overrides_removed, ["config/dropped.json"]);
+		assert_eq!(
+			diff.overrides_kept,
+			["config/clash.json", "config/edited.json", "config/dropped-edited.json"]
+		);
+	}
+
+	#[test]
+	fn server_only_files_never_show_up_in_the_diff() {
+		let old = index(serde_json::json!([]));
+		let mut server = file("mods/server.jar", "ee");
+		server["env"] = serde_json::json!({ "client": "unsupported", "server": "required" });
+		let new = index(serde_json::json!([server]));
+
+		assert!(diff_indexes(&old, &new).is_empty());
+	}
+
+	#[test]
+	fn kept_overrides_alone_are_no_change() {
+		let diff = ModpackDiff {
+			overrides_kept: vec!["config/edited.json".into()],
+			..ModpackDiff::default()
+		};
+		assert!(diff.is_empty());
+	}
+}
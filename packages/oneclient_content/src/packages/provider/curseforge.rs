@@ -460,7 +460,8 @@ struct CfFile {
     #[serde(default)]
     hashes: Vec<CfHash>,
     file_fingerprint: u32,
-    download_url: String,
+    /// Null when the author opted out of third-party distribution
+    download_url: Option<String>,
     file_length: u64,
     #[serde(default)]
     dependencies: Vec<CfDependency>,
@@ -648,7 +649,7 @@ impl From<CfFile> for VersionDetail {
             downloads: f.download_count,
             files: vec![VersionFile {
                 sha1,
-                url: f.download_url,
+                url: f.download_url.unwrap_or_default(),
                 file_name: f.file_name,
                 primary: true,
                 size: f.file_length,
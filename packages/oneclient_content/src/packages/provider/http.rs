@@ -43,6 +43,7 @@ pub async fn download_url(
     let request = reqwest::Request::new(Method::GET, url.parse()?);
     let options = ResponseOptions {
         notify: child.map(|c| ResponseNotifyOptions::grouped(c.clone())),
+        ..Default::default()
     };
     client
         .download_file(request, dest, options, &ctx.events)
@@ -2,10 +2,12 @@ mod curseforge;
 pub mod http;
 mod modrinth;
 mod registry;
+mod self_hosted;
 
 pub use curseforge::CurseForgeProvider;
 pub use modrinth::ModrinthProvider;
 pub use registry::PackageProviderRegistry;
+pub use self_hosted::{PackageIndex, SelfHostedProvider};
 
 use oneclient_common::domain::ProviderId;
 use crate::packages::file_identity::FileIdentity;
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 
-use super::{CurseForgeProvider, ModrinthProvider, PackageProvider};
+use super::{CurseForgeProvider, ModrinthProvider, PackageProvider, SelfHostedProvider};
 use crate::error::ContentResult;
 use oneclient_common::domain::ProviderId;
 use crate::packages::error::PackageError;
@@ -14,15 +14,20 @@ use crate::ctx::ContentCtx;
 #[derive(Clone)]
 pub struct PackageProviderRegistry {
     providers: HashMap<ProviderId, Arc<dyn PackageProvider>>,
+    /// Kept typed so settings can swap its indexes without rebuilding the registry
+    self_hosted: Arc<SelfHostedProvider>,
 }
 
 impl PackageProviderRegistry {
     pub fn new() -> Self {
+        let self_hosted = Arc::new(SelfHostedProvider::new());
         let mut registry = Self {
             providers: HashMap::new(),
+            self_hosted: Arc::clone(&self_hosted),
         };
         registry.register(Arc::new(ModrinthProvider));
         registry.register(Arc::new(CurseForgeProvider));
+        registry.register(self_hosted);
         registry
     }
 
@@ -37,10 +42,19 @@ impl PackageProviderRegistry {
             .ok_or(PackageError::ProviderNotRegistered(id).into())
     }
 
+    pub fn self_hosted(&self) -> &SelfHostedProvider {
+        &self.self_hosted
+    }
+
+    /// The self-hosted provider only joins hash lookups once it has an index
+    /// otherwise every lookup would fail on it
     pub fn remote_ids(&self) -> Vec<ProviderId> {
+        let self_hosted = self.self_hosted.is_configured().then_some(ProviderId::SelfHosted);
+
         ProviderId::remote_providers()
             .iter()
             .copied()
+            .chain(self_hosted)
             .filter(|id| self.providers.contains_key(id))
             .collect()
     }
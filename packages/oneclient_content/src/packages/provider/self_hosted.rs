@@ -0,0 +1,689 @@
+//! A static JSON index served from anywhere
+//!
+//! ```json
+//! {
+//!   "name": "Internal",
+//!   "projects": [{
+//!     "id": "toolbox",
+//!     "name": "Toolbox",
+//!     "summary": "Internal helpers",
+//!     "content_type": "mod",
+//!     "authors": ["Polyfrost"],
+//!     "versions": [{
+//!       "id": "toolbox-1.2.0",
+//!       "version_number": "1.2.0",
+//!       "published": "2026-01-01T00:00:00Z",
+//!       "game_versions": ["1.21.4"],
+//!       "loaders": ["fabric"],
+//!       "files": [{ "file_name": "toolbox-1.2.0.jar", "url": "files/toolbox-1.2.0.jar", "sha1": "…", "size": 1024 }],
+//!       "dependencies": [{ "project_id": "fabric-api", "version_id": null, "kind": "required" }]
+//!     }]
+//!   }]
+//! }
+//! ```
+//!
+//! Relative file urls resolve against the index url so a folder on any static
+//! host is enough
+//!
+//! Any number of indexes can be configured project and version ids leave this
+//! provider as `<index id>:<id>` so every stored row knows which index it came
+//! from and two indexes using the same ids never collide
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::PackageProvider;
+use super::http::fetch_json;
+use crate::ctx::ContentCtx;
+use crate::error::ContentResult;
+use crate::packages::error::PackageError;
+use crate::packages::file_identity::FileIdentity;
+use crate::packages::types::{
+    DEFAULT_PAGE_SIZE, PackageBody, Page, ProjectDetail, ProjectMember, ProjectSummary,
+    ReleaseType, SearchFilters, SearchSort, VersionDependency, VersionDetail, VersionFile,
+    VersionLookup, VersionSummary,
+};
+use oneclient_common::domain::{ContentType, GameLoader, ProviderId};
+
+/// Registered unconditionally every call fails with
+/// [`PackageError::ProviderNotRegistered`] until an index is set
+#[derive(Default)]
+pub struct SelfHostedProvider {
+    indexes: RwLock<Vec<(String, Url)>>,
+}
+
+/// One index in the launcher settings
+/// the id is fixed when it is added so editing the url keeps what was installed from it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageIndex {
+    pub id: String,
+    pub url: String,
+}
+
+impl PackageIndex {
+    pub fn new(url: impl Into<String>) -> Self {
+        let url = url.into().trim().to_string();
+        let id = polyio::sha1_bytes(url.as_bytes())[..8].to_string();
+        Self { id, url }
+    }
+}
+
+#[derive(Deserialize)]
+struct IndexDocument {
+    #[serde(default)]
+    projects: Vec<IndexProject>,
+}
+
+#[derive(Deserialize)]
+struct IndexProject {
+    id: String,
+    #[serde(default)]
+    slug: Option<String>,
+    name: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// A folder name `mod` `resourcepack` `shader` `datapack` `modpack`
+    #[serde(default)]
+    content_type: Option<String>,
+    #[serde(default)]
+    authors: Vec<String>,
+    #[serde(default)]
+    icon_url: Option<String>,
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    versions: Vec<IndexVersion>,
+}
+
+#[derive(Clone, Deserialize)]
+struct IndexVersion {
+    id: String,
+    #[serde(default)]
+    name: Option<String>,
+    version_number: String,
+    published: DateTime<Utc>,
+    #[serde(default)]
+    release_type: ReleaseType,
+    #[serde(default)]
+    changelog: Option<String>,
+    #[serde(default)]
+    game_versions: Vec<String>,
+    #[serde(default)]
+    loaders: Vec<String>,
+    files: Vec<IndexFile>,
+    #[serde(default)]
+    dependencies: Vec<VersionDependency>,
+}
+
+#[derive(Clone, Deserialize)]
+struct IndexFile {
+    file_name: String,
+    url: String,
+    sha1: String,
+    size: u64,
+    #[serde(default)]
+    primary: bool,
+}
+
+impl SelfHostedProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An index with a bad url or a reused id is skipped it never blocks the rest
+    pub fn set_indexes(&self, indexes: &[PackageIndex]) {
+        let mut parsed: Vec<(String, Url)> = Vec::new();
+        for index in indexes {
+            if index.id.is_empty()
+                || index.id.contains(':')
+                || parsed.iter().any(|(id, _)| *id == index.id)
+            {
+                tracing::warn!(id = %index.id, "ignoring package index with an unusable id");
+                continue;
+            }
+            match Url::parse(index.url.trim()) {
+                Ok(url) => parsed.push((index.id.clone(), url)),
+                Err(err) => {
+                    tracing::warn!(id = %index.id, %err, "ignoring invalid package index url");
+                }
+            }
+        }
+
+        *self.indexes.write().unwrap_or_else(|e| e.into_inner()) = parsed;
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self
+            .indexes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+
+    fn configured(&self) -> ContentResult<Vec<(String, Url)>> {
+        let indexes = self
+            .indexes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if indexes.is_empty() {
+            return Err(PackageError::ProviderNotRegistered(ProviderId::SelfHosted).into());
+        }
+        Ok(indexes)
+    }
+
+    /// Fetched whole on every call an index is small and an edit on the server
+    /// shows up without a restart
+    async fn index(&self, index: &str, ctx: &ContentCtx) -> ContentResult<(Url, IndexDocument)> {
+        let url = self
+            .configured()?
+            .into_iter()
+            .find(|(id, _)| id == index)
+            .map(|(_, url)| url)
+            .ok_or_else(|| PackageError::UnknownPackageIndex(index.to_string()))?;
+
+        let document: IndexDocument = fetch_json(&ctx.net, Method::GET, url.as_str(), None).await?;
+        Ok((url, document))
+    }
+
+    /// One unreachable index only drops its own results
+    async fn all_indexes(&self, ctx: &ContentCtx) -> ContentResult<Vec<Fetched>> {
+        let mut fetched = Vec::new();
+        for (index, url) in self.configured()? {
+            match fetch_json::<IndexDocument>(&ctx.net, Method::GET, url.as_str(), None).await {
+                Ok(document) => fetched.push(Fetched {
+                    index,
+                    base: url,
+                    document,
+                }),
+                Err(err) => tracing::warn!(%index, %err, "skipping unreachable package index"),
+            }
+        }
+        Ok(fetched)
+    }
+
+    async fn project(&self, project_id: &str, ctx: &ContentCtx) -> ContentResult<(Fetched, usize)> {
+        let (index, local) = split_id(project_id)?;
+        let (base, document) = self.index(index, ctx).await?;
+        let position = document
+            .projects
+            .iter()
+            .position(|project| project.id == local)
+            .ok_or_else(|| PackageError::ArtifactMissing(format!("project {project_id}")))?;
+        Ok((
+            Fetched {
+                index: index.to_string(),
+                base,
+                document,
+            },
+            position,
+        ))
+    }
+}
+
+struct Fetched {
+    index: String,
+    base: Url,
+    document: IndexDocument,
+}
+
+fn qualify(index: &str, id: &str) -> String {
+    format!("{index}:{id}")
+}
+
+/// A bare id has no index to go to
+fn split_id(id: &str) -> ContentResult<(&str, &str)> {
+    id.split_once(':')
+        .ok_or_else(|| PackageError::UnknownPackageIndex(id.to_string()).into())
+}
+
+#[async_trait::async_trait]
+impl PackageProvider for SelfHostedProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::SelfHosted
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, filters, ctx))]
+    async fn search(
+        &self,
+        filters: &SearchFilters,
+        ctx: &ContentCtx,
+    ) -> ContentResult<Page<ProjectSummary>> {
+        let fetched = self.all_indexes(ctx).await?;
+        let mut matches: Vec<ProjectSummary> = fetched
+            .iter()
+            .flat_map(|fetched| {
+                fetched
+                    .document
+                    .projects
+                    .iter()
+                    .filter(|project| project.matches(filters))
+                    .map(|project| project.summary(&fetched.index))
+            })
+            .collect();
+
+        if matches!(filters.sort, Some(SearchSort::Newest | SearchSort::Updated)) {
+            matches.sort_by_key(|project| std::cmp::Reverse(project.updated));
+        }
+
+        let offset = filters.offset.unwrap_or(0);
+        let limit = filters.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        Ok(Page {
+            offset,
+            limit,
+            total: matches.len(),
+            items: matches.into_iter().skip(offset).take(limit).collect(),
+        })
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, ctx))]
+    async fn get_project(
+        &self,
+        project_id: &str,
+        ctx: &ContentCtx,
+    ) -> ContentResult<ProjectDetail> {
+        let (fetched, position) = self.project(project_id, ctx).await?;
+        Ok(fetched.document.projects[position].detail(&fetched.index))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, project_ids, ctx))]
+    async fn get_projects(
+        &self,
+        project_ids: &[String],
+        ctx: &ContentCtx,
+    ) -> ContentResult<Vec<ProjectDetail>> {
+        if project_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let wanted: HashSet<&str> = project_ids.iter().map(String::as_str).collect();
+        Ok(self
+            .all_indexes(ctx)
+            .await?
+            .iter()
+            .flat_map(|fetched| {
+                fetched
+                    .document
+                    .projects
+                    .iter()
+                    .filter(|project| {
+                        wanted.contains(qualify(&fetched.index, &project.id).as_str())
+                    })
+                    .map(|project| project.detail(&fetched.index))
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, ctx))]
+    async fn list_versions(
+        &self,
+        project_id: &str,
+        game_version: Option<&str>,
+        loader: Option<GameLoader>,
+        offset: usize,
+        limit: usize,
+        ctx: &ContentCtx,
+    ) -> ContentResult<Page<VersionSummary>> {
+        let (fetched, position) = self.project(project_id, ctx).await?;
+        let project = &fetched.document.projects[position];
+        let mut versions: Vec<VersionSummary> = project
+            .versions
+            .iter()
+            .filter(|version| {
+                game_version.is_none_or(|wanted| version.game_versions.iter().any(|v| v == wanted))
+            })
+            .filter(|version| loader.is_none_or(|wanted| version.loaders().contains(&wanted)))
+            .map(|version| version.summary(&fetched.index, &project.id, &fetched.base))
+            .collect();
+        versions.sort_by_key(|version| std::cmp::Reverse(version.published));
+
+        Ok(Page {
+            offset,
+            limit,
+            total: versions.len(),
+            items: versions.into_iter().skip(offset).take(limit).collect(),
+        })
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, ctx))]
+    async fn get_version(
+        &self,
+        project_id: &str,
+        version_id: &str,
+        ctx: &ContentCtx,
+    ) -> ContentResult<VersionDetail> {
+        let (fetched, position) = self.project(project_id, ctx).await?;
+        let project = &fetched.document.projects[position];
+        let (_, local) = split_id(version_id)?;
+        project
+            .versions
+            .iter()
+            .find(|version| version.id == local)
+            .map(|version| version.detail(&fetched.index, &project.id, &fetched.base))
+            .ok_or_else(|| PackageError::ArtifactMissing(format!("version {version_id}")).into())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, version_ids, ctx))]
+    async fn get_versions(
+        &self,
+        version_ids: &[String],
+        ctx: &ContentCtx,
+    ) -> ContentResult<Vec<VersionDetail>> {
+        if version_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let wanted: HashSet<&str> = version_ids.iter().map(String::as_str).collect();
+        let mut out = Vec::new();
+        for fetched in self.all_indexes(ctx).await? {
+            for project in &fetched.document.projects {
+                for version in &project.versions {
+                    if wanted.contains(qualify(&fetched.index, &version.id).as_str()) {
+                        out.push(version.detail(&fetched.index, &project.id, &fetched.base));
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn lookup_versions(
+        &self,
+        identities: &[FileIdentity],
+        ctx: &ContentCtx,
+    ) -> ContentResult<VersionLookup> {
+        let mut out = VersionLookup::new();
+        if identities.is_empty() {
+            return Ok(out);
+        }
+
+        let wanted: HashSet<&str> = identities.iter().map(|id| id.sha1.as_str()).collect();
+        for fetched in self.all_indexes(ctx).await? {
+            for project in &fetched.document.projects {
+                for version in &project.versions {
+                    for file in &version.files {
+                        let sha1 = polyio::normalize_hash(&file.sha1);
+                        if wanted.contains(sha1.as_str()) && !out.contains_key(&sha1) {
+                            out.insert(
+                                sha1,
+                                version.detail(&fetched.index, &project.id, &fetched.base),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl IndexProject {
+    fn content_type(&self) -> ContentType {
+        self.content_type
+            .as_deref()
+            .and_then(ContentType::from_folder_name)
+            .unwrap_or(ContentType::Mod)
+    }
+
+    fn matches(&self, filters: &SearchFilters) -> bool {
+        if filters
+            .content_type
+            .is_some_and(|wanted| wanted != self.content_type())
+        {
+            return false;
+        }
+
+        let query = filters.normalized_query().to_lowercase();
+        if !query.is_empty()
+            && ![&self.id, &self.name, &self.summary]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&query))
+        {
+            return false;
+        }
+
+        let game_versions = filters.game_versions.as_deref().unwrap_or_default();
+        let loaders = filters.loaders.as_deref().unwrap_or_default();
+        game_versions.is_empty() && loaders.is_empty()
+            || self.versions.iter().any(|version| {
+                (game_versions.is_empty()
+                    || version.game_versions.iter().any(|v| game_versions.contains(v)))
+                    && (loaders.is_empty()
+                        || version.loaders().iter().any(|l| loaders.contains(l)))
+            })
+    }
+
+    fn summary(&self, index: &str) -> ProjectSummary {
+        let detail = self.detail(index);
+        ProjectSummary {
+            id: detail.id,
+            slug: detail.slug,
+            provider: ProviderId::SelfHosted,
+            content_type: detail.content_type,
+            name: detail.name,
+            summary: detail.summary,
+            author: detail.author,
+            icon_url: detail.icon_url,
+            downloads: 0,
+            created: detail.created,
+            updated: detail.updated,
+            loaders: detail.loaders,
+            game_versions: detail.game_versions,
+        }
+    }
+
+    fn detail(&self, index: &str) -> ProjectDetail {
+        let published = self.versions.iter().map(|version| version.published);
+        let created = published.clone().min().unwrap_or(DateTime::UNIX_EPOCH);
+        let updated = published.max().unwrap_or(DateTime::UNIX_EPOCH);
+
+        let mut game_versions: Vec<String> = Vec::new();
+        let mut loaders: Vec<GameLoader> = Vec::new();
+        for version in &self.versions {
+            for game_version in &version.game_versions {
+                if !game_versions.contains(game_version) {
+                    game_versions.push(game_version.clone());
+                }
+            }
+            for loader in version.loaders() {
+                if !loaders.contains(&loader) {
+                    loaders.push(loader);
+                }
+            }
+        }
+
+        ProjectDetail {
+            id: qualify(index, &self.id),
+            slug: self.slug.clone().unwrap_or_else(|| self.id.clone()),
+            provider: ProviderId::SelfHosted,
+            content_type: self.content_type(),
+            name: self.name.clone(),
+            summary: self.summary.clone(),
+            author: self.authors.join(", "),
+            members: self
+                .authors
+                .iter()
+                .map(|name| ProjectMember {
+                    name: name.clone(),
+                    role: String::new(),
+                    url: None,
+                    avatar_url: None,
+                })
+                .collect(),
+            gallery: Vec::new(),
+            body: PackageBody::Raw(self.description.clone().unwrap_or_default()),
+            license: self.license.clone(),
+            links: Vec::new(),
+            version_ids: self
+                .versions
+                .iter()
+                .map(|version| qualify(index, &version.id))
+                .collect(),
+            game_versions,
+            loaders,
+            icon_url: self.icon_url.clone(),
+            created,
+            updated,
+            downloads: 0,
+        }
+    }
+}
+
+impl IndexVersion {
+    /// Unknown loader names are dropped rather than failing the whole index
+    fn loaders(&self) -> Vec<GameLoader> {
+        self.loaders
+            .iter()
+            .filter_map(|loader| GameLoader::from_str(loader).ok())
+            .collect()
+    }
+
+    fn summary(&self, index: &str, project_id: &str, base: &Url) -> VersionSummary {
+        let detail = self.detail(index, project_id, base);
+        VersionSummary {
+            version_id: detail.version_id,
+            project_id: detail.project_id,
+            name: detail.name,
+            version_number: detail.version_number,
+            published: detail.published,
+            release_type: self.release_type.clone(),
+            game_versions: detail.game_versions,
+            loaders: detail.loaders,
+            downloads: 0,
+            file_size: detail.files.iter().map(|file| file.size).sum(),
+        }
+    }
+
+    /// Dependencies point into the same index
+    fn detail(&self, index: &str, project_id: &str, base: &Url) -> VersionDetail {
+        VersionDetail {
+            version_id: qualify(index, &self.id),
+            project_id: qualify(index, project_id),
+            name: self.name.clone().unwrap_or_else(|| self.version_number.clone()),
+            version_number: self.version_number.clone(),
+            changelog: self.changelog.clone(),
+            game_versions: self.game_versions.clone(),
+            loaders: self.loaders(),
+            published: self.published,
+            downloads: 0,
+            files: self
+                .files
+                .iter()
+                .map(|file| VersionFile {
+                    sha1: polyio::normalize_hash(&file.sha1),
+                    url: base
+                        .join(&file.url)
+                        .map_or_else(|_| file.url.clone(), String::from),
+                    file_name: file.file_name.clone(),
+                    primary: file.primary,
+                    size: file.size,
+                    fingerprint: None,
+                })
+                .collect(),
+            dependencies: self
+                .dependencies
+                .iter()
+                .map(|dep| VersionDependency {
+                    project_id: dep.project_id.as_deref().map(|id| qualify(index, id)),
+                    version_id: dep.version_id.as_deref().map(|id| qualify(index, id)),
+                    kind: dep.kind,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(raw: serde_json::Value) -> IndexProject {
+        serde_json::from_value(raw).unwrap()
+    }
+
+    fn toolbox() -> IndexProject {
+        project(serde_json::json!({
+            "id": "toolbox",
+            "name": "Toolbox",
+            "summary": "Internal helpers",
+            "versions": [{
+                "id": "toolbox-1.2.0",
+                "version_number": "1.2.0",
+                "published": "2026-01-01T00:00:00Z",
+                "game_versions": ["1.21.4"],
+                "loaders": ["fabric", "rift"],
+                "files": [{
+                    "file_name": "toolbox-1.2.0.jar",
+                    "url": "files/toolbox-1.2.0.jar",
+                    "sha1": "ABCDEF",
+                    "size": 10
+                }]
+            }]
+        }))
+    }
+
+    #[test]
+    fn relative_file_urls_resolve_against_the_index() {
+        let base = Url::parse("https://mods.example.com/index/packages.json").unwrap();
+        let version = toolbox().versions[0].detail("internal", "toolbox", &base);
+
+        assert_eq!(
+            version.files[0].url,
+            "https://mods.example.com/index/files/toolbox-1.2.0.jar"
+        );
+        assert_eq!(version.files[0].sha1, "abcdef");
+        assert_eq!(version.version_id, "internal:toolbox-1.2.0");
+        assert_eq!(version.project_id, "internal:toolbox");
+        assert_eq!(version.loaders, [GameLoader::Fabric], "unknown loaders are dropped");
+    }
+
+    #[test]
+    fn search_filters_by_query_and_compatibility() {
+        let project = toolbox();
+        let filters = |query: &str, version: &str| SearchFilters {
+            query: Some(query.into()),
+            game_versions: Some(vec![version.into()]),
+            ..Default::default()
+        };
+
+        assert!(project.matches(&filters("TOOL", "1.21.4")));
+        assert!(!project.matches(&filters("tool", "1.20.1")));
+        assert!(!project.matches(&filters("sodium", "1.21.4")));
+        assert!(!project.matches(&SearchFilters {
+            content_type: Some(ContentType::Shader),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn only_usable_indexes_are_kept() {
+        let provider = SelfHostedProvider::new();
+        assert!(!provider.is_configured());
+
+        let good = PackageIndex::new(" https://mods.example.com/index.json ");
+        assert_eq!(good.url, "https://mods.example.com/index.json");
+        provider.set_indexes(&[
+            good.clone(),
+            PackageIndex::new("not a url"),
+            PackageIndex {
+                id: "a:b".into(),
+                url: good.url.clone(),
+            },
+            good.clone(),
+        ]);
+        assert_eq!(provider.configured().unwrap().len(), 1);
+
+        provider.set_indexes(&[]);
+        assert!(!provider.is_configured());
+    }
+}
@@ -177,7 +177,7 @@ async fn file_len(path: &Path) -> u64 {
 	polyio::stat(path).await.map(|m| m.len()).unwrap_or(0)
 }
 
-async fn walk_files(root: &Path) -> Vec<PathBuf> {
+pub(crate) async fn walk_files(root: &Path) -> Vec<PathBuf> {
 	let mut files = Vec::new();
 	let mut stack = vec![root.to_path_buf()];
 
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 
 use oneclient_db::models::ClusterRow;
@@ -53,25 +54,32 @@ pub async fn try_unlink_materialized(
 	// The shared game dir belongs to whichever cluster played last
 	// touching a file we did not put there would delete another cluster's or
 	// the user's content
-	let relative = manifest::entry_path(content_type.folder_name(), file_name);
-	if !loaded.owns(cluster.id, &relative) {
+	if loaded.cluster_id != cluster.id {
 		return false;
 	}
-
-	let path = game_dir.join(content_type.folder_name()).join(file_name);
-	if let Err(err) = remove_entry(&path).await {
-		tracing::debug!(
-			file = file_name,
-			error = %err,
-			"could not drop package from the game folder now; it goes at the next launch"
-		);
+	let placed = loaded.placements(content_type.folder_name(), file_name);
+	if placed.is_empty() {
 		return false;
 	}
 
-	loaded.entries.retain(|entry| entry.path != relative);
+	let mut removed = HashSet::new();
+	for relative in &placed {
+		match remove_entry(&game_dir.join(relative)).await {
+			Ok(()) => {
+				removed.insert(relative.as_str());
+			}
+			Err(err) => tracing::debug!(
+				file = relative,
+				error = %err,
+				"could not drop package from the game folder now; it goes at the next launch"
+			),
+		}
+	}
+
+	loaded.entries.retain(|entry| !removed.contains(entry.path.as_str()));
 	manifest::save(&game_dir, &loaded).await;
 
-	true
+	removed.len() == placed.len()
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+use oneclient_cluster::worlds::SAVES_DIR;
 use serde::{Deserialize, Serialize};
 
 pub const MANIFEST_NAME: &str = ".oneclient-materialized.json";
@@ -12,6 +13,10 @@ pub struct ManifestEntry {
 	/// Slash-separated relative to the game directory e.g. `mods/sodium.jar`
 	pub path: String,
 	pub hash: String,
+	/// Set for a datapack which sits a folder deeper inside this world's
+	/// `saves` folder than the content folders do
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub world: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +47,20 @@ impl MaterializedManifest {
 		self.entries.iter().any(|e| e.path == relative_path)
 	}
 
+	/// Every place one file was materialized a datapack lands once per world
+	#[must_use]
+	pub fn placements(&self, content_folder: &str, file_name: &str) -> Vec<String> {
+		let flat = entry_path(content_folder, file_name);
+		self.entries
+			.iter()
+			.filter(|e| match &e.world {
+				Some(world) => e.path == world_entry_path(world, content_folder, file_name),
+				None => e.path == flat,
+			})
+			.map(|e| e.path.clone())
+			.collect()
+	}
+
 	/// The cluster check is what makes deleting out of the shared game directory safe
 	#[must_use]
 	pub fn owns(&self, cluster_id: i64, relative_path: &str) -> bool {
@@ -98,6 +117,11 @@ pub fn entry_path(content_folder: &str, file_name: &str) -> String {
 	format!("{content_folder}/{file_name}")
 }
 
+#[must_use]
+pub fn world_entry_path(world: &str, content_folder: &str, file_name: &str) -> String {
+	format!("{SAVES_DIR}/{world}/{content_folder}/{file_name}")
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -108,6 +132,7 @@ mod tests {
 			vec![ManifestEntry {
 				path: "mods/sodium.jar".into(),
 				hash: "abc".into(),
+				world: None,
 			}],
 		)
 	}
@@ -121,6 +146,29 @@ mod tests {
 		assert!(!manifest.owns(7, "mods/iris.jar"));
 	}
 
+	#[test]
+	fn a_datapack_is_found_in_every_world_it_went_into() {
+		let mut manifest = manifest();
+		for world in ["Castle", "Skyblock"] {
+			manifest.entries.push(ManifestEntry {
+				path: world_entry_path(world, "datapacks", "trees.zip"),
+				hash: "def".into(),
+				world: Some(world.into()),
+			});
+		}
+
+		assert_eq!(
+			manifest.placements("datapacks", "trees.zip"),
+			vec![
+				"saves/Castle/datapacks/trees.zip",
+				"saves/Skyblock/datapacks/trees.zip"
+			]
+		);
+		assert_eq!(manifest.placements("mods", "sodium.jar"), vec!["mods/sodium.jar"]);
+		// A flat entry of the same name is a different file
+		assert!(manifest.placements("datapacks", "sodium.jar").is_empty());
+	}
+
 	#[tokio::test]
 	async fn round_trips_through_the_game_dir() {
 		let root = polyio::testing::ScratchDir::new("manifest_round_trip");
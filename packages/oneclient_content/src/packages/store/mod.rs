@@ -9,6 +9,7 @@ pub use gc::{
     GcReport, collect_unused_artifacts, evict_if_unused, find_unreferenced_files,
     remove_unreferenced_files,
 };
+pub(crate) use gc::walk_files;
 pub use link::{link_or_copy, remove_entry, try_unlink_materialized};
 pub use paths::{artifact_absolute_path, cache_file_path, relative_cache_path};
 
@@ -17,11 +18,14 @@ use oneclient_db::models::{ArtifactRow, ClusterRow};
 
 use oneclient_common::domain::{ContentType, GameLoader, ProviderId};
 use super::error::PackageError;
-use super::types::{CachedArtifact, ProjectDetail, ProviderReleaseInfo, VersionDetail, LinkedArtifactInfo};
+use super::jar_metadata;
+use super::types::{CachedArtifact, ProjectDetail, ProviderReleaseInfo, VersionDetail, LinkedArtifactInfo, WorldTarget};
 use polyio::{normalize_hash, sha1_file};
 use oneclient_events::GroupedProgressChild;
 use crate::ctx::ContentCtx;
 use crate::error::{ContentError, ContentResult};
+use oneclient_cluster::worlds::is_plain_folder;
+use std::collections::HashMap;
 use std::path::Path;
 
 pub struct PackageStore;
@@ -80,6 +84,7 @@ impl PackageStore {
         project: &ProjectDetail,
         version: &VersionDetail,
         cluster_id: i64,
+        world: &WorldTarget,
         skip_compatibility: bool,
         force_download: bool,
         child: Option<&GroupedProgressChild>,
@@ -87,6 +92,7 @@ impl PackageStore {
     ) -> ContentResult<ArtifactRow> {
         tracing::info!("installing package to cluster");
         let cluster = Self::get_cluster(cluster_id, ctx).await?;
+        ensure_world_folder(world)?;
 
         if !skip_compatibility {
             ensure_compatible(project, version, &cluster)?;
@@ -103,9 +109,46 @@ impl PackageStore {
         .await?;
 
         Self::link_artifact(&artifact, &cluster, None, ctx).await?;
+        // Written every time a reinstall aimed elsewhere moves the pack
+        if project.content_type == ContentType::DataPack {
+            artifact_dao::set_cluster_artifact_world(
+                &ctx.db,
+                cluster_id,
+                &artifact.hash,
+                world.world(),
+            )
+            .await?;
+        }
         Ok(artifact)
     }
 
+    /// Like the enabled flag only the choice is recorded copies in worlds it
+    /// no longer targets go now if they can and the next launch places the rest
+    #[tracing::instrument(level = "debug", skip(ctx))]
+    pub async fn set_datapack_world(
+        cluster_id: i64,
+        hash: &str,
+        world: &WorldTarget,
+        ctx: &ContentCtx,
+    ) -> ContentResult<()> {
+        ensure_world_folder(world)?;
+        let cluster = Self::get_cluster(cluster_id, ctx).await?;
+        let artifact = artifact_dao::get_artifact_by_hash(&ctx.db, hash)
+            .await?
+            .ok_or(PackageError::ArtifactMissing(hash.to_string()))?;
+        if artifact.content_type != ContentType::DataPack as i64 {
+            return Err(PackageError::NotPerWorld.into());
+        }
+        let link = artifact_dao::get_cluster_artifact(&ctx.db, cluster_id, hash)
+            .await?
+            .ok_or(PackageError::ArtifactMissing(hash.to_string()))?;
+
+        artifact_dao::set_cluster_artifact_world(&ctx.db, cluster_id, hash, world.world()).await?;
+        link::try_unlink_materialized(&cluster, ContentType::DataPack, &link.cluster_file_name)
+            .await;
+        Ok(())
+    }
+
     /// Writes nothing to disk
     /// the artifact is materialized into the game directory at launch the only
     /// moment no game is holding the files open
@@ -145,6 +188,11 @@ impl PackageStore {
         ctx: &ContentCtx,
     ) -> ContentResult<Vec<LinkedArtifactInfo>> {
         let links = artifact_dao::list_cluster_artifacts(&ctx.db, cluster_id).await?;
+        let worlds: HashMap<String, String> =
+            artifact_dao::list_cluster_artifact_worlds(&ctx.db, cluster_id)
+                .await?
+                .into_iter()
+                .collect();
         let mut items = Vec::with_capacity(links.len());
 
         for link in links {
@@ -159,6 +207,7 @@ impl PackageStore {
             let release = artifact_dao::get_release_by_hash(&ctx.db, &link.hash).await?;
 
             items.push(LinkedArtifactInfo {
+                target_world: worlds.get(&link.hash).cloned(),
                 hash: link.hash,
                 cluster_file_name: link.cluster_file_name,
                 enabled: link.enabled != 0,
@@ -297,6 +346,12 @@ impl PackageStore {
 
         let cluster = Self::get_cluster(cluster_id, ctx).await?;
         Self::link_artifact(&row, &cluster, None, ctx).await?;
+
+        if content_type == ContentType::Mod {
+            let loader = GameLoader::from_repr(cluster.mc_loader as u8).unwrap_or(GameLoader::Vanilla);
+            jar_metadata::identify_local_jar(&row, &dest, loader, ctx).await;
+        }
+
         Ok(row)
     }
 
@@ -347,6 +402,15 @@ async fn row_to_cached(
     })
 }
 
+fn ensure_world_folder(world: &WorldTarget) -> ContentResult<()> {
+    match world.world() {
+        Some(folder) if !is_plain_folder(folder) => {
+            Err(PackageError::InvalidWorld(folder.to_string()).into())
+        }
+        _ => Ok(()),
+    }
+}
+
 fn ensure_compatible(
     project: &ProjectDetail,
     version: &VersionDetail,
@@ -445,6 +509,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn a_datapack_world_must_be_a_folder_in_saves() {
+        assert!(ensure_world_folder(&WorldTarget::AllWorlds).is_ok());
+        assert!(ensure_world_folder(&WorldTarget::World("New World".into())).is_ok());
+        assert!(matches!(
+            ensure_world_folder(&WorldTarget::World("../mods".into())),
+            Err(ContentError::Package(PackageError::InvalidWorld(_)))
+        ));
+    }
+
     #[test]
     fn resource_pack_installs_into_modded_cluster() {
         let result = ensure_compatible(
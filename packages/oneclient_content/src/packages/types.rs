@@ -221,6 +221,33 @@ pub struct ProviderReleaseInfo {
 
 pub type VersionLookup = HashMap<String, VersionDetail>;
 
+/// Where a datapack goes vanilla only loads them from inside a world
+/// Ignored for every other content type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldTarget {
+	/// Each world in the game dir at every launch including ones made since
+	#[default]
+	AllWorlds,
+	/// A folder under `saves`
+	World(String),
+}
+
+impl WorldTarget {
+	#[must_use]
+	pub fn from_world(world: Option<String>) -> Self {
+		world.map_or(Self::AllWorlds, Self::World)
+	}
+
+	#[must_use]
+	pub fn world(&self) -> Option<&str> {
+		match self {
+			Self::AllWorlds => None,
+			Self::World(world) => Some(world),
+		}
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinkedArtifactInfo {
 	pub hash: String,
@@ -235,4 +262,7 @@ pub struct LinkedArtifactInfo {
 	pub provider: Option<ProviderId>,
 	/// Provider publish time RFC 3339
 	pub published_at: Option<String>,
+	/// Datapacks only `None` is every world
+	#[serde(default)]
+	pub target_world: Option<String>,
 }
@@ -15,12 +15,15 @@ use oneclient_db::dao::browser_package_update as update_dao;
 use oneclient_db::dao::cluster_bundle as bundle_dao;
 use oneclient_db::models::{BrowserPackageUpdateRow, ClusterRow};
 use oneclient_events::GroupedProgressChild;
+use oneclient_net::Priority;
 
 use crate::ctx::ContentCtx;
 use crate::error::{ContentError, ContentResult};
-use crate::packages::dependencies::pick_version;
+use crate::packages::dependencies::{
+	apply_dependency_upgrade, confirm_dependency_plan, pick_version, plan_dependencies_or_ask,
+};
 use crate::packages::store::{PackageStore, evict_if_unused, try_unlink_materialized};
-use crate::packages::types::LinkedArtifactInfo;
+use crate::packages::types::{LinkedArtifactInfo, WorldTarget};
 
 /// Matches the bundle installer's fan-out
 /// the providers are the bottleneck
@@ -115,10 +118,20 @@ fn browser_installed(
 
 /// An unreachable project lands in [`BrowserUpdateCheck::unchecked`]
 /// only an unreadable cluster errors
+/// Runs at background priority like the bundle check
 #[tracing::instrument(level = "debug", skip(ctx))]
 pub async fn check_browser_package_updates(
 	cluster_id: i64,
 	ctx: &ContentCtx,
+) -> ContentResult<BrowserUpdateCheck> {
+	Priority::Background
+		.scope(check_browser_package_updates_inner(cluster_id, ctx))
+		.await
+}
+
+async fn check_browser_package_updates_inner(
+	cluster_id: i64,
+	ctx: &ContentCtx,
 ) -> ContentResult<BrowserUpdateCheck> {
 	let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
 	let linked = PackageStore::list_linked_artifacts(cluster_id, ctx).await?;
@@ -317,6 +330,49 @@ pub async fn apply_browser_package_update(
 		.get_version(&update.project_id, &update.latest_version_id, ctx)
 		.await?;
 
+	let plan =
+		plan_dependencies_or_ask(update.provider, &project, &version, update.cluster_id, ctx).await?;
+	confirm_dependency_plan(&plan, ctx).await?;
+
+	// A datapack's new version goes into the same world as the old one
+	let world = WorldTarget::from_world(
+		artifact_dao::get_cluster_artifact_world(&ctx.db, update.cluster_id, &update.hash).await?,
+	);
+
+	// Dependencies first as a fresh install does
+	// one that fails is logged and the update still goes ahead
+	for dependency in &plan.install {
+		if let Err(err) = PackageStore::install_to_cluster(
+			update.provider,
+			&dependency.project,
+			&dependency.version,
+			update.cluster_id,
+			&world,
+			false,
+			false,
+			None,
+			ctx,
+		)
+		.await
+		{
+			tracing::warn!(dependency = %dependency.project.name, %err, "failed to install dependency");
+		}
+	}
+	for upgrade in &plan.upgrade {
+		if let Err(err) =
+			apply_dependency_upgrade(update.provider, upgrade, update.cluster_id, &world, None, ctx).await
+		{
+			tracing::warn!(dependency = %upgrade.to.project.name, %err, "failed to upgrade dependency");
+		}
+	}
+	for dependency in &plan.enable {
+		if let Err(err) =
+			crate::bundles::set_artifact_enabled_to(update.cluster_id, &dependency.hash, true, ctx).await
+		{
+			tracing::warn!(dependency = %dependency.name, %err, "failed to enable dependency");
+		}
+	}
+
 	// Compatibility is not re-checked
 	// a provider disagreeing at install time would strand the user on a build
 	// they cannot move off
@@ -325,6 +381,7 @@ pub async fn apply_browser_package_update(
 		&project,
 		&version,
 		update.cluster_id,
+		&world,
 		true,
 		false,
 		child,
@@ -368,7 +425,7 @@ pub async fn apply_browser_package_update(
 /// Bundle-owned copies are stepped over
 /// unlinking one here would have the next bundle sync put it straight back
 #[tracing::instrument(level = "debug", skip(ctx))]
-async fn unlink_other_versions(
+pub(crate) async fn unlink_other_versions(
 	cluster_id: i64,
 	provider: ProviderId,
 	project_id: &str,
@@ -380,7 +437,7 @@ async fn unlink_other_versions(
 		cluster_id,
 		provider as i64,
 		project_id,
-		keep_hash,
+		Some(keep_hash),
 	)
 	.await?;
 
@@ -412,7 +469,7 @@ async fn unlink_other_versions(
 /// `packages` must not depend on `bundles` and its override reconciliation
 /// does not apply here
 #[tracing::instrument(level = "debug", skip(ctx))]
-async fn unlink_superseded(cluster_id: i64, hash: &str, ctx: &ContentCtx) -> ContentResult<()> {
+pub(crate) async fn unlink_superseded(cluster_id: i64, hash: &str, ctx: &ContentCtx) -> ContentResult<()> {
 	let cluster = PackageStore::get_cluster(cluster_id, ctx).await?;
 	let content_type = artifact_dao::get_artifact_by_hash(&ctx.db, hash)
 		.await?
@@ -457,6 +514,7 @@ mod tests {
 			display_version: None,
 			provider,
 			published_at: None,
+			target_world: None,
 		}
 	}
 
@@ -0,0 +1,67 @@
+mod common;
+
+use oneclient_content::packages::{
+	BrowserPackageUpdate, PackageStore, ProviderId, WorldTarget, apply_browser_package_update,
+};
+
+use common::Setup;
+
+const FILES: common::Files = &[
+	("addon-1.jar", b"addon 1.0.0"),
+	("addon-2.jar", b"addon 2.0.0"),
+	("library-1.jar", b"library 1.0.0"),
+];
+
+/// Version two of the addon is the first to need the library
+fn index() -> String {
+	let version = |id: &str, number: &str, name: &str, deps: &str| {
+		common::version(id, number, "2026-01-01T00:00:00Z", name, common::file(FILES, name), deps)
+	};
+
+	format!(
+		r#"{{
+			"projects": [
+				{{ "id": "addon", "name": "Addon", "versions": [{}, {}] }},
+				{{ "id": "library", "name": "Library", "versions": [{}] }}
+			]
+		}}"#,
+		version("addon-1", "1.0.0", "addon-1.jar", ""),
+		version(
+			"addon-2",
+			"2.0.0",
+			"addon-2.jar",
+			r#"{ "project_id": "library", "version_id": null, "kind": "required" }"#
+		),
+		version("library-1", "1.0.0", "library-1.jar", ""),
+	)
+}
+
+#[tokio::test]
+async fn an_update_installs_a_dependency_it_newly_needs() {
+	let setup = Setup::new("browser-update-deps", FILES, index).await;
+	let ctx = &setup.ctx;
+	let hash = setup.install("addon", "addon-1", WorldTarget::AllWorlds).await;
+
+	let update = BrowserPackageUpdate {
+		cluster_id: setup.cluster_id,
+		hash,
+		provider: ProviderId::SelfHosted,
+		project_id: setup.id("addon"),
+		installed_version_id: setup.id("addon-1"),
+		installed_version_name: "1.0.0".into(),
+		latest_version_id: setup.id("addon-2"),
+		latest_version_name: "2.0.0".into(),
+		display_name: "Addon".into(),
+		skipped: false,
+	};
+	apply_browser_package_update(&update, None, ctx).await.unwrap();
+
+	let mut linked: Vec<_> = PackageStore::list_linked_artifacts(setup.cluster_id, ctx)
+		.await
+		.unwrap()
+		.into_iter()
+		.filter_map(|link| link.version_id)
+		.collect();
+	linked.sort();
+	assert_eq!(linked, [setup.id("addon-2"), setup.id("library-1")]);
+}
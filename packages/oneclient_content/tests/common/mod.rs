@@ -0,0 +1,165 @@
+//! Fixtures for the tests that run against a self-hosted package index
+
+#![allow(dead_code)]
+
+use oneclient_common::paths;
+use oneclient_content::ContentCtx;
+use oneclient_content::packages::{
+	GameLoader, PackageIndex, PackageProviderRegistry, PackageStore, ProviderId, WorldTarget,
+};
+use oneclient_db::dao::cluster as cluster_dao;
+use oneclient_db::models::NewCluster;
+use oneclient_events::EventBus;
+use oneclient_net::{NetConfig, RequestClient};
+use polyio::testing::ScratchDir as Scratch;
+
+/// Served by file name
+pub type Files = &'static [(&'static str, &'static [u8])];
+
+pub fn file(files: Files, name: &str) -> &'static [u8] {
+	files
+		.iter()
+		.find(|(file, _)| *file == name)
+		.map(|(_, bytes)| *bytes)
+		.expect("known file")
+}
+
+/// One of a project's `versions` its file is served from `files/`
+pub fn version(
+	id: &str,
+	number: &str,
+	published: &str,
+	file: &str,
+	bytes: &[u8],
+	deps: &str,
+) -> String {
+	format!(
+		r#"{{
+			"id": "{id}",
+			"version_number": "{number}",
+			"published": "{published}",
+			"game_versions": ["1.21.4"],
+			"loaders": ["fabric"],
+			"files": [{{ "file_name": "{file}", "url": "files/{file}", "sha1": "{}", "size": {} }}],
+			"dependencies": [{deps}]
+		}}"#,
+		polyio::sha1_bytes(bytes),
+		bytes.len()
+	)
+}
+
+/// One of `files` by the request path and the index for anything else
+/// returns the index URL
+pub async fn serve(files: Files, index: fn() -> String) -> String {
+	use tokio::io::AsyncWriteExt;
+
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+		.await
+		.expect("bind");
+	let port = listener.local_addr().expect("addr").port();
+
+	tokio::spawn(async move {
+		while let Ok((mut stream, _)) = listener.accept().await {
+			tokio::spawn(async move {
+				let mut request = [0u8; 1024];
+				let read = tokio::io::AsyncReadExt::read(&mut stream, &mut request)
+					.await
+					.unwrap_or(0);
+				let head = String::from_utf8_lossy(&request[..read]);
+				let path = head.split_whitespace().nth(1).unwrap_or_default();
+
+				let body = match files.iter().find(|(name, _)| path.ends_with(name)) {
+					Some((_, bytes)) => bytes.to_vec(),
+					None => index().into_bytes(),
+				};
+
+				let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+				let _ = stream.write_all(head.as_bytes()).await;
+				let _ = stream.write_all(&body).await;
+				let _ = stream.flush().await;
+			});
+		}
+	});
+
+	format!("http://127.0.0.1:{port}/repo/index.json")
+}
+
+pub async fn ctx(scratch: &Scratch) -> ContentCtx {
+	let db = oneclient_db::connect(scratch.join("app.db"))
+		.await
+		.expect("db");
+	let net = RequestClient::new(NetConfig::default()).expect("client");
+	ContentCtx::new(db, net, EventBus::channel().0, PackageProviderRegistry::new())
+}
+
+pub struct Setup {
+	_scratch: Scratch,
+	pub ctx: ContentCtx,
+	pub index: String,
+	pub cluster_id: i64,
+}
+
+impl Setup {
+	/// A fabric 1.21.4 cluster with the index configured
+	pub async fn new(name: &str, files: Files, index: fn() -> String) -> Self {
+		let scratch = Scratch::new(name);
+		// Set once per process so it must outlive any one test's scratch dir
+		paths::set_launcher_dir(
+			std::env::temp_dir().join(format!("oneclient-content-tests-{}", std::process::id())),
+		);
+		let ctx = ctx(&scratch).await;
+
+		let index = PackageIndex::new(serve(files, index).await);
+		ctx.providers.self_hosted().set_indexes(std::slice::from_ref(&index));
+
+		let cluster = cluster_dao::insert(
+			&ctx.db,
+			&NewCluster {
+				name: "Test",
+				folder_name: "test",
+				mc_version: "1.21.4",
+				mc_loader: GameLoader::Fabric as i64,
+				mc_loader_version: None,
+				setting_profile_name: None,
+				stage: 0,
+			},
+		)
+		.await
+		.expect("cluster");
+
+		Self {
+			_scratch: scratch,
+			ctx,
+			index: index.id,
+			cluster_id: cluster.id,
+		}
+	}
+
+	pub fn id(&self, local: &str) -> String {
+		format!("{}:{local}", self.index)
+	}
+
+	/// Returns the linked hash
+	pub async fn install(&self, project: &str, version: &str, world: WorldTarget) -> String {
+		let provider = self.ctx.providers.get(ProviderId::SelfHosted).unwrap();
+		let project = provider.get_project(&self.id(project), &self.ctx).await.unwrap();
+		let version = provider
+			.get_version(&project.id, &self.id(version), &self.ctx)
+			.await
+			.unwrap();
+		PackageStore::install_to_cluster(
+			ProviderId::SelfHosted,
+			&project,
+			&version,
+			self.cluster_id,
+			&world,
+			false,
+			false,
+			None,
+			&self.ctx,
+		)
+		.await
+		.unwrap()
+		.hash
+	}
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use oneclient_content::packages::{
+	PackageStore, ProviderId, WorldTarget, apply_dependency_upgrade, plan_dependencies,
+};
+
+use common::Setup;
+
+const FILES: common::Files = &[
+	("quests-1.zip", b"quests 1.0.0"),
+	("loot-1.zip", b"loot 1.0.0"),
+	("loot-2.zip", b"loot 2.0.0"),
+];
+
+/// The quests datapack is pinned to the older loot tables
+fn index() -> String {
+	let version = |id: &str, number: &str, name: &str, published: &str, deps: &str| {
+		common::version(id, number, published, name, common::file(FILES, name), deps)
+	};
+	let day = |day: u32| format!("2026-01-{day:02}T00:00:00Z");
+
+	format!(
+		r#"{{
+			"projects": [
+				{{ "id": "quests", "name": "Quests", "content_type": "datapacks", "versions": [{}] }},
+				{{ "id": "loot", "name": "Loot", "content_type": "datapacks", "versions": [{}, {}] }}
+			]
+		}}"#,
+		version(
+			"quests-1",
+			"1.0.0",
+			"quests-1.zip",
+			&day(3),
+			r#"{ "project_id": "loot", "version_id": "loot-1", "kind": "required" }"#
+		),
+		version("loot-1", "1.0.0", "loot-1.zip", &day(1), ""),
+		version("loot-2", "2.0.0", "loot-2.zip", &day(2), ""),
+	)
+}
+
+#[tokio::test]
+async fn an_upgraded_datapack_dependency_keeps_its_world() {
+	let setup = Setup::new("dependency-upgrade-world", FILES, index).await;
+	let ctx = &setup.ctx;
+	let castle = WorldTarget::World("Castle".into());
+	setup.install("loot", "loot-2", castle.clone()).await;
+
+	let provider = ctx.providers.get(ProviderId::SelfHosted).unwrap();
+	let project = provider.get_project(&setup.id("quests"), ctx).await.unwrap();
+	let version = provider
+		.get_version(&project.id, &setup.id("quests-1"), ctx)
+		.await
+		.unwrap();
+	let plan = plan_dependencies(ProviderId::SelfHosted, &project, &version, setup.cluster_id, ctx)
+		.await
+		.unwrap();
+	assert_eq!(plan.upgrade.len(), 1);
+
+	// Not the world the pack that needs it goes into
+	apply_dependency_upgrade(
+		ProviderId::SelfHosted,
+		&plan.upgrade[0],
+		setup.cluster_id,
+		&WorldTarget::AllWorlds,
+		None,
+		ctx,
+	)
+	.await
+	.unwrap();
+
+	let links = PackageStore::list_linked_artifacts(setup.cluster_id, ctx)
+		.await
+		.unwrap();
+	let loot = links
+		.iter()
+		.find(|link| link.version_id.as_deref() == Some(setup.id("loot-1").as_str()))
+		.expect("downgraded loot is linked");
+	assert_eq!(WorldTarget::from_world(loot.target_world.clone()), castle);
+}
+
+#[tokio::test]
+async fn a_disabled_required_dependency_is_turned_back_on() {
+	let setup = Setup::new("dependency-disabled", FILES, index).await;
+	let ctx = &setup.ctx;
+	let loot = setup.install("loot", "loot-1", WorldTarget::AllWorlds).await;
+	PackageStore::set_artifact_enabled_to(setup.cluster_id, &loot, false, ctx)
+		.await
+		.unwrap();
+
+	let provider = ctx.providers.get(ProviderId::SelfHosted).unwrap();
+	let project = provider.get_project(&setup.id("quests"), ctx).await.unwrap();
+	let version = provider
+		.get_version(&project.id, &setup.id("quests-1"), ctx)
+		.await
+		.unwrap();
+	let plan = plan_dependencies(ProviderId::SelfHosted, &project, &version, setup.cluster_id, ctx)
+		.await
+		.unwrap();
+
+	assert!(plan.install.is_empty());
+	assert!(plan.upgrade.is_empty());
+	assert_eq!(
+		plan.enable.iter().map(|off| off.hash.as_str()).collect::<Vec<_>>(),
+		vec![loot.as_str()]
+	);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use oneclient_content::packages::{FileIdentity, PackageIndex, ProviderId, SearchFilters};
+use polyio::testing::ScratchDir as Scratch;
+
+const INDEX: &str = r#"{
+	"projects": [
+		{
+			"id": "toolbox",
+			"name": "Toolbox",
+			"summary": "Internal helpers",
+			"authors": ["Polyfrost"],
+			"versions": [
+				{
+					"id": "toolbox-1.1.0",
+					"version_number": "1.1.0",
+					"published": "2025-06-01T00:00:00Z",
+					"game_versions": ["1.21.4"],
+					"loaders": ["fabric"],
+					"files": [{ "file_name": "toolbox-1.1.0.jar", "url": "files/toolbox-1.1.0.jar", "sha1": "1111", "size": 4 }]
+				},
+				{
+					"id": "toolbox-1.2.0",
+					"version_number": "1.2.0",
+					"published": "2026-01-01T00:00:00Z",
+					"game_versions": ["1.21.4"],
+					"loaders": ["fabric"],
+					"files": [{ "file_name": "toolbox-1.2.0.jar", "url": "files/toolbox-1.2.0.jar", "sha1": "2222", "size": 4 }]
+				}
+			]
+		},
+		{
+			"id": "shiny",
+			"name": "Shiny Shaders",
+			"content_type": "shaderpacks",
+			"versions": []
+		}
+	]
+}"#;
+
+/// Answers every request with the index whatever the path
+async fn serve_index() -> String {
+	common::serve(&[], || INDEX.to_string()).await
+}
+
+#[tokio::test]
+async fn unconfigured_index_stays_out_of_lookups() {
+	let scratch = Scratch::new("self-hosted-off");
+	let ctx = common::ctx(&scratch).await;
+
+	assert!(!ctx.providers.remote_ids().contains(&ProviderId::SelfHosted));
+
+	let provider = ctx.providers.get(ProviderId::SelfHosted).unwrap();
+	assert!(provider.get_project("internal:toolbox", &ctx).await.is_err());
+}
+
+#[tokio::test]
+async fn index_serves_search_projects_and_versions() {
+	let scratch = Scratch::new("self-hosted-on");
+	let ctx = common::ctx(&scratch).await;
+	let url = serve_index().await;
+	let index = PackageIndex::new(&url);
+	ctx.providers.self_hosted().set_indexes(std::slice::from_ref(&index));
+	let id = |local: &str| format!("{}:{local}", index.id);
+	assert!(ctx.providers.remote_ids().contains(&ProviderId::SelfHosted));
+
+	let provider = ctx.providers.get(ProviderId::SelfHosted).unwrap();
+
+	let page = provider
+		.search(
+			&SearchFilters {
+				query: Some("helpers".into()),
+				..Default::default()
+			},
+			&ctx,
+		)
+		.await
+		.unwrap();
+	assert_eq!(page.total, 1);
+	assert_eq!(page.items[0].id, id("toolbox"));
+	assert_eq!(page.items[0].provider, ProviderId::SelfHosted);
+
+	let project = provider.get_project(&id("toolbox"), &ctx).await.unwrap();
+	assert_eq!(project.author, "Polyfrost");
+	assert_eq!(project.version_ids.len(), 2);
+
+	let versions = provider
+		.list_versions(&id("toolbox"), Some("1.21.4"), None, 0, 10, &ctx)
+		.await
+		.unwrap();
+	assert_eq!(versions.items[0].version_id, id("toolbox-1.2.0"), "newest first");
+
+	let version = provider
+		.get_version(&id("toolbox"), &id("toolbox-1.1.0"), &ctx)
+		.await
+		.unwrap();
+	assert_eq!(
+		version.files[0].url,
+		url.replace("index.json", "files/toolbox-1.1.0.jar")
+	);
+
+	let found = provider
+		.lookup_versions(&[FileIdentity::from_sha1("2222")], &ctx)
+		.await
+		.unwrap();
+	assert_eq!(found["2222"].version_id, id("toolbox-1.2.0"));
+}
+
+#[tokio::test]
+async fn the_same_ids_in_two_indexes_stay_apart() {
+	let scratch = Scratch::new("self-hosted-two");
+	let ctx = common::ctx(&scratch).await;
+	let first = PackageIndex::new(serve_index().await);
+	let second = PackageIndex::new(serve_index().await);
+	ctx.providers
+		.self_hosted()
+		.set_indexes(&[first.clone(), second.clone()]);
+
+	let provider = ctx.providers.get(ProviderId::SelfHosted).unwrap();
+	let page = provider
+		.search(
+			&SearchFilters {
+				query: Some("helpers".into()),
+				..Default::default()
+			},
+			&ctx,
+		)
+		.await
+		.unwrap();
+	let ids: Vec<&str> = page.items.iter().map(|item| item.id.as_str()).collect();
+	assert_eq!(ids.len(), 2);
+	assert!(ids.contains(&format!("{}:toolbox", first.id).as_str()));
+	assert!(ids.contains(&format!("{}:toolbox", second.id).as_str()));
+
+	ctx.providers.self_hosted().set_indexes(std::slice::from_ref(&first));
+	let project = format!("{}:toolbox", second.id);
+	assert!(provider.get_project(&project, &ctx).await.is_err(), "removed index");
+}
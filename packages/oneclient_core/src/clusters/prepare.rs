@@ -9,7 +9,8 @@ use oneclient_cluster::Cluster;
 use oneclient_cluster::ClusterError;
 use oneclient_cluster::ClusterStage;
 use crate::game::{
-    self, download_minecraft, download_version_info, get_loader_version, resolve_minecraft_version,
+    self, JavaRequirement, download_minecraft, download_version_info, get_loader_version,
+    resolve_minecraft_version,
 };
 use oneclient_java::JavaRuntime;
 use oneclient_mc::MetadataStore;
@@ -233,22 +234,17 @@ async fn install_cluster(
     )
     .await?;
 
-    let java_major = version_info
-        .java_version
-        .as_ref()
-        .map(|v| v.major_version)
-        .ok_or(ClusterError::MissingJavaVersion)?;
-
-    let java = if let Some(runtime) =
-        state.java.runtime_for_profile(profile.java_path.as_deref()).await?
-    {
-        runtime
-    } else {
-        state
-            .java
-            .prepare(java_major, search_for_java, auto_install_java, Some(progress))
-            .await?
-    };
+    let requirement = JavaRequirement::for_version(&version_info, cluster.mc_loader, &mc_version);
+    let java = crate::game::select_java(
+        state,
+        cluster,
+        &profile,
+        requirement,
+        search_for_java,
+        auto_install_java,
+        Some(progress),
+    )
+    .await?;
 
     download_minecraft(
         &state.services.mc(),
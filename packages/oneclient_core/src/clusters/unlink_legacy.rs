@@ -142,6 +142,7 @@ async fn adopt_dedicated(
 		entries.push(ManifestEntry {
 			path: relative,
 			hash: link.hash,
+			world: None,
 		});
 	}
 
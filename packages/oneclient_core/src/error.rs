@@ -99,6 +99,7 @@ impl LauncherError {
             || matches!(
                 self,
                 LauncherError::JavaError(oneclient_java::JavaError::Cancelled)
+                    | LauncherError::GameError(crate::game::GameError::AccountCancelled(_))
             )
     }
 
@@ -0,0 +1,154 @@
+//! Which account a cluster launches with A cluster can keep its own account
+//! say an alt for one server and falls back to the launcher default otherwise
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use oneclient_auth::{AuthError, MinecraftAccount};
+use oneclient_db::dao::cluster_account as cluster_account_dao;
+use oneclient_events::{Choice, Prompt};
+
+use crate::game::GameError;
+use crate::state::LauncherState;
+use crate::{LauncherError, LauncherResult};
+
+/// `None` when the cluster uses the default account A stored id that no longer
+/// parses is treated the same
+pub async fn cluster_account(
+    state: &LauncherState,
+    cluster_id: i64,
+) -> LauncherResult<Option<Uuid>> {
+    let stored = cluster_account_dao::get(&state.services.db, cluster_id).await?;
+    Ok(stored.and_then(|id| Uuid::parse_str(&id).ok()))
+}
+
+/// `None` goes back to the default account
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn set_cluster_account(
+    state: &LauncherState,
+    cluster_id: i64,
+    account_id: Option<Uuid>,
+) -> LauncherResult<()> {
+    let db = &state.services.db;
+    match account_id {
+        Some(id) => {
+            if state.auth.get_account(id).await.is_none() {
+                return Err(AuthError::AccountNotFound(id).into());
+            }
+            cluster_account_dao::set(db, cluster_id, &id.to_string()).await?;
+        }
+        None => cluster_account_dao::clear(db, cluster_id).await?,
+    }
+    Ok(())
+}
+
+/// Clusters that preferred the account go back to the default so none of them
+/// asks about it at its next launch
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn remove_account(state: &LauncherState, account_id: Uuid) -> LauncherResult<()> {
+    state.auth.remove_account(account_id).await?;
+    let cleared =
+        cluster_account_dao::clear_account(&state.services.db, &account_id.to_string()).await?;
+    if cleared > 0 {
+        tracing::info!(%account_id, cleared, "clusters fell back to the default account");
+    }
+    Ok(())
+}
+
+enum Unusable {
+    Removed,
+    SignInFailed { username: String, err: AuthError },
+}
+
+impl Unusable {
+    fn into_error(self, account_id: Uuid) -> LauncherError {
+        match self {
+            Unusable::Removed => AuthError::AccountNotFound(account_id).into(),
+            Unusable::SignInFailed { err, .. } => err.into(),
+        }
+    }
+}
+
+/// The cluster's own account when it has one and it can sign in otherwise the
+/// user is asked whether to play as the default account this time
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn account_for_cluster(
+    state: &Arc<LauncherState>,
+    cluster_id: i64,
+) -> LauncherResult<Option<MinecraftAccount>> {
+    let Some(preferred) = cluster_account(state, cluster_id).await? else {
+        return Ok(state.auth.default_account_for_launch().await?);
+    };
+
+    let problem = match state.auth.get_account(preferred).await {
+        None => Unusable::Removed,
+        Some(account) => match state.auth.account_for_launch(preferred).await {
+            Ok(account) => return Ok(Some(account)),
+            // Nothing about this account in particular the default would fail
+            // the same way
+            Err(err @ (AuthError::CredentialsLocked | AuthError::WrongPassphrase)) => {
+                return Err(err.into());
+            }
+            Err(err) => Unusable::SignInFailed {
+                username: account.username,
+                err,
+            },
+        },
+    };
+
+    let cluster = state.clusters.get(cluster_id).await?;
+    let default = state
+        .auth
+        .default_account()
+        .await?
+        .filter(|account| account.id != preferred);
+
+    let (title, body) = match &problem {
+        Unusable::Removed => (
+            format!("The account for {} was removed", cluster.name),
+            format!(
+                "{} was set to launch with an account that is no longer signed in.",
+                cluster.name
+            ),
+        ),
+        Unusable::SignInFailed { username, err } => (
+            format!("{username} could not sign in"),
+            format!(
+                "{} launches as {username} but its sign-in failed: {err}. Sign in again from Accounts to keep using it.",
+                cluster.name
+            ),
+        ),
+    };
+
+    let Some(default) = default else {
+        return Err(problem.into_error(preferred));
+    };
+
+    let prompt = Prompt::new(title, body)
+        .option(
+            Choice::primary(
+                "account.use_default",
+                format!("Play as {} this time", default.username),
+            ),
+            (),
+        )
+        .dismiss("Cancel");
+
+    match state.services.events.ask(prompt).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(GameError::AccountCancelled(cluster.name).into()),
+        Err(err) => {
+            tracing::warn!(cluster_id, "could not offer the default account: {err}");
+            return Err(problem.into_error(preferred));
+        }
+    }
+
+    // A removed account is not coming back an expired one can be signed into
+    // again so its preference stays
+    if matches!(problem, Unusable::Removed) {
+        set_cluster_account(state, cluster_id, None).await?;
+    }
+
+    Ok(Some(state.auth.account_for_launch(default.id).await?))
+}
@@ -4,6 +4,7 @@ use std::net::IpAddr;
 use chrono::{DateTime, Datelike, Local, Timelike};
 use oneclient_db::dao::game_session as session_dao;
 use oneclient_db::models::{GameSessionServerRow, SessionSpan};
+use oneclient_mc::QuickPlay;
 
 use crate::error::LauncherResult;
 
@@ -162,6 +163,26 @@ pub struct ServerStat {
 	pub is_ip: bool,
 }
 
+impl ServerStat {
+	#[must_use]
+	pub fn quick_play(&self) -> QuickPlay {
+		QuickPlay::server(
+			&self.address,
+			self.port.and_then(|port| u16::try_from(port).ok()),
+		)
+	}
+}
+
+/// What a one-click rejoin goes back to the most recent join not the most
+/// played server
+#[must_use]
+pub fn last_played_server(servers: &[ServerStat]) -> Option<&ServerStat> {
+	servers
+		.iter()
+		.filter(|server| server.last_played.is_some())
+		.max_by(|a, b| a.last_played.cmp(&b.last_played))
+}
+
 fn address_is_ip(address: &str) -> bool {
 	address.parse::<IpAddr>().is_ok()
 }
@@ -331,6 +352,32 @@ mod tests {
 		assert!(servers[1].is_ip);
 	}
 
+	#[test]
+	fn the_last_joined_server_is_rejoined_not_the_most_played() {
+		let rows = vec![
+			server(
+				"mc.hypixel.net",
+				"2026-01-01T10:00:00+00:00",
+				Some("2026-01-01T14:00:00+00:00"),
+			),
+			GameSessionServerRow {
+				port: Some(25570),
+				..server("play.example.com", "2026-01-03T10:00:00+00:00", None)
+			},
+		];
+
+		let servers = aggregate_servers(&rows);
+		let last = last_played_server(&servers).expect("a server was joined");
+		assert_eq!(servers[0].address, "mc.hypixel.net");
+		assert_eq!(
+			last.quick_play(),
+			QuickPlay::Multiplayer {
+				address: "play.example.com:25570".to_string()
+			}
+		);
+		assert!(last_played_server(&[]).is_none());
+	}
+
 	#[test]
 	fn gamer_needs_five_hour_average() {
 		let spans = vec![span("2026-01-01T12:00:00+00:00", "2026-01-01T18:00:00+00:00")];
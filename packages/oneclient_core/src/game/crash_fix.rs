@@ -0,0 +1,269 @@
+//! Every fix changes the cluster so it is offered after a crash and never
+//! applied without the user agreeing to it
+
+use std::sync::Arc;
+
+use oneclient_cluster::ProfileUpdate;
+use oneclient_common::patch::Patch;
+use oneclient_content::packages::audit::{self, ModAuditKind};
+use oneclient_content::packages::jar_metadata;
+use oneclient_events::{Choice, Prompt};
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+
+use crate::LauncherResult;
+use crate::game::GameError;
+use crate::game::diagnosis::{CrashDiagnosis, CrashFix};
+use crate::state::LauncherState;
+
+/// A cluster's share of memory stops here the OS and the launcher need the rest
+const MEMORY_SHARE: (u64, u64) = (3, 4);
+
+/// Asks rather than acting verification can re-download much of the game and
+/// the other fixes change the cluster's settings or content
+/// The offer returns on the next crash
+#[tracing::instrument(skip(state, diagnosis), level = "debug")]
+pub async fn offer_crash_fix(
+    state: &Arc<LauncherState>,
+    cluster_id: i64,
+    diagnosis: &CrashDiagnosis,
+) {
+    let Some(fix) = diagnosis.fix() else {
+        // Nothing to change here the explanation is still worth having
+        state
+            .services
+            .events
+            .notify(diagnosis.title())
+            .body(diagnosis.body())
+            .error()
+            .send();
+        return;
+    };
+
+    enum Answer {
+        Apply,
+    }
+
+    let answer = state
+        .services
+        .events
+        .ask(
+            Prompt::new(diagnosis.title(), diagnosis.body())
+                .option(Choice::primary("fix", fix.label()), Answer::Apply)
+                .dismiss("Not now"),
+        )
+        .await;
+
+    match answer {
+        Ok(Some(_)) => {}
+        // Dismissed or nobody there to ask neither is consent to change the cluster
+        Ok(None) => {
+            tracing::info!(cluster_id, ?fix, "user declined the post-crash fix");
+            return;
+        }
+        Err(err) => {
+            tracing::warn!(cluster_id, "could not offer a post-crash fix: {err}");
+            return;
+        }
+    }
+
+    match apply_crash_fix(state, cluster_id, &fix).await {
+        Ok(summary) => {
+            state
+                .services
+                .events
+                .notify("Fix applied")
+                .body(summary)
+                .send();
+        }
+        Err(err) => {
+            tracing::error!(cluster_id, ?fix, "post-crash fix failed: {err:#}");
+            state
+                .services
+                .events
+                .notify("Fix failed")
+                .body(err.to_string())
+                .error()
+                .send();
+        }
+    }
+}
+
+/// The summary is what the user is told changed
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn apply_crash_fix(
+    state: &Arc<LauncherState>,
+    cluster_id: i64,
+    fix: &CrashFix,
+) -> LauncherResult<String> {
+    match fix {
+        CrashFix::VerifyFiles => {
+            let report = crate::verify::verify_cluster_files(state, cluster_id).await?;
+            Ok(report.summary())
+        }
+        CrashFix::SwitchJava { major } => {
+            // Offers to install the runtime when none is found
+            let runtime = state.java.prepare(*major, true, false, None).await?;
+            update_cluster_profile(
+                state,
+                cluster_id,
+                ProfileUpdate {
+                    java_path: Patch::Set(runtime.absolute_path.clone()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            Ok(format!(
+                "The cluster now runs on Java {} ({})",
+                runtime.major, runtime.version
+            ))
+        }
+        CrashFix::RaiseMemory => {
+            let cluster = state.clusters.get(cluster_id).await?;
+            let global = state.settings.read().global_game_settings.clone();
+            let current = state
+                .clusters
+                .resolve_settings(&global, &cluster)
+                .await?
+                .mem_max
+                .unwrap_or(2048);
+
+            let raised = raised_memory(current, total_memory_mb());
+            if raised <= current {
+                return Err(GameError::CrashFixUnavailable(format!(
+                    "{current} MB is already as much memory as this computer can spare"
+                ))
+                .into());
+            }
+
+            update_cluster_profile(
+                state,
+                cluster_id,
+                ProfileUpdate {
+                    mem_max: Patch::Set(raised),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            Ok(format!("Memory raised from {current} MB to {raised} MB"))
+        }
+        CrashFix::DisableMod { mod_id } => {
+            let ctx = state.services.content();
+            let hashes: Vec<String> = jar_metadata::cluster_jar_metadata(cluster_id, &ctx)
+                .await?
+                .into_iter()
+                .filter(|(_, metadata)| metadata.iter().any(|m| m.mod_id == *mod_id))
+                .map(|(link, _)| link.hash)
+                .collect();
+
+            if hashes.is_empty() {
+                return Err(GameError::CrashFixUnavailable(format!(
+                    "{mod_id} is not an enabled mod in this cluster"
+                ))
+                .into());
+            }
+            disable_artifacts(cluster_id, &hashes, &ctx).await?;
+            Ok(format!("Disabled {mod_id}"))
+        }
+        CrashFix::DisableDuplicates { mod_id } => {
+            let cluster = state.clusters.get(cluster_id).await?;
+            let ctx = state.services.content();
+            let mods = jar_metadata::cluster_jar_metadata(cluster_id, &ctx).await?;
+
+            let jars: Vec<(String, Vec<_>)> = mods
+                .iter()
+                .map(|(link, metadata)| (link.cluster_file_name.clone(), metadata.clone()))
+                .collect();
+            let hashes: Vec<String> =
+                audit::audit_mods(&jars, cluster.mc_loader, &cluster.mc_version)
+                    .into_iter()
+                    .filter(|finding| match &finding.kind {
+                        ModAuditKind::DuplicateModId { mod_id: found, .. } => {
+                            mod_id.as_ref().is_none_or(|wanted| wanted == found)
+                        }
+                        _ => false,
+                    })
+                    .filter_map(|finding| {
+                        mods.iter()
+                            .find(|(link, _)| link.cluster_file_name == finding.file_name)
+                            .map(|(link, _)| link.hash.clone())
+                    })
+                    .collect();
+
+            if hashes.is_empty() {
+                return Err(GameError::CrashFixUnavailable(
+                    "no duplicate mods are enabled in this cluster".to_string(),
+                )
+                .into());
+            }
+            disable_artifacts(cluster_id, &hashes, &ctx).await?;
+            Ok(format!("Disabled {} older duplicate mod(s)", hashes.len()))
+        }
+    }
+}
+
+/// Clusters made before named profiles had none the first change gives them one
+pub(crate) async fn update_cluster_profile(
+    state: &Arc<LauncherState>,
+    cluster_id: i64,
+    update: ProfileUpdate,
+) -> LauncherResult<()> {
+    let cluster = state.clusters.get(cluster_id).await?;
+    if cluster.setting_profile_name.is_none() {
+        let global = state.settings.read().global_game_settings.clone();
+        state
+            .clusters
+            .create_and_assign_profile(&global, cluster_id, &cluster.name)
+            .await?;
+    }
+
+    state.clusters.update_profile(cluster_id, update).await?;
+    Ok(())
+}
+
+/// Disabled through the bundle layer so a bundle update does not turn them
+/// straight back on
+async fn disable_artifacts(
+    cluster_id: i64,
+    hashes: &[String],
+    ctx: &oneclient_content::ContentCtx,
+) -> LauncherResult<()> {
+    for hash in hashes {
+        oneclient_content::bundles::set_artifact_enabled_to(cluster_id, hash, false, ctx).await?;
+    }
+    Ok(())
+}
+
+fn total_memory_mb() -> Option<u64> {
+    let system = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()),
+    );
+    let total = system.total_memory() / 1024 / 1024;
+    (total > 0).then_some(total)
+}
+
+/// Doubles and rounds to whole 512 MB steps capped at [`MEMORY_SHARE`] of the
+/// machine when its size is known
+fn raised_memory(current: u32, total_mb: Option<u64>) -> u32 {
+    let doubled = u64::from(current.max(512)) * 2;
+    let capped = total_mb.map_or(doubled, |total| {
+        doubled.min(total * MEMORY_SHARE.0 / MEMORY_SHARE.1)
+    });
+    let rounded = capped / 512 * 512;
+    u32::try_from(rounded).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_doubles_within_the_machine() {
+        assert_eq!(raised_memory(2048, Some(16384)), 4096);
+        assert_eq!(raised_memory(4096, None), 8192);
+        assert_eq!(
+            raised_memory(6144, Some(8192)),
+            6144,
+            "three quarters of 8 GB"
+        );
+    }
+}
@@ -1,7 +1,11 @@
 //! Minecraft's exit code says only that it died the log says why and some
 //! reasons are ones the launcher can fix
 
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CrashDiagnosis {
@@ -12,9 +16,66 @@ pub enum CrashDiagnosis {
         /// something to depend on
         file: Option<String>,
     },
+    /// Class files newer than the JVM running them usually a Java pinned on
+    /// the profile or a mod built for a newer Java than the game asks for
+    WrongJava {
+        required: u32,
+        running: Option<u32>,
+    },
+    OutOfMemory,
+    /// `dependency` is `minecraft` itself for a mod built for another version
+    MissingDependency { mod_id: String, dependency: String },
+    /// At least one of the two is known a line naming neither is not matched
+    MixinFailure {
+        mod_id: Option<String>,
+        config: Option<String>,
+    },
+    /// Some loaders only say that there are duplicates not which
+    DuplicateMod { mod_id: Option<String> },
+    GraphicsDriver,
+    PortInUse { port: Option<u16> },
+}
+
+/// What the launcher can change on the user's behalf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrashFix {
+    VerifyFiles,
+    /// Pins the runtime on the cluster's profile
+    SwitchJava { major: u32 },
+    RaiseMemory,
+    DisableMod { mod_id: String },
+    /// Keeps the newest copy `None` covers every duplicated id
+    DisableDuplicates { mod_id: Option<String> },
+}
+
+impl CrashFix {
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self {
+            Self::VerifyFiles => "Verify and repair".to_string(),
+            Self::SwitchJava { major } => format!("Switch to Java {major}"),
+            Self::RaiseMemory => "Raise memory".to_string(),
+            Self::DisableMod { mod_id } => format!("Disable {mod_id}"),
+            Self::DisableDuplicates { .. } => "Disable duplicates".to_string(),
+        }
+    }
 }
 
 impl CrashDiagnosis {
+    #[must_use]
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::CorruptArchive { .. } => "A damaged file crashed the game",
+            Self::WrongJava { .. } => "The game needs a newer Java",
+            Self::OutOfMemory => "The game ran out of memory",
+            Self::MissingDependency { .. } => "A mod is missing a dependency",
+            Self::MixinFailure { .. } => "A mod failed to patch the game",
+            Self::DuplicateMod { .. } => "A mod is installed twice",
+            Self::GraphicsDriver => "The graphics driver failed",
+            Self::PortInUse { .. } => "A network port is already in use",
+        }
+    }
+
     #[must_use]
     pub fn body(&self) -> String {
         match self {
@@ -26,6 +87,87 @@ impl CrashDiagnosis {
                  library or mod files — it is damaged. Verifying will re-download \
                  anything that does not match."
                 .to_string(),
+            Self::WrongJava {
+                required,
+                running: Some(running),
+            } => format!(
+                "Part of the game needs Java {required} but it ran on Java {running}. \
+                 Switching this cluster to Java {required} lets it start."
+            ),
+            Self::WrongJava {
+                required,
+                running: None,
+            } => format!(
+                "Part of the game needs Java {required} or newer. \
+                 Switching this cluster to Java {required} lets it start."
+            ),
+            Self::OutOfMemory => "The game used all the memory it was given. \
+                 Raising the memory limit for this cluster usually fixes this."
+                .to_string(),
+            Self::MissingDependency { mod_id, dependency } if dependency == "minecraft" => format!(
+                "{mod_id} was built for a different Minecraft version. \
+                 Disabling it lets the game start."
+            ),
+            Self::MissingDependency { mod_id, dependency } => format!(
+                "{mod_id} needs {dependency}, which is missing or the wrong version. \
+                 Install it from the browser, or disable {mod_id} to start without it."
+            ),
+            Self::MixinFailure {
+                mod_id: Some(mod_id),
+                ..
+            } => format!(
+                "{mod_id} could not patch the game — it likely does not support this \
+                 Minecraft version or clashes with another mod. Disabling it lets the game start."
+            ),
+            Self::MixinFailure {
+                mod_id: None,
+                config,
+            } => format!(
+                "A mod could not patch the game ({}). Update or remove it and try again.",
+                config.as_deref().unwrap_or("unknown mixin")
+            ),
+            Self::DuplicateMod {
+                mod_id: Some(mod_id),
+            } => format!(
+                "{mod_id} is installed more than once. \
+                 Disabling the older copies lets the game start."
+            ),
+            Self::DuplicateMod { mod_id: None } => "A mod is installed more than once. \
+                 Disabling the older copies lets the game start."
+                .to_string(),
+            Self::GraphicsDriver => "The game could not start OpenGL — the graphics driver \
+                 is missing or outdated, or the game ran on the wrong GPU. \
+                 Update your graphics driver and try again."
+                .to_string(),
+            Self::PortInUse { port: Some(port) } => format!(
+                "Port {port} is already taken by another program, often a second copy of \
+                 the game or a server. Close it and try again."
+            ),
+            Self::PortInUse { port: None } => "A network port the game needs is already \
+                 taken by another program, often a second copy of the game or a server. \
+                 Close it and try again."
+                .to_string(),
+        }
+    }
+
+    /// `None` when the cause is outside the launcher's reach a driver or
+    /// another program
+    #[must_use]
+    pub fn fix(&self) -> Option<CrashFix> {
+        match self {
+            Self::CorruptArchive { .. } => Some(CrashFix::VerifyFiles),
+            Self::WrongJava { required, .. } => Some(CrashFix::SwitchJava { major: *required }),
+            Self::OutOfMemory => Some(CrashFix::RaiseMemory),
+            Self::MissingDependency { mod_id, .. } => Some(CrashFix::DisableMod {
+                mod_id: mod_id.clone(),
+            }),
+            Self::MixinFailure { mod_id, .. } => mod_id
+                .clone()
+                .map(|mod_id| CrashFix::DisableMod { mod_id }),
+            Self::DuplicateMod { mod_id } => Some(CrashFix::DisableDuplicates {
+                mod_id: mod_id.clone(),
+            }),
+            Self::GraphicsDriver | Self::PortInUse { .. } => None,
         }
     }
 }
@@ -39,10 +181,101 @@ const CORRUPT_ARCHIVE_MARKERS: [&str; 4] = [
     "zip END header not found",
 ];
 
-/// Runs against every line the game prints so it stays substring scans rather
-/// than anything that parses the line
+/// Fabric prints `for mod` and `from mod` Forge names only the config
+const MIXIN_MARKERS: [&str; 5] = [
+    "Mixin apply for mod",
+    "Mixin apply failed",
+    "FAILED during APPLY",
+    "Critical injection failure",
+    "MixinApplyError",
+];
+
+const DUPLICATE_MARKERS: [&str; 5] = [
+    "is present in multiple files",
+    "is present multiple times",
+    "Duplicate mod",
+    "Found duplicate mods",
+    "DuplicateModsFoundException",
+];
+
+/// GLFW's "no usable OpenGL" errors and the driver DLLs native crashes land in
+const GRAPHICS_MARKERS: [&str; 12] = [
+    "GLFW error 65542",
+    "GLFW error 65543",
+    "Pixel format not accelerated",
+    "driver does not appear to support OpenGL",
+    "Could not create context",
+    "atio6axx.dll",
+    "atioglxx.dll",
+    "nvoglv64.dll",
+    "nvoglv32.dll",
+    "ig9icd64.dll",
+    "ig75icd64.dll",
+    "libnvidia-glcore",
+];
+
+const PORT_MARKERS: [&str; 2] = ["Address already in use", "FAILED TO BIND TO PORT"];
+
+/// Tried in order on each line the first match wins
+const RULES: [fn(&str) -> Option<CrashDiagnosis>; 8] = [
+    corrupt_archive,
+    wrong_java,
+    out_of_memory,
+    missing_dependency,
+    mixin_failure,
+    duplicate_mod,
+    graphics_driver,
+    port_in_use,
+];
+
+/// Runs against every line the game prints so each rule rejects on a
+/// substring before it parses anything
 #[must_use]
 pub fn diagnose(line: &str) -> Option<CrashDiagnosis> {
+    RULES.iter().find_map(|rule| rule(line))
+}
+
+/// The earliest recognised line in a whole log or crash report
+#[must_use]
+pub fn diagnose_text(text: &str) -> Option<CrashDiagnosis> {
+    text.lines().find_map(diagnose)
+}
+
+/// Only the newest report written since `since` an older one is from a
+/// crash the user already saw
+pub(crate) async fn diagnose_crash_reports(
+    game_dir: &Path,
+    since: DateTime<Utc>,
+) -> Option<CrashDiagnosis> {
+    let mut entries = polyio::read_dir(game_dir.join("crash-reports")).await.ok()?;
+
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "txt") {
+            continue;
+        }
+        let Ok(modified) = entry.metadata().await.and_then(|meta| meta.modified()) else {
+            continue;
+        };
+        if DateTime::<Utc>::from(modified) < since {
+            continue;
+        }
+        if newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, path));
+        }
+    }
+
+    let (_, path) = newest?;
+    let report = polyio::read_to_string(&path).await.ok()?;
+    let diagnosis = diagnose_text(&report);
+    if diagnosis.is_some() {
+        tracing::warn!(report = %path.display(), ?diagnosis, "recognised a crash cause in the crash report");
+    }
+    diagnosis
+}
+
+fn corrupt_archive(line: &str) -> Option<CrashDiagnosis> {
     if !CORRUPT_ARCHIVE_MARKERS
         .iter()
         .any(|marker| line.contains(marker))
@@ -55,6 +288,163 @@ pub fn diagnose(line: &str) -> Option<CrashDiagnosis> {
     })
 }
 
+/// Class file versions are the Java major plus 44
+fn wrong_java(line: &str) -> Option<CrashDiagnosis> {
+    if !line.contains("UnsupportedClassVersionError") {
+        return None;
+    }
+
+    let required = class_file_major(line, "class file version ")
+        .or_else(|| class_file_major(line, "major.minor version "))?;
+    Some(CrashDiagnosis::WrongJava {
+        required,
+        running: class_file_major(line, "versions up to "),
+    })
+}
+
+fn class_file_major(line: &str, label: &str) -> Option<u32> {
+    let digits: String = after(line, label)?
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse::<u32>().ok()?.checked_sub(44)
+}
+
+/// Running out of threads is an OS limit more heap would not help
+fn out_of_memory(line: &str) -> Option<CrashDiagnosis> {
+    (line.contains("java.lang.OutOfMemoryError") && !line.contains("native thread"))
+        .then_some(CrashDiagnosis::OutOfMemory)
+}
+
+fn missing_dependency(line: &str) -> Option<CrashDiagnosis> {
+    let line = line.trim().trim_start_matches(['-', ' ', '\t']);
+
+    // Forge 1.17 to 1.20
+    // `Mod ID: 'architectury', Requested by: 'rei', Expected range: '[9.1,)', Actual version: '[MISSING]'`
+    if let Some(rest) = after(line, "Mod ID: '")
+        && let Some(requested) = after(line, "Requested by: '")
+    {
+        return missing(until(requested, '\''), until(rest, '\''));
+    }
+
+    // Fabric 0.12 and later
+    // `Mod 'Sodium Extra' (sodium-extra) 0.5.1 requires any version of fabric-api, which is missing!`
+    if line.starts_with("Mod '")
+        && (line.contains("which is missing") || line.contains("wrong version is present"))
+    {
+        let mod_id = until(after(line, "(")?, ')');
+        let requirement = after(line, " requires ")?;
+        let target = until(&requirement[requirement.rfind(" of ")? + 4..], ',');
+        let dependency = match after(target, "(") {
+            Some(id) => until(id, ')'),
+            None => target.trim_start_matches("mod ").trim_matches('\''),
+        };
+        return missing(mod_id, dependency);
+    }
+
+    // Fabric before 0.12
+    // `Could not find required mod: sodium-extra requires {sodium @ [>=0.4]}`
+    if let Some(rest) = after(line, "Could not find required mod: ") {
+        let (mod_id, requirement) = rest.split_once(" requires ")?;
+        let dependency = requirement
+            .trim_start_matches('{')
+            .split([' ', '@', '}'])
+            .next()?;
+        return missing(mod_id, dependency);
+    }
+
+    // Forge 1.12 `Mod jei (Just Enough Items) requires [forge@[14.23,)]`
+    // and NeoForge `Mod rei requires architectury 9.1.12 or above`
+    let rest = after(line, "MissingModsException: ").unwrap_or(line);
+    if let Some(rest) = rest.strip_prefix("Mod ")
+        && !rest.starts_with('\'')
+    {
+        let (subject, requirement) = rest.split_once(" requires ")?;
+        let dependency = requirement
+            .trim_start_matches('[')
+            .split(['@', ' ', ']'])
+            .next()?;
+        return missing(subject.split_whitespace().next()?, dependency);
+    }
+
+    None
+}
+
+fn missing(mod_id: &str, dependency: &str) -> Option<CrashDiagnosis> {
+    let (mod_id, dependency) = (mod_id.trim(), dependency.trim());
+    (!mod_id.is_empty() && !dependency.is_empty()).then(|| CrashDiagnosis::MissingDependency {
+        mod_id: mod_id.to_string(),
+        dependency: dependency.to_string(),
+    })
+}
+
+fn mixin_failure(line: &str) -> Option<CrashDiagnosis> {
+    if !MIXIN_MARKERS.iter().any(|marker| line.contains(marker)) {
+        return None;
+    }
+
+    let mod_id = after(line, "for mod ")
+        .or_else(|| after(line, "from mod "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|id| id.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-'))
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    let config = line
+        .split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')' | ':'))
+        .find(|token| token.ends_with(".mixins.json") || token.ends_with(".mixin.json"))
+        .map(str::to_string);
+
+    (mod_id.is_some() || config.is_some())
+        .then_some(CrashDiagnosis::MixinFailure { mod_id, config })
+}
+
+fn duplicate_mod(line: &str) -> Option<CrashDiagnosis> {
+    if !DUPLICATE_MARKERS.iter().any(|marker| line.contains(marker)) {
+        return None;
+    }
+
+    let quoted = ['\'', '`']
+        .into_iter()
+        .find_map(|quote| after(line, &quote.to_string()).map(|rest| until(rest, quote)));
+    let mod_id = quoted
+        .or_else(|| after(line, "Mod ").and_then(|rest| rest.split_whitespace().next()))
+        .filter(|id| !id.is_empty() && *id != "ID")
+        .map(str::to_string);
+
+    Some(CrashDiagnosis::DuplicateMod { mod_id })
+}
+
+fn graphics_driver(line: &str) -> Option<CrashDiagnosis> {
+    GRAPHICS_MARKERS
+        .iter()
+        .any(|marker| line.contains(marker))
+        .then_some(CrashDiagnosis::GraphicsDriver)
+}
+
+fn port_in_use(line: &str) -> Option<CrashDiagnosis> {
+    if !PORT_MARKERS.iter().any(|marker| line.contains(marker)) {
+        return None;
+    }
+
+    let lower = line.to_ascii_lowercase();
+    let port = after(&lower, "port")
+        .map(|rest| rest.trim_start_matches([' ', ':', '=', '!']))
+        .and_then(|rest| {
+            let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        });
+
+    Some(CrashDiagnosis::PortInUse { port })
+}
+
+fn after<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    line.find(label).map(|at| &line[at + label.len()..])
+}
+
+fn until(text: &str, end: char) -> &str {
+    text.split(end).next().unwrap_or(text)
+}
+
 /// Opportunistic some JVMs omit the path entirely and a missing name still
 /// leads to the same repair
 fn jar_in(line: &str) -> Option<String> {
@@ -97,7 +487,7 @@ impl CrashWatch {
             && let Ok(mut found) = self.found.lock()
             && found.is_none()
         {
-            tracing::warn!(?diagnosis, "recognised a repairable crash cause in the game log");
+            tracing::warn!(?diagnosis, "recognised a crash cause in the game log");
             *found = Some(diagnosis);
         }
     }
@@ -151,6 +541,209 @@ mod tests {
         );
     }
 
+    #[test]
+    fn a_class_version_error_names_both_javas() {
+        let line = "Exception in thread \"main\" java.lang.UnsupportedClassVersionError: \
+                    net/minecraft/client/main/Main has been compiled by a more recent version of \
+                    the Java Runtime (class file version 65.0), this version of the Java Runtime \
+                    only recognizes class file versions up to 52.0";
+
+        let diagnosis = diagnose(line);
+        assert_eq!(
+            diagnosis,
+            Some(CrashDiagnosis::WrongJava {
+                required: 21,
+                running: Some(8),
+            })
+        );
+        assert_eq!(
+            diagnosis.and_then(|d| d.fix()),
+            Some(CrashFix::SwitchJava { major: 21 })
+        );
+    }
+
+    #[test]
+    fn an_old_jvm_class_version_error_still_names_the_requirement() {
+        let line = "java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main : \
+                    Unsupported major.minor version 52.0";
+
+        assert_eq!(
+            diagnose(line),
+            Some(CrashDiagnosis::WrongJava {
+                required: 8,
+                running: None,
+            })
+        );
+    }
+
+    #[test]
+    fn heap_exhaustion_asks_for_more_memory() {
+        for line in [
+            "java.lang.OutOfMemoryError: Java heap space",
+            "Caused by: java.lang.OutOfMemoryError: GC overhead limit exceeded",
+        ] {
+            assert_eq!(diagnose(line), Some(CrashDiagnosis::OutOfMemory), "{line}");
+        }
+        assert_eq!(
+            diagnose("java.lang.OutOfMemoryError: unable to create native thread"),
+            None
+        );
+    }
+
+    #[test]
+    fn fabric_missing_dependencies_name_the_mod_and_what_it_needs() {
+        let missing = "\t - Mod 'Sodium Extra' (sodium-extra) 0.5.1 requires any version of \
+                       fabric-api, which is missing!";
+        assert_eq!(
+            diagnose(missing),
+            Some(CrashDiagnosis::MissingDependency {
+                mod_id: "sodium-extra".to_string(),
+                dependency: "fabric-api".to_string(),
+            })
+        );
+
+        let wrong = "\t - Mod 'Iris' (iris) 1.7.0+mc1.20.6 requires version 0.5.8 or later of \
+                     mod 'Sodium' (sodium), but only the wrong version is present: 0.5.3!";
+        assert_eq!(
+            diagnose(wrong),
+            Some(CrashDiagnosis::MissingDependency {
+                mod_id: "iris".to_string(),
+                dependency: "sodium".to_string(),
+            })
+        );
+
+        let minecraft = " - Mod 'Mod Menu' (modmenu) 9.0.0 requires any version between 1.20 \
+                         (inclusive) and 1.21 (exclusive) of minecraft, but only the wrong \
+                         version is present: 1.21.4!";
+        assert_eq!(
+            diagnose(minecraft),
+            Some(CrashDiagnosis::MissingDependency {
+                mod_id: "modmenu".to_string(),
+                dependency: "minecraft".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn forge_missing_dependencies_in_every_era() {
+        let cases = [
+            (
+                "\tMod ID: 'architectury', Requested by: 'rei', Expected range: '[9.1.12,)', \
+                 Actual version: '[MISSING]'",
+                "rei",
+                "architectury",
+            ),
+            (
+                "net.minecraftforge.fml.common.MissingModsException: Mod jei (Just Enough Items) \
+                 requires [forge@[14.23.5.2816,)]",
+                "jei",
+                "forge",
+            ),
+            ("Mod rei requires architectury 9.1.12 or above", "rei", "architectury"),
+            (
+                "Could not find required mod: sodium-extra requires {sodium @ [>=0.4]}",
+                "sodium-extra",
+                "sodium",
+            ),
+        ];
+
+        for (line, mod_id, dependency) in cases {
+            assert_eq!(
+                diagnose(line),
+                Some(CrashDiagnosis::MissingDependency {
+                    mod_id: mod_id.to_string(),
+                    dependency: dependency.to_string(),
+                }),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn mixin_failures_name_the_mod_when_the_loader_does() {
+        let fabric = "[main/ERROR]: Mixin apply for mod iris failed iris.mixins.json:MixinLevelRenderer \
+                      from mod iris -> net.minecraft.class_761: \
+                      org.spongepowered.asm.mixin.injection.throwables.InvalidInjectionException";
+        assert_eq!(
+            diagnose(fabric),
+            Some(CrashDiagnosis::MixinFailure {
+                mod_id: Some("iris".to_string()),
+                config: Some("iris.mixins.json".to_string()),
+            })
+        );
+        assert_eq!(
+            diagnose(fabric).and_then(|d| d.fix()),
+            Some(CrashFix::DisableMod {
+                mod_id: "iris".to_string(),
+            })
+        );
+
+        let forge = "Mixin [create.mixins.json:accessor.DispenserAccessor] from phase [DEFAULT] \
+                     in config [create.mixins.json] FAILED during APPLY";
+        assert_eq!(
+            diagnose(forge),
+            Some(CrashDiagnosis::MixinFailure {
+                mod_id: None,
+                config: Some("create.mixins.json".to_string()),
+            })
+        );
+        assert_eq!(diagnose(forge).and_then(|d| d.fix()), None);
+    }
+
+    #[test]
+    fn duplicate_mods_are_recognised() {
+        assert_eq!(
+            diagnose("Mod jei is present in multiple files: jei-1.jar, jei-2.jar"),
+            Some(CrashDiagnosis::DuplicateMod {
+                mod_id: Some("jei".to_string()),
+            })
+        );
+        assert_eq!(
+            diagnose("java.lang.RuntimeException: Duplicate mod ID 'sodium'"),
+            Some(CrashDiagnosis::DuplicateMod {
+                mod_id: Some("sodium".to_string()),
+            })
+        );
+        assert_eq!(
+            diagnose("net.minecraftforge.fml.common.DuplicateModsFoundException"),
+            Some(CrashDiagnosis::DuplicateMod { mod_id: None })
+        );
+    }
+
+    #[test]
+    fn driver_and_port_failures_have_no_launcher_fix() {
+        let driver = "[Render thread/ERROR]: GLFW error 65542: WGL: The driver does not appear \
+                      to support OpenGL";
+        assert_eq!(diagnose(driver), Some(CrashDiagnosis::GraphicsDriver));
+        assert_eq!(
+            diagnose("# C  [atio6axx.dll+0x1a2b3c]"),
+            Some(CrashDiagnosis::GraphicsDriver)
+        );
+
+        let bind = "java.net.BindException: Address already in use: bind";
+        assert_eq!(diagnose(bind), Some(CrashDiagnosis::PortInUse { port: None }));
+        assert_eq!(
+            diagnose("**** FAILED TO BIND TO PORT 25565!"),
+            Some(CrashDiagnosis::PortInUse { port: Some(25565) })
+        );
+        assert_eq!(CrashDiagnosis::GraphicsDriver.fix(), None);
+    }
+
+    #[test]
+    fn a_crash_report_is_read_for_its_first_cause() {
+        let report = "---- Minecraft Crash Report ----\n\
+                      // Who set us up the TNT?\n\
+                      \n\
+                      Time: 2025-03-01 12:00:00\n\
+                      Description: Initializing game\n\
+                      \n\
+                      java.lang.OutOfMemoryError: Java heap space\n\
+                      \tat java.base/java.util.Arrays.copyOf(Arrays.java:3537)\n\
+                      \tat net.minecraft.client.main.Main.main(Main.java:239)\n";
+
+        assert_eq!(diagnose_text(report), Some(CrashDiagnosis::OutOfMemory));
+    }
+
     #[test]
     fn ordinary_game_output_is_not_a_crash() {
         // A false positive would nag the user into re-downloading for nothing
@@ -159,6 +752,9 @@ mod tests {
             "[main/INFO]: Loading 42 mods",
             "Loaded jar file sodium-0.5.jar",
             "[Worker-Main-1/WARN]: Unable to play unknown soundEvent",
+            "[main/INFO]: Mixing configs for sodium.mixins.json",
+            "[Render thread/INFO]: Backend library: LWJGL version 3.3.3",
+            "[Server thread/INFO]: Starting integrated minecraft server version 1.21.4",
             "",
         ] {
             assert_eq!(diagnose(line), None, "{line}");
@@ -18,6 +18,9 @@ pub enum GameError {
     #[error("cluster is missing a required Java version")]
     MissingJavaVersion,
 
+    #[error("this cluster needs {required} but is set to use Java {found}")]
+    IncompatibleJava { required: String, found: u32 },
+
     #[error("forge processor failed: {0}")]
     ProcessorFailed(String),
 
@@ -38,6 +41,24 @@ pub enum GameError {
     )]
     SharedDirectoryBusy(String),
 
+    #[error("{0} is running in this game directory; close it before changing its worlds")]
+    WorldsInUse(String),
+
+    #[error("{0} is running in this game directory; close it before changing its server list")]
+    ServersInUse(String),
+
+    #[error("cancelled: {0} mod(s) in the mods folder would not load")]
+    ModAuditCancelled(usize),
+
+    #[error("could not apply the fix: {0}")]
+    CrashFixUnavailable(String),
+
+    #[error("cancelled: the account chosen for {0} cannot be used")]
+    AccountCancelled(String),
+
+    #[error("account {0} has no authentication server; sign in again")]
+    MissingAuthServer(String),
+
     #[error("failed to spawn the game process: {0}")]
     Spawn(String),
 }
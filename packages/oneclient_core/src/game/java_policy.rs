@@ -0,0 +1,244 @@
+//! Which Java a cluster can run on worked out from its version metadata
+//! A pinned runtime that does not fit is caught here rather than by the JVM
+//! failing with `UnsupportedClassVersionError`
+
+use std::sync::Arc;
+
+use interfrost::api::minecraft::VersionInfo;
+use oneclient_cluster::ProfileUpdate;
+use oneclient_common::domain::GameLoader;
+use oneclient_common::patch::Patch;
+use oneclient_common::version::parse_mc_version;
+use oneclient_events::{Choice, GroupedProgressSession, Prompt};
+use oneclient_java::{JavaError, JavaRuntime};
+
+use crate::LauncherResult;
+use crate::clusters::Cluster;
+use crate::game::GameError;
+use crate::settings::GameSettingsProfile;
+use crate::state::LauncherState;
+
+const LAUNCHWRAPPER_MAIN_CLASS: &str = "net.minecraft.launchwrapper.Launch";
+
+/// The last Java LaunchWrapper runs on
+const LAUNCHWRAPPER_MAX_JAVA: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaRequirement {
+    /// What gets installed when nothing suitable is found
+    pub major: u32,
+    /// Set when the loader breaks on anything newer
+    pub max_major: Option<u32>,
+}
+
+impl JavaRequirement {
+    /// `info` is the merged vanilla and loader metadata `None` when it
+    /// declares no Java version
+    #[must_use]
+    pub fn for_version(info: &VersionInfo, loader: GameLoader, mc_version: &str) -> Option<Self> {
+        let declared = info.java_version.as_ref()?.major_version;
+        Some(Self::new(declared, loader, mc_version, &info.main_class))
+    }
+
+    #[must_use]
+    pub fn new(declared: u32, loader: GameLoader, mc_version: &str, main_class: &str) -> Self {
+        let max_major = max_major(loader, mc_version, main_class);
+        Self {
+            major: max_major.map_or(declared, |max| declared.min(max)),
+            max_major,
+        }
+    }
+
+    #[must_use]
+    pub fn accepts(&self, major: u32) -> bool {
+        major >= self.major && self.max_major.is_none_or(|max| major <= max)
+    }
+
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self.max_major {
+            Some(max) if max == self.major => format!("Java {max}"),
+            Some(max) => format!("Java {} to {max}", self.major),
+            None => format!("Java {} or newer", self.major),
+        }
+    }
+}
+
+/// LaunchWrapper casts the system class loader to `URLClassLoader` which
+/// stopped being one in Java 9 old Forge is the usual way in but anything
+/// launched through it breaks the same way
+fn max_major(loader: GameLoader, mc_version: &str, main_class: &str) -> Option<u32> {
+    let launchwrapper = main_class == LAUNCHWRAPPER_MAIN_CLASS;
+    let legacy_forge = loader == GameLoader::Forge
+        && parse_mc_version(mc_version).is_some_and(|version| version.major < 13);
+
+    (launchwrapper || legacy_forge).then_some(LAUNCHWRAPPER_MAX_JAVA)
+}
+
+enum MismatchAnswer {
+    Switch,
+    KeepPinned,
+}
+
+/// The profile's pinned runtime is used when the requirement accepts it
+/// otherwise the user picks between switching and keeping it before anything
+/// changes
+#[tracing::instrument(skip(state, cluster, profile, progress), fields(cluster_id = cluster.id), level = "debug")]
+pub(crate) async fn select_java(
+    state: &Arc<LauncherState>,
+    cluster: &Cluster,
+    profile: &GameSettingsProfile,
+    requirement: Option<JavaRequirement>,
+    search_for_java: bool,
+    auto_install: bool,
+    progress: Option<&GroupedProgressSession>,
+) -> LauncherResult<JavaRuntime> {
+    let pinned = state
+        .java
+        .runtime_for_profile(profile.java_path.as_deref())
+        .await?;
+
+    let Some(requirement) = requirement else {
+        // Nothing to hold the pin to so it is trusted as before
+        return pinned.ok_or_else(|| GameError::MissingJavaVersion.into());
+    };
+
+    let Some(pinned) = pinned else {
+        return Ok(state
+            .java
+            .prepare(requirement.major, search_for_java, auto_install, progress)
+            .await?);
+    };
+
+    if requirement.accepts(pinned.major) {
+        return Ok(pinned);
+    }
+
+    tracing::warn!(
+        cluster_id = cluster.id,
+        pinned = pinned.major,
+        required = %requirement.describe(),
+        "pinned Java runtime does not suit the cluster"
+    );
+
+    let too_old = pinned.major < requirement.major;
+    let mut prompt = Prompt::new(
+        format!("Java {} cannot run {}", pinned.major, cluster.name),
+        format!(
+            "{} needs {} but its settings use Java {} ({}). Switch to Java {}? It is downloaded if it is not installed.",
+            cluster.name,
+            requirement.describe(),
+            pinned.major,
+            pinned.absolute_path,
+            requirement.major
+        ),
+    )
+    .option(
+        Choice::primary("java.switch", format!("Use Java {}", requirement.major)),
+        MismatchAnswer::Switch,
+    );
+    // Too old never starts at all too new is worth a try for someone who
+    // knows their setup
+    if !too_old {
+        prompt = prompt.option(
+            Choice::new("java.keep", "Launch anyway"),
+            MismatchAnswer::KeepPinned,
+        );
+    }
+
+    match state.services.events.ask(prompt.dismiss("Cancel")).await {
+        Ok(Some(chosen)) => match chosen.value {
+            MismatchAnswer::Switch => {
+                switch_java(state, cluster, requirement, search_for_java, progress).await
+            }
+            MismatchAnswer::KeepPinned => Ok(pinned),
+        },
+        Ok(None) => Err(JavaError::Cancelled.into()),
+        Err(err) => {
+            tracing::warn!(
+                cluster_id = cluster.id,
+                "could not offer a Java switch: {err}"
+            );
+            Err(GameError::IncompatibleJava {
+                required: requirement.describe(),
+                found: pinned.major,
+            }
+            .into())
+        }
+    }
+}
+
+/// The prompt already said a missing runtime is downloaded so it is installed
+/// without asking a second time
+async fn switch_java(
+    state: &Arc<LauncherState>,
+    cluster: &Cluster,
+    requirement: JavaRequirement,
+    search_for_java: bool,
+    progress: Option<&GroupedProgressSession>,
+) -> LauncherResult<JavaRuntime> {
+    let runtime = state
+        .java
+        .prepare(requirement.major, search_for_java, true, progress)
+        .await?;
+
+    super::crash_fix::update_cluster_profile(
+        state,
+        cluster.id,
+        ProfileUpdate {
+            java_path: Patch::Set(runtime.absolute_path.clone()),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        cluster_id = cluster.id,
+        major = runtime.major,
+        "switched the cluster's Java runtime"
+    );
+    Ok(runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vanilla_takes_the_declared_major_or_newer() {
+        let requirement = JavaRequirement::new(
+            17,
+            GameLoader::Vanilla,
+            "1.20.1",
+            "net.minecraft.client.main.Main",
+        );
+
+        assert!(requirement.accepts(17));
+        assert!(requirement.accepts(21));
+        assert!(!requirement.accepts(8));
+        assert_eq!(requirement.describe(), "Java 17 or newer");
+    }
+
+    #[test]
+    fn old_forge_is_held_to_java_8() {
+        let requirement = JavaRequirement::new(8, GameLoader::Forge, "1.12.2", "");
+
+        assert!(requirement.accepts(8));
+        assert!(!requirement.accepts(17));
+        assert_eq!(requirement.describe(), "Java 8");
+
+        let modern = JavaRequirement::new(17, GameLoader::Forge, "1.18.2", "");
+        assert_eq!(modern.max_major, None);
+    }
+
+    #[test]
+    fn anything_on_launchwrapper_is_capped() {
+        let requirement =
+            JavaRequirement::new(8, GameLoader::Vanilla, "1.5.2", LAUNCHWRAPPER_MAIN_CLASS);
+        assert_eq!(requirement.max_major, Some(8));
+
+        // A bound below the declared major wins what is installed follows it
+        let conflicting = JavaRequirement::new(17, GameLoader::Forge, "1.7.10", "");
+        assert_eq!(conflicting.major, 8);
+    }
+}
@@ -13,7 +13,7 @@ use crate::clusters::Cluster;
 use oneclient_discord::Presence;
 use crate::game::session::SessionRecorder;
 use crate::game::tail::spawn_log_tail;
-use crate::game::GameError;
+use crate::game::{GameError, JavaRequirement};
 use oneclient_mc::{
     self as arguments, download_minecraft, download_version_info, get_loader_version,
     game_files_missing, resolve_minecraft_version,
@@ -28,20 +28,56 @@ pub fn is_running(state: &LauncherState, cluster_id: i64) -> bool {
     state.games.is_running(cluster_id)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct LaunchedGame {
     pub cluster_id: i64,
     pub pid: Option<u32>,
+    /// Finishes once the session is fully wound down after the game exits
+    /// `Exited` is emitted before that so a caller about to drop the runtime
+    /// must wait on this instead
+    pub session: tokio::task::JoinHandle<GameExit>,
+}
+
+/// How the game's process ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GameExit {
+    /// `code` is `None` when a signal ended it
+    Exited { code: Option<i64>, success: bool },
+    /// The process could not be waited on
+    Failed(String),
+}
+
+impl GameExit {
+    pub fn is_clean(&self) -> bool {
+        matches!(self, Self::Exited { success: true, .. })
+    }
 }
 
-#[tracing::instrument(skip(state, account))]
 pub async fn launch_cluster(
     state: &Arc<LauncherState>,
     cluster_id: i64,
     account: &MinecraftAccount,
     search_for_java: bool,
 ) -> LauncherResult<LaunchedGame> {
-    tracing::info!(cluster_id, search_for_java, "launching cluster");
+    launch_cluster_with(state, cluster_id, account, search_for_java, None).await
+}
+
+/// `quick_play` drops the player straight into a world server or realm
+/// instead of the title screen
+#[tracing::instrument(skip(state, account))]
+pub async fn launch_cluster_with(
+    state: &Arc<LauncherState>,
+    cluster_id: i64,
+    account: &MinecraftAccount,
+    search_for_java: bool,
+    quick_play: Option<&arguments::QuickPlay>,
+) -> LauncherResult<LaunchedGame> {
+    tracing::info!(
+        cluster_id,
+        search_for_java,
+        ?quick_play,
+        "launching cluster"
+    );
 
     let parallel = state.settings.read().allow_parallel_running_clusters;
     if !parallel && state.games.is_running(cluster_id) {
@@ -173,19 +209,17 @@ pub async fn launch_cluster(
         "resolved launch metadata"
     );
 
-    let java = if let Some(runtime) =
-        state.java.runtime_for_profile(profile.java_path.as_deref()).await?
-    {
-        runtime
-    } else {
-        let major = version_info
-            .java_version
-            .as_ref()
-            .map(|v| v.major_version)
-            .ok_or(GameError::MissingJavaVersion)?;
-
-        state.java.prepare(major, search_for_java, false, None).await?
-    };
+    let requirement = JavaRequirement::for_version(&version_info, cluster.mc_loader, &mc_version);
+    let java = crate::game::select_java(
+        state,
+        &cluster,
+        &profile,
+        requirement,
+        search_for_java,
+        false,
+        None,
+    )
+    .await?;
 
     match game_files_missing(&version_info, &java.os_arch, updated) {
         Ok(true) => {
@@ -229,6 +263,19 @@ pub async fn launch_cluster(
         tracing::warn!(cluster_id, error = %err, "failed to materialize cluster content");
     }
 
+    match crate::game::mod_audit::audit_mods_before_launch(&state.services, &cluster, &cwd).await {
+        Ok(false) => {}
+        Ok(true) => {
+            if let Err(err) = crate::game::materialize_content(&state.services, &cluster, &cwd).await {
+                tracing::warn!(cluster_id, error = %err, "failed to materialize cluster content");
+            }
+        }
+        Err(err) => {
+            stage(LaunchStage::Exited);
+            return Err(err);
+        }
+    }
+
     if !dedicated {
         // Redirects the shared dir's `logs`/`crash-reports` into this cluster's
         // folder so output is attributable unlinked on exit
@@ -252,6 +299,26 @@ pub async fn launch_cluster(
         updated,
     )?;
 
+    // A Yggdrasil account's token means nothing to Mojang without the agent
+    // the game would start but every server would reject the session
+    let authlib_injector = if account.is_yggdrasil() {
+        let injector: LauncherResult<_> = match account.auth_server.as_deref() {
+            Some(api_root) => arguments::ensure_authlib_injector(&state.services.mc(), api_root)
+                .await
+                .map_err(Into::into),
+            None => Err(GameError::MissingAuthServer(account.username.clone()).into()),
+        };
+        match injector {
+            Ok(injector) => Some(injector),
+            Err(err) => {
+                stage(LaunchStage::Exited);
+                return Err(err);
+            }
+        }
+    } else {
+        None
+    };
+
     let jvm_args = arguments::java_arguments(
         updated,
         arg_map.get(&ArgumentType::Jvm).map(Vec::as_slice),
@@ -263,9 +330,10 @@ pub async fn launch_cluster(
         profile.launch_args.clone().unwrap_or_default(),
         &java.os_arch,
         java.major,
+        authlib_injector.as_ref(),
     )?;
 
-    let mut mc_args = arguments::minecraft_arguments(
+    let mc_args = arguments::minecraft_arguments(
         updated,
         arg_map.get(&ArgumentType::Game).map(Vec::as_slice),
         version_info.minecraft_arguments.as_deref(),
@@ -279,7 +347,15 @@ pub async fn launch_cluster(
         version.type_,
         profile.resolution.unwrap_or_default(),
         &java.os_arch,
-    )?;
+        quick_play,
+    );
+    let mut mc_args = match mc_args {
+        Ok(args) => args,
+        Err(err) => {
+            stage(LaunchStage::Exited);
+            return Err(err.into());
+        }
+    };
     arguments::append_profile_game_arguments(&mut mc_args, profile.force_fullscreen, None);
 
     run_hook(profile.hook_pre.as_deref(), &cwd).await;
@@ -373,7 +449,7 @@ pub async fn launch_cluster(
 
     let state = Arc::clone(state);
     let post_hook = profile.hook_post.clone();
-    tokio::spawn(async move {
+    let session = tokio::spawn(async move {
         let cluster = cluster;
         let status = tokio::select! {
             status = child.wait() => status,
@@ -385,6 +461,13 @@ pub async fn launch_cluster(
 
         tail.stop().await;
 
+        let exit = match &status {
+            Ok(status) => GameExit::Exited {
+                code: status.code().map(i64::from),
+                success: status.success(),
+            },
+            Err(err) => GameExit::Failed(err.to_string()),
+        };
         let outcome = match status {
             Ok(status) => Exit::Observed {
                 code: status.code().map(i64::from),
@@ -394,7 +477,7 @@ pub async fn launch_cluster(
             Err(err) => Exit::Failed(err.to_string()),
         };
 
-        finalize_session(
+        let snapshot = finalize_session(
             &state,
             &cluster,
             &cwd,
@@ -410,12 +493,21 @@ pub async fn launch_cluster(
             },
         )
         .await;
+
+        if let Err(err) = snapshot.await {
+            tracing::warn!(cluster_id, error = %err, "world snapshot task failed");
+        }
+        exit
     });
 
-    Ok(LaunchedGame { cluster_id, pid })
+    Ok(LaunchedGame {
+        cluster_id,
+        pid,
+        session,
+    })
 }
 
-async fn running_cluster_name(state: &Arc<LauncherState>, cluster_id: i64) -> String {
+pub(crate) async fn running_cluster_name(state: &LauncherState, cluster_id: i64) -> String {
     state
         .clusters
         .get(cluster_id)
@@ -469,7 +561,7 @@ pub(crate) struct SessionEnd {
 }
 
 /// Shared by the live exit path and by recovery of sessions that outlived the
-/// launcher
+/// launcher returns the world snapshot still running in the background
 pub(crate) async fn finalize_session(
     state: &Arc<LauncherState>,
     cluster: &Cluster,
@@ -478,7 +570,7 @@ pub(crate) async fn finalize_session(
     post_hook: Option<&str>,
     recorder: Option<SessionRecorder>,
     end: SessionEnd,
-) {
+) -> tokio::task::JoinHandle<()> {
     let cluster_id = cluster.id;
     let played = (end.ended_at - end.started_at).to_std().unwrap_or_default();
 
@@ -506,8 +598,29 @@ pub(crate) async fn finalize_session(
         recorder.finish_at(&end.ended_at.to_rfc3339(), code).await;
     }
 
+    let crashed = !matches!(end.outcome, Exit::Observed { success: true, .. });
+    // The log said nothing recognisable the crash report may still and it is
+    // only reachable until the shared dir's log redirect is undone below
+    let diagnosis = match end.diagnosis {
+        Some(diagnosis) => Some(diagnosis),
+        None if crashed && !matches!(end.outcome, Exit::Inferred) => {
+            crate::game::diagnosis::diagnose_crash_reports(cwd, end.started_at).await
+        }
+        None => None,
+    };
+
     run_hook(post_hook, cwd).await;
 
+    // Spawned hashing a large world must not hold up the close notification
+    let snapshot = {
+        let state = Arc::clone(state);
+        let cluster = cluster.clone();
+        let started_at = end.started_at;
+        tokio::spawn(async move {
+            crate::worlds::snapshot_after_session(&state, &cluster, started_at).await;
+        })
+    };
+
     if dedicated {
         // The folder stays materialized so it remains a real Minecraft directory
         // for external tools adopting drop-ins now keeps the UI right on close
@@ -520,7 +633,6 @@ pub(crate) async fn finalize_session(
     }
 
     let name = &cluster.name;
-    let crashed = !matches!(end.outcome, Exit::Observed { success: true, .. });
 
     match end.outcome {
         Exit::Observed { success: true, .. } => state
@@ -541,70 +653,11 @@ pub(crate) async fn finalize_session(
 
     // Only when the game actually died a `ZipException` it recovered from is not
     // worth interrupting a finished session over
-    if crashed && let Some(diagnosis) = end.diagnosis {
-        offer_repair(state, cluster_id, &diagnosis).await;
-    }
-}
-
-/// Asks rather than acting verification can re-download much of the game too
-/// much to start unprompted
-/// The offer returns on the next crash
-#[tracing::instrument(skip(state, diagnosis), level = "debug")]
-pub async fn offer_repair(
-    state: &Arc<LauncherState>,
-    cluster_id: i64,
-    diagnosis: &crate::game::diagnosis::CrashDiagnosis,
-) {
-    enum Answer {
-        Verify,
-    }
-
-    let answer = state
-        .services
-        .events
-        .ask(
-            oneclient_events::Prompt::new("A damaged file crashed the game", diagnosis.body())
-                .option(
-                    oneclient_events::Choice::primary("verify", "Verify and repair"),
-                    Answer::Verify,
-                )
-                .dismiss("Not now"),
-        )
-        .await;
-
-    match answer {
-        Ok(Some(_)) => {}
-        // Dismissed or nobody there to ask neither is consent to re-download
-        Ok(None) => {
-            tracing::info!(cluster_id, "user declined the post-crash repair");
-            return;
-        }
-        Err(err) => {
-            tracing::warn!(cluster_id, "could not offer a post-crash repair: {err}");
-            return;
-        }
+    if crashed && let Some(diagnosis) = diagnosis {
+        crate::game::offer_crash_fix(state, cluster_id, &diagnosis).await;
     }
 
-    match crate::verify::verify_cluster_files(state, cluster_id).await {
-        Ok(report) => {
-            state
-                .services
-                .events
-                .notify("Repair complete")
-                .body(report.summary())
-                .send();
-        }
-        Err(err) => {
-            tracing::error!(cluster_id, "post-crash repair failed: {err:#}");
-            state
-                .services
-                .events
-                .notify("Repair failed")
-                .body(err.to_string())
-                .error()
-                .send();
-        }
-    }
+    snapshot
 }
 
 fn base_command(profile: &GameSettingsProfile, java_path: &str) -> Command {
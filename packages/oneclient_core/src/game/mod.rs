@@ -1,27 +1,39 @@
+mod account;
 mod analytics;
+mod crash_fix;
 mod error;
+mod java_policy;
 mod launch;
 mod log_replay;
+mod mod_audit;
 mod process;
 mod reattach;
 mod session;
 mod shared_dir;
 mod tail;
 
+pub use account::{
+    account_for_cluster, cluster_account, remove_account, set_cluster_account,
+};
 pub use analytics::{
     Analytics, DayPlaytime, Persona, PlaytimeStats, ServerStat, WEEKDAY_LABELS, aggregate_servers,
-    cluster_analytics, global_analytics,
+    cluster_analytics, global_analytics, last_played_server,
 };
 pub use oneclient_mc::{
     append_profile_game_arguments, classpaths, download_to_path, fetch_bytes_verified,
     get_classpath_library, get_library, java_arguments, main_class, minecraft_arguments,
     processor_arguments,
 };
+pub use oneclient_mc::{QuickPlay, QuickPlayKind};
 pub mod diagnosis;
 
-pub use diagnosis::{CrashDiagnosis, diagnose};
+pub use crash_fix::{apply_crash_fix, offer_crash_fix};
+pub use diagnosis::{CrashDiagnosis, CrashFix, diagnose, diagnose_text};
 pub use error::GameError;
-pub use launch::{LaunchedGame, is_running, launch_cluster, offer_repair};
+pub use java_policy::JavaRequirement;
+pub(crate) use java_policy::select_java;
+pub use launch::{GameExit, LaunchedGame, is_running, launch_cluster, launch_cluster_with};
+pub(crate) use launch::running_cluster_name;
 pub use process::{
     GameProcess, GameProcessManager, is_process_alive, kill_process, process_start_time,
 };
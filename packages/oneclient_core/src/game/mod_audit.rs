@@ -0,0 +1,144 @@
+//! Runs over the folder after it is materialized so what is checked is exactly
+//! what the game is about to load
+
+use std::path::Path;
+
+use oneclient_common::domain::ContentType;
+use oneclient_content::packages::PackageStore;
+use oneclient_content::packages::audit::{self, ModAuditFinding};
+use oneclient_events::{Choice, Prompt};
+
+use crate::LauncherResult;
+use crate::clusters::Cluster;
+use crate::game::GameError;
+use crate::state::LauncherServices;
+
+enum Answer {
+    Disable,
+    LaunchAnyway,
+}
+
+/// `Ok(true)` when files were disabled and the folder needs materializing again
+#[tracing::instrument(skip(services, cluster), fields(cluster_id = cluster.id), level = "debug")]
+pub(crate) async fn audit_mods_before_launch(
+    services: &LauncherServices,
+    cluster: &Cluster,
+    game_dir: &Path,
+) -> LauncherResult<bool> {
+    let mods_dir = game_dir.join(ContentType::Mod.folder_name());
+    let findings =
+        match audit::audit_mods_dir(&mods_dir, cluster.mc_loader, &cluster.mc_version).await {
+            Ok(findings) => findings,
+            Err(err) => {
+                // The audit only ever saves a crash it is not worth failing a launch over
+                tracing::warn!(error = %err, "could not audit the mods folder");
+                return Ok(false);
+            }
+        };
+
+    if findings.is_empty() {
+        return Ok(false);
+    }
+
+    tracing::warn!(
+        count = findings.len(),
+        ?findings,
+        "mods folder has files that will not load"
+    );
+    let body = findings
+        .iter()
+        .map(ModAuditFinding::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let answer = services
+        .events
+        .ask(
+            Prompt::new("Some mods will not load", body)
+                .option(
+                    Choice::primary("disable", "Disable and launch"),
+                    Answer::Disable,
+                )
+                .option(Choice::new("launch", "Launch anyway"), Answer::LaunchAnyway)
+                .dismiss("Cancel"),
+        )
+        .await;
+
+    match answer {
+        Ok(Some(chosen)) => match chosen.value {
+            Answer::Disable => {
+                disable_findings(services, cluster, &mods_dir, &findings).await;
+                Ok(true)
+            }
+            Answer::LaunchAnyway => {
+                tracing::warn!("user chose to launch with mods that will not load");
+                Ok(false)
+            }
+        },
+        // Same as an incomplete install a headless caller must not silently
+        // launch into a crash
+        Ok(None) => Err(GameError::ModAuditCancelled(findings.len()).into()),
+        Err(err) => {
+            tracing::warn!("could not ask about the mods folder: {err}");
+            Err(GameError::ModAuditCancelled(findings.len()).into())
+        }
+    }
+}
+
+/// Tracked files are disabled on the cluster so the next materialize leaves
+/// them out anything else is renamed aside the way the game ignores it but only
+/// in a dedicated folder in the shared one it may be another cluster's or the
+/// player's so it is reported and left alone
+async fn disable_findings(
+    services: &LauncherServices,
+    cluster: &Cluster,
+    mods_dir: &Path,
+    findings: &[ModAuditFinding],
+) {
+    let ctx = services.content();
+    let linked = PackageStore::list_linked_artifacts(cluster.id, &ctx)
+        .await
+        .unwrap_or_default();
+    let dedicated = cluster.uses_dedicated_dir();
+    let mut left = Vec::new();
+
+    for finding in findings {
+        let link = linked.iter().find(|link| {
+            link.content_type == ContentType::Mod && link.cluster_file_name == finding.file_name
+        });
+
+        let result: LauncherResult<()> = match link {
+            Some(link) => oneclient_content::bundles::set_artifact_enabled_to(
+                cluster.id, &link.hash, false, &ctx,
+            )
+            .await
+            .map_err(Into::into),
+            None if dedicated => {
+                let path = mods_dir.join(&finding.file_name);
+                let disabled = mods_dir.join(format!("{}.disabled", finding.file_name));
+                polyio::rename(&path, &disabled).await.map_err(Into::into)
+            }
+            None => {
+                left.push(finding.file_name.clone());
+                Ok(())
+            }
+        };
+
+        if let Err(err) = result {
+            tracing::warn!(file = %finding.file_name, error = %err, "failed to disable a flagged mod");
+        }
+    }
+
+    if !left.is_empty() {
+        tracing::warn!(?left, "flagged mods in the shared folder are not this cluster's");
+        services
+            .events
+            .notify("Some mods were left in place")
+            .body(format!(
+                "These files in the shared mods folder do not belong to {} so they were not disabled:\n{}",
+                cluster.name,
+                left.join("\n")
+            ))
+            .send();
+    }
+}
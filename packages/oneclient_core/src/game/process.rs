@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::Instant;
 
+use chrono::{DateTime, Utc};
 use sysinfo::{Pid, ProcessesToUpdate, Signal, System};
 use tokio::sync::oneshot;
 
@@ -64,6 +65,8 @@ pub struct GameProcessManager {
     inner: Mutex<HashMap<i64, GameProcess>>,
     kills: Mutex<HashMap<i64, oneshot::Sender<()>>>,
     dirs: Mutex<HashMap<i64, PathBuf>>,
+    /// Earliest session start per dir whose snapshot waits for the last game there
+    deferred_snapshots: Mutex<HashMap<PathBuf, DateTime<Utc>>>,
 }
 
 impl GameProcessManager {
@@ -128,6 +131,26 @@ impl GameProcessManager {
             .map(|(id, _)| *id)
     }
 
+    /// `None` while another game still plays in `dir` its `since` is queued for
+    /// whichever exit leaves the dir empty which then gets the earliest one
+    /// Checked and queued under one lock so an exit in between cannot miss it
+    pub fn claim_snapshot(&self, dir: &Path, since: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let dirs = self.dirs.lock().unwrap();
+        let mut deferred = self.deferred_snapshots.lock().unwrap();
+        if dirs.values().any(|d| d.as_path() == dir) {
+            deferred
+                .entry(dir.to_path_buf())
+                .and_modify(|earliest| *earliest = (*earliest).min(since))
+                .or_insert(since);
+            return None;
+        }
+        Some(
+            deferred
+                .remove(dir)
+                .map_or(since, |earliest| earliest.min(since)),
+        )
+    }
+
     pub fn dir_in_use_by(&self, dir: &Path, exclude: i64) -> Option<i64> {
         self.dirs
             .lock()
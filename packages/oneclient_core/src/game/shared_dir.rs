@@ -11,7 +11,7 @@ use oneclient_content::packages::store::manifest::{
     self, ManifestEntry, MaterializedManifest,
 };
 use oneclient_content::packages::store::{artifact_absolute_path, link_or_copy, remove_entry};
-use oneclient_content::packages::PackageStore;
+use oneclient_content::packages::{PackageStore, jar_metadata};
 use crate::state::LauncherServices;
 
 const REDIRECTED_DIRS: [&str; 2] = ["logs", "crash-reports"];
@@ -30,11 +30,17 @@ struct Desired {
     hash: String,
     /// Where it lives in the artifact cache
     src: std::path::PathBuf,
+    /// A datapack is wanted once per world it goes into
+    world: Option<String>,
 }
 
 impl Desired {
     fn relative_path(&self) -> String {
-        manifest::entry_path(self.content_type.folder_name(), &self.file_name)
+        let folder = self.content_type.folder_name();
+        match &self.world {
+            Some(world) => manifest::world_entry_path(world, folder, &self.file_name),
+            None => manifest::entry_path(folder, &self.file_name),
+        }
     }
 }
 
@@ -93,7 +99,7 @@ pub async fn materialize_content(
         }
     }
 
-    let entries = link_desired(game_dir, &desired).await;
+    let entries = link_desired(game_dir, &desired, previous.as_ref()).await;
     manifest::save(game_dir, &MaterializedManifest::new(cluster.id, entries)).await;
 
     sync_fabric_dep_overrides(cluster, game_dir).await?;
@@ -128,7 +134,19 @@ pub async fn dematerialize_content(
         ensure_note(&dir, content_type).await;
     }
 
-    manifest::clear(game_dir).await;
+    // Worlds stay in the shared dir for every cluster only this cluster's
+    // datapacks have to come out of them and whatever would not is kept on
+    // record so the next launch still knows to remove it
+    let stuck = match current.as_ref().filter(|m| m.cluster_id == cluster.id) {
+        Some(current) => clear_world_entries(game_dir, current).await,
+        None => Vec::new(),
+    };
+
+    if stuck.is_empty() {
+        manifest::clear(game_dir).await;
+    } else {
+        manifest::save(game_dir, &MaterializedManifest::new(cluster.id, stuck)).await;
+    }
     Ok(())
 }
 
@@ -138,9 +156,11 @@ async fn desired_content(
 ) -> LauncherResult<Vec<Desired>> {
     let linked = PackageStore::list_linked_artifacts(cluster.id, &services.content()).await?;
     let mut desired = Vec::with_capacity(linked.len());
+    let mut worlds: Option<Vec<String>> = None;
 
     for link in linked {
-        if !link.enabled || !SWAP_TYPES.contains(&link.content_type) {
+        let per_world = link.content_type == ContentType::DataPack;
+        if !link.enabled || !(per_world || SWAP_TYPES.contains(&link.content_type)) {
             continue;
         }
 
@@ -155,31 +175,86 @@ async fn desired_content(
             continue;
         }
 
-        desired.push(Desired {
-            content_type: link.content_type,
-            file_name: link.cluster_file_name,
-            hash: link.hash,
-            src,
-        });
+        if !per_world {
+            desired.push(Desired {
+                content_type: link.content_type,
+                file_name: link.cluster_file_name,
+                hash: link.hash,
+                src,
+                world: None,
+            });
+            continue;
+        }
+
+        // Only worlds that exist linking into a missing one would create a
+        // folder the game then lists as a broken world
+        if worlds.is_none() {
+            worlds = Some(world_folders(cluster).await);
+        }
+        let existing = worlds.as_deref().unwrap_or_default();
+        let targets: Vec<&String> = match &link.target_world {
+            Some(world) => existing.iter().filter(|folder| *folder == world).collect(),
+            None => existing.iter().collect(),
+        };
+        if targets.is_empty() {
+            tracing::debug!(hash = %link.hash, world = ?link.target_world, "no world to place datapack in");
+        }
+        for world in targets {
+            desired.push(Desired {
+                content_type: link.content_type,
+                file_name: link.cluster_file_name.clone(),
+                hash: link.hash.clone(),
+                src: src.clone(),
+                world: Some(world.clone()),
+            });
+        }
     }
 
     Ok(desired)
 }
 
+async fn world_folders(cluster: &Cluster) -> Vec<String> {
+    match oneclient_cluster::worlds::list_worlds(cluster).await {
+        Ok(worlds) => worlds.into_iter().map(|world| world.folder).collect(),
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to list worlds; datapacks are not placed");
+            Vec::new()
+        }
+    }
+}
+
 /// Only files that made it are recorded so a failed link is never later
 /// mistaken for ours and deleted out from under the user
-async fn link_desired(game_dir: &Path, desired: &[Desired]) -> Vec<ManifestEntry> {
+async fn link_desired(
+    game_dir: &Path,
+    desired: &[Desired],
+    previous: Option<&MaterializedManifest>,
+) -> Vec<ManifestEntry> {
     let mut entries = Vec::with_capacity(desired.len());
 
     for item in desired {
-        let dest = game_dir
-            .join(item.content_type.folder_name())
-            .join(&item.file_name);
+        let path = item.relative_path();
+        let dest = game_dir.join(&path);
+
+        // A world's `datapacks` is never stashed like the swap folders so a
+        // file there no manifest lists is the player's and linking over it
+        // would delete it
+        if item.world.is_some()
+            && !previous.is_some_and(|previous| previous.entries.iter().any(|e| e.path == path))
+            && polyio::symlink_metadata(&dest).await.is_ok()
+        {
+            tracing::warn!(
+                file = %path,
+                "a datapack of the same name is already in this world; leaving the player's file"
+            );
+            continue;
+        }
 
         match link_or_copy(&item.src, &dest).await {
             Ok(()) => entries.push(ManifestEntry {
-                path: item.relative_path(),
+                path,
                 hash: item.hash.clone(),
+                world: item.world.clone(),
             }),
             Err(err) => tracing::warn!(
                 file = %item.file_name,
@@ -219,6 +294,27 @@ async fn prune_previous(
     }
 }
 
+/// Only what the manifest says this cluster put inside a world a pack the
+/// player copied into a world's `datapacks` themselves is left there
+/// Returns the entries that could not be removed
+async fn clear_world_entries(
+    game_dir: &Path,
+    current: &MaterializedManifest,
+) -> Vec<ManifestEntry> {
+    let mut stuck = Vec::new();
+    for entry in current.entries.iter().filter(|entry| entry.world.is_some()) {
+        if let Err(err) = remove_entry(&game_dir.join(&entry.path)).await {
+            tracing::warn!(
+                file = %entry.path,
+                error = %err,
+                "failed to take datapack out of a shared world; it goes at the next launch"
+            );
+            stuck.push(entry.clone());
+        }
+    }
+    stuck
+}
+
 /// Names in one folder that are the launcher's not the user's what we
 /// materialized last plus what the database tracks (covering files just
 /// adopted by [`import_manual_content`])
@@ -262,6 +358,7 @@ pub async fn import_manual_content(
     };
 
     let manifest = manifest::load(game_dir).await;
+    let mut imported_mods = HashSet::new();
 
     for content_type in SWAP_TYPES {
         let dir = game_dir.join(content_type.folder_name());
@@ -312,8 +409,11 @@ pub async fn import_manual_content(
             }
 
             match PackageStore::import_local_file(&path, content_type, cluster.id, &services.content()).await {
-                Ok(_) => {
-                    tracing::debug!(file = name, "registered manually-added content")
+                Ok(row) => {
+                    tracing::debug!(file = name, "registered manually-added content");
+                    if content_type == ContentType::Mod {
+                        imported_mods.insert(row.hash);
+                    }
                 }
                 Err(err) => tracing::warn!(
                     file = name,
@@ -323,6 +423,49 @@ pub async fn import_manual_content(
             }
         }
     }
+
+    if !imported_mods.is_empty() {
+        warn_dependency_issues(services, cluster, &imported_mods).await;
+    }
+}
+
+/// Only issues raised by the new jars the rest of the cluster was already
+/// checked when it was installed
+async fn warn_dependency_issues(
+    services: &LauncherServices,
+    cluster: &Cluster,
+    imported: &HashSet<String>,
+) {
+    let mods = match jar_metadata::cluster_jar_metadata(cluster.id, &services.content()).await {
+        Ok(mods) => mods,
+        Err(err) => {
+            tracing::warn!(error = %err, "could not read jar metadata for a dependency check");
+            return;
+        }
+    };
+
+    let new_ids: HashSet<&str> = mods
+        .iter()
+        .filter(|(link, _)| imported.contains(&link.hash))
+        .flat_map(|(_, metadata)| metadata.iter().map(|m| m.mod_id.as_str()))
+        .collect();
+    let all: Vec<_> = mods.iter().flat_map(|(_, metadata)| metadata.iter().cloned()).collect();
+    let issues: Vec<String> = jar_metadata::jar_dependency_issues(&all)
+        .into_iter()
+        .filter(|issue| new_ids.contains(issue.mod_id()))
+        .map(|issue| issue.to_string())
+        .collect();
+
+    if issues.is_empty() {
+        return;
+    }
+    tracing::warn!(?issues, "manually-added mods have dependency problems");
+    services
+        .events
+        .notify("Manually added mods may not load")
+        .body(issues.join("\n"))
+        .error()
+        .send();
 }
 
 /// Already in the artifact cache i.e. the launcher put it in the game dir
@@ -647,6 +790,7 @@ mod tests {
                 .map(|p| ManifestEntry {
                     path: (*p).to_string(),
                     hash: "hash".into(),
+                    world: None,
                 })
                 .collect(),
         )
@@ -894,6 +1038,78 @@ mod tests {
         names
     }
 
+    /// A shared world must not keep loading a datapack once the cluster that
+    /// put it there has closed while the player's own packs stay
+    #[tokio::test]
+    async fn world_datapacks_leave_the_shared_dir_on_exit() {
+        let root = polyio::testing::ScratchDir::new("world_datapacks");
+        let game_dir = root.path();
+        let datapacks = game_dir.join("saves/Castle/datapacks");
+        polyio::create_dir_all(&datapacks).await.unwrap();
+
+        let src = game_dir.join("cached.zip");
+        polyio::write(&src, b"pack".as_slice()).await.unwrap();
+        let desired = Desired {
+            content_type: ContentType::DataPack,
+            file_name: "trees.zip".into(),
+            hash: "hash".into(),
+            src,
+            world: Some("Castle".into()),
+        };
+        polyio::write(datapacks.join("mine.zip"), b"pack".as_slice())
+            .await
+            .unwrap();
+
+        let entries = link_desired(game_dir, std::slice::from_ref(&desired), None).await;
+        assert_eq!(entries[0].path, "saves/Castle/datapacks/trees.zip");
+        assert!(datapacks.join("trees.zip").exists());
+
+        clear_world_entries(game_dir, &MaterializedManifest::new(1, entries)).await;
+        assert!(
+            polyio::symlink_metadata(datapacks.join("trees.zip"))
+                .await
+                .is_err()
+        );
+        assert!(datapacks.join("mine.zip").exists());
+
+        std::fs::remove_dir_all(root.path()).ok();
+    }
+
+    /// An "all worlds" datapack named like one the player copied into a world
+    /// must not replace it only a file we placed there before is relinked
+    #[tokio::test]
+    async fn world_datapacks_never_replace_the_players_file() {
+        let root = polyio::testing::ScratchDir::new("world_datapack_collision");
+        let game_dir = root.path();
+        let datapacks = game_dir.join("saves/Castle/datapacks");
+        polyio::create_dir_all(&datapacks).await.unwrap();
+
+        let src = game_dir.join("cached.zip");
+        polyio::write(&src, b"ours".as_slice()).await.unwrap();
+        let desired = Desired {
+            content_type: ContentType::DataPack,
+            file_name: "trees.zip".into(),
+            hash: "hash".into(),
+            src,
+            world: Some("Castle".into()),
+        };
+        polyio::write(datapacks.join("trees.zip"), b"mine".as_slice())
+            .await
+            .unwrap();
+
+        let entries = link_desired(game_dir, std::slice::from_ref(&desired), None).await;
+        assert!(entries.is_empty(), "the player's file must not be recorded as ours");
+        assert_eq!(std::fs::read(datapacks.join("trees.zip")).unwrap(), b"mine");
+
+        let previous = manifest_of(1, &["saves/Castle/datapacks/trees.zip"]);
+        let entries =
+            link_desired(game_dir, std::slice::from_ref(&desired), Some(&previous)).await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(std::fs::read(datapacks.join("trees.zip")).unwrap(), b"ours");
+
+        std::fs::remove_dir_all(root.path()).ok();
+    }
+
     #[test]
     fn ownership_spans_the_manifest_and_the_database() {
         let manifest = manifest_of(1, &["mods/from_manifest.jar", "shaderpacks/bsl.zip"]);
@@ -8,7 +8,7 @@ use tokio::sync::Mutex;
 use polyio::sha1_bytes;
 use oneclient_net::RequestError;
 use oneclient_common::paths;
-use oneclient_net::RequestClient;
+use oneclient_net::{RequestClient, ResponseExt};
 use crate::LauncherResult;
 
 pub const DEFAULT_IMAGE_EDGE: u32 = 1600;
@@ -113,8 +113,7 @@ async fn download(net: &RequestClient, url: &str) -> LauncherResult<Bytes> {
     let parsed = url.parse().map_err(RequestError::from)?;
     let request = reqwest::Request::new(Method::GET, parsed);
     let res = net.send(request).await?;
-    let bytes = res.bytes().await.map_err(RequestError::from)?;
-    Ok(bytes)
+    Ok(res.throttled_bytes().await?)
 }
 
 fn extension_from_url(url: &str) -> Option<String> {
@@ -0,0 +1,295 @@
+//! Keeps the runtimes the launcher installed current and offers back the space
+//! of the ones nothing uses any more
+//! Runtimes found on the system are never updated or removed
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use oneclient_db::dao::setting_profile as profile_dao;
+use oneclient_events::{Choice, GroupedProgressSession, Prompt};
+use oneclient_java::JavaRuntime;
+use oneclient_net::Priority;
+
+use crate::LauncherResult;
+use crate::settings::store::{save_global_profile, save_settings};
+use crate::state::LauncherState;
+use crate::storage::{dir_size, format_bytes};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaUpdate {
+    pub from: JavaRuntime,
+    pub to: JavaRuntime,
+    /// Setting profiles moved onto the new runtime the global one included
+    pub repointed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnusedJava {
+    pub runtime: JavaRuntime,
+    pub dir: PathBuf,
+    pub bytes: u64,
+}
+
+/// One failed update is logged and the rest still run
+#[tracing::instrument(skip(state, progress))]
+pub async fn update_managed_java(
+    state: &Arc<LauncherState>,
+    progress: Option<&GroupedProgressSession>,
+) -> LauncherResult<Vec<JavaUpdate>> {
+    let mut updates = Vec::new();
+
+    for runtime in state.java.managed_runtimes().await? {
+        let package = match state.java.available_update(&runtime).await {
+            Ok(Some(package)) => package,
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::warn!(path = %runtime.absolute_path, "could not check for a Java update: {err}");
+                continue;
+            }
+        };
+
+        let updated = match state
+            .java
+            .update_runtime(&runtime, &package, progress)
+            .await
+        {
+            Ok(updated) => updated,
+            Err(err) => {
+                tracing::warn!(path = %runtime.absolute_path, package = %package.name, "Java update failed: {err}");
+                continue;
+            }
+        };
+
+        let repointed =
+            match repoint_profiles(state, &runtime.absolute_path, &updated.absolute_path).await {
+                Ok(repointed) => repointed,
+                Err(err) => {
+                    tracing::warn!(path = %runtime.absolute_path, to = %updated.absolute_path, "could not move profiles onto the updated Java: {err}");
+                    continue;
+                }
+            };
+        tracing::info!(
+            from = %runtime.version,
+            to = %updated.version,
+            repointed,
+            "updated managed Java runtime"
+        );
+        updates.push(JavaUpdate {
+            from: runtime,
+            to: updated,
+            repointed,
+        });
+    }
+
+    Ok(updates)
+}
+
+async fn repoint_profiles(state: &LauncherState, old: &str, new: &str) -> LauncherResult<u64> {
+    let mut repointed = profile_dao::repoint_java_path(&state.services.db, old, new).await?;
+
+    let global = state.settings.read().global_game_settings.clone();
+    if global.java_path.as_deref() == Some(old) {
+        let mut global = global;
+        global.java_path = Some(new.to_string());
+        save_global_profile(&state.settings, global).await?;
+        repointed += 1;
+    }
+
+    Ok(repointed)
+}
+
+/// A managed runtime no profile names is still kept when it is the one an
+/// unpinned cluster of its major would pick
+#[tracing::instrument(skip(state))]
+pub async fn unused_managed_java(state: &LauncherState) -> LauncherResult<Vec<UnusedJava>> {
+    let mut referenced: HashSet<String> = profile_dao::list_all(&state.services.db)
+        .await?
+        .into_iter()
+        .filter_map(|row| row.java_path)
+        .collect();
+    if let Some(path) = state.settings.read().global_game_settings.java_path.clone() {
+        referenced.insert(path);
+    }
+
+    let runtimes = state.java.list_runtimes().await?;
+    let mut unused = Vec::new();
+    for runtime in unreferenced_managed(&runtimes, &referenced) {
+        let Some(dir) = oneclient_java::managed_install_dir(runtime) else {
+            continue;
+        };
+        unused.push(UnusedJava {
+            bytes: dir_size(&dir).await,
+            runtime: runtime.clone(),
+            dir,
+        });
+    }
+
+    Ok(unused)
+}
+
+fn unreferenced_managed<'a>(
+    runtimes: &'a [JavaRuntime],
+    referenced: &HashSet<String>,
+) -> Vec<&'a JavaRuntime> {
+    // Same ordering as the store's `latest_by_major` which `prepare` goes by
+    let mut preferred: BTreeMap<u32, &str> = BTreeMap::new();
+    for runtime in runtimes {
+        let best = preferred.entry(runtime.major).or_insert(&runtime.version);
+        if runtime.version.as_str() > *best {
+            *best = &runtime.version;
+        }
+    }
+
+    runtimes
+        .iter()
+        .filter(|runtime| oneclient_java::managed_install_dir(runtime).is_some())
+        .filter(|runtime| !referenced.contains(&runtime.absolute_path))
+        .filter(|runtime| preferred.get(&runtime.major) != Some(&runtime.version.as_str()))
+        .collect()
+}
+
+/// Returns the bytes freed
+#[tracing::instrument(skip(state))]
+pub async fn remove_unused_java(state: &LauncherState) -> LauncherResult<u64> {
+    let unused = unused_managed_java(state).await?;
+    Ok(remove_runtimes(state, unused).await)
+}
+
+async fn remove_runtimes(state: &LauncherState, unused: Vec<UnusedJava>) -> u64 {
+    let mut freed = 0;
+    for unused in unused {
+        match state.java.remove_managed_runtime(&unused.runtime).await {
+            Ok(()) => freed += unused.bytes,
+            Err(err) => {
+                tracing::warn!(dir = %unused.dir.display(), "could not remove Java runtime: {err}");
+            }
+        }
+    }
+    freed
+}
+
+enum UnusedAnswer {
+    Remove,
+    Keep,
+}
+
+/// Updates first so the runtimes they replace are offered for removal in the
+/// same pass
+/// The downloads run at background priority whoever calls this
+/// Removing is never the primary choice so an unattended run keeps them and
+/// only an explicit keep is remembered
+#[tracing::instrument(skip(state, progress))]
+pub async fn maintain_java(
+    state: &Arc<LauncherState>,
+    progress: Option<&GroupedProgressSession>,
+) -> LauncherResult<Vec<JavaUpdate>> {
+    let updates = Priority::Background
+        .scope(update_managed_java(state, progress))
+        .await?;
+
+    let all_unused = unused_managed_java(state).await?;
+    let still_unused: Vec<String> = all_unused
+        .iter()
+        .map(|unused| unused.runtime.absolute_path.clone())
+        .collect();
+    let kept = state.settings.read().kept_java.clone();
+    let unused: Vec<UnusedJava> = all_unused
+        .into_iter()
+        .filter(|unused| !kept.contains(&unused.runtime.absolute_path))
+        .collect();
+    if unused.is_empty() {
+        return Ok(updates);
+    }
+
+    let bytes = unused.iter().map(|unused| unused.bytes).sum();
+    let versions = unused
+        .iter()
+        .map(|unused| format!("{} {}", unused.runtime.vendor, unused.runtime.version))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let prompt = Prompt::new(
+        "Remove unused Java runtimes?",
+        format!(
+            "No cluster uses {versions} any more. Removing {} frees {}.",
+            if unused.len() == 1 { "it" } else { "them" },
+            format_bytes(bytes)
+        ),
+    )
+    .option(
+        Choice::danger("java.remove_unused", "Remove"),
+        UnusedAnswer::Remove,
+    )
+    .option(Choice::new("java.keep_unused", "Keep"), UnusedAnswer::Keep);
+
+    match state.services.events.ask(prompt).await {
+        Ok(Some(chosen)) => match chosen.value {
+            UnusedAnswer::Remove => {
+                let freed = remove_runtimes(state, unused).await;
+                tracing::info!(freed, "removed unused Java runtimes");
+            }
+            UnusedAnswer::Keep => {
+                // Everything still unused was either just kept or kept before
+                // a runtime removed or back in use since drops out
+                let settings = {
+                    let mut lock = state.settings.write();
+                    lock.kept_java = still_unused;
+                    lock.clone()
+                };
+                save_settings(&settings).await?;
+            }
+        },
+        // Closed or nobody there to answer asked again next time
+        Ok(None) => {}
+        Err(err) => tracing::warn!("could not offer to remove unused Java: {err}"),
+    }
+
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use oneclient_common::paths;
+    use oneclient_java::JavaVendor;
+
+    fn runtime(path: PathBuf, major: u32, version: &str) -> JavaRuntime {
+        JavaRuntime {
+            absolute_path: path.to_string_lossy().into_owned(),
+            major,
+            version: version.to_string(),
+            vendor: JavaVendor::Zulu,
+            os_arch: "x86_64".to_string(),
+            is_jdk: true,
+            probe_version: 0,
+        }
+    }
+
+    #[test]
+    fn only_unnamed_managed_runtimes_that_are_not_preferred_are_unused() {
+        paths::set_launcher_dir(std::env::temp_dir().join(format!(
+            "oneclient-java-maintenance-{}",
+            uuid::Uuid::new_v4()
+        )));
+        let java = paths::java_dir().unwrap();
+        let old = runtime(java.join("zulu21.40").join("bin/java"), 21, "21.0.5");
+        let pinned = runtime(java.join("zulu21.38").join("bin/java"), 21, "21.0.4");
+        let newest = runtime(java.join("zulu21.42").join("bin/java"), 21, "21.0.7");
+        let system = runtime(PathBuf::from("/usr/lib/jvm/java-17/bin/java"), 17, "17.0.1");
+        let runtimes = vec![old.clone(), pinned.clone(), newest, system];
+
+        let referenced = HashSet::from([pinned.absolute_path.clone()]);
+        let unused = unreferenced_managed(&runtimes, &referenced);
+
+        assert_eq!(
+            unused
+                .iter()
+                .map(|runtime| &runtime.absolute_path)
+                .collect::<Vec<_>>(),
+            vec![&old.absolute_path]
+        );
+    }
+}
@@ -8,18 +8,23 @@ pub mod clusters;
 mod error;
 pub mod game;
 pub mod images;
+pub mod java_maintenance;
 mod java_store;
 pub mod logger;
 pub mod migration;
+pub mod packages;
 pub mod recovery;
+pub mod servers;
 pub mod reporting;
 pub mod settings;
 pub mod storage;
 pub mod tos;
 mod state;
 pub mod simulate;
+pub mod skins;
 pub mod verify;
 pub mod versions;
+pub mod worlds;
 
 pub use oneclient_content::bundles::{
     apply_bundle_updates, check_bundle_updates, effective_enabled, install_bundle,
@@ -40,7 +45,10 @@ pub use clusters::{
     ensure_from_bundles, ensure_from_versions, estimate_cluster_download,
 };
 pub use error::{LauncherError, LauncherResult, SentryExclusion};
-pub use game::{GameError, LaunchedGame, get_loader_versions, launch_cluster};
+pub use game::{
+    GameError, GameExit, LaunchedGame, QuickPlay, get_loader_versions, launch_cluster,
+    launch_cluster_with,
+};
 pub use images::ImageCacheStore;
 pub use oneclient_cluster::logs::{
     LogFileInfo, LogKind, LogLevel, LogLine, LogsError, MclogsUploadResponse, ReadOptions,
@@ -54,6 +62,9 @@ pub use migration::{
 pub use oneclient_cluster::screenshots::{
     ScreenshotInfo, ScreenshotsError, delete_screenshot, list_cluster_screenshots, load_screenshot,
 };
+pub use servers::{
+    ServerEntry, ServersError, add_server, list_servers, move_server, remove_server,
+};
 pub use oneclient_content::packages::LinkedArtifactInfo;
 pub use oneclient_content::packages::updates::{
     BrowserPackageUpdate, BrowserUpdateCheck, apply_browser_package_update,
@@ -0,0 +1,275 @@
+//! Installing one package into a cluster with whatever it depends on shared
+//! by every front-end so they resolve dependencies the same way
+
+use std::sync::Arc;
+
+use oneclient_content::packages::{PackageStore, ProviderId, pick_version};
+
+pub use oneclient_content::packages::WorldTarget;
+
+use crate::state::LauncherState;
+use crate::{LauncherError, LauncherResult};
+
+/// The session is detached not finished so the caller can reuse its notification
+/// as the "Installed" / "Install failed" result
+/// `session_id` is `None` if no download ran
+pub struct PackageInstall {
+    pub session_id: Option<uuid::Uuid>,
+    pub result: LauncherResult<String>,
+    pub dependencies: Vec<String>,
+    /// Unresolved or failed dependencies the package still installs
+    pub missing_dependencies: Vec<String>,
+}
+
+impl PackageInstall {
+    fn failed(err: LauncherError) -> Self {
+        Self {
+            session_id: None,
+            result: Err(err),
+            dependencies: Vec::new(),
+            missing_dependencies: Vec::new(),
+        }
+    }
+}
+
+pub fn install_body(name: &str, dependencies: &[String], missing: &[String]) -> String {
+    let mut body = format!("Added {name}");
+
+    if !dependencies.is_empty() {
+        body.push_str(&format!(
+            " with {} dependenc{}",
+            dependencies.len(),
+            if dependencies.len() == 1 { "y" } else { "ies" }
+        ));
+    }
+    body.push('.');
+
+    if !missing.is_empty() {
+        body.push_str(&format!(" Could not add: {}.", missing.join(", ")));
+    }
+
+    body
+}
+
+/// `world` only matters for datapacks their dependencies follow them into it
+pub async fn install_package(
+    state: &Arc<LauncherState>,
+    provider: ProviderId,
+    project_id: &str,
+    version_id: &str,
+    cluster_id: i64,
+    world: WorldTarget,
+) -> PackageInstall {
+    let lookup = async {
+        let provider_impl = state.services.packages.get(provider)?;
+        let project = provider_impl
+            .get_project(project_id, &state.services.content())
+            .await?;
+        let version = provider_impl
+            .get_version(project_id, version_id, &state.services.content())
+            .await?;
+        LauncherResult::Ok((project, version))
+    }
+    .await;
+
+    let (project, version) = match lookup {
+        Ok(found) => found,
+        Err(err) => return PackageInstall::failed(err),
+    };
+
+    // Resolved before the session starts so its children can be announced up front
+    let mut resolution = oneclient_content::packages::DependencyPlan::default();
+    if oneclient_content::packages::resolves_dependencies(project.content_type) {
+        match oneclient_content::packages::plan_dependencies_or_ask(
+            provider,
+            &project,
+            &version,
+            cluster_id,
+            &state.services.content(),
+        )
+        .await
+        {
+            Ok(resolved) => resolution = resolved,
+            Err(err) => return PackageInstall::failed(err.into()),
+        }
+    }
+
+    if let Err(err) =
+        oneclient_content::packages::confirm_dependency_plan(&resolution, &state.services.content())
+            .await
+    {
+        return PackageInstall::failed(err.into());
+    }
+
+    let session = oneclient_events::GroupedProgressSession::start(
+        &state.services.events,
+        format!("Installing {}", project.name),
+    );
+
+    let size = version.primary_file().map(|f| f.size).unwrap_or(0);
+    let dependency_bytes: u64 = resolution
+        .install
+        .iter()
+        .chain(resolution.upgrade.iter().map(|upgrade| &upgrade.to))
+        .map(|dep| dep.version.primary_file().map(|f| f.size).unwrap_or(0))
+        .sum();
+    session.expect(
+        oneclient_events::TaskCategory::Packages,
+        1 + (resolution.install.len() + resolution.upgrade.len()) as u64,
+        size + dependency_bytes,
+    );
+
+    let mut installed_dependencies = Vec::new();
+    let mut missing_dependencies = resolution.unresolved;
+
+    // Dependencies first so the package is never in a cluster without them however the run ends
+    for dependency in &resolution.install {
+        let child = session.child(
+            dependency.project.name.clone(),
+            dependency
+                .version
+                .primary_file()
+                .map(|f| f.size)
+                .unwrap_or(0),
+            oneclient_events::TaskCategory::Packages,
+        );
+
+        let result = PackageStore::install_to_cluster(
+            provider,
+            &dependency.project,
+            &dependency.version,
+            cluster_id,
+            &world,
+            false,
+            false,
+            Some(&child),
+            &state.services.content(),
+        )
+        .await;
+
+        child.finish();
+
+        match result {
+            Ok(_) => installed_dependencies.push(dependency.project.name.clone()),
+            Err(err) => {
+                tracing::warn!(
+                    dependency = %dependency.project.name,
+                    %err,
+                    "failed to install dependency"
+                );
+                missing_dependencies.push(dependency.project.name.clone());
+            }
+        }
+    }
+
+    for upgrade in &resolution.upgrade {
+        let dependency = &upgrade.to;
+        let child = session.child(
+            dependency.project.name.clone(),
+            dependency
+                .version
+                .primary_file()
+                .map(|f| f.size)
+                .unwrap_or(0),
+            oneclient_events::TaskCategory::Packages,
+        );
+
+        let result = oneclient_content::packages::apply_dependency_upgrade(
+            provider,
+            upgrade,
+            cluster_id,
+            &world,
+            Some(&child),
+            &state.services.content(),
+        )
+        .await;
+
+        child.finish();
+
+        match result {
+            Ok(_) => installed_dependencies.push(dependency.project.name.clone()),
+            Err(err) => {
+                tracing::warn!(
+                    dependency = %dependency.project.name,
+                    %err,
+                    "failed to upgrade dependency"
+                );
+                missing_dependencies.push(dependency.project.name.clone());
+            }
+        }
+    }
+
+    for dependency in &resolution.enable {
+        let result = oneclient_content::bundles::set_artifact_enabled_to(
+            cluster_id,
+            &dependency.hash,
+            true,
+            &state.services.content(),
+        )
+        .await;
+
+        match result {
+            Ok(()) => installed_dependencies.push(dependency.name.clone()),
+            Err(err) => {
+                tracing::warn!(dependency = %dependency.name, %err, "failed to enable dependency");
+                missing_dependencies.push(dependency.name.clone());
+            }
+        }
+    }
+
+    let child = session.child(
+        project.name.clone(),
+        size,
+        oneclient_events::TaskCategory::Packages,
+    );
+
+    let result = PackageStore::install_to_cluster(
+        provider,
+        &project,
+        &version,
+        cluster_id,
+        &world,
+        false,
+        false,
+        Some(&child),
+        &state.services.content(),
+    )
+    .await;
+
+    child.finish();
+
+    PackageInstall {
+        session_id: Some(session.detach()),
+        result: result.map(|_| project.name).map_err(LauncherError::from),
+        dependencies: installed_dependencies,
+        missing_dependencies,
+    }
+}
+
+/// What gets installed when no version is named the newest release that fits
+/// the cluster
+pub async fn latest_version_for(
+    state: &LauncherState,
+    provider: ProviderId,
+    project_id: &str,
+    cluster_id: i64,
+) -> LauncherResult<Option<String>> {
+    let ctx = state.services.content();
+    let provider_impl = state.services.packages.get(provider)?;
+    let cluster = PackageStore::get_cluster(cluster_id, &ctx).await?;
+
+    Ok(pick_version(provider_impl, project_id, &cluster, &ctx)
+        .await?
+        .map(|version| version.version_id))
+}
+
+/// Moves an installed datapack to another world or to all of them
+pub async fn set_datapack_world(
+    state: &LauncherState,
+    cluster_id: i64,
+    hash: &str,
+    world: &WorldTarget,
+) -> LauncherResult<()> {
+    let ctx = state.services.content();
+    Ok(PackageStore::set_datapack_world(cluster_id, hash, world, &ctx).await?)
+}
@@ -0,0 +1,39 @@
+//! Server list edits go through here so they can be refused while a game has
+//! the directory open it rewrites `servers.dat` on exit and would drop them
+
+use oneclient_cluster::servers;
+
+use crate::LauncherResult;
+use crate::game::GameError;
+use crate::state::LauncherState;
+use crate::worlds::idle_cluster;
+
+pub use oneclient_cluster::servers::{ServerEntry, ServersError, list_servers};
+
+pub async fn add_server(
+    state: &LauncherState,
+    cluster_id: i64,
+    entry: ServerEntry,
+) -> LauncherResult<()> {
+    let cluster = idle_cluster(state, cluster_id, GameError::ServersInUse).await?;
+    Ok(servers::add_server(&cluster, entry).await?)
+}
+
+pub async fn remove_server(
+    state: &LauncherState,
+    cluster_id: i64,
+    address: &str,
+) -> LauncherResult<ServerEntry> {
+    let cluster = idle_cluster(state, cluster_id, GameError::ServersInUse).await?;
+    Ok(servers::remove_server(&cluster, address).await?)
+}
+
+pub async fn move_server(
+    state: &LauncherState,
+    cluster_id: i64,
+    address: &str,
+    to: usize,
+) -> LauncherResult<()> {
+    let cluster = idle_cluster(state, cluster_id, GameError::ServersInUse).await?;
+    Ok(servers::move_server(&cluster, address, to).await?)
+}
@@ -3,6 +3,8 @@ use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 
 use oneclient_cluster::GameSettingsProfile;
+use oneclient_cluster::worlds::SnapshotPolicy;
+use oneclient_content::packages::PackageIndex;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
@@ -30,6 +32,8 @@ pub struct LauncherSettings {
 	pub enable_gamemode: bool,
 	pub discord_enabled: bool,
 	pub max_concurrent_requests: usize,
+	/// KiB per second shared by every download `None` is unlimited
+	pub download_limit_kib: Option<u64>,
 	pub global_game_settings: GameSettingsProfile,
 	pub allow_parallel_running_clusters: bool,
 	pub dynamic_background_enabled: bool,
@@ -43,6 +47,13 @@ pub struct LauncherSettings {
 	pub curseforge_api_key: Option<String>,
 	pub custom_api_endpoint: Option<String>,
 	pub custom_meta_url_base: Option<String>,
+	/// Self-hosted JSON package indexes searched alongside Modrinth and CurseForge
+	pub package_indexes: Vec<PackageIndex>,
+	/// Taken of every world a session saved once its game exits
+	pub world_snapshots: SnapshotPolicy,
+	/// Paths of unused managed runtimes the user chose to keep so they are not
+	/// offered for removal again
+	pub kept_java: Vec<String>,
 }
 
 impl LauncherSettings {
@@ -65,6 +76,7 @@ impl Default for LauncherSettings {
 			discord_enabled: true,
 			enable_gamemode: false,
 			max_concurrent_requests: 25,
+			download_limit_kib: None,
 			global_game_settings: GameSettingsProfile::default_global_profile(),
 			allow_parallel_running_clusters: false,
 			dynamic_background_enabled: true,
@@ -78,6 +90,9 @@ impl Default for LauncherSettings {
 			curseforge_api_key: None,
 			custom_api_endpoint: None,
 			custom_meta_url_base: None,
+			package_indexes: Vec::new(),
+			world_snapshots: SnapshotPolicy::default(),
+			kept_java: Vec::new(),
 		}
 	}
 }
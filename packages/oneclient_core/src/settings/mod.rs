@@ -18,5 +18,14 @@ pub fn net_config(settings: &LauncherSettings) -> oneclient_net::NetConfig {
 		settings.custom_api_endpoint.as_deref(),
 		settings.custom_meta_url_base.as_deref(),
 	)
+	.with_download_limit(settings.download_limit_kib)
+}
+
+/// A bad url only switches that index off it never blocks saving the rest
+pub fn apply_package_indexes(
+	packages: &oneclient_content::packages::provider::PackageProviderRegistry,
+	settings: &LauncherSettings,
+) {
+	packages.self_hosted().set_indexes(&settings.package_indexes);
 }
 
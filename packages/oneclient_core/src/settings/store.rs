@@ -53,6 +53,7 @@ pub async fn save_settings_and_apply(
 ) -> LauncherResult<()> {
     save_settings(settings).await?;
     services.requester.set_config(super::net_config(settings));
+    super::apply_package_indexes(&services.packages, settings);
     Ok(())
 }
 
@@ -0,0 +1,87 @@
+//! Skin and cape changes for the account's Minecraft profile the token comes
+//! through the auth service so an expired one is renewed before the write
+//! Only Microsoft accounts have a profile Mojang lets us change
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use oneclient_mc::{self as minecraft, MojangFullPlayerProfile, SavedSkin, SkinVariant};
+
+use crate::state::LauncherState;
+use crate::{LauncherError, LauncherResult};
+
+pub use oneclient_mc::{list_saved_skins, remove_saved_skin, saved_skin_path};
+
+async fn access_token(state: &LauncherState, account_id: Uuid) -> LauncherResult<String> {
+    let account = state.auth.account_for_launch(account_id).await?;
+    if !account.is_microsoft() {
+        return Err(LauncherError::Minecraft(format!(
+            "{} is not a Microsoft account so its skin cannot be changed here",
+            account.username
+        )));
+    }
+    Ok(account.access_token)
+}
+
+/// `save_as` also keeps the image in the account's skin library
+#[tracing::instrument(skip(state, png), level = "debug")]
+pub async fn upload_skin(
+    state: &Arc<LauncherState>,
+    account_id: Uuid,
+    png: Vec<u8>,
+    variant: SkinVariant,
+    save_as: Option<&str>,
+) -> LauncherResult<MojangFullPlayerProfile> {
+    // Before the token so a bad file never costs a renewal
+    minecraft::validate_skin_png(&png)?;
+
+    if let Some(name) = save_as {
+        minecraft::save_skin(account_id, name, &png, variant.clone()).await?;
+    }
+
+    let token = access_token(state, account_id).await?;
+    let profile = minecraft::upload_skin(&state.services.requester, &token, png, variant).await?;
+    tracing::info!(%account_id, "uploaded skin");
+    Ok(profile)
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn apply_saved_skin(
+    state: &Arc<LauncherState>,
+    account_id: Uuid,
+    skin_id: &str,
+) -> LauncherResult<MojangFullPlayerProfile> {
+    let (skin, png) = minecraft::load_saved_skin(account_id, skin_id).await?;
+    upload_skin(state, account_id, png, skin.variant, None).await
+}
+
+/// Nothing is uploaded the library is local
+pub async fn save_skin(
+    account_id: Uuid,
+    name: &str,
+    png: &[u8],
+    variant: SkinVariant,
+) -> LauncherResult<SavedSkin> {
+    Ok(minecraft::save_skin(account_id, name, png, variant).await?)
+}
+
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn reset_skin(
+    state: &Arc<LauncherState>,
+    account_id: Uuid,
+) -> LauncherResult<MojangFullPlayerProfile> {
+    let token = access_token(state, account_id).await?;
+    Ok(minecraft::reset_skin(&state.services.requester, &token).await?)
+}
+
+/// `None` hides the cape
+#[tracing::instrument(skip(state), level = "debug")]
+pub async fn set_cape(
+    state: &Arc<LauncherState>,
+    account_id: Uuid,
+    cape_id: Option<&str>,
+) -> LauncherResult<MojangFullPlayerProfile> {
+    let token = access_token(state, account_id).await?;
+    Ok(minecraft::set_active_cape(&state.services.requester, &token, cape_id).await?)
+}
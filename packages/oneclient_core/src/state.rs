@@ -7,7 +7,7 @@ use oneclient_auth::AuthService;
 use crate::clusters::ClusterManager;
 use oneclient_content::bundles::BundlesManager;
 use oneclient_discord::DiscordRpc;
-use oneclient_net::RequestClient;
+use oneclient_net::{Priority, RequestClient};
 use crate::images::ImageCacheStore;
 use oneclient_java::JavaService;
 use oneclient_events::EventBus;
@@ -76,6 +76,7 @@ impl LauncherState {
 		services
 			.requester
 			.set_config(crate::settings::net_config(&settings));
+		crate::settings::apply_package_indexes(&services.packages, &settings);
         let auth = Arc::new(
 			AuthService::load(services.requester.clone(), services.events.clone()).await?,
 		);
@@ -106,9 +107,10 @@ impl LauncherState {
 	}
 }
 
+/// Both tasks download at background priority
 pub fn run_startup_tasks(state: &Arc<LauncherState>) {
 	let background = Arc::clone(state);
-	tokio::spawn(async move {
+	tokio::spawn(Priority::Background.scope(async move {
 			let recovery = match crate::recovery::reconstruct_from_disk(&background).await {
 				Ok(report) => report,
 				Err(err) => {
@@ -157,5 +159,13 @@ pub fn run_startup_tasks(state: &Arc<LauncherState>) {
 			}
 
 		background.services.events.signal(oneclient_events::Signal::SyncComplete);
-	});
+	}));
+
+	// Its own task so a runtime download never holds up the sync signal
+	let java = Arc::clone(state);
+	tokio::spawn(Priority::Background.scope(async move {
+		if let Err(err) = crate::java_maintenance::maintain_java(&java, None).await {
+			tracing::warn!("Java runtime maintenance failed: {err:#}");
+		}
+	}));
 }
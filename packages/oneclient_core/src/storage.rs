@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::LauncherResult;
+use crate::java_maintenance::{remove_unused_java, unused_managed_java};
 use crate::state::LauncherState;
 use oneclient_common::domain::ContentType;
 use oneclient_common::paths;
@@ -45,6 +46,8 @@ pub struct StorageReport {
     pub clusters: Vec<StorageEntry>,
     pub unreferenced_cache: ReclaimableEntry,
     pub legacy_cluster_content: ReclaimableEntry,
+    /// Launcher-installed runtimes no setting profile points at
+    pub unused_java_runtimes: ReclaimableEntry,
 }
 
 #[tracing::instrument(skip(state))]
@@ -85,12 +88,19 @@ pub async fn storage_report(state: &LauncherState) -> LauncherResult<StorageRepo
         files: unreferenced.len(),
     };
 
+    let unused_java = unused_managed_java(state).await?;
+    let unused_java_runtimes = ReclaimableEntry {
+        bytes: unused_java.iter().map(|unused| unused.bytes).sum(),
+        files: unused_java.len(),
+    };
+
     Ok(StorageReport {
         total_bytes: dir_size(launcher).await,
         categories,
         clusters,
         unreferenced_cache,
         legacy_cluster_content: legacy_cluster_content(state).await?,
+        unused_java_runtimes,
     })
 }
 
@@ -224,6 +234,15 @@ pub async fn clean_legacy_cluster_content(state: &LauncherState) -> LauncherResu
     Ok(report.removed)
 }
 
+pub async fn clean_unused_java(state: &LauncherState) -> LauncherResult<u64> {
+    if showing_fixture() {
+        tracing::info!("fixture storage report is active; skipping Java cleanup");
+        return Ok(0);
+    }
+
+    remove_unused_java(state).await
+}
+
 /// Set `ONECLIENT_FAKE_STORAGE` to `empty` `clean` or `full` to return a fixture
 /// instead of scanning disk
 /// Read on every refresh so no rebuild is needed
@@ -245,6 +264,7 @@ fn fixture_report() -> Option<StorageReport> {
             clusters: Vec::new(),
             unreferenced_cache: ReclaimableEntry::default(),
             legacy_cluster_content: ReclaimableEntry::default(),
+            unused_java_runtimes: ReclaimableEntry::default(),
         },
         "clean" => StorageReport {
             total_bytes: 1_284_000_000,
@@ -265,6 +285,7 @@ fn fixture_report() -> Option<StorageReport> {
             ],
             unreferenced_cache: ReclaimableEntry::default(),
             legacy_cluster_content: ReclaimableEntry::default(),
+            unused_java_runtimes: ReclaimableEntry::default(),
         },
         "full" => StorageReport {
             total_bytes: 4_930_000_000,
@@ -294,6 +315,10 @@ fn fixture_report() -> Option<StorageReport> {
                 bytes: 261_000_000,
                 files: 439,
             },
+            unused_java_runtimes: ReclaimableEntry {
+                bytes: 52_000_000,
+                files: 1,
+            },
         },
         other => {
             tracing::warn!(
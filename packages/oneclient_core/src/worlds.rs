@@ -0,0 +1,107 @@
+//! World changes go through here so they can be refused while a game has the
+//! directory open in the shared `.minecraft` that is any non-dedicated
+//! cluster's game not only the one whose page asked
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use oneclient_cluster::worlds;
+
+use crate::LauncherResult;
+use crate::clusters::Cluster;
+use crate::game::GameError;
+use crate::state::LauncherState;
+
+pub use oneclient_cluster::worlds::{
+    Difficulty, GameMode, LevelSummary, SnapshotPolicy, WorldBackup, WorldInfo, WorldSnapshot,
+    WorldsError, list_world_backups, list_world_snapshots, list_worlds, load_world_icon,
+    read_world,
+};
+
+/// `in_use` builds the refusal from the running cluster's name so each
+/// caller can say what it was kept from changing
+pub(crate) async fn idle_cluster(
+    state: &LauncherState,
+    cluster_id: i64,
+    in_use: fn(String) -> GameError,
+) -> LauncherResult<Cluster> {
+    let cluster = state.clusters.get(cluster_id).await?;
+    if let Some(other) = state.games.dir_in_use(&cluster.game_dir()?) {
+        let name = crate::game::running_cluster_name(state, other).await;
+        return Err(in_use(name).into());
+    }
+    Ok(cluster)
+}
+
+/// Refused while running even though nothing changes the game writes region
+/// files as it goes so the copy could be torn
+pub async fn backup_world(
+    state: &LauncherState,
+    cluster_id: i64,
+    folder: &str,
+) -> LauncherResult<WorldBackup> {
+    let cluster = idle_cluster(state, cluster_id, GameError::WorldsInUse).await?;
+    Ok(worlds::backup_world(&cluster, folder).await?)
+}
+
+pub async fn restore_world_backup(
+    state: &LauncherState,
+    cluster_id: i64,
+    backup: &Path,
+) -> LauncherResult<String> {
+    let cluster = idle_cluster(state, cluster_id, GameError::WorldsInUse).await?;
+    Ok(worlds::restore_world_backup(&cluster, backup).await?)
+}
+
+/// Snapshots are only ever restored here taking them is automatic
+pub async fn restore_world_snapshot(
+    state: &LauncherState,
+    cluster_id: i64,
+    snapshot: &Path,
+) -> LauncherResult<String> {
+    let cluster = idle_cluster(state, cluster_id, GameError::WorldsInUse).await?;
+    Ok(worlds::restore_world_snapshot(&cluster, snapshot).await?)
+}
+
+pub async fn delete_world(
+    state: &LauncherState,
+    cluster_id: i64,
+    folder: &str,
+) -> LauncherResult<()> {
+    let cluster = idle_cluster(state, cluster_id, GameError::WorldsInUse).await?;
+    Ok(worlds::delete_world(&cluster, folder).await?)
+}
+
+/// Once a session's game has exited failures are only logged a missed
+/// snapshot must not turn a clean exit into an error
+pub(crate) async fn snapshot_after_session(
+    state: &LauncherState,
+    cluster: &Cluster,
+    since: DateTime<Utc>,
+) {
+    let cluster_id = cluster.id;
+    // Another cluster still playing in the shared dir could be mid-save so
+    // the last game to leave it snapshots from the earliest session start
+    let since = match cluster.game_dir() {
+        Ok(dir) => match state.games.claim_snapshot(&dir, since) {
+            Some(since) => since,
+            None => {
+                tracing::debug!(
+                    cluster_id,
+                    "game dir still in use deferring world snapshots"
+                );
+                return;
+            }
+        },
+        Err(_) => since,
+    };
+
+    let policy = state.settings.read().world_snapshots;
+    match worlds::snapshot_played_worlds(cluster, since, &policy).await {
+        Ok(taken) if !taken.is_empty() => {
+            tracing::info!(cluster_id, count = taken.len(), "snapshotted played worlds");
+        }
+        Ok(_) => {}
+        Err(err) => tracing::warn!(cluster_id, error = %err, "failed to snapshot worlds"),
+    }
+}
@@ -0,0 +1,12 @@
+-- The account a cluster launches with when it should not be the launcher-wide
+-- default, e.g. an alt kept for one server. No row means "use the default".
+--
+-- `account_id` is the account's UUID as stored by the credentials store, which
+-- lives outside this database, so there is no foreign key on it; a removed
+-- account is noticed at launch time and the user is asked what to do.
+CREATE TABLE cluster_accounts (
+    cluster_id INTEGER PRIMARY KEY NOT NULL,
+    account_id TEXT NOT NULL,
+    FOREIGN KEY (cluster_id) REFERENCES clusters (id) ON DELETE CASCADE
+);
+CREATE INDEX cluster_accounts_account_id_idx ON cluster_accounts (account_id);
@@ -0,0 +1,5 @@
+-- Vanilla only loads datapacks from inside a world, so a datapack link names
+-- the `saves/` folder it goes into. NULL means every world in the game
+-- directory, which is also what every link written before this migration and
+-- every non-datapack link keeps meaning.
+ALTER TABLE `cluster_artifacts` ADD COLUMN `target_world` TEXT;
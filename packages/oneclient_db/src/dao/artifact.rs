@@ -204,12 +204,13 @@ pub async fn link_cluster_artifact(
 
 /// `DISTINCT` is required `provider_releases` is keyed by version so one
 /// artifact joins several rows and would otherwise be reported repeatedly
+/// `exclude_hash` of `None` lists every installed version
 pub async fn list_cluster_artifacts_for_project(
 	pool: &SqlitePool,
 	cluster_id: i64,
 	provider: i64,
 	project_id: &str,
-	exclude_hash: &str,
+	exclude_hash: Option<&str>,
 ) -> Result<Vec<ClusterArtifactRow>, sqlx::Error> {
 	sqlx::query_as!(
 		ClusterArtifactRow,
@@ -220,7 +221,7 @@ pub async fn list_cluster_artifacts_for_project(
 		WHERE ca.cluster_id = ?
 			AND pr.provider = ?
 			AND pr.project_id = ?
-			AND ca.hash <> ?
+			AND ca.hash IS NOT ?
 		"#,
 		cluster_id,
 		provider,
@@ -263,6 +264,54 @@ pub async fn unlink_cluster_artifact(
 	Ok(())
 }
 
+/// Datapacks only `None` puts the pack into every world
+/// Runtime queries the column is read apart from [`ClusterArtifactRow`] so
+/// nothing that ignores worlds has to carry it
+pub async fn set_cluster_artifact_world(
+	pool: &SqlitePool,
+	cluster_id: i64,
+	hash: &str,
+	world: Option<&str>,
+) -> Result<(), sqlx::Error> {
+	sqlx::query("UPDATE cluster_artifacts SET target_world = ? WHERE cluster_id = ? AND hash = ?")
+		.bind(world)
+		.bind(cluster_id)
+		.bind(hash)
+		.execute(pool)
+		.await?;
+
+	Ok(())
+}
+
+pub async fn get_cluster_artifact_world(
+	pool: &SqlitePool,
+	cluster_id: i64,
+	hash: &str,
+) -> Result<Option<String>, sqlx::Error> {
+	let world: Option<Option<String>> = sqlx::query_scalar(
+		"SELECT target_world FROM cluster_artifacts WHERE cluster_id = ? AND hash = ?",
+	)
+	.bind(cluster_id)
+	.bind(hash)
+	.fetch_optional(pool)
+	.await?;
+
+	Ok(world.flatten())
+}
+
+/// `(hash, world)` for the links aimed at one world every other link is in all of them
+pub async fn list_cluster_artifact_worlds(
+	pool: &SqlitePool,
+	cluster_id: i64,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+	sqlx::query_as(
+		"SELECT hash, target_world FROM cluster_artifacts WHERE cluster_id = ? AND target_world IS NOT NULL",
+	)
+	.bind(cluster_id)
+	.fetch_all(pool)
+	.await
+}
+
 pub async fn list_cluster_artifacts(
 	pool: &SqlitePool,
 	cluster_id: i64,
@@ -322,3 +371,59 @@ pub async fn update_cluster_artifact(
 	.fetch_one(pool)
 	.await
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	async fn pool() -> SqlitePool {
+		let pool = SqlitePool::connect("sqlite::memory:")
+			.await
+			.expect("in-memory sqlite");
+		sqlx::migrate!().run(&pool).await.expect("migrations run");
+		sqlx::query(
+			r#"
+			INSERT INTO clusters (id, name, folder_name, mc_version)
+			VALUES (1, 'one', 'one', '1.21.4'), (2, 'two', 'two', '1.21.4');
+			INSERT INTO artifacts (hash, content_type, path, file_name)
+			VALUES ('a', 0, 'a.zip', 'a.zip'), ('b', 0, 'b.zip', 'b.zip');
+			INSERT INTO cluster_artifacts (cluster_id, hash, cluster_file_name)
+			VALUES (1, 'a', 'a.zip'), (1, 'b', 'b.zip'), (2, 'a', 'a.zip');
+			"#,
+		)
+		.execute(&pool)
+		.await
+		.expect("artifact rows");
+		pool
+	}
+
+	#[tokio::test]
+	async fn a_link_goes_into_one_world_and_back_to_all_of_them() {
+		let pool = pool().await;
+		assert_eq!(get_cluster_artifact_world(&pool, 1, "a").await.unwrap(), None);
+
+		set_cluster_artifact_world(&pool, 1, "a", Some("Survival")).await.unwrap();
+		assert_eq!(
+			get_cluster_artifact_world(&pool, 1, "a").await.unwrap().as_deref(),
+			Some("Survival")
+		);
+		// The same pack linked into another cluster keeps its own world
+		assert_eq!(get_cluster_artifact_world(&pool, 2, "a").await.unwrap(), None);
+
+		set_cluster_artifact_world(&pool, 1, "a", None).await.unwrap();
+		assert_eq!(get_cluster_artifact_world(&pool, 1, "a").await.unwrap(), None);
+	}
+
+	#[tokio::test]
+	async fn only_links_aimed_at_a_world_are_listed() {
+		let pool = pool().await;
+		set_cluster_artifact_world(&pool, 1, "b", Some("Creative")).await.unwrap();
+		set_cluster_artifact_world(&pool, 2, "a", Some("Survival")).await.unwrap();
+
+		assert_eq!(
+			list_cluster_artifact_worlds(&pool, 1).await.unwrap(),
+			vec![("b".to_string(), "Creative".to_string())]
+		);
+		assert_eq!(get_cluster_artifact_world(&pool, 1, "missing").await.unwrap(), None);
+	}
+}
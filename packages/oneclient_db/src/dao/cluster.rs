@@ -201,6 +201,31 @@ pub async fn set_stage(pool: &SqlitePool, id: i64, stage: i64) -> Result<Cluster
 	.await
 }
 
+pub async fn set_loader(
+	pool: &SqlitePool,
+	id: i64,
+	mc_loader: i64,
+	mc_loader_version: Option<&str>,
+) -> Result<ClusterRow, sqlx::Error> {
+	sqlx::query_as!(
+		ClusterRow,
+		r#"
+		UPDATE clusters
+		SET mc_loader = ?,
+		    mc_loader_version = ?
+		WHERE id = ?
+		RETURNING
+			id, name, folder_name, setting_profile_name, mc_version, mc_loader,
+			stage, mc_loader_version, created_at, last_played, overall_played, linked_modpack_hash
+		"#,
+		mc_loader,
+		mc_loader_version,
+		id
+	)
+	.fetch_one(pool)
+	.await
+}
+
 pub async fn add_playtime(
 	pool: &SqlitePool,
 	id: i64,
@@ -0,0 +1,105 @@
+use sqlx::SqlitePool;
+
+use crate::error::DbResult;
+
+/// `None` when the cluster launches with the default account
+pub async fn get(pool: &SqlitePool, cluster_id: i64) -> DbResult<Option<String>> {
+	let account_id = sqlx::query_scalar!(
+		r#"SELECT account_id FROM cluster_accounts WHERE cluster_id = ?"#,
+		cluster_id
+	)
+	.fetch_optional(pool)
+	.await?;
+
+	Ok(account_id)
+}
+
+pub async fn set(pool: &SqlitePool, cluster_id: i64, account_id: &str) -> DbResult<()> {
+	sqlx::query!(
+		r#"
+		INSERT INTO cluster_accounts (cluster_id, account_id)
+		VALUES (?, ?)
+		ON CONFLICT(cluster_id) DO UPDATE SET account_id = excluded.account_id
+		"#,
+		cluster_id,
+		account_id
+	)
+	.execute(pool)
+	.await?;
+
+	Ok(())
+}
+
+pub async fn clear(pool: &SqlitePool, cluster_id: i64) -> DbResult<()> {
+	sqlx::query!(
+		r#"DELETE FROM cluster_accounts WHERE cluster_id = ?"#,
+		cluster_id
+	)
+	.execute(pool)
+	.await?;
+
+	Ok(())
+}
+
+/// For when the account itself is removed returns how many clusters fell back
+/// to the default
+pub async fn clear_account(pool: &SqlitePool, account_id: &str) -> DbResult<u64> {
+	let result = sqlx::query!(
+		r#"DELETE FROM cluster_accounts WHERE account_id = ?"#,
+		account_id
+	)
+	.execute(pool)
+	.await?;
+
+	Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	async fn pool() -> SqlitePool {
+		let pool = SqlitePool::connect("sqlite::memory:")
+			.await
+			.expect("in-memory sqlite");
+		sqlx::migrate!().run(&pool).await.expect("migrations run");
+		// Runtime query not the macro the offline cache is library-only
+		sqlx::query(
+			r#"
+			INSERT INTO clusters (id, name, folder_name, mc_version)
+			VALUES (1, 'one', 'one', '1.21.4'), (2, 'two', 'two', '1.8.9')
+			"#,
+		)
+		.execute(&pool)
+		.await
+		.expect("cluster rows");
+		pool
+	}
+
+	#[tokio::test]
+	async fn a_cluster_has_at_most_one_preferred_account() {
+		let pool = pool().await;
+		assert_eq!(get(&pool, 1).await.unwrap(), None);
+
+		set(&pool, 1, "alt").await.unwrap();
+		set(&pool, 1, "main").await.unwrap();
+		assert_eq!(get(&pool, 1).await.unwrap().as_deref(), Some("main"));
+
+		clear(&pool, 1).await.unwrap();
+		assert_eq!(get(&pool, 1).await.unwrap(), None);
+	}
+
+	#[tokio::test]
+	async fn removing_the_account_or_cluster_drops_the_preference() {
+		let pool = pool().await;
+		set(&pool, 1, "alt").await.unwrap();
+		set(&pool, 2, "alt").await.unwrap();
+
+		sqlx::query("DELETE FROM clusters WHERE id = 2")
+			.execute(&pool)
+			.await
+			.unwrap();
+		assert_eq!(clear_account(&pool, "alt").await.unwrap(), 1);
+		assert_eq!(get(&pool, 1).await.unwrap(), None);
+	}
+}
@@ -3,6 +3,7 @@ pub mod artifact;
 pub mod browser_package_update;
 pub mod bundle;
 pub mod cluster;
+pub mod cluster_account;
 pub mod cluster_bundle;
 pub mod game_session;
 pub mod java;
@@ -106,3 +106,16 @@ pub async fn delete_by_name(pool: &SqlitePool, name: &str) -> Result<(), DbError
 
     Ok(())
 }
+
+/// Every profile on `old` moves to `new` returns how many did
+pub async fn repoint_java_path(pool: &SqlitePool, old: &str, new: &str) -> DbResult<u64> {
+    let result = sqlx::query!(
+        r#"UPDATE setting_profiles SET java_path = ? WHERE java_path = ?"#,
+        new,
+        old,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -56,6 +56,8 @@ impl JavaPackage {
 pub enum PackageArchive {
 	Zip,
 	TarGz,
+	/// Mojang's per-file runtime manifest rather than an archive
+	Manifest,
 }
 
 impl PackageArchive {
@@ -60,6 +60,12 @@ pub enum JavaError {
 
 	#[error("failed to extract archive '{archive}'")]
 	ArchiveExtractFailed { archive: String },
+
+	#[error("'{path}' was not installed by the launcher")]
+	NotManaged { path: String },
+
+	#[error("runtime manifest for '{name}' is invalid: {reason}")]
+	InvalidRuntimeManifest { name: String, reason: String },
 }
 
 impl JavaError {
@@ -17,6 +17,10 @@ pub async fn install_package(
 	events: &EventBus,
 	progress: Option<&GroupedProgressSession>,
 ) -> JavaResult<PathBuf> {
+	if package.archive == PackageArchive::Manifest {
+		return crate::vendors::mojang::install_runtime(package, net, events, progress).await;
+	}
+
 	let java_dir = paths::java_dir()?;
 	polyio::create_dir_all(&java_dir).await?;
 
@@ -72,6 +76,7 @@ pub async fn install_package(
 	match package.archive {
 		PackageArchive::Zip => polyio::extract_zip(&archive_path, &extract_root).await?,
 		PackageArchive::TarGz => polyio::extract_tar_gz(&archive_path, &extract_root).await?,
+		PackageArchive::Manifest => unreachable!("installed file by file above"),
 	}
 
 	let executable = resolve_installed_executable(&extract_root, package);
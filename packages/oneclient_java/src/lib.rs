@@ -21,7 +21,7 @@ pub use locate::{LocatedJava, best_for_major, locate_java};
 pub use platform::{HostArch, HostOs, HostTarget};
 pub use service::{
 	AvailableJava, INSTALLABLE_MAJORS, JAVA_CHOICE_DOWNLOAD, JAVA_CHOICE_FOLDER, JAVA_VENDOR_HINT,
-	JavaService,
+	JavaService, managed_install_dir,
 };
 pub use store::{JavaStore, MemoryJavaStore, StoreError, StoreResult};
 pub use vendors::JavaVendor;
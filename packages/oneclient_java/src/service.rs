@@ -1,8 +1,9 @@
 use std::collections::BTreeMap;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 
+use oneclient_common::paths;
 use oneclient_events::{Choice, EventBus, GroupedProgressSession, Prompt};
 use oneclient_net::RequestClient;
 
@@ -152,6 +153,69 @@ impl JavaService {
 		self.register_checked(&executable, None).await
 	}
 
+	#[tracing::instrument(level = "debug", skip(self))]
+	pub async fn managed_runtimes(&self) -> JavaResult<Vec<JavaRuntime>> {
+		Ok(self
+			.list_runtimes()
+			.await?
+			.into_iter()
+			.filter(|runtime| managed_install_dir(runtime).is_some())
+			.collect())
+	}
+
+	/// `None` when the runtime is current its vendor stopped listing the major
+	/// or the launcher did not install it
+	#[tracing::instrument(level = "debug", skip(self, runtime), fields(path = %runtime.absolute_path))]
+	pub async fn available_update(&self, runtime: &JavaRuntime) -> JavaResult<Option<JavaPackage>> {
+		let Some(dir) = managed_install_dir(runtime) else {
+			return Ok(None);
+		};
+		let Some(provider) = provider_for_install(&dir, &runtime.vendor) else {
+			return Ok(None);
+		};
+		let Some(package) = provider
+			.latest_package_by_major(runtime.major, &self.net)
+			.await?
+		else {
+			return Ok(None);
+		};
+
+		Ok(is_newer(&package.java_version, &runtime.version).then_some(package))
+	}
+
+	/// Installs beside the old runtime which stays recorded until removed so
+	/// anything still pointing at it keeps working
+	#[tracing::instrument(skip(self, runtime, package, progress), fields(path = %runtime.absolute_path))]
+	pub async fn update_runtime(
+		&self,
+		runtime: &JavaRuntime,
+		package: &JavaPackage,
+		progress: Option<&GroupedProgressSession>,
+	) -> JavaResult<JavaRuntime> {
+		let provider = provider_for_vendor(&package.vendor).ok_or(JavaError::PackageNotFound {
+			major: runtime.major,
+		})?;
+		let executable = provider
+			.install_package(package, &self.net, &self.events, progress)
+			.await?;
+
+		self.register_checked(&executable, Some(runtime.major)).await
+	}
+
+	/// Deletes the whole install folder refusing anything the launcher did not
+	/// install so a system JDK is never touched
+	#[tracing::instrument(skip(self, runtime), fields(path = %runtime.absolute_path))]
+	pub async fn remove_managed_runtime(&self, runtime: &JavaRuntime) -> JavaResult<()> {
+		let dir = managed_install_dir(runtime).ok_or_else(|| JavaError::NotManaged {
+			path: runtime.absolute_path.clone(),
+		})?;
+
+		polyio::remove_dir_all(&dir).await?;
+		self.store.delete_by_path(&runtime.absolute_path).await?;
+		tracing::info!(dir = %dir.display(), "removed managed Java runtime");
+		Ok(())
+	}
+
 	#[tracing::instrument(skip(self))]
 	pub async fn remove_runtime(&self, absolute_path: &str) -> JavaResult<()> {
 		self.store.delete_by_path(absolute_path).await?;
@@ -394,6 +458,63 @@ fn provider_for_vendor(vendor: &JavaVendor) -> Option<Box<dyn vendors::JavaRunti
 		.find(|provider| &provider.vendor() == vendor)
 }
 
+/// Mojang's runtimes report whoever built them (Microsoft Oracle) so the
+/// folder name is what identifies them
+fn provider_for_install(
+	dir: &Path,
+	vendor: &JavaVendor,
+) -> Option<Box<dyn vendors::JavaRuntimeProvider>> {
+	let mojang = dir
+		.file_name()
+		.and_then(|name| name.to_str())
+		.is_some_and(|name| name.starts_with(vendors::mojang::INSTALL_PREFIX));
+
+	provider_for_vendor(if mojang { &JavaVendor::Mojang } else { vendor })
+}
+
+/// The folder directly under [`paths::java_dir`] holding the runtime `None`
+/// when the launcher did not install it
+#[must_use]
+pub fn managed_install_dir(runtime: &JavaRuntime) -> Option<PathBuf> {
+	let java_dir = paths::java_dir().ok()?;
+	let relative = Path::new(&runtime.absolute_path)
+		.strip_prefix(&java_dir)
+		.ok()?;
+
+	match relative.components().next()? {
+		Component::Normal(name) => Some(java_dir.join(name)),
+		_ => None,
+	}
+}
+
+/// `1.8.0_51` is Java 8 and `17.0.8+7` is Java 17
+pub(crate) fn version_parts(version: &str) -> Option<Vec<u32>> {
+	let parts: Vec<u32> = version
+		.strip_prefix("1.")
+		.unwrap_or(version)
+		.split(['.', '_', '+', '-', 'u'])
+		.map_while(|part| part.parse().ok())
+		.collect();
+
+	(!parts.is_empty()).then_some(parts)
+}
+
+/// Feature interim and update a vendor's own build number is not a Java
+/// update Java 8 has no interim and vendors spell its update `8.0.452`
+/// `8.452.09` or `8u452`
+fn release_key(parts: &[u32]) -> [u32; 3] {
+	let at = |index: usize| parts.get(index).copied().unwrap_or(0);
+	match at(0) {
+		8 if at(1) != 0 => [8, 0, at(1)],
+		_ => [at(0), at(1), at(2)],
+	}
+}
+
+fn is_newer(available: &[u32], installed: &str) -> bool {
+	version_parts(installed)
+		.is_some_and(|installed| release_key(available) > release_key(&installed))
+}
+
 /// Java 8 and earlier report `1.8.0_412` where the major is the *second*
 /// component 9 and later report `21.0.3` where it is the first
 pub(crate) fn parse_major_version(version: &str) -> Result<u32, JavaError> {
@@ -428,6 +549,25 @@ mod tests {
 		assert_eq!(parse_major_version("17").unwrap(), 17);
 	}
 
+	#[test]
+	fn only_a_newer_java_release_is_an_update() {
+		assert!(is_newer(&[21, 0, 7], "21.0.3"));
+		assert!(!is_newer(&[21, 0, 7], "21.0.7"));
+		assert!(!is_newer(&[21], "21.0.3"), "a bare major says nothing");
+		assert!(
+			!is_newer(&[21, 0, 7, 6, 1], "21.0.7"),
+			"a vendor build of the same release"
+		);
+	}
+
+	#[test]
+	fn java_8_updates_compare_however_the_vendor_spells_them() {
+		assert!(is_newer(&[8, 0, 452], "1.8.0_412"));
+		assert!(is_newer(&[8, 452, 9, 1], "1.8.0_412"));
+		assert_eq!(version_parts("8u452+9"), Some(vec![8, 452, 9]));
+		assert!(!is_newer(&[8, 452, 9], "1.8.0_452"));
+	}
+
 	#[test]
 	fn garbage_is_rejected_rather_than_defaulted() {
 		assert!(parse_major_version("not-a-version").is_err());
@@ -8,6 +8,7 @@ use polyio::Checksum;
 use crate::data::{JavaPackage, PackageArchive};
 use crate::error::JavaResult;
 use crate::platform::{HostArch, HostOs, HostTarget};
+use crate::service::version_parts;
 use crate::vendors::{JavaRuntimeProvider, JavaVendor};
 
 pub struct CorrettoRuntimeProvider;
@@ -59,29 +60,41 @@ impl JavaRuntimeProvider for CorrettoRuntimeProvider {
 
             // Prefer the versioned URL the `latest` redirect resolves at
             // download time and can move away from this index's checksum
-            let download_url = entry
-                .get("resource")
-                .and_then(Value::as_str)
-                .map_or_else(
-                    || latest_url(this_major, CORRETTO_EXT.0),
-                    |resource| format!("https://corretto.aws{resource}"),
-                );
+            let resource = entry.get("resource").and_then(Value::as_str);
+            let download_url = resource.map_or_else(
+                || latest_url(this_major, CORRETTO_EXT.0),
+                |resource| format!("https://corretto.aws{resource}"),
+            );
 
             let checksum = entry
                 .get("checksum_sha256")
                 .and_then(Value::as_str)
                 .map(Checksum::sha256);
 
-            let name = format!(
-                "amazon-corretto-{this_major}-{CORRETTO_ARCH}-{CORRETTO_OS}-jdk.{}",
-                CORRETTO_EXT.0
-            );
+            // The versioned file name so an update extracts beside the old
+            // runtime rather than over it
+            let name = resource
+                .and_then(|resource| resource.rsplit('/').next())
+                .filter(|file| !file.is_empty())
+                .map_or_else(
+                    || {
+                        format!(
+                            "amazon-corretto-{this_major}-{CORRETTO_ARCH}-{CORRETTO_OS}-jdk.{}",
+                            CORRETTO_EXT.0
+                        )
+                    },
+                    str::to_string,
+                );
+            let java_version = resource
+                .and_then(resource_version)
+                .filter(|parts| parts.first() == Some(&this_major))
+                .unwrap_or_else(|| vec![this_major]);
 
             packages.push(
                 JavaPackage {
                     archive: CORRETTO_EXT.1,
                     download_url,
-                    java_version: vec![this_major],
+                    java_version,
                     name,
                     vendor: JavaVendor::Corretto,
                     checksum: None,
@@ -97,6 +110,12 @@ impl JavaRuntimeProvider for CorrettoRuntimeProvider {
     }
 }
 
+/// `/downloads/resources/21.0.7.6.1/amazon-corretto-...` names the release
+/// in the folder
+fn resource_version(resource: &str) -> Option<Vec<u32>> {
+    version_parts(resource.rsplit('/').nth(1)?)
+}
+
 fn latest_url(major: u32, ext: &str) -> String {
     format!(
         "https://corretto.aws/downloads/latest/amazon-corretto-{major}-{CORRETTO_ARCH}-{CORRETTO_OS}-jdk.{ext}"
@@ -121,3 +140,19 @@ const CORRETTO_EXT: (&str, PackageArchive) = (
     HostTarget::CURRENT.archive_ext(),
     HostTarget::CURRENT.archive(),
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_release_comes_from_the_resource_folder() {
+        assert_eq!(
+            resource_version(
+                "/downloads/resources/21.0.7.6.1/amazon-corretto-21.0.7.6.1-linux-x64.tar.gz"
+            ),
+            Some(vec![21, 0, 7, 6, 1])
+        );
+        assert_eq!(resource_version("amazon-corretto-21-x64-linux-jdk.tar.gz"), None);
+    }
+}
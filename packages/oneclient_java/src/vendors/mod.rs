@@ -10,11 +10,13 @@ use crate::error::JavaResult;
 mod adoptium;
 mod corretto;
 mod liberica;
+pub(crate) mod mojang;
 mod zulu;
 
 pub use adoptium::AdoptiumRuntimeProvider;
 pub use corretto::CorrettoRuntimeProvider;
 pub use liberica::LibericaRuntimeProvider;
+pub use mojang::MojangRuntimeProvider;
 use serde::{Deserialize, Deserializer, Serialize};
 pub use zulu::ZuluRuntimeProvider;
 
@@ -24,6 +26,7 @@ pub fn runtime_providers() -> Vec<Box<dyn JavaRuntimeProvider>> {
         Box::new(AdoptiumRuntimeProvider),
         Box::new(CorrettoRuntimeProvider),
         Box::new(LibericaRuntimeProvider),
+        Box::new(MojangRuntimeProvider),
     ]
 }
 
@@ -67,6 +70,8 @@ pub enum JavaVendor {
 	Microsoft,
 	Adoptium,
 	Liberica,
+	/// The runtimes the vanilla launcher installs
+	Mojang,
 	OpenJDK,
 	Other(String),
 }
@@ -77,6 +82,7 @@ impl std::fmt::Display for JavaVendor {
             JavaVendor::Zulu => f.write_str("Zulu"),
             JavaVendor::Adoptium => f.write_str("Temurin"),
             JavaVendor::Liberica => f.write_str("Liberica"),
+            JavaVendor::Mojang => f.write_str("Mojang"),
             JavaVendor::Corretto => f.write_str("Corretto"),
             JavaVendor::Microsoft => f.write_str("Microsoft"),
             JavaVendor::Oracle => f.write_str("Oracle"),
@@ -96,6 +102,8 @@ impl FromStr for JavaVendor {
 			Self::Adoptium
 		} else if v.contains("liberica") || v.contains("bellsoft") {
 			Self::Liberica
+		} else if v.contains("mojang") {
+			Self::Mojang
 		} else if v.contains("microsoft") {
 			Self::Microsoft
 		} else if v.contains("openjdk") {
@@ -0,0 +1,443 @@
+//! The runtimes the vanilla launcher uses named by the component a version
+//! JSON asks for in `javaVersion.component`
+//! Mojang ships a manifest of individual files rather than an archive so
+//! installing is closer to downloading assets than to unpacking a JDK
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use futures_util::{StreamExt, stream};
+use reqwest::{Method, Request};
+use serde::Deserialize;
+use url::Url;
+
+use oneclient_common::constants::MOJANG_JAVA_RUNTIMES_URL;
+use oneclient_common::paths;
+use oneclient_events::{EventBus, GroupedProgressSession, TaskCategory};
+use oneclient_net::RequestClient;
+use polyio::Checksum;
+
+use crate::data::{JavaPackage, PackageArchive};
+use crate::error::{JavaError, JavaResult};
+use crate::platform::{HostArch, HostOs, HostTarget};
+use crate::resolve::resolve_java_executable;
+use crate::service::version_parts;
+use crate::vendors::{JavaRuntimeProvider, JavaVendor};
+
+const RUNTIME_DOWNLOAD_CONCURRENCY: usize = 16;
+
+/// Install folders start with this which is how an installed runtime is
+/// traced back to Mojang
+pub(crate) const INSTALL_PREFIX: &str = "mojang-";
+
+/// Kept next to the runtime so a reinstall only fetches files that changed
+const MANIFEST_FILE: &str = ".mojang-manifest.json";
+
+pub struct MojangRuntimeProvider;
+
+// { platform: { component: [entry] } } an empty list means the component is
+// not shipped for that platform
+type RuntimeIndex = HashMap<String, HashMap<String, Vec<RuntimeEntry>>>;
+
+#[derive(Debug, Deserialize)]
+struct RuntimeEntry {
+    manifest: Download,
+    version: RuntimeVersion,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeVersion {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Download {
+    sha1: String,
+    size: u64,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileManifest {
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ManifestEntry {
+    Directory,
+    File {
+        #[serde(default)]
+        executable: bool,
+        downloads: FileDownloads,
+    },
+    Link {
+        target: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct FileDownloads {
+    raw: Download,
+}
+
+#[async_trait::async_trait]
+impl JavaRuntimeProvider for MojangRuntimeProvider {
+    fn vendor(&self) -> JavaVendor {
+        JavaVendor::Mojang
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, net))]
+    async fn list_packages(
+        &self,
+        major: Option<u32>,
+        net: &RequestClient,
+    ) -> JavaResult<Vec<JavaPackage>> {
+        let mut packages: Vec<JavaPackage> = fetch_components(net)
+            .await?
+            .into_iter()
+            .map(|(_, package)| package)
+            .filter(|package| major.is_none() || package.java_version.first() == major.as_ref())
+            .collect();
+
+        // Newest first so the gamma runtime wins over beta for Java 17
+        packages.sort_by(|a, b| b.java_version.cmp(&a.java_version));
+        tracing::debug!(count = packages.len(), "listed Mojang runtimes");
+        Ok(packages)
+    }
+}
+
+impl MojangRuntimeProvider {
+    /// `None` when Mojang does not ship the component for this platform
+    #[tracing::instrument(level = "debug", skip(self, net))]
+    pub async fn package_for_component(
+        &self,
+        component: &str,
+        net: &RequestClient,
+    ) -> JavaResult<Option<JavaPackage>> {
+        Ok(fetch_components(net)
+            .await?
+            .into_iter()
+            .find_map(|(name, package)| (name == component).then_some(package)))
+    }
+}
+
+async fn fetch_components(net: &RequestClient) -> JavaResult<Vec<(String, JavaPackage)>> {
+    let Some(platform) = MOJANG_PLATFORM else {
+        return Ok(Vec::new());
+    };
+
+    let mut index = net
+        .send_as::<RuntimeIndex>(Request::new(
+            Method::GET,
+            Url::parse(MOJANG_JAVA_RUNTIMES_URL)?,
+        ))
+        .await?;
+
+    Ok(index
+        .remove(platform)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(component, _)| is_runtime_component(component))
+        .filter_map(|(component, entries)| {
+            let package = map_runtime_entry(&component, entries.into_iter().next()?)?;
+            Some((component, package))
+        })
+        .collect())
+}
+
+/// The index also lists the launcher's own executable and snapshot-only
+/// runtimes neither of which should ever be picked by major
+fn is_runtime_component(component: &str) -> bool {
+    (component.starts_with("java-runtime-") || component.starts_with("jre-"))
+        && !component.ends_with("-snapshot")
+}
+
+fn map_runtime_entry(component: &str, entry: RuntimeEntry) -> Option<JavaPackage> {
+    let java_version = version_parts(&entry.version.name)?;
+
+    Some(
+        JavaPackage {
+            archive: PackageArchive::Manifest,
+            download_url: entry.manifest.url,
+            java_version,
+            // Versioned so an update installs beside the runtime it replaces
+            name: format!("{INSTALL_PREFIX}{component}-{}", entry.version.name),
+            vendor: JavaVendor::Mojang,
+            checksum: None,
+            size: Some(entry.manifest.size),
+        }
+        .with_checksum(Some(Checksum::sha1(&entry.manifest.sha1))),
+    )
+}
+
+#[tracing::instrument(level = "debug", skip(net, events, progress), fields(name = %package.name))]
+pub(crate) async fn install_runtime(
+    package: &JavaPackage,
+    net: &RequestClient,
+    events: &EventBus,
+    progress: Option<&GroupedProgressSession>,
+) -> JavaResult<PathBuf> {
+    let root = paths::java_dir()?.join(polyio::sanitize_path(&package.name));
+    polyio::create_dir_all(&root).await?;
+
+    let manifest_path = root.join(MANIFEST_FILE);
+    oneclient_net::download_verified(
+        net,
+        events,
+        &package.download_url,
+        &manifest_path,
+        package.checksum.as_ref(),
+        package.size.unwrap_or(0),
+        None,
+    )
+    .await?;
+    let manifest: FileManifest = polyio::read_json(&manifest_path).await?;
+
+    let mut files = Vec::new();
+    let mut links = Vec::new();
+    for (name, entry) in manifest.files {
+        // Paths come from the network and end up under the java directory
+        let relative = safe_relative(&name)
+            .ok_or_else(|| invalid_manifest(package, format!("unsafe path {name:?}")))?;
+        match entry {
+            ManifestEntry::Directory => polyio::create_dir_all(root.join(relative)).await?,
+            ManifestEntry::File {
+                executable,
+                downloads,
+            } => files.push((relative, executable, downloads.raw)),
+            ManifestEntry::Link { target } => {
+                if resolve_link(&relative, &target).is_none() {
+                    return Err(invalid_manifest(
+                        package,
+                        format!("link {name:?} points outside the runtime"),
+                    ));
+                }
+                links.push((relative, target));
+            }
+        }
+    }
+
+    let total: u64 = files.iter().map(|(_, _, raw)| raw.size).sum();
+    let major = package.java_version.first().copied().unwrap_or(0);
+    let child = progress
+        .map(|session| session.child(format!("Mojang Java {major}"), total, TaskCategory::Java));
+    let done = Arc::new(AtomicU64::new(0));
+
+    let results = stream::iter(files.into_iter().map(|(relative, executable, raw)| {
+        let dest = root.join(relative);
+        let child = child.clone();
+        let done = done.clone();
+
+        async move {
+            let expected = Checksum::sha1(&raw.sha1);
+            let current = dest.is_file()
+                && polyio::sha1_file(&dest)
+                    .await
+                    .is_ok_and(|actual| expected.matches(&actual));
+            if !current {
+                oneclient_net::download_verified(
+                    net,
+                    events,
+                    &raw.url,
+                    &dest,
+                    Some(&expected),
+                    raw.size,
+                    None,
+                )
+                .await?;
+            }
+            if executable {
+                mark_executable(&dest).await?;
+            }
+
+            let done = done.fetch_add(raw.size, Ordering::Relaxed) + raw.size;
+            if let Some(child) = &child {
+                child.set_progress(done, Some(total));
+            }
+            Ok::<_, JavaError>(())
+        }
+    }))
+    .buffer_unordered(RUNTIME_DOWNLOAD_CONCURRENCY)
+    .collect::<Vec<_>>()
+    .await;
+    results.into_iter().collect::<JavaResult<Vec<_>>>()?;
+
+    // Mojang's Windows manifests carry no links which is as well since
+    // Windows has no unprivileged symlink
+    #[cfg(unix)]
+    for (relative, target) in links {
+        let link = root.join(relative);
+        if polyio::symlink_metadata(&link).await.is_ok() {
+            polyio::remove_file(&link).await?;
+        }
+        polyio::symlink_file(&target, &link).await?;
+    }
+    #[cfg(not(unix))]
+    let _ = links;
+
+    if let Some(child) = &child {
+        child.finish();
+    }
+
+    let executable = resolve_java_executable(&root)?;
+    tracing::info!(name = %package.name, "installed Mojang Java runtime");
+    Ok(executable)
+}
+
+fn safe_relative(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let normal = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    (normal && !name.is_empty()).then(|| path.to_path_buf())
+}
+
+/// Where a link lands relative to the runtime root `None` if it escapes
+fn resolve_link(link: &Path, target: &str) -> Option<PathBuf> {
+    let mut resolved = link.parent()?.to_path_buf();
+    for component in Path::new(target).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(resolved)
+}
+
+async fn mark_executable(path: &Path) -> JavaResult<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+            .await
+            .map_err(|source| polyio::IOError::PathIOError {
+                source,
+                path: path.display().to_string(),
+            })?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+fn invalid_manifest(package: &JavaPackage, reason: String) -> JavaError {
+    JavaError::InvalidRuntimeManifest {
+        name: package.name.clone(),
+        reason,
+    }
+}
+
+/// Mojang has no musl or 32-bit ARM builds and no Linux ARM builds at all
+const MOJANG_PLATFORM: Option<&str> = match (HostTarget::CURRENT.os, HostTarget::CURRENT.arch) {
+    (HostOs::Windows, HostArch::X86_64) => Some("windows-x64"),
+    (HostOs::Windows, HostArch::X86) => Some("windows-x86"),
+    (HostOs::Windows, HostArch::Aarch64) => Some("windows-arm64"),
+    (HostOs::MacOs, HostArch::X86_64) => Some("mac-os"),
+    (HostOs::MacOs, HostArch::Aarch64) => Some("mac-os-arm64"),
+    (HostOs::Linux { musl: false }, HostArch::X86_64) => Some("linux"),
+    (HostOs::Linux { musl: false }, HostArch::X86) => Some("linux-i386"),
+    _ => None,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_and_modern_versions_sort_by_major() {
+        assert_eq!(version_parts("1.8.0_51"), Some(vec![8, 0, 51]));
+        assert_eq!(version_parts("17.0.8"), Some(vec![17, 0, 8]));
+        assert_eq!(version_parts("21.0.7"), Some(vec![21, 0, 7]));
+        assert_eq!(version_parts("latest"), None);
+    }
+
+    #[test]
+    fn only_runtimes_are_listed() {
+        assert!(is_runtime_component("java-runtime-delta"));
+        assert!(is_runtime_component("jre-legacy"));
+        assert!(!is_runtime_component("java-runtime-gamma-snapshot"));
+        assert!(!is_runtime_component("minecraft-java-exe"));
+    }
+
+    #[test]
+    fn manifest_paths_stay_inside_the_runtime() {
+        assert!(safe_relative("bin/java").is_some());
+        assert!(safe_relative("../bin/java").is_none());
+        assert!(safe_relative("/etc/passwd").is_none());
+        assert!(safe_relative("").is_none());
+
+        let link = Path::new("jre.bundle/Contents/MacOS/libjli.dylib");
+        assert_eq!(
+            resolve_link(link, "../Home/lib/libjli.dylib"),
+            Some(PathBuf::from("jre.bundle/Contents/Home/lib/libjli.dylib"))
+        );
+        assert_eq!(resolve_link(Path::new("legal"), "../../outside"), None);
+        assert_eq!(resolve_link(link, "/usr/lib/libjli.dylib"), None);
+    }
+
+    #[test]
+    fn manifest_entries_parse_by_type() {
+        let manifest: FileManifest = serde_json::from_value(serde_json::json!({
+            "files": {
+                "bin": { "type": "directory" },
+                "bin/java": {
+                    "type": "file",
+                    "executable": true,
+                    "downloads": {
+                        "raw": {
+                            "sha1": "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                            "size": 0,
+                            "url": "https://piston-data.mojang.com/v1/objects/da39a3ee5e6b4b0d3255bfef95601890afd80709/java"
+                        }
+                    }
+                },
+                "lib/libjli.so": { "type": "link", "target": "../bin/libjli.so" }
+            }
+        }))
+        .expect("a valid manifest");
+
+        assert!(matches!(manifest.files["bin"], ManifestEntry::Directory));
+        assert!(matches!(
+            manifest.files["bin/java"],
+            ManifestEntry::File {
+                executable: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            manifest.files["lib/libjli.so"],
+            ManifestEntry::Link { .. }
+        ));
+    }
+
+    #[test]
+    fn index_entries_become_versioned_packages() {
+        let entry: RuntimeEntry = serde_json::from_value(serde_json::json!({
+            "availability": { "group": 6, "progress": 100 },
+            "manifest": {
+                "sha1": "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                "size": 140000,
+                "url": "https://piston-meta.mojang.com/v1/packages/x/manifest.json"
+            },
+            "version": { "name": "21.0.7", "released": "2025-04-15T00:00:00+00:00" }
+        }))
+        .expect("a valid entry");
+
+        let package = map_runtime_entry("java-runtime-delta", entry).expect("a package");
+        assert_eq!(package.name, "mojang-java-runtime-delta-21.0.7");
+        assert_eq!(package.java_version.first(), Some(&21));
+        assert_eq!(package.archive, PackageArchive::Manifest);
+        assert!(package.checksum.is_some());
+    }
+}
@@ -68,7 +68,7 @@ async fn every_vendor_publishes_a_usable_checksum() {
 
 #[tokio::test]
 #[ignore = "requires network"]
-async fn liberica_publishes_sha1_and_the_rest_publish_sha256() {
+async fn liberica_and_mojang_publish_sha1_and_the_rest_publish_sha256() {
     let net = RequestClient::new(NetConfig::default()).expect("a client");
 
     for provider in runtime_providers() {
@@ -83,7 +83,7 @@ async fn liberica_publishes_sha1_and_the_rest_publish_sha256() {
         };
 
         let expected = match vendor.to_string().as_str() {
-            "Liberica" => ChecksumAlgorithm::Sha1,
+            "Liberica" | "Mojang" => ChecksumAlgorithm::Sha1,
             _ => ChecksumAlgorithm::Sha256,
         };
 
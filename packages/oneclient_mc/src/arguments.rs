@@ -6,7 +6,9 @@ use interfrost::api::modded::SidedDataEntry;
 use interfrost::utils::get_path_from_artifact;
 
 use oneclient_common::constants::{self, DUMMY_REPLACE_NEWLINE};
-use crate::rules::validate_rules;
+use crate::quick_play::QuickPlay;
+use crate::rules::{LaunchFeatures, validate_rules, validate_rules_for};
+use crate::authlib_injector::AuthlibInjector;
 use crate::error::McError;
 use oneclient_common::Resolution;
 use crate::error::McResult;
@@ -23,6 +25,7 @@ pub fn java_arguments(
     custom_args: String,
     java_arch: &str,
     java_major: u32,
+    authlib_injector: Option<&AuthlibInjector>,
 ) -> McResult<Vec<String>> {
     let mut parsed = Vec::new();
     if let Some(args) = arguments {
@@ -40,6 +43,7 @@ pub fn java_arguments(
                 )
             },
             java_arch,
+            LaunchFeatures::default(),
         )?;
     } else {
         parsed.push(format!(
@@ -52,6 +56,10 @@ pub fn java_arguments(
         parsed.push(classpaths.to_string());
     }
 
+    if let Some(injector) = authlib_injector {
+        parsed.extend(injector.jvm_arguments());
+    }
+
     let custom = split_custom_args(&custom_args);
 
     parsed.extend(performance_flags(java_major, java_arch, mem_max, &custom));
@@ -158,6 +166,8 @@ fn split_custom_args(raw: &str) -> Vec<String> {
     args
 }
 
+/// `quick_play` goes through the version's own feature-gated arguments when it
+/// declares them and falls back to `--server`/`--port` otherwise
 #[allow(clippy::too_many_arguments)]
 pub fn minecraft_arguments(
     version_updated: bool,
@@ -173,52 +183,60 @@ pub fn minecraft_arguments(
     version_type: VersionType,
     resolution: Resolution,
     java_arch: &str,
+    quick_play: Option<&QuickPlay>,
 ) -> McResult<Vec<String>> {
+    let declared = quick_play.filter(|target| args.is_some_and(|args| target.declared_by(args)));
+    let parse = |arg: &str| -> McResult<String> {
+        let parsed = parse_minecraft_argument(
+            arg,
+            access_token,
+            username,
+            uuid,
+            version,
+            asset_index,
+            game_directory,
+            assets_directory,
+            version_type,
+            resolution,
+        )?;
+        Ok(match declared {
+            Some(target) => target.fill(&parsed),
+            None => parsed,
+        })
+    };
+
+    let mut parsed = Vec::new();
     if let Some(args) = args {
-        let mut parsed = Vec::new();
+        let features = LaunchFeatures {
+            quick_play: declared.map(QuickPlay::kind),
+        };
         parse_arguments(
             version_updated,
             args,
             &mut parsed,
-            |arg| {
-                parse_minecraft_argument(
-                    arg,
-                    access_token,
-                    username,
-                    uuid,
-                    version,
-                    asset_index,
-                    game_directory,
-                    assets_directory,
-                    version_type,
-                    resolution,
-                )
-            },
+            parse,
             java_arch,
+            features,
         )?;
-
-        Ok(parsed)
     } else if let Some(legacy_args) = legacy_args {
-        let mut parsed = Vec::new();
         for arg in legacy_args.split(' ') {
-            parsed.push(parse_minecraft_argument(
-                &arg.replace(' ', DUMMY_REPLACE_NEWLINE),
-                access_token,
-                username,
-                uuid,
-                version,
-                asset_index,
-                game_directory,
-                assets_directory,
-                version_type,
-                resolution,
-            )?);
+            parsed.push(parse(&arg.replace(' ', DUMMY_REPLACE_NEWLINE))?);
         }
+    }
 
-        Ok(parsed)
-    } else {
-        Ok(Vec::new())
+    if let Some(target) = quick_play
+        && declared.is_none()
+    {
+        let legacy = target
+            .legacy_arguments()
+            .ok_or_else(|| McError::QuickPlayUnsupported {
+                target: target.kind().label(),
+                version: version.to_string(),
+            })?;
+        parsed.extend(legacy);
     }
+
+    Ok(parsed)
 }
 
 pub fn append_profile_game_arguments(
@@ -480,6 +498,7 @@ fn parse_arguments<ParseFn>(
     parsed: &mut Vec<String>,
     parse_function: ParseFn,
     java_arch: &str,
+    features: LaunchFeatures,
 ) -> McResult<()>
 where
     ParseFn: Fn(&str) -> McResult<String>,
@@ -493,7 +512,7 @@ where
                 }
             }
             Argument::Ruled { rules, value } => {
-                if validate_rules(rules, java_arch, version_updated) {
+                if validate_rules_for(rules, java_arch, version_updated, features) {
                     match value {
                         ArgumentValue::Single(arg) => {
                             parsed.push(parse_function(&arg.replace(' ', DUMMY_REPLACE_NEWLINE))?);
@@ -0,0 +1,148 @@
+//! authlib-injector is a Java agent that points the game's session skin and
+//! profile lookups at a Yggdrasil server instead of Mojang's
+//! It runs inside the game with full access so it is never started unverified
+
+use std::path::{Path, PathBuf};
+
+use oneclient_common::constants::AUTHLIB_INJECTOR_LATEST_URL;
+use oneclient_common::paths;
+use oneclient_net::{EtagPolicy, fetch_cached};
+use polyio::{Checksum, ChecksumAlgorithm};
+use serde::Deserialize;
+
+use crate::McCtx;
+use crate::error::{McError, McResult};
+
+#[derive(Debug, Deserialize)]
+struct Artifact {
+    version: String,
+    download_url: String,
+    checksums: ArtifactChecksums,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtifactChecksums {
+    sha256: String,
+}
+
+/// The agent jar and the server it redirects to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthlibInjector {
+    pub jar: PathBuf,
+    pub api_root: String,
+}
+
+impl AuthlibInjector {
+    /// Must come before the main class the agent patches authlib as it loads
+    #[must_use]
+    pub fn jvm_arguments(&self) -> Vec<String> {
+        vec![
+            format!("-javaagent:{}={}", self.jar.display(), self.api_root),
+            "-Dauthlibinjector.side=client".to_string(),
+        ]
+    }
+}
+
+/// The release metadata is cached so an account that launched once still
+/// launches offline
+#[tracing::instrument(level = "debug", skip(ctx))]
+pub async fn ensure_authlib_injector(ctx: &McCtx, api_root: &str) -> McResult<AuthlibInjector> {
+    let dir = authlib_injector_dir()?;
+    let fetched = fetch_cached(
+        &ctx.net,
+        AUTHLIB_INJECTOR_LATEST_URL,
+        &dir.join("latest.json"),
+        EtagPolicy::CommitNow,
+    )
+    .await?
+    .ok_or(McError::FetchError)?;
+    let artifact: Artifact = fetched.json()?;
+
+    let jar = artifact_path(&dir, &artifact.version)?;
+    let expected = Checksum::new(ChecksumAlgorithm::Sha256, &artifact.checksums.sha256);
+    if !expected.is_well_formed() {
+        return Err(McError::Minecraft(format!(
+            "authlib-injector {} has no usable SHA-256",
+            artifact.version
+        )));
+    }
+
+    let verified = jar.is_file()
+        && polyio::checksum_file(&jar, ChecksumAlgorithm::Sha256)
+            .await
+            .is_ok_and(|actual| expected.matches(&actual));
+    if !verified {
+        tracing::info!(version = %artifact.version, "downloading authlib-injector");
+        oneclient_net::download_verified(
+            &ctx.net,
+            &ctx.events,
+            &artifact.download_url,
+            &jar,
+            Some(&expected),
+            0,
+            None,
+        )
+        .await?;
+    }
+
+    Ok(AuthlibInjector {
+        jar,
+        api_root: api_root.to_string(),
+    })
+}
+
+fn authlib_injector_dir() -> McResult<PathBuf> {
+    Ok(paths::libraries_dir()?
+        .join("moe")
+        .join("yushi")
+        .join("authlib-injector"))
+}
+
+/// The version comes from the network and ends up in a path
+fn artifact_path(dir: &Path, version: &str) -> McResult<PathBuf> {
+    let safe = !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+        && !version.starts_with('.');
+    if !safe {
+        return Err(McError::Minecraft(format!(
+            "authlib-injector reported an invalid version {version:?}"
+        )));
+    }
+
+    Ok(dir
+        .join(version)
+        .join(format!("authlib-injector-{version}.jar")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_points_at_the_api_root() {
+        let injector = AuthlibInjector {
+            jar: PathBuf::from("libraries/authlib-injector-1.2.5.jar"),
+            api_root: "https://example.com/api/yggdrasil".to_string(),
+        };
+
+        assert_eq!(
+            injector.jvm_arguments()[0],
+            format!(
+                "-javaagent:{}=https://example.com/api/yggdrasil",
+                injector.jar.display()
+            )
+        );
+    }
+
+    #[test]
+    fn versions_cannot_escape_the_directory() {
+        let dir = Path::new("authlib-injector");
+
+        assert!(artifact_path(dir, "1.2.5").is_ok());
+        assert!(artifact_path(dir, "../../evil").is_err());
+        assert!(artifact_path(dir, "..").is_err());
+        assert!(artifact_path(dir, "").is_err());
+    }
+}
@@ -35,6 +35,18 @@ pub enum McError {
 	#[error("cancelled: {failed} file(s) could not be downloaded")]
 	IncompleteInstallCancelled { failed: usize },
 
+	#[error("Minecraft {version} cannot launch straight into {target}")]
+	QuickPlayUnsupported {
+		target: &'static str,
+		version: String,
+	},
+
+	#[error("not a usable skin: {0}")]
+	InvalidSkin(String),
+
+	#[error("saved skin {0} was not found")]
+	SkinNotFound(String),
+
 	/// A Mojang response was well-formed JSON but not what the API documents
 	#[error("minecraft: {0}")]
 	Minecraft(String),
@@ -18,14 +18,18 @@ impl McCtx {
 }
 
 mod arguments;
+mod authlib_injector;
 mod download;
 mod error;
 mod install;
 mod manifest;
 mod profile;
+mod quick_play;
 mod rules;
+mod skin_library;
 
 pub use arguments::*;
+pub use authlib_injector::{AuthlibInjector, ensure_authlib_injector};
 pub use download::{download_to_path, fetch_bytes_verified};
 pub use error::{McError, McResult};
 pub use install::*;
@@ -33,5 +37,10 @@ pub use manifest::MetadataStore;
 pub use profile::{
 	MojangCape, MojangFullPlayerProfile, MojangPlayerProfile, MojangSkin, PlayerProfileView,
 	SkinVariant, fetch_logged_in_profile, fetch_player_profile, fetch_player_profile_view,
+	reset_skin, set_active_cape, upload_skin, validate_skin_png,
+};
+pub use quick_play::{QuickPlay, QuickPlayKind};
+pub use rules::{LaunchFeatures, validate_rules, validate_rules_for};
+pub use skin_library::{
+	SavedSkin, list_saved_skins, load_saved_skin, remove_saved_skin, save_skin, saved_skin_path,
 };
-pub use rules::validate_rules;
@@ -3,7 +3,7 @@ use chrono::{DateTime, TimeDelta, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
-use oneclient_net::RequestClient;
+use oneclient_net::{RequestClient, RequestError};
 use crate::error::{McError, McResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -87,6 +87,17 @@ pub enum SkinVariant {
     Slim,
 }
 
+impl SkinVariant {
+    /// The spelling the skin upload endpoint expects
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Slim => "slim",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MojangFullPlayerProfile {
     pub id: String,
@@ -233,14 +244,134 @@ pub async fn fetch_logged_in_profile(
     let mut profile = client
         .send_json::<MojangFullPlayerProfile>(Method::GET, url, None, &header_refs)
         .await?;
+    normalize_profile_urls(&mut profile);
+
+    Ok(profile)
+}
+
+const PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Checked before upload so a wrong file fails with a reason instead of a bare
+/// 400 64x32 is the pre-1.8 layout without the second layer
+pub fn validate_skin_png(bytes: &[u8]) -> McResult<()> {
+    // The first chunk of every PNG is IHDR which opens with width then height
+    if bytes.len() < 24 || !bytes.starts_with(PNG_SIGNATURE) || &bytes[12..16] != b"IHDR" {
+        return Err(McError::InvalidSkin("the file is not a PNG image".into()));
+    }
+
+    let dimension =
+        |at: usize| u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+    match (dimension(16), dimension(20)) {
+        (64, 64 | 32) => Ok(()),
+        (width, height) => Err(McError::InvalidSkin(format!(
+            "skins must be 64x64 or 64x32 pixels, this one is {width}x{height}"
+        ))),
+    }
+}
+
+/// A PNG header with just enough of IHDR for `validate_skin_png` and the
+/// skin library to accept
+#[cfg(test)]
+pub(crate) fn test_png(width: u32, height: u32) -> Vec<u8> {
+    let mut bytes = PNG_SIGNATURE.to_vec();
+    bytes.extend_from_slice(b"\0\0\0\x0dIHDR");
+    bytes.extend_from_slice(&width.to_be_bytes());
+    bytes.extend_from_slice(&height.to_be_bytes());
+    bytes.extend_from_slice(&[8, 6, 0, 0, 0]);
+    bytes
+}
+
+#[tracing::instrument(level = "debug", skip(client, access_token, png))]
+pub async fn upload_skin(
+    client: &RequestClient,
+    access_token: &str,
+    png: Vec<u8>,
+    variant: SkinVariant,
+) -> McResult<MojangFullPlayerProfile> {
+    validate_skin_png(&png)?;
+
+    let file = reqwest::multipart::Part::bytes(png)
+        .file_name("skin.png")
+        .mime_str("image/png")
+        .map_err(RequestError::from)?;
+    let form = reqwest::multipart::Form::new()
+        .text("variant", variant.as_str())
+        .part("file", file);
+    let request = client
+        .http()
+        .post(format!("{PROFILE_URL}/skins"))
+        .bearer_auth(access_token)
+        .multipart(form)
+        .build()
+        .map_err(RequestError::from)?;
+
+    profile_written(client.send_as(request).await?).await
+}
+
+/// Back to the default skin for the account's UUID
+#[tracing::instrument(level = "debug", skip(client, access_token))]
+pub async fn reset_skin(
+    client: &RequestClient,
+    access_token: &str,
+) -> McResult<MojangFullPlayerProfile> {
+    let url = format!("{PROFILE_URL}/skins/active").parse()?;
+    profile_written(send_authorized(client, access_token, Method::DELETE, url, None).await?).await
+}
+
+/// `None` hides whichever cape is showing the cape stays owned
+#[tracing::instrument(level = "debug", skip(client, access_token))]
+pub async fn set_active_cape(
+    client: &RequestClient,
+    access_token: &str,
+    cape_id: Option<&str>,
+) -> McResult<MojangFullPlayerProfile> {
+    let url = format!("{PROFILE_URL}/capes/active").parse()?;
+    let profile = match cape_id {
+        Some(id) => {
+            let body = serde_json::json!({ "capeId": id });
+            send_authorized(client, access_token, Method::PUT, url, Some(body)).await?
+        }
+        None => send_authorized(client, access_token, Method::DELETE, url, None).await?,
+    };
+    profile_written(profile).await
+}
 
+async fn send_authorized(
+    client: &RequestClient,
+    access_token: &str,
+    method: Method,
+    url: reqwest::Url,
+    body: Option<serde_json::Value>,
+) -> McResult<MojangFullPlayerProfile> {
+    let headers = auth_headers(access_token);
+    let header_refs: Vec<(&str, &str)> = headers
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect();
+
+    Ok(client
+        .send_json::<MojangFullPlayerProfile>(method, url, body, &header_refs)
+        .await?)
+}
+
+fn normalize_profile_urls(profile: &mut MojangFullPlayerProfile) {
     for skin in &mut profile.skins {
         skin.url = normalize_texture_url(&skin.url);
     }
     for cape in &mut profile.capes {
         cape.url = normalize_texture_url(&cape.url);
     }
+}
 
+/// Every write answers with the whole profile and leaves the cached summary
+/// out of date
+async fn profile_written(
+    mut profile: MojangFullPlayerProfile,
+) -> McResult<MojangFullPlayerProfile> {
+    normalize_profile_urls(&mut profile);
+    forget_cached_profile(&profile.id).await;
     Ok(profile)
 }
 
@@ -339,6 +470,23 @@ async fn write_cached_profile(uuid: &str, view: &PlayerProfileView) {
     }
 }
 
+/// Cache keys follow whatever form the caller passed so both spellings of the
+/// UUID go
+async fn forget_cached_profile(uuid: &str) {
+    let Ok(parsed) = uuid::Uuid::parse_str(normalize_uuid(uuid)) else {
+        return;
+    };
+
+    for form in [parsed.simple().to_string(), parsed.hyphenated().to_string()] {
+        if let Some(path) = profile_cache_path(&form)
+            && path.exists()
+            && let Err(err) = polyio::remove_file(&path).await
+        {
+            tracing::warn!("failed to drop cached profile for {uuid}: {err}");
+        }
+    }
+}
+
 async fn read_cached_profile(uuid: &str) -> Option<CachedProfile> {
     let path = profile_cache_path(uuid)?;
     let bytes = polyio::read(&path).await.ok()?;
@@ -375,6 +523,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn skins_must_be_64_wide_pngs() {
+        assert!(validate_skin_png(&test_png(64, 64)).is_ok());
+        assert!(validate_skin_png(&test_png(64, 32)).is_ok(), "pre-1.8 layout");
+        assert!(validate_skin_png(&test_png(128, 128)).is_err());
+        assert!(validate_skin_png(b"GIF89a not a png at all").is_err());
+    }
+
     #[tokio::test]
     async fn fresh_cache_entry_is_served_without_network() {
         temp_launcher_dir();
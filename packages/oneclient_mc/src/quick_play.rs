@@ -0,0 +1,191 @@
+//! Starting the game straight into a world server or realm
+//! 1.20 and later declare `--quickPlay*` arguments behind feature rules older
+//! versions only understand `--server`/`--port` and cannot open a world at all
+
+use interfrost::api::minecraft::Argument;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PORT: u16 = 25565;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuickPlay {
+    /// The world's folder name under `saves` not its display name
+    Singleplayer {
+        world: String,
+    },
+    /// `host` or `host:port` exactly as the server list would take it
+    Multiplayer {
+        address: String,
+    },
+    Realms {
+        realm_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuickPlayKind {
+    Singleplayer,
+    Multiplayer,
+    Realms,
+}
+
+impl QuickPlayKind {
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Singleplayer => "a singleplayer world",
+            Self::Multiplayer => "a server",
+            Self::Realms => "a realm",
+        }
+    }
+}
+
+impl QuickPlay {
+    /// A port of `None` or the default one is left off the address
+    #[must_use]
+    pub fn server(host: &str, port: Option<u16>) -> Self {
+        let address = match port {
+            Some(port) if port != DEFAULT_PORT && host.contains(':') => format!("[{host}]:{port}"),
+            Some(port) if port != DEFAULT_PORT => format!("{host}:{port}"),
+            _ => host.to_string(),
+        };
+        Self::Multiplayer { address }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> QuickPlayKind {
+        match self {
+            Self::Singleplayer { .. } => QuickPlayKind::Singleplayer,
+            Self::Multiplayer { .. } => QuickPlayKind::Multiplayer,
+            Self::Realms { .. } => QuickPlayKind::Realms,
+        }
+    }
+
+    /// Substitutes the one placeholder this target answers the others are
+    /// never reached because their rules do not pass
+    #[must_use]
+    pub fn fill(&self, argument: &str) -> String {
+        match self {
+            Self::Singleplayer { world } => argument.replace("${quickPlaySingleplayer}", world),
+            Self::Multiplayer { address } => argument.replace("${quickPlayMultiplayer}", address),
+            Self::Realms { realm_id } => argument.replace("${quickPlayRealms}", realm_id),
+        }
+    }
+
+    /// Whether the version's own game arguments carry a rule for this target
+    #[must_use]
+    pub fn declared_by(&self, args: &[Argument]) -> bool {
+        let kind = self.kind();
+        args.iter().any(|arg| match arg {
+            Argument::Ruled { rules, .. } => rules.iter().any(|rule| {
+                rule.features.as_ref().is_some_and(|features| {
+                    let flag = match kind {
+                        QuickPlayKind::Singleplayer => features.is_quick_play_singleplayer,
+                        QuickPlayKind::Multiplayer => features.is_quick_play_multiplayer,
+                        QuickPlayKind::Realms => features.is_quick_play_realms,
+                    };
+                    flag == Some(true)
+                })
+            }),
+            Argument::Normal(_) => false,
+        })
+    }
+
+    /// For versions that predate quick play `None` when the target has no
+    /// equivalent there
+    #[must_use]
+    pub fn legacy_arguments(&self) -> Option<Vec<String>> {
+        let Self::Multiplayer { address } = self else {
+            return None;
+        };
+        let (host, port) = split_address(address);
+        Some(vec![
+            "--server".to_string(),
+            host.to_string(),
+            "--port".to_string(),
+            port.unwrap_or(DEFAULT_PORT).to_string(),
+        ])
+    }
+}
+
+/// A bare IPv6 address has colons but no port only a bracketed one can carry
+/// both
+fn split_address(address: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = address.strip_prefix('[')
+        && let Some((host, tail)) = rest.split_once(']')
+    {
+        return (
+            host,
+            tail.strip_prefix(':').and_then(|port| port.parse().ok()),
+        );
+    }
+
+    match address.split_once(':') {
+        Some((host, port)) if !port.contains(':') => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (address, None),
+        },
+        _ => (address, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_split_into_host_and_port() {
+        assert_eq!(split_address("mc.example.com"), ("mc.example.com", None));
+        assert_eq!(
+            split_address("mc.example.com:25577"),
+            ("mc.example.com", Some(25577))
+        );
+        assert_eq!(split_address("[::1]:25570"), ("::1", Some(25570)));
+        assert_eq!(split_address("[::1]"), ("::1", None));
+        assert_eq!(split_address("fe80::1"), ("fe80::1", None));
+    }
+
+    #[test]
+    fn old_versions_join_a_server_through_server_and_port() {
+        assert_eq!(
+            QuickPlay::server("mc.example.com", None).legacy_arguments(),
+            Some(vec![
+                "--server".to_string(),
+                "mc.example.com".to_string(),
+                "--port".to_string(),
+                "25565".to_string(),
+            ])
+        );
+        assert_eq!(
+            QuickPlay::Singleplayer {
+                world: "New World".to_string()
+            }
+            .legacy_arguments(),
+            None
+        );
+    }
+
+    #[test]
+    fn the_default_port_is_left_off() {
+        assert_eq!(
+            QuickPlay::server("mc.example.com", Some(25565)),
+            QuickPlay::Multiplayer {
+                address: "mc.example.com".to_string()
+            }
+        );
+        assert_eq!(
+            QuickPlay::server("mc.example.com", Some(25570)).fill("${quickPlayMultiplayer}"),
+            "mc.example.com:25570"
+        );
+        assert_eq!(
+            QuickPlay::server("fe80::1", Some(25570)).legacy_arguments(),
+            Some(vec![
+                "--server".to_string(),
+                "fe80::1".to_string(),
+                "--port".to_string(),
+                "25570".to_string(),
+            ])
+        );
+    }
+}
@@ -2,11 +2,30 @@ use interfrost::api::minecraft::{Os, OsRule, Rule, RuleAction};
 
 use oneclient_common::os_ext::OsExt;
 
+use crate::quick_play::QuickPlayKind;
+
+/// What a feature rule in the version JSON is judged against We are never a
+/// demo user always pass a resolution and never ask for a quick play log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LaunchFeatures {
+    pub quick_play: Option<QuickPlayKind>,
+}
+
 #[must_use]
 pub fn validate_rules(rules: &[Rule], java_arch: &str, updated: bool) -> bool {
+    validate_rules_for(rules, java_arch, updated, LaunchFeatures::default())
+}
+
+#[must_use]
+pub fn validate_rules_for(
+    rules: &[Rule],
+    java_arch: &str,
+    updated: bool,
+    features: LaunchFeatures,
+) -> bool {
     let mut rule = rules
         .iter()
-        .map(|r| validate_rule(r, java_arch, updated))
+        .map(|r| validate_rule(r, java_arch, updated, features))
         .collect::<Vec<Option<bool>>>();
 
     if rules
@@ -20,19 +39,33 @@ pub fn validate_rules(rules: &[Rule], java_arch: &str, updated: bool) -> bool {
 }
 
 #[must_use]
-pub fn validate_rule(rule: &Rule, java_arch: &str, updated: bool) -> Option<bool> {
+pub fn validate_rule(
+    rule: &Rule,
+    java_arch: &str,
+    updated: bool,
+    launch: LaunchFeatures,
+) -> Option<bool> {
     let result = match rule {
         Rule { os: Some(os), .. } => validate_os_rule(os, java_arch, updated),
         Rule {
             features: Some(features),
             ..
         } => {
-            !features.is_demo_user.unwrap_or(true)
-                || features.has_custom_resolution.unwrap_or(false)
-                || !features.has_quick_plays_support.unwrap_or(true)
-                || !features.is_quick_play_multiplayer.unwrap_or(true)
-                || !features.is_quick_play_realms.unwrap_or(true)
-                || !features.is_quick_play_singleplayer.unwrap_or(true)
+            let quick_play = |kind| launch.quick_play == Some(kind);
+            features.is_demo_user.is_none_or(|wanted| !wanted)
+                && features.has_custom_resolution.is_none_or(|wanted| wanted)
+                && features
+                    .has_quick_plays_support
+                    .is_none_or(|wanted| !wanted)
+                && features
+                    .is_quick_play_singleplayer
+                    .is_none_or(|wanted| wanted == quick_play(QuickPlayKind::Singleplayer))
+                && features
+                    .is_quick_play_multiplayer
+                    .is_none_or(|wanted| wanted == quick_play(QuickPlayKind::Multiplayer))
+                && features
+                    .is_quick_play_realms
+                    .is_none_or(|wanted| wanted == quick_play(QuickPlayKind::Realms))
         }
         _ => return Some(true),
     };
@@ -0,0 +1,188 @@
+//! Skins the user kept for later one folder per account under
+//! [`paths::skins_dir`] the PNGs are named by hash so saving the same image
+//! twice keeps one copy
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use oneclient_common::paths;
+
+use crate::error::{McError, McResult};
+use crate::profile::{SkinVariant, validate_skin_png};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SavedSkin {
+    /// SHA-1 of the PNG
+    pub id: String,
+    pub name: String,
+    pub variant: SkinVariant,
+    pub added: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Library {
+    skins: Vec<SavedSkin>,
+}
+
+fn account_dir(account: Uuid) -> McResult<PathBuf> {
+    Ok(paths::skins_dir()?.join(account.simple().to_string()))
+}
+
+fn index_path(account: Uuid) -> McResult<PathBuf> {
+    Ok(account_dir(account)?.join("library.json"))
+}
+
+/// Ids come back from the UI and end up in a path
+pub fn saved_skin_path(account: Uuid, id: &str) -> McResult<PathBuf> {
+    if id.len() != 40 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(McError::SkinNotFound(id.to_string()));
+    }
+    Ok(account_dir(account)?.join(format!("{id}.png")))
+}
+
+async fn read_library(account: Uuid) -> McResult<Library> {
+    let path = index_path(account)?;
+    if !path.exists() {
+        return Ok(Library::default());
+    }
+    Ok(polyio::read_json(&path).await?)
+}
+
+async fn write_library(account: Uuid, library: &Library) -> McResult<()> {
+    polyio::write_json_atomic(index_path(account)?, library).await?;
+    Ok(())
+}
+
+/// Newest first
+#[tracing::instrument(level = "debug")]
+pub async fn list_saved_skins(account: Uuid) -> McResult<Vec<SavedSkin>> {
+    let mut skins = read_library(account).await?.skins;
+    skins.sort_by_key(|skin| std::cmp::Reverse(skin.added));
+    Ok(skins)
+}
+
+/// Saving an image already in the library renames it instead of adding a
+/// second entry
+#[tracing::instrument(level = "debug", skip(png))]
+pub async fn save_skin(
+    account: Uuid,
+    name: &str,
+    png: &[u8],
+    variant: SkinVariant,
+) -> McResult<SavedSkin> {
+    validate_skin_png(png)?;
+
+    let id = polyio::sha1_bytes(png);
+    let path = saved_skin_path(account, &id)?;
+    if !path.exists() {
+        polyio::write_atomic(&path, png).await?;
+    }
+
+    let mut library = read_library(account).await?;
+    let name = name.trim();
+    let saved = match library.skins.iter_mut().find(|skin| skin.id == id) {
+        Some(existing) => {
+            existing.name = name.to_string();
+            existing.variant = variant;
+            existing.clone()
+        }
+        None => {
+            let saved = SavedSkin {
+                id,
+                name: name.to_string(),
+                variant,
+                added: Utc::now(),
+            };
+            library.skins.push(saved.clone());
+            saved
+        }
+    };
+
+    write_library(account, &library).await?;
+    Ok(saved)
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn load_saved_skin(account: Uuid, id: &str) -> McResult<(SavedSkin, Vec<u8>)> {
+    let path = saved_skin_path(account, id)?;
+    let skin = read_library(account)
+        .await?
+        .skins
+        .into_iter()
+        .find(|skin| skin.id == id)
+        .ok_or_else(|| McError::SkinNotFound(id.to_string()))?;
+
+    let png = polyio::read(&path).await?;
+    Ok((skin, png))
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn remove_saved_skin(account: Uuid, id: &str) -> McResult<()> {
+    let path = saved_skin_path(account, id)?;
+    let mut library = read_library(account).await?;
+
+    let before = library.skins.len();
+    library.skins.retain(|skin| skin.id != id);
+    if library.skins.len() == before {
+        return Err(McError::SkinNotFound(id.to_string()));
+    }
+
+    write_library(account, &library).await?;
+    if path.exists() {
+        polyio::remove_file(&path).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::test_png as png;
+
+    #[tokio::test]
+    async fn saved_skins_round_trip_per_account() {
+        oneclient_common::paths::set_launcher_dir(
+            std::env::temp_dir().join(format!("oneclient-skins-test-{}", Uuid::new_v4())),
+        );
+        let account = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        let saved = save_skin(account, "Armour", &png(64, 64), SkinVariant::Slim)
+            .await
+            .unwrap();
+        let renamed = save_skin(account, " Knight ", &png(64, 64), SkinVariant::Classic)
+            .await
+            .unwrap();
+        assert_eq!(saved.id, renamed.id, "same image same entry");
+        assert_eq!(renamed.name, "Knight");
+
+        let listed = list_saved_skins(account).await.unwrap();
+        assert_eq!(listed, vec![renamed.clone()]);
+        assert!(list_saved_skins(other).await.unwrap().is_empty());
+
+        let (skin, bytes) = load_saved_skin(account, &saved.id).await.unwrap();
+        assert_eq!(skin.variant, SkinVariant::Classic);
+        assert_eq!(bytes, png(64, 64));
+
+        remove_saved_skin(account, &saved.id).await.unwrap();
+        assert!(list_saved_skins(account).await.unwrap().is_empty());
+        assert!(!saved_skin_path(account, &saved.id).unwrap().exists());
+    }
+
+    #[tokio::test]
+    async fn wrong_sizes_and_paths_are_refused() {
+        let account = Uuid::new_v4();
+
+        assert!(matches!(
+            save_skin(account, "Huge", &png(128, 128), SkinVariant::Classic).await,
+            Err(McError::InvalidSkin(_))
+        ));
+        assert!(matches!(
+            load_saved_skin(account, "../../auth").await,
+            Err(McError::SkinNotFound(_))
+        ));
+    }
+}
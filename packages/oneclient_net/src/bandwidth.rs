@@ -0,0 +1,252 @@
+//! One token bucket for every body stream in the process so a cap holds
+//! however many downloads run at once
+//! Throttling happens after each chunk arrives which stalls the socket read
+//! and lets TCP slow the sender
+
+use std::future::Future;
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+use tokio::sync::Notify;
+
+/// How much a stream that went quiet may catch up in one go
+const BURST: Duration = Duration::from_millis(250);
+const MIN_BURST_BYTES: f64 = 16.0 * 1024.0;
+/// How long after the last interactive chunk background work only takes what
+/// is spare
+const INTERACTIVE_GRACE: Duration = Duration::from_millis(500);
+const BACKGROUND_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Something the user started and is waiting on
+    #[default]
+    Interactive,
+    /// Bundle sync update checks and other unattended work only gets the
+    /// bandwidth interactive transfers leave unused while a limit is set so an
+    /// install the user starts meanwhile gets the bandwidth first
+    Background,
+}
+
+tokio::task_local! {
+    static PRIORITY: Priority;
+}
+
+impl Priority {
+    /// What body streams read by the current task run at
+    #[must_use]
+    pub fn current() -> Self {
+        PRIORITY.try_with(|priority| *priority).unwrap_or_default()
+    }
+
+    /// Runs `future` with every body stream it reads at this priority tasks it
+    /// spawns start back at the default
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        PRIORITY.scope(self, future).await
+    }
+}
+
+struct Bucket {
+    /// Negative while a chunk larger than the bucket is being paid off
+    tokens: f64,
+    refilled: Instant,
+    last_interactive: Option<Instant>,
+}
+
+pub(crate) struct Limiter {
+    /// Bytes per second `None` is unlimited swapped on a settings save without
+    /// touching streams mid-flight
+    rate: ArcSwap<Option<NonZeroU64>>,
+    /// Wakes every waiting chunk on a rate change a wait worked out at the old
+    /// rate could otherwise outlast the change by minutes
+    rate_changed: Notify,
+    bucket: Mutex<Bucket>,
+}
+
+/// Process-wide since the link it protects is
+pub(crate) fn limiter() -> &'static Limiter {
+    static LIMITER: OnceLock<Limiter> = OnceLock::new();
+    LIMITER.get_or_init(Limiter::new)
+}
+
+impl Limiter {
+    fn new() -> Self {
+        Self {
+            rate: ArcSwap::from_pointee(None),
+            rate_changed: Notify::new(),
+            bucket: Mutex::new(Bucket {
+                tokens: 0.0,
+                refilled: Instant::now(),
+                last_interactive: None,
+            }),
+        }
+    }
+
+    pub fn set_rate(&self, rate: Option<NonZeroU64>) {
+        self.rate.store(Arc::new(rate));
+        self.rate_changed.notify_waiters();
+    }
+
+    /// The rate is re-read on every wake so a raised or lifted limit applies
+    /// to chunks already waiting
+    pub async fn acquire(&self, bytes: u64, priority: Priority) {
+        loop {
+            // Registered before the rate is read so a change in between still
+            // wakes this chunk
+            let changed = self.rate_changed.notified();
+            let Some(rate) = **self.rate.load() else {
+                return;
+            };
+            match self.take(bytes, rate, priority, Instant::now()) {
+                None => return,
+                Some(wait) => {
+                    tokio::select! {
+                        () = tokio::time::sleep(wait) => {}
+                        () = changed => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// `None` once the bytes are taken otherwise how long to wait before
+    /// asking again
+    /// Taken on credit so a chunk larger than the bucket still passes and the
+    /// debt holds back the next one
+    fn take(
+        &self,
+        bytes: u64,
+        rate: NonZeroU64,
+        priority: Priority,
+        now: Instant,
+    ) -> Option<Duration> {
+        let rate = rate.get() as f64;
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let capacity = (rate * BURST.as_secs_f64()).max(MIN_BURST_BYTES);
+        let elapsed = now.saturating_duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.refilled = now;
+
+        match priority {
+            Priority::Interactive => bucket.last_interactive = Some(now),
+            Priority::Background => {
+                let busy = bucket
+                    .last_interactive
+                    .is_some_and(|last| now.saturating_duration_since(last) < INTERACTIVE_GRACE);
+                // Never on credit which would hold the next interactive chunk
+                // back interactive streams wake the moment their debt clears so
+                // a poll only finds what they left over
+                let spare = bucket.tokens >= (bytes as f64).min(capacity);
+                if busy && !spare {
+                    return Some(BACKGROUND_POLL);
+                }
+            }
+        }
+
+        if bucket.tokens < 0.0 {
+            return Some(Duration::from_secs_f64(-bucket.tokens / rate));
+        }
+        bucket.tokens -= bytes as f64;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: NonZeroU64 = NonZeroU64::new(64 * 1024).unwrap();
+
+    #[test]
+    fn bytes_past_the_rate_wait_for_the_bucket_to_refill() {
+        let limiter = Limiter::new();
+        let start = Instant::now();
+
+        assert_eq!(
+            limiter.take(64 * 1024, RATE, Priority::Interactive, start),
+            None
+        );
+        // A second's worth of debt needs a second to pay off
+        let wait = limiter.take(1, RATE, Priority::Interactive, start).unwrap();
+        assert!((wait.as_secs_f64() - 1.0).abs() < 0.01, "{wait:?}");
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.take(1, RATE, Priority::Interactive, later), None);
+    }
+
+    #[test]
+    fn background_work_yields_to_interactive_transfers() {
+        let limiter = Limiter::new();
+        let start = Instant::now();
+        let idle = start + Duration::from_secs(2);
+
+        assert_eq!(limiter.take(1, RATE, Priority::Background, start), None);
+        // An interactive stream using the whole rate leaves nothing spare
+        assert_eq!(
+            limiter.take(64 * 1024, RATE, Priority::Interactive, idle),
+            None
+        );
+        assert_eq!(
+            limiter.take(1, RATE, Priority::Background, idle),
+            Some(BACKGROUND_POLL)
+        );
+        assert!(
+            limiter
+                .take(1, RATE, Priority::Background, idle + INTERACTIVE_GRACE)
+                .is_some(),
+            "still paying off the interactive chunk"
+        );
+        assert_eq!(
+            limiter.take(1, RATE, Priority::Background, idle + Duration::from_secs(2)),
+            None
+        );
+    }
+
+    #[test]
+    fn background_work_takes_what_a_slow_interactive_transfer_leaves() {
+        let limiter = Limiter::new();
+        let start = Instant::now() + Duration::from_secs(2);
+
+        assert_eq!(limiter.take(1024, RATE, Priority::Interactive, start), None);
+        assert_eq!(limiter.take(1024, RATE, Priority::Background, start), None);
+        // A chunk larger than the bucket passes once the bucket is full
+        let full = start + BURST;
+        assert_eq!(
+            limiter.take(1024 * 1024, RATE, Priority::Background, full),
+            None
+        );
+    }
+
+    /// Typing a limit into settings passes through tiny rates on the way a
+    /// chunk waiting at one must not stay stuck once the rate goes up
+    #[tokio::test]
+    async fn a_raised_rate_wakes_chunks_already_waiting() {
+        let limiter = Arc::new(Limiter::new());
+        limiter.set_rate(NonZeroU64::new(1024));
+        limiter.acquire(64 * 1024, Priority::Interactive).await;
+
+        let waiting = {
+            let limiter = Arc::clone(&limiter);
+            tokio::spawn(async move { limiter.acquire(1, Priority::Interactive).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        limiter.set_rate(Some(RATE));
+
+        tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .expect("the chunk should not wait out the old rate's debt")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn priority_follows_the_scope_it_was_set_in() {
+        assert_eq!(Priority::current(), Priority::Interactive);
+        let inside = Priority::Background
+            .scope(async { Priority::current() })
+            .await;
+        assert_eq!(inside, Priority::Background);
+    }
+}
@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use reqwest::{Method, StatusCode, header};
 
 use crate::error::RequestError;
+use crate::response::ResponseExt;
 use crate::service::RequestClient;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -108,7 +109,7 @@ pub async fn fetch_cached(
 				.and_then(|value| value.to_str().ok())
 				.map(str::to_string);
 
-			let bytes = res.bytes().await?.to_vec();
+			let bytes = res.throttled_bytes().await?.to_vec();
 			polyio::write_atomic(cache_path, &bytes).await?;
 
 			let etag = match (&server_etag, policy) {
@@ -1,3 +1,5 @@
+use std::num::NonZeroU64;
+
 use oneclient_common::constants;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -7,6 +9,8 @@ pub struct NetConfig {
 	pub metadata_api_url: String,
 	/// Never has a trailing slash
 	pub meta_url_base: String,
+	/// Bytes per second shared by every download `None` is unlimited
+	pub download_limit: Option<NonZeroU64>,
 }
 
 impl Default for NetConfig {
@@ -16,6 +20,7 @@ impl Default for NetConfig {
 			modrinth_api_key: None,
 			metadata_api_url: constants::METADATA_API_URL.to_string(),
 			meta_url_base: constants::META_URL_BASE.to_string(),
+			download_limit: None,
 		}
 	}
 }
@@ -45,6 +50,15 @@ impl NetConfig {
 		self
 	}
 
+	/// In KiB per second as the settings store it `0` is unlimited like `None`
+	#[must_use]
+	pub fn with_download_limit(mut self, kib_per_sec: Option<u64>) -> Self {
+		self.download_limit = kib_per_sec
+			.map(|kib| kib.saturating_mul(1024))
+			.and_then(NonZeroU64::new);
+		self
+	}
+
 	#[must_use]
 	pub fn modrinth_headers(&self) -> Vec<(String, String)> {
 		match &self.modrinth_api_key {
@@ -94,4 +108,13 @@ mod tests {
 		);
 		assert_eq!(config.metadata_api_url, "https://a");
 	}
+
+	#[test]
+	fn a_zero_download_limit_is_unlimited() {
+		let config = NetConfig::default().with_download_limit(Some(512));
+		assert_eq!(config.download_limit, NonZeroU64::new(512 * 1024));
+
+		let config = config.with_download_limit(Some(0));
+		assert_eq!(config.download_limit, None);
+	}
 }
@@ -2,9 +2,12 @@ use std::path::Path;
 
 use oneclient_events::{EventBus, GroupedProgressChild, TaskPhase};
 use polyio::{Checksum, ChecksumStream, normalize_hash};
-use reqwest::Method;
+use reqwest::header::{ACCEPT_ENCODING, HeaderValue, IF_RANGE, RANGE};
+use reqwest::{Method, StatusCode};
+use tokio::io::AsyncWriteExt;
 
 use crate::error::RequestError;
+use crate::partial::{self, Partial, PartialMeta};
 use crate::response::{ResponseExt, ResponseNotifyOptions, ResponseOptions};
 use crate::service::RequestClient;
 
@@ -12,6 +15,9 @@ use crate::service::RequestClient;
 /// land this covers failures mid-body which would otherwise silently leave an
 /// install incomplete
 const MAX_DOWNLOAD_ATTEMPTS: u32 = 3;
+/// Smaller files are cheaper to fetch again than to track
+const RESUME_MIN_BYTES: u64 = 1024 * 1024;
+const MAX_WRITE_BUFFER: u64 = 256 * 1024;
 
 /// Only errors meaning the bytes arrived wrong are retried a 404 bad URL or
 /// full disk fails identically every time
@@ -21,6 +27,7 @@ fn worth_retrying(err: &RequestError) -> bool {
         RequestError::ReqwestError(_)
             | RequestError::HashMismatch { .. }
             | RequestError::IncompleteBody { .. }
+            | RequestError::ResumeMismatch { .. }
     )
 }
 
@@ -44,7 +51,8 @@ pub async fn matches_on_disk(path: &Path, expected_sha1: &str) -> bool {
 }
 
 /// `dest` is only created once the hash matches so a failed attempt leaves
-/// nothing behind for a later `exists()` check to trip over
+/// nothing behind for a later `exists()` check to trip over what did arrive
+/// waits in a hidden partial beside it for the next attempt or call to resume
 #[tracing::instrument(level = "debug", skip(client, events, notify), fields(%url))]
 pub async fn download_verified(
 	client: &RequestClient,
@@ -65,18 +73,22 @@ pub async fn download_verified(
 
 	// Needs a fixed id up front or each retry opens a new UI entry
 	let notify = notify.map(ResponseNotifyOptions::pinned);
+	let partial = Partial::claim(dest);
 
 	let mut attempt = 1;
 	loop {
-		match download_attempt(
+		// Boxed or every caller's future embeds the whole attempt and the deeper
+		// install chains overflow the layout depth limit
+		match Box::pin(download_attempt(
 			client,
 			events,
 			url,
 			dest,
+			&partial,
 			expected,
 			expected_size,
 			notify.as_ref(),
-		)
+		))
 		.await
 		{
 			Ok(()) => break,
@@ -100,6 +112,26 @@ pub async fn download_verified(
 	Ok(())
 }
 
+/// `If-Range` turns the range into a request for the whole file when it
+/// changed since the partial was fetched
+async fn send_from(
+	client: &RequestClient,
+	url: &str,
+	resume: Option<&(u64, PartialMeta)>,
+) -> Result<reqwest::Response, RequestError> {
+	let mut request = reqwest::Request::new(Method::GET, url.parse()?);
+	let headers = request.headers_mut();
+	// Ranges count the bytes on the wire so those must be the bytes on disk
+	headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("identity"));
+	if let Some((offset, meta)) = resume
+		&& let Some(validator) = meta.validator()
+	{
+		headers.insert(RANGE, HeaderValue::try_from(format!("bytes={offset}-"))?);
+		headers.insert(IF_RANGE, HeaderValue::try_from(validator)?);
+	}
+	client.send(request).await
+}
+
 /// The progress child is shared across attempts and reports absolute byte
 /// counts so a restarted attempt rewinds its bar rather than double-counting
 #[allow(clippy::too_many_arguments)]
@@ -108,6 +140,7 @@ async fn download_attempt(
 	events: &EventBus,
 	url: &str,
 	dest: &Path,
+	partial: &Partial,
 	expected: Option<&Checksum>,
 	expected_size: u64,
 	notify: Option<&ResponseNotifyOptions>,
@@ -117,13 +150,19 @@ async fn download_attempt(
 		child.set_phase(TaskPhase::Downloading);
 	}
 
-	let request = reqwest::Request::new(Method::GET, url.parse()?);
-	let response = client.send(request).await?;
+	let mut resume = partial.resume_point(url, expected, expected_size).await;
+	let mut response = send_from(client, url, resume.as_ref()).await?;
+	// The partial runs past the end of what the server holds now
+	if resume.is_some() && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+		partial.discard().await;
+		resume = None;
+		response = send_from(client, url, None).await?;
+	}
 
 	// Without this an error body is written to disk under the requested file's name
 	let status = response.status();
 	if !status.is_success() {
-		let bytes = response.bytes().await?;
+		let bytes = response.throttled_bytes().await?;
 		return Err(RequestError::HttpStatus {
 			status: status.as_u16(),
 			url: url.to_string(),
@@ -131,34 +170,78 @@ async fn download_attempt(
 		});
 	}
 
-	// Prefer the manifest size Content-Length is absent for compressed or chunked bodies
+	// Anything but a 206 is the whole file again
+	let offset = match resume {
+		Some((offset, _)) if status == StatusCode::PARTIAL_CONTENT => {
+			if partial::content_range_start(response.headers()) != Some(offset) {
+				partial.discard().await;
+				return Err(RequestError::ResumeMismatch {
+					url: url.to_string(),
+					offset,
+				});
+			}
+			offset
+		}
+		_ => {
+			let size = if expected_size > 0 {
+				Some(expected_size)
+			} else {
+				response.content_length()
+			};
+			if size.is_none_or(|size| size >= RESUME_MIN_BYTES) {
+				partial
+					.record(&PartialMeta::new(url, expected, response.headers()))
+					.await?;
+			} else {
+				partial.discard().await;
+			}
+			0
+		}
+	};
+
+	// Prefer the manifest size Content-Length is absent for chunked bodies
 	let declared = if expected_size > 0 {
 		Some(expected_size)
 	} else {
-		response.content_length()
+		response.content_length().map(|len| offset + len)
+	};
+
+	let mut hasher = match expected {
+		Some(expected) if offset > 0 => {
+			match partial::hash_prefix(&partial.path, expected.algorithm, offset).await {
+				Ok(hasher) => Some(hasher),
+				Err(err) => {
+					partial.discard().await;
+					return Err(err.into());
+				}
+			}
+		}
+		Some(expected) => Some(ChecksumStream::new(expected.algorithm)),
+		None => None,
 	};
-	let total = declared.unwrap_or(0).max(1);
 
 	let options = ResponseOptions {
 		notify: notify.cloned(),
+		resumed_from: offset,
 	};
 	let stream = response.stream(options, events).await?;
 
-	let mut hasher = expected.map(|expected| ChecksumStream::new(expected.algorithm));
-	let mut received = 0u64;
-	{
-		let stream = futures_lite::StreamExt::map(stream, |item| {
-			if let Ok(chunk) = &item {
-				received += chunk.len() as u64;
-				if let Some(hasher) = hasher.as_mut() {
-					hasher.update(chunk);
-				}
-			}
-			item
-		});
-		let stream = std::pin::pin!(stream);
-		// Writes to a scratch sibling and renames so `dest` is never truncated
-		polyio::write_stream(dest, stream, Some(total)).await?;
+	let buffer = declared.map_or(MAX_WRITE_BUFFER, |size| {
+		size.saturating_sub(offset).clamp(4096, MAX_WRITE_BUFFER)
+	});
+	let mut received = offset;
+	let written = write_body(
+		&partial.path,
+		offset > 0,
+		buffer as usize,
+		stream,
+		&mut hasher,
+		&mut received,
+	)
+	.await;
+	if let Err(err) = written {
+		partial.settle().await;
+		return Err(err);
 	}
 
 	if let (Some(expected), Some(hasher)) = (expected, hasher) {
@@ -168,8 +251,8 @@ async fn download_attempt(
 
 		let actual = hasher.finish();
 		if !expected.matches(&actual) {
-			// Remove it rather than leave it for the next `exists()` check
-			let _ = polyio::remove_file(dest).await;
+			// A bad prefix would fail every resume so the next attempt starts over
+			partial.discard().await;
 			return Err(RequestError::HashMismatch {
 				source_desc: dest.display().to_string(),
 				expected: format!("{} {}", expected.algorithm.name(), expected.hex),
@@ -180,7 +263,12 @@ async fn download_attempt(
 		&& received != declared
 	{
 		// With no hash the byte count is the only evidence the transfer finished
-		let _ = polyio::remove_file(dest).await;
+		// a short body is resumable but a long one is some other file
+		if received < declared {
+			partial.settle().await;
+		} else {
+			partial.discard().await;
+		}
 		return Err(RequestError::IncompleteBody {
 			source_desc: dest.display().to_string(),
 			expected: declared,
@@ -188,9 +276,54 @@ async fn download_attempt(
 		});
 	}
 
+	partial.finish(dest).await?;
 	Ok(())
 }
 
+/// Flushed even when the body fails partway so every byte that arrived is
+/// there for the next attempt to resume from
+async fn write_body(
+	path: &Path,
+	append: bool,
+	buffer: usize,
+	stream: impl futures_lite::Stream<Item = Result<bytes::Bytes, RequestError>>,
+	hasher: &mut Option<ChecksumStream>,
+	received: &mut u64,
+) -> Result<(), RequestError> {
+	let file = tokio::fs::OpenOptions::new()
+		.create(true)
+		.write(true)
+		.append(append)
+		.truncate(!append)
+		.open(path)
+		.await
+		.map_err(polyio::IOError::from)?;
+	let mut writer = tokio::io::BufWriter::with_capacity(buffer, file);
+
+	let mut stream = std::pin::pin!(stream);
+	let mut result = Ok(());
+	while let Some(chunk) = futures_lite::StreamExt::next(&mut stream).await {
+		let chunk = match chunk {
+			Ok(chunk) => chunk,
+			Err(err) => {
+				result = Err(err);
+				break;
+			}
+		};
+		if let Err(err) = writer.write_all(&chunk).await {
+			result = Err(polyio::IOError::from(err).into());
+			break;
+		}
+		*received += chunk.len() as u64;
+		if let Some(hasher) = hasher.as_mut() {
+			hasher.update(&chunk);
+		}
+	}
+
+	writer.flush().await.map_err(polyio::IOError::from)?;
+	result
+}
+
 #[tracing::instrument(level = "debug", skip(client, events, progress), fields(%url))]
 pub async fn fetch_verified(
 	client: &RequestClient,
@@ -204,6 +337,7 @@ pub async fn fetch_verified(
 
 	let options = ResponseOptions {
 		notify: progress.clone().map(ResponseNotifyOptions::grouped),
+		..Default::default()
 	};
 	let stream = response.stream(options, events).await?;
 
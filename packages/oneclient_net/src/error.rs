@@ -63,6 +63,10 @@ pub enum RequestError {
         actual: u64,
     },
 
+    /// The partial is dropped so the retry fetches the whole file
+    #[error("{url} did not resume the download at byte {offset}")]
+    ResumeMismatch { url: String, offset: u64 },
+
     #[error("Invalid URL: {0}")]
     UrlParseError(#[from] url::ParseError),
 
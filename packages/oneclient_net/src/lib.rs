@@ -1,13 +1,16 @@
+mod bandwidth;
 mod cache;
 mod config;
 mod download;
 mod error;
+mod partial;
 mod request;
 mod response;
 mod service;
 
 pub mod status;
 
+pub use bandwidth::Priority;
 pub use cache::{EtagPolicy, Fetched, commit_etag, fetch_cached};
 pub use config::NetConfig;
 pub use download::{download_verified, fetch_verified, matches_on_disk};
@@ -0,0 +1,238 @@
+//! A download cut off mid-body keeps what arrived beside its destination with
+//! the validators needed to ask the server for only the rest
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+use polyio::{Checksum, ChecksumAlgorithm, ChecksumStream};
+use reqwest::header::{CONTENT_RANGE, ETAG, HeaderMap, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncReadExt;
+
+const HASH_BUFFER: usize = 64 * 1024;
+
+/// Partials a download in this process is writing two downloads of one
+/// destination appending to the same file would corrupt both
+static CLAIMED: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PartialMeta {
+	pub url: String,
+	/// What the finished file must hash to so a caller after a different file
+	/// at the same destination never continues this one
+	pub checksum: Option<String>,
+	pub etag: Option<String>,
+	pub last_modified: Option<String>,
+}
+
+impl PartialMeta {
+	pub fn new(url: &str, expected: Option<&Checksum>, headers: &HeaderMap) -> Self {
+		let header = |name| {
+			headers
+				.get(name)
+				.and_then(|value| value.to_str().ok())
+				.map(str::to_string)
+		};
+		Self {
+			url: url.to_string(),
+			checksum: expected.map(checksum_key),
+			etag: header(ETAG),
+			last_modified: header(LAST_MODIFIED),
+		}
+	}
+
+	/// For `If-Range` which takes no weak ETag those fall back to the date
+	pub fn validator(&self) -> Option<&str> {
+		self.etag
+			.as_deref()
+			.filter(|etag| !etag.starts_with("W/"))
+			.or(self.last_modified.as_deref())
+	}
+}
+
+fn checksum_key(checksum: &Checksum) -> String {
+	format!("{}:{}", checksum.algorithm.name(), checksum.hex)
+}
+
+/// Held for the whole of one `download_verified` call
+pub(crate) struct Partial {
+	pub path: PathBuf,
+	meta_path: PathBuf,
+	/// `false` when another download holds this destination's partial this one
+	/// then writes a throwaway file and never leaves anything to resume
+	resumable: bool,
+}
+
+impl Partial {
+	pub fn claim(dest: &Path) -> Self {
+		let name = dest
+			.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_else(|| "download".to_string());
+		let sibling = |file: String| match dest.parent() {
+			Some(parent) => parent.join(file),
+			None => PathBuf::from(file),
+		};
+
+		let shared = sibling(format!(".{name}.part"));
+		let resumable = CLAIMED
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(shared.clone());
+		let path = if resumable {
+			shared
+		} else {
+			sibling(format!(".{name}.{}.part", uuid::Uuid::new_v4()))
+		};
+
+		Self {
+			meta_path: path.with_extension("part.json"),
+			path,
+			resumable,
+		}
+	}
+
+	/// Bytes kept from an earlier attempt at the same file with the validators
+	/// they were fetched under anything unusable is cleared
+	pub async fn resume_point(
+		&self,
+		url: &str,
+		expected: Option<&Checksum>,
+		expected_size: u64,
+	) -> Option<(u64, PartialMeta)> {
+		if !self.resumable {
+			return None;
+		}
+
+		let usable = async {
+			let meta: PartialMeta = polyio::read_json(&self.meta_path).await.ok()?;
+			let len = polyio::stat(&self.path).await.ok()?.len();
+			let same_file = meta.url == url && meta.checksum == expected.map(checksum_key);
+			// A partial as long as the whole file should have been renamed into
+			// place so it is something else
+			let short = expected_size == 0 || len < expected_size;
+			(same_file && short && len > 0 && meta.validator().is_some()).then_some((len, meta))
+		}
+		.await;
+
+		if usable.is_none() {
+			self.discard().await;
+		}
+		usable
+	}
+
+	/// Written before the body so a cut-off transfer always has its validators
+	/// on disk
+	pub async fn record(&self, meta: &PartialMeta) -> Result<(), polyio::IOError> {
+		if !self.resumable {
+			return Ok(());
+		}
+		if meta.validator().is_none() {
+			// Or a body that cannot be resumed would inherit an older one's
+			let _ = polyio::remove_file(&self.meta_path).await;
+			return Ok(());
+		}
+		polyio::write_json_atomic(&self.meta_path, meta).await
+	}
+
+	/// After a failed attempt kept only when a later one can continue it
+	pub async fn settle(&self) {
+		let recorded = polyio::try_exists(&self.meta_path).await.unwrap_or(false);
+		if !self.resumable || !recorded {
+			self.discard().await;
+		}
+	}
+
+	pub async fn discard(&self) {
+		let _ = polyio::remove_file(&self.path).await;
+		let _ = polyio::remove_file(&self.meta_path).await;
+	}
+
+	pub async fn finish(&self, dest: &Path) -> Result<(), polyio::IOError> {
+		polyio::rename(&self.path, dest).await?;
+		let _ = polyio::remove_file(&self.meta_path).await;
+		Ok(())
+	}
+}
+
+impl Drop for Partial {
+	fn drop(&mut self) {
+		if self.resumable {
+			CLAIMED
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner)
+				.remove(&self.path);
+		}
+	}
+}
+
+/// Where a `206` body starts `None` when the header is missing or malformed
+pub(crate) fn content_range_start(headers: &HeaderMap) -> Option<u64> {
+	let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+	let range = value.trim().strip_prefix("bytes ")?;
+	let (start, _) = range.split_once('-')?;
+	start.trim().parse().ok()
+}
+
+/// The hasher as it stood after the first `len` bytes so a resumed body
+/// carries on from there
+pub(crate) async fn hash_prefix(
+	path: &Path,
+	algorithm: ChecksumAlgorithm,
+	len: u64,
+) -> Result<ChecksumStream, polyio::IOError> {
+	let mut hasher = ChecksumStream::new(algorithm);
+	let mut file = tokio::fs::File::open(path)
+		.await
+		.map_err(polyio::IOError::from)?
+		.take(len);
+	let mut buffer = vec![0u8; HASH_BUFFER];
+
+	loop {
+		let n = file.read(&mut buffer).await.map_err(polyio::IOError::from)?;
+		if n == 0 {
+			break;
+		}
+		hasher.update(&buffer[..n]);
+	}
+
+	Ok(hasher)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use reqwest::header::HeaderValue;
+
+	#[test]
+	fn content_range_start_reads_the_first_byte() {
+		let mut headers = HeaderMap::new();
+		assert_eq!(content_range_start(&headers), None);
+
+		headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 100-199/200"));
+		assert_eq!(content_range_start(&headers), Some(100));
+
+		headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes */200"));
+		assert_eq!(content_range_start(&headers), None);
+	}
+
+	#[test]
+	fn a_weak_etag_falls_back_to_the_date() {
+		let mut headers = HeaderMap::new();
+		headers.insert(ETAG, HeaderValue::from_static("W/\"abc\""));
+		headers.insert(
+			LAST_MODIFIED,
+			HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+		);
+		let meta = PartialMeta::new("https://example.test/a.jar", None, &headers);
+		assert_eq!(meta.validator(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+
+		headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+		let meta = PartialMeta::new("https://example.test/a.jar", None, &headers);
+		assert_eq!(meta.validator(), Some("\"abc\""));
+
+		let meta = PartialMeta::new("https://example.test/a.jar", None, &HeaderMap::new());
+		assert_eq!(meta.validator(), None);
+	}
+}
@@ -9,6 +9,9 @@ const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(
 #[derive(Clone, Default)]
 pub struct ResponseOptions {
     pub notify: Option<ResponseNotifyOptions>,
+    /// Bytes already on disk from an earlier attempt so a resumed body's bar
+    /// starts where the last one stopped
+    pub resumed_from: u64,
 }
 
 #[derive(Clone)]
@@ -71,6 +74,25 @@ pub trait ResponseExt {
         options: ResponseOptions,
         events: &EventBus,
     ) -> Result<impl futures_lite::Stream<Item = Result<bytes::Bytes, RequestError>>, RequestError>;
+
+    /// In place of `bytes()` which would read past the download limit
+    async fn throttled_bytes(self) -> Result<bytes::Bytes, RequestError>;
+}
+
+/// The body paced by the process-wide limit at the priority of the task that
+/// asked for it since that task is the one polling
+fn throttled(
+    response: Response,
+) -> impl futures_lite::Stream<Item = reqwest::Result<bytes::Bytes>> + Send {
+    let priority = crate::bandwidth::Priority::current();
+    futures_util::StreamExt::then(response.bytes_stream(), move |item| async move {
+        if let Ok(chunk) = &item {
+            crate::bandwidth::limiter()
+                .acquire(chunk.len() as u64, priority)
+                .await;
+        }
+        item
+    })
 }
 
 #[async_trait::async_trait]
@@ -82,8 +104,8 @@ impl ResponseExt for Response {
         events: &EventBus,
     ) -> Result<impl futures_lite::Stream<Item = Result<bytes::Bytes, RequestError>>, RequestError>
     {
-        let total = self.content_length().unwrap_or(0).max(1);
-        let mut current = 0u64;
+        let mut current = options.resumed_from;
+        let total = (current + self.content_length().unwrap_or(0)).max(1);
 
         let grouped_child = options
             .notify
@@ -103,16 +125,18 @@ impl ResponseExt for Response {
             .map(|_| options.notify.as_ref().and_then(|n| n.standalone_id).unwrap_or_else(Uuid::new_v4));
 
         if let Some(ref child) = grouped_child {
-            child.set_progress(0, Some(total));
+            child.set_progress(current, Some(total));
         } else if let (Some(id), Some(label)) = (&standalone_id, &standalone_label) {
-            events.progress(*id, label, 0, total);
+            events.progress(*id, label, current, total);
         }
 
         let events = events.clone();
+        let body = throttled(self);
+
         // One event per chunk would bury the UI in tens of thousands of updates
         // so sample the last one always emits so the bar lands on complete
         let mut last_emit: Option<std::time::Instant> = None;
-        let stream = futures_lite::StreamExt::map(self.bytes_stream(), move |item| {
+        let stream = futures_lite::StreamExt::map(body, move |item| {
             match item {
                 Ok(chunk) => {
                     current += chunk.len() as u64;
@@ -144,4 +168,13 @@ impl ResponseExt for Response {
 
         Ok(stream)
     }
+
+    async fn throttled_bytes(self) -> Result<bytes::Bytes, RequestError> {
+        let mut body = std::pin::pin!(throttled(self));
+        let mut bytes = Vec::new();
+        while let Some(chunk) = futures_lite::StreamExt::next(&mut body).await {
+            bytes.extend_from_slice(&chunk?);
+        }
+        Ok(bytes.into())
+    }
 }
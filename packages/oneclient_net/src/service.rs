@@ -99,15 +99,20 @@ impl RequestClient {
         self.config.load()
     }
 
+    /// Also sets the process-wide download limit so with several clients the
+    /// last one configured wins
     pub fn set_config(&self, config: NetConfig) {
+        crate::bandwidth::limiter().set_rate(config.download_limit);
         self.config.store(Arc::new(config));
     }
 
     pub fn new(config: NetConfig) -> Result<Self, RequestError> {
+        // No total timeout a capped or background download may rightly take
+        // hours the read timeout restarts on every chunk and still catches a
+        // connection that went dead
         let mut builder = ClientBuilder::new()
             .connect_timeout(std::time::Duration::from_secs(10))
             .read_timeout(std::time::Duration::from_secs(30))
-            .timeout(std::time::Duration::from_mins(10))
             .tls_backend_rustls()
             .user_agent(format!(
                 "OneClient {} ({})",
@@ -129,6 +134,7 @@ impl RequestClient {
         }
 
         let client = builder.build()?;
+        crate::bandwidth::limiter().set_rate(config.download_limit);
 
         Ok(Self {
             client,
@@ -233,7 +239,7 @@ impl RequestClient {
         let status = res.status();
         if !status.is_success() {
             let url = res.url().to_string();
-            let bytes = res.bytes().await?;
+            let bytes = res.throttled_bytes().await?;
             return Err(RequestError::HttpStatus {
                 status: status.as_u16(),
                 url,
@@ -255,7 +261,7 @@ impl RequestClient {
         let res = self.send(request).await?;
         let status = res.status();
         let url = res.url().to_string();
-        let bytes = res.bytes().await?;
+        let bytes = res.throttled_bytes().await?;
 
         if !status.is_success() {
             return Err(RequestError::HttpStatus {
@@ -0,0 +1,223 @@
+use std::sync::{Arc, Mutex};
+
+use oneclient_events::EventBus;
+use oneclient_net::{NetConfig, RequestClient, download_verified};
+use polyio::Checksum;
+use polyio::testing::ScratchDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Past the size below which a download is not worth resuming
+const SIZE: usize = 3 * 1024 * 1024;
+const CUT: usize = 2 * 1024 * 1024;
+
+fn payload(seed: u8) -> Vec<u8> {
+    (0..SIZE).map(|i| (i % 251) as u8 ^ seed).collect()
+}
+
+#[derive(Default)]
+struct State {
+    body: Vec<u8>,
+    etag: &'static str,
+    /// The next full response stops after `CUT` bytes
+    cut_next: bool,
+    /// Answers ranged requests with a 404 so a download gives up and leaves
+    /// its partial behind
+    refuse_ranges: bool,
+    /// `Range` header of every request in order
+    ranges: Vec<Option<String>>,
+}
+
+/// Serves one file honouring `Range` only while `If-Range` still names the
+/// current ETag as a real server would
+#[derive(Clone, Default)]
+struct RangeServer {
+    state: Arc<Mutex<State>>,
+}
+
+impl RangeServer {
+    async fn start(body: Vec<u8>, etag: &'static str) -> (Self, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/jdk.tar.gz", listener.local_addr().unwrap());
+        let server = Self::default();
+        {
+            let mut state = server.state.lock().unwrap();
+            state.body = body;
+            state.etag = etag;
+            state.cut_next = true;
+        }
+
+        let handler = server.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move { handler.serve(stream).await });
+            }
+        });
+
+        (server, url)
+    }
+
+    fn replace(&self, body: Vec<u8>, etag: &'static str) {
+        let mut state = self.state.lock().unwrap();
+        state.body = body;
+        state.etag = etag;
+    }
+
+    fn ranges(&self) -> Vec<Option<String>> {
+        self.state.lock().unwrap().ranges.clone()
+    }
+
+    async fn serve(&self, mut stream: TcpStream) {
+        let mut buf = Vec::new();
+        let head = loop {
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await.unwrap_or(0);
+            if n == 0 {
+                return;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break String::from_utf8_lossy(&buf[..end]).to_string();
+            }
+        };
+        let header = |wanted: &str| {
+            head.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case(wanted)
+                    .then(|| value.trim().to_string())
+            })
+        };
+        let range = header("range");
+        let if_range = header("if-range");
+
+        let (head, body) = {
+            let mut state = self.state.lock().unwrap();
+            state.ranges.push(range.clone());
+
+            let start = range
+                .as_deref()
+                .filter(|_| if_range.as_deref() == Some(state.etag))
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.strip_suffix('-'))
+                .and_then(|start| start.parse::<usize>().ok());
+            let len = state.body.len();
+
+            match start {
+                _ if range.is_some() && state.refuse_ranges => (
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                    Vec::new(),
+                ),
+                Some(start) => (
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\nETag: {}\r\nContent-Range: bytes {start}-{}/{len}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        state.etag,
+                        len - 1,
+                        len - start
+                    ),
+                    state.body[start..].to_vec(),
+                ),
+                None => {
+                    // Promises the whole file but hangs up partway like a
+                    // dropped connection
+                    let end = if std::mem::take(&mut state.cut_next) {
+                        CUT
+                    } else {
+                        len
+                    };
+                    (
+                        format!(
+                            "HTTP/1.1 200 OK\r\nETag: {}\r\nContent-Length: {len}\r\nConnection: close\r\n\r\n",
+                            state.etag
+                        ),
+                        state.body[..end].to_vec(),
+                    )
+                }
+            }
+        };
+
+        let _ = stream.write_all(head.as_bytes()).await;
+        let _ = stream.write_all(&body).await;
+        let _ = stream.flush().await;
+    }
+}
+
+fn client() -> (RequestClient, EventBus) {
+    let (events, _) = EventBus::channel();
+    (RequestClient::new(NetConfig::default()).unwrap(), events)
+}
+
+fn leftovers(dir: &std::path::Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.contains(".part"))
+        .collect()
+}
+
+#[tokio::test]
+async fn a_cut_off_download_resumes_from_where_it_stopped() {
+    let body = payload(0);
+    let expected = Checksum::sha1(polyio::sha1_bytes(&body));
+    let (server, url) = RangeServer::start(body.clone(), "\"v1\"").await;
+    let (client, events) = client();
+    let dir = ScratchDir::new("download_resume");
+    let dest = dir.join("jdk.tar.gz");
+
+    download_verified(
+        &client,
+        &events,
+        &url,
+        &dest,
+        Some(&expected),
+        SIZE as u64,
+        None,
+    )
+    .await
+    .expect("the second attempt should finish the file");
+
+    assert_eq!(std::fs::read(&dest).unwrap(), body);
+    // Only the missing tail is fetched again
+    assert_eq!(server.ranges(), vec![None, Some(format!("bytes={CUT}-"))]);
+    assert!(
+        leftovers(dir.path()).is_empty(),
+        "{:?}",
+        leftovers(dir.path())
+    );
+}
+
+#[tokio::test]
+async fn a_partial_of_a_file_that_changed_is_not_spliced_onto_the_new_one() {
+    let old = payload(0);
+    let new = payload(0x5A);
+    let (server, url) = RangeServer::start(old, "\"v1\"").await;
+    server.state.lock().unwrap().refuse_ranges = true;
+    let (client, events) = client();
+    let dir = ScratchDir::new("download_resume_changed");
+    let dest = dir.join("jdk.tar.gz");
+
+    // No hash so only `If-Range` can tell the two files apart
+    let download = || download_verified(&client, &events, &url, &dest, None, SIZE as u64, None);
+    download().await.expect_err("the resume is refused");
+    assert_eq!(
+        leftovers(dir.path()).len(),
+        2,
+        "the partial and its validators"
+    );
+
+    server.state.lock().unwrap().refuse_ranges = false;
+    server.replace(new.clone(), "\"v2\"");
+    download()
+        .await
+        .expect("the new file should download whole");
+
+    assert_eq!(std::fs::read(&dest).unwrap(), new);
+    let resumed = Some(format!("bytes={CUT}-"));
+    assert_eq!(server.ranges(), vec![None, resumed.clone(), resumed]);
+    assert!(
+        leftovers(dir.path()).is_empty(),
+        "{:?}",
+        leftovers(dir.path())
+    );
+}
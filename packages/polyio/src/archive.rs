@@ -237,6 +237,37 @@ pub async fn read_zip_file_entries(
 	Ok(out)
 }
 
+/// [`read_zip_file_entries`] for an archive already in memory such as a jar
+/// nested in another
+#[tracing::instrument(level = "debug", skip(data, filter))]
+pub async fn read_zip_bytes_entries(
+	data: Vec<u8>,
+	filter: impl Fn(&str) -> bool,
+) -> PolyIOResult<Vec<(String, Vec<u8>)>> {
+	let reader = async_zip::base::read::mem::ZipFileReader::new(data).await?;
+	let entries = reader.file().entries();
+
+	let mut out = Vec::new();
+	for index in 0..entries.len() {
+		let entry = entries.get(index).expect("expected more zip entries");
+		let Ok(name) = entry.filename().as_str() else {
+			continue;
+		};
+		let name = name.to_string();
+
+		if entry.dir().unwrap_or(false) || !filter(&name) {
+			continue;
+		}
+
+		let mut entry_reader = reader.reader_without_entry(index).await?;
+		let mut data = Vec::new();
+		futures_lite::AsyncReadExt::read_to_end(&mut entry_reader, &mut data).await?;
+		out.push((name, data));
+	}
+
+	Ok(out)
+}
+
 /// Returns a zip file entry's bytes without reading the entire file into memory
 #[tracing::instrument(
     level = "debug",
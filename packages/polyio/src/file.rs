@@ -137,6 +137,16 @@ pub async fn read_gz_to_string(path: impl AsRef<std::path::Path>) -> PolyIOResul
 	Ok(dst)
 }
 
+/// For gzip already in memory like NBT files read whole
+#[tracing::instrument(level = "debug", skip(data), fields(len = data.len()))]
+pub async fn gunzip(data: &[u8]) -> PolyIOResult<Vec<u8>> {
+	let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(data);
+	let mut dst = Vec::with_capacity(data.len() * 4);
+	tokio::io::AsyncReadExt::read_to_end(&mut decoder, &mut dst).await?;
+
+	Ok(dst)
+}
+
 #[tracing::instrument(
     level = "debug",
     skip(path),
@@ -1,5 +1,5 @@
 use std::fmt::Write;
-use std::io::Read;
+use std::io::{Read, Write as _};
 use std::path::Path;
 
 use digest::Digest;
@@ -70,6 +70,73 @@ pub fn sha1_file_sync(path: &Path) -> PolyIOResult<String> {
 	Ok(hasher.finish())
 }
 
+/// The SHA-1 of what was written which is what `to` holds even if `from`
+/// changed while it was read
+pub async fn sha1_copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> PolyIOResult<String> {
+	let from = from.as_ref().to_path_buf();
+	let to = to.as_ref().to_path_buf();
+	tokio::task::spawn_blocking(move || sha1_copy_sync(&from, &to))
+		.await
+		.map_err(std::io::Error::other)?
+}
+
+pub fn sha1_copy_sync(from: &Path, to: &Path) -> PolyIOResult<String> {
+	let at = |path: &Path| {
+		let path = path.to_string_lossy().to_string();
+		move |source: std::io::Error| IOError::PathIOError { source, path }
+	};
+	let mut reader = std::fs::File::open(from).map_err(at(from))?;
+	let mut writer = std::fs::File::create(to).map_err(at(to))?;
+	let mut hasher = Sha1Stream::new();
+	let mut buffer = vec![0u8; MAX_HASH_BUFFER];
+
+	loop {
+		let n = reader.read(&mut buffer).map_err(at(from))?;
+		if n == 0 {
+			break;
+		}
+		hasher.update(&buffer[..n]);
+		writer.write_all(&buffer[..n]).map_err(at(to))?;
+	}
+	writer.sync_all().map_err(at(to))?;
+
+	Ok(hasher.finish())
+}
+
+/// Same blocking-pool dispatch as [`sha1_file`] for the algorithms a manifest
+/// names alongside SHA-1
+pub async fn checksum_file(
+	path: impl AsRef<Path>,
+	algorithm: ChecksumAlgorithm,
+) -> PolyIOResult<String> {
+	let path = path.as_ref().to_path_buf();
+	tokio::task::spawn_blocking(move || checksum_file_sync(&path, algorithm))
+		.await
+		.map_err(std::io::Error::other)?
+}
+
+pub fn checksum_file_sync(path: &Path, algorithm: ChecksumAlgorithm) -> PolyIOResult<String> {
+	let mut file = std::fs::File::open(path).map_err(|e| IOError::PathIOError {
+		source: e,
+		path: path.to_string_lossy().to_string(),
+	})?;
+	let mut hasher = ChecksumStream::new(algorithm);
+	let mut buffer = vec![0u8; MAX_HASH_BUFFER];
+
+	loop {
+		let n = file.read(&mut buffer).map_err(|e| IOError::PathIOError {
+			source: e,
+			path: path.to_string_lossy().to_string(),
+		})?;
+		if n == 0 {
+			break;
+		}
+		hasher.update(&buffer[..n]);
+	}
+
+	Ok(hasher.finish())
+}
+
 #[must_use]
 pub fn sha1_bytes(data: &[u8]) -> String {
 	let mut hasher = Sha1Stream::new();
@@ -81,6 +148,7 @@ pub fn sha1_bytes(data: &[u8]) -> String {
 pub enum ChecksumAlgorithm {
 	Sha1,
 	Sha256,
+	Sha512,
 }
 
 impl ChecksumAlgorithm {
@@ -91,6 +159,7 @@ impl ChecksumAlgorithm {
 		match self {
 			Self::Sha1 => 40,
 			Self::Sha256 => 64,
+			Self::Sha512 => 128,
 		}
 	}
 
@@ -99,6 +168,7 @@ impl ChecksumAlgorithm {
 		match self {
 			Self::Sha1 => "SHA-1",
 			Self::Sha256 => "SHA-256",
+			Self::Sha512 => "SHA-512",
 		}
 	}
 }
@@ -129,6 +199,11 @@ impl Checksum {
 		Self::new(ChecksumAlgorithm::Sha256, hex)
 	}
 
+	#[must_use]
+	pub fn sha512(hex: impl AsRef<str>) -> Self {
+		Self::new(ChecksumAlgorithm::Sha512, hex)
+	}
+
 	/// Whether the hash is the right shape for its algorithm
 	/// Callers drop malformed checksums and download unverified
 	#[must_use]
@@ -146,6 +221,7 @@ impl Checksum {
 pub enum ChecksumStream {
 	Sha1(sha1::Sha1),
 	Sha256(sha2::Sha256),
+	Sha512(sha2::Sha512),
 }
 
 impl ChecksumStream {
@@ -154,6 +230,7 @@ impl ChecksumStream {
 		match algorithm {
 			ChecksumAlgorithm::Sha1 => Self::Sha1(sha1::Sha1::new()),
 			ChecksumAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+			ChecksumAlgorithm::Sha512 => Self::Sha512(sha2::Sha512::new()),
 		}
 	}
 
@@ -161,6 +238,7 @@ impl ChecksumStream {
 		match self {
 			Self::Sha1(hasher) => Digest::update(hasher, data),
 			Self::Sha256(hasher) => Digest::update(hasher, data),
+			Self::Sha512(hasher) => Digest::update(hasher, data),
 		}
 	}
 
@@ -169,6 +247,7 @@ impl ChecksumStream {
 		match self {
 			Self::Sha1(hasher) => to_hex(&hasher.finalize()),
 			Self::Sha256(hasher) => to_hex(&hasher.finalize()),
+			Self::Sha512(hasher) => to_hex(&hasher.finalize()),
 		}
 	}
 }
@@ -229,6 +308,15 @@ mod tests {
 			sha256.finish(),
 			"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
 		);
+
+		let mut sha512 = ChecksumStream::new(ChecksumAlgorithm::Sha512);
+		sha512.update(b"ab");
+		sha512.update(b"c");
+		assert_eq!(
+			sha512.finish(),
+			"ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+			 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+		);
 	}
 
 	#[test]